@@ -1,9 +1,8 @@
-use gateio_rs::api::spot::{Order, create_batch_orders, create_order};
 #[cfg(feature = "enable-hyper")]
 use gateio_rs::{
     api::spot::{
-        get_account, get_account_book, get_batch_user_fee, get_currency_pair, get_currency_pairs,
-        get_ticker,
+        ClientOrderId, Order, create_batch_orders, create_order, get_account, get_account_book,
+        get_batch_user_fee, get_currency_pair, get_currency_pairs, get_ticker,
     },
     http::Credentials,
     hyper::GateHttpClient,
@@ -106,7 +105,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 8: Batch orders
     println!("Creating batch orders...");
     let order1 = Order::new("BTC_USDT", "buy", "0.001")
-        .text("t-abc123")
+        .text(ClientOrderId::new("t-abc123")?)
         .order_type("limit")
         .account("unified")
         .price("65000")
@@ -114,7 +113,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .iceberg("0");
 
     let order2 = Order::new("ETH_USDT", "buy", "0.01")
-        .text("t-def456")
+        .text(ClientOrderId::new("t-def456")?)
         .order_type("limit")
         .account("unified")
         .price("3000")
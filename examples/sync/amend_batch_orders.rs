@@ -18,7 +18,7 @@ fn main() -> Result<(), Box<gateio_rs::ureq::Error>> {
             .amount("0.01"),
         spot::OrderAmendment::new("order_id_2", "ETH_USDT")
             .price("3000")
-            .amend_text("Updated price"),
+            .amend_text(spot::ClientOrderId::new("t-reprice").unwrap()),
     ];
 
     // Create request
@@ -0,0 +1,26 @@
+use gateio_rs::backfill::{Backfill, CsvSink, Interval};
+use gateio_rs::ureq::GateHttpClient;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = GateHttpClient::default();
+
+    // Download one week of 1 minute candles, resuming if interrupted
+    let mut sink = CsvSink::create("BTC_USDT-1m.csv")?;
+    let report = Backfill::new("BTC_USDT", Interval::Min1, 1_700_000_000, 1_700_604_800)
+        .checkpoint("BTC_USDT-1m.checkpoint")
+        .run(&client, &mut sink)?;
+
+    println!(
+        "{} candles in {} windows, {} duplicates dropped",
+        report.candles, report.windows, report.duplicates
+    );
+    for gap in &report.gaps {
+        println!(
+            "missing {} bars from {}",
+            gap.bars(Interval::Min1),
+            gap.from
+        );
+    }
+
+    Ok(())
+}
@@ -20,7 +20,7 @@ fn main() -> Result<(), Box<gateio_rs::ureq::Error>> {
     // Create cross liquidation orders
     let orders = vec![
         spot::CrossLiquidateOrder::new("BTC_USDT", "0.01", "45000")
-            .text("Cross liquidation order")
+            .text(spot::ClientOrderId::new("t-cross-liquidation").unwrap())
             .action_mode("ACK"),
     ];
 
@@ -1,6 +1,6 @@
 use gateio_rs::{
     api::spot::{
-        Order, create_batch_orders, create_order, get_account, get_account_book,
+        ClientOrderId, Order, create_batch_orders, create_order, get_account, get_account_book,
         get_batch_user_fee, get_currency_pair, get_currency_pairs, get_ticker,
     },
     http::Credentials,
@@ -101,7 +101,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 8: Batch orders
     println!("Creating batch orders...");
     let order1 = Order::new("BTC_USDT", "buy", "0.001")
-        .text("t-abc123")
+        .text(ClientOrderId::new("t-abc123")?)
         .order_type("limit")
         .account("unified")
         .price("65000")
@@ -109,7 +109,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .iceberg("0");
 
     let order2 = Order::new("ETH_USDT", "buy", "0.01")
-        .text("t-def456")
+        .text(ClientOrderId::new("t-def456")?)
         .order_type("limit")
         .account("unified")
         .price("3000")
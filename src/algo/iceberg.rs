@@ -0,0 +1,117 @@
+use super::{Algo, AlgoAction, AlgoLimits, AlgoProgress, AlgoStatus, Fills, Market, OrderIntent};
+use crate::api::spot::OrderDetails;
+use serde::{Deserialize, Serialize};
+
+/// Large limit order showing only a small part of its size.
+///
+/// One child order of at most `display` is kept on the book at `price`;
+/// when it is filled, the next one is placed from the hidden remainder.
+/// Changing the price with [`Iceberg::set_price`] amends the working child.
+///
+/// ```rust
+/// use gateio_rs::algo::{Algo, AlgoAction, Iceberg, Market};
+///
+/// let mut iceberg = Iceberg::new("buy", 10.0, 29_950.0, 0.5);
+/// let actions = iceberg.next_actions(&Market::new(30_000.0), 0);
+/// assert!(matches!(&actions[..], [AlgoAction::Place(order)] if order.amount == 0.5));
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Iceberg {
+    /// Side of the child orders
+    pub side: String,
+    /// Total amount to execute
+    pub amount: f64,
+    /// Limit price of the child orders
+    pub price: f64,
+    /// Amount shown on the book at a time
+    pub display: f64,
+    /// Limits of the child orders
+    pub limits: AlgoLimits,
+    stopped: bool,
+    fills: Fills,
+}
+
+impl Iceberg {
+    /// Creates an iceberg of `amount` at `price`, showing `display` at a time
+    pub fn new(side: &str, amount: f64, price: f64, display: f64) -> Self {
+        Self {
+            side: side.to_owned(),
+            amount,
+            price,
+            display,
+            limits: AlgoLimits::new(),
+            stopped: false,
+            fills: Fills::default(),
+        }
+    }
+
+    /// Sets the limits of the child orders
+    pub fn limits(mut self, limits: AlgoLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Moves the iceberg to `price`; the working child is amended on the next step
+    pub fn set_price(&mut self, price: f64) {
+        self.price = price;
+    }
+
+    fn remaining(&self) -> f64 {
+        self.amount - self.fills.filled
+    }
+}
+
+impl Algo for Iceberg {
+    fn next_actions(&mut self, _market: &Market, _now_ms: i64) -> Vec<AlgoAction> {
+        if self.stopped {
+            return Vec::new();
+        }
+        if let Some(working) = self.fills.working().next() {
+            if working.price == self.price {
+                return Vec::new();
+            }
+            return vec![AlgoAction::Amend {
+                order_id: working.id.clone(),
+                price: self.price,
+            }];
+        }
+        let remaining = self.remaining();
+        if self.limits.is_dust(remaining, self.amount) {
+            return Vec::new();
+        }
+        vec![AlgoAction::Place(OrderIntent {
+            side: self.side.clone(),
+            amount: self.limits.cap(remaining.min(self.display)),
+            price: self.price,
+            time_in_force: "gtc".into(),
+        })]
+    }
+
+    fn on_order(&mut self, order: &OrderDetails) {
+        self.fills.record(order);
+    }
+
+    fn stop(&mut self) -> Vec<AlgoAction> {
+        self.stopped = true;
+        self.fills.cancel_working()
+    }
+
+    fn progress(&self) -> AlgoProgress {
+        let status = if self.limits.is_dust(self.remaining(), self.amount) {
+            AlgoStatus::Completed
+        } else if self.stopped {
+            AlgoStatus::Stopped
+        } else {
+            AlgoStatus::Running
+        };
+        self.fills.progress(status, self.amount)
+    }
+
+    fn child_limits(&self) -> &AlgoLimits {
+        &self.limits
+    }
+
+    fn working_orders(&self) -> Vec<String> {
+        self.fills.working().map(|c| c.id.clone()).collect()
+    }
+}
@@ -0,0 +1,834 @@
+//! Client-side execution algorithms.
+//!
+//! An algorithm decides which child orders to place, amend or cancel; an
+//! [`Execution`] carries its decisions out with `create_order`,
+//! `amend_order` and `cancel_order` and feeds the resulting orders back:
+//!
+//! - [`TrailingStop`]: exits once the price retraces from its best level
+//! - [`Slicer`]: TWAP or VWAP, spreading an amount over a time window
+//! - [`Iceberg`]: shows a small part of a large limit order at a time
+//!
+//! Every step is driven by a [`Market`] snapshot, built from a ticker
+//! (polled by [`Execution::run`] or received from a WebSocket feed), and
+//! returns a [`StepReport`] with the actions taken and the progress so far.
+//!
+//! Algorithms keep all their state in plain serializable structs: save
+//! them with serde between steps and hand them to a new [`Execution`] to
+//! resume after a restart. Child orders are limit orders; aggressive ones
+//! are immediate-or-cancel at the best price moved by
+//! [`AlgoLimits::max_slippage`]. In dry-run mode no request is sent and
+//! every child order is assumed filled at its price.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::algo::{AlgoLimits, Execution, Slicer};
+//! use gateio_rs::{http::Credentials, ureq::GateHttpClient};
+//! use std::time::{Duration, SystemTime, UNIX_EPOCH};
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//! let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+//!
+//! // Buy 2 BTC over one hour in 12 slices
+//! let twap = Slicer::twap("buy", 2.0, now_ms, Duration::from_secs(3600), 12)
+//!     .limits(AlgoLimits::new().min_child_amount(0.0001).max_slippage(0.002));
+//!
+//! let mut execution = Execution::new(twap, "BTC_USDT").precision(4, 1);
+//! let progress = execution.run(&client, Duration::from_secs(5))?;
+//! println!("filled {} at {:?}", progress.filled, progress.avg_price);
+//!
+//! // The state can be saved and resumed later
+//! let saved = serde_json::to_string(execution.algo())?;
+//! let resumed: Slicer = serde_json::from_str(&saved)?;
+//! let execution = Execution::new(resumed, "BTC_USDT").precision(4, 1);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod iceberg;
+mod slicer;
+mod trailing;
+
+pub use iceberg::Iceberg;
+pub use slicer::Slicer;
+pub use trailing::{Trail, TrailingStop};
+
+use crate::api::spot::{
+    OrderDetails, amend_order, amend_order::AmendOrder, cancel_order, cancel_order::CancelOrder,
+    create_order, create_order::CreateOrder,
+};
+use crate::http::GateError;
+use crate::utils::{now_ms, number, parse};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Market state driving a step
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Market {
+    /// Last traded price
+    pub last: f64,
+    /// Best bid
+    pub bid: Option<f64>,
+    /// Best ask
+    pub ask: Option<f64>,
+}
+
+impl Market {
+    /// Creates a snapshot from the last traded price
+    pub fn new(last: f64) -> Self {
+        Self {
+            last,
+            bid: None,
+            ask: None,
+        }
+    }
+
+    /// Sets the best bid
+    pub fn bid(mut self, bid: f64) -> Self {
+        self.bid = Some(bid);
+        self
+    }
+
+    /// Sets the best ask
+    pub fn ask(mut self, ask: f64) -> Self {
+        self.ask = Some(ask);
+        self
+    }
+
+    /// Parses a `get_ticker` response for a single currency pair
+    pub fn from_ticker(body: &str) -> Result<Self, GateError> {
+        #[derive(Deserialize)]
+        struct Ticker {
+            last: String,
+            #[serde(default)]
+            highest_bid: String,
+            #[serde(default)]
+            lowest_ask: String,
+        }
+        let invalid = |message: &str| {
+            GateError::InvalidBody(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                message.to_owned(),
+            )))
+        };
+        let tickers: Vec<Ticker> = parse(body)?;
+        let ticker = tickers
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid("empty ticker list"))?;
+        let price = |value: &str| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|price| price.is_finite() && *price > 0.0)
+        };
+        let last =
+            price(&ticker.last).ok_or_else(|| invalid("last price is not a positive number"))?;
+        // An empty side of the book is reported as an empty string
+        let quote = |value: &str, message: &str| match value {
+            "" => Ok(None),
+            value => price(value).map(Some).ok_or_else(|| invalid(message)),
+        };
+        Ok(Self {
+            last,
+            bid: quote(&ticker.highest_bid, "highest bid is not a positive number")?,
+            ask: quote(&ticker.lowest_ask, "lowest ask is not a positive number")?,
+        })
+    }
+
+    /// Limit price crossing the spread by at most `slippage` (a fraction)
+    pub fn marketable_price(&self, side: &str, slippage: f64) -> f64 {
+        if side == "buy" {
+            self.ask.unwrap_or(self.last) * (1.0 + slippage)
+        } else {
+            self.bid.unwrap_or(self.last) * (1.0 - slippage)
+        }
+    }
+}
+
+/// Limits applied to the child orders of an algorithm
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct AlgoLimits {
+    /// Child orders below this amount are not placed
+    pub min_child_amount: f64,
+    /// Largest amount of a single child order
+    pub max_child_amount: Option<f64>,
+    /// Largest number of child orders placed over the whole run
+    pub max_child_orders: Option<usize>,
+    /// How far aggressive child orders may cross the best price, as a fraction
+    pub max_slippage: f64,
+    /// How far any child order price may be from the last price, as a fraction
+    pub price_band: Option<f64>,
+}
+
+impl Default for AlgoLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlgoLimits {
+    /// Creates limits with 0.1% slippage and no other bound
+    pub fn new() -> Self {
+        Self {
+            min_child_amount: 0.0,
+            max_child_amount: None,
+            max_child_orders: None,
+            max_slippage: 0.001,
+            price_band: None,
+        }
+    }
+
+    /// Sets the smallest amount of a child order
+    pub fn min_child_amount(mut self, amount: f64) -> Self {
+        self.min_child_amount = amount.max(0.0);
+        self
+    }
+
+    /// Sets the largest amount of a child order
+    pub fn max_child_amount(mut self, amount: f64) -> Self {
+        self.max_child_amount = Some(amount);
+        self
+    }
+
+    /// Sets the largest number of child orders
+    pub fn max_child_orders(mut self, orders: usize) -> Self {
+        self.max_child_orders = Some(orders);
+        self
+    }
+
+    /// Sets how far aggressive child orders may cross the best price
+    pub fn max_slippage(mut self, slippage: f64) -> Self {
+        self.max_slippage = slippage.max(0.0);
+        self
+    }
+
+    /// Sets how far child order prices may be from the last price
+    pub fn price_band(mut self, band: f64) -> Self {
+        self.price_band = Some(band);
+        self
+    }
+
+    /// Caps `amount` to the largest child order
+    pub(crate) fn cap(&self, amount: f64) -> f64 {
+        self.max_child_amount.map_or(amount, |max| amount.min(max))
+    }
+
+    /// Whether `remaining` is too small to be worth another child order
+    pub(crate) fn is_dust(&self, remaining: f64, total: f64) -> bool {
+        remaining < self.min_child_amount || remaining <= total * 1e-9
+    }
+}
+
+/// Child order an algorithm wants placed
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct OrderIntent {
+    /// Order side
+    pub side: String,
+    /// Amount in base currency
+    pub amount: f64,
+    /// Limit price
+    pub price: f64,
+    /// Time in force, `gtc` or `ioc`
+    pub time_in_force: String,
+}
+
+/// Step decided by an algorithm
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum AlgoAction {
+    /// Place a child order
+    Place(OrderIntent),
+    /// Move a working child order to a new price
+    Amend {
+        /// Order ID
+        order_id: String,
+        /// New limit price
+        price: f64,
+    },
+    /// Cancel a working child order
+    Cancel {
+        /// Order ID
+        order_id: String,
+    },
+}
+
+/// Lifecycle of an algorithm
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum AlgoStatus {
+    /// Waiting for its start time or activation price
+    #[default]
+    Pending,
+    /// Working
+    Running,
+    /// The whole amount was filled
+    Completed,
+    /// The time window ended before the whole amount was filled
+    Expired,
+    /// Stopped on request
+    Stopped,
+}
+
+impl AlgoStatus {
+    /// Whether the algorithm is done
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            AlgoStatus::Completed | AlgoStatus::Expired | AlgoStatus::Stopped
+        )
+    }
+}
+
+/// Progress of an algorithm
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct AlgoProgress {
+    /// Lifecycle status
+    pub status: AlgoStatus,
+    /// Amount to execute
+    pub target: f64,
+    /// Amount filled so far
+    pub filled: f64,
+    /// Average fill price
+    pub avg_price: Option<f64>,
+    /// Child orders placed so far
+    pub child_orders: usize,
+    /// Child orders still working
+    pub working_orders: usize,
+}
+
+impl AlgoProgress {
+    /// Amount left to execute
+    pub fn remaining(&self) -> f64 {
+        (self.target - self.filled).max(0.0)
+    }
+
+    /// Filled share of the target, from 0 to 1
+    pub fn fraction(&self) -> f64 {
+        if self.target > 0.0 {
+            (self.filled / self.target).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Execution algorithm driven by an [`Execution`]
+pub trait Algo {
+    /// Actions to take for the market state at `now_ms`
+    fn next_actions(&mut self, market: &Market, now_ms: i64) -> Vec<AlgoAction>;
+
+    /// Records the latest state of a child order
+    fn on_order(&mut self, order: &OrderDetails);
+
+    /// Stops the algorithm, returning the actions cancelling its working orders
+    fn stop(&mut self) -> Vec<AlgoAction>;
+
+    /// Progress so far
+    fn progress(&self) -> AlgoProgress;
+
+    /// Limits of the child orders
+    fn child_limits(&self) -> &AlgoLimits;
+
+    /// IDs of the child orders still working
+    fn working_orders(&self) -> Vec<String>;
+}
+
+/// Child order of an algorithm
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ChildOrder {
+    pub(crate) id: String,
+    pub(crate) price: f64,
+    pub(crate) filled: f64,
+    pub(crate) value: f64,
+    pub(crate) open: bool,
+}
+
+/// Fill accounting shared by the algorithms
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Fills {
+    pub(crate) filled: f64,
+    pub(crate) value: f64,
+    pub(crate) orders: Vec<ChildOrder>,
+}
+
+impl Fills {
+    /// Records an order update, counting only what it adds
+    pub(crate) fn record(&mut self, order: &OrderDetails) {
+        let filled = number(&order.filled_amount);
+        let value = match number(&order.filled_total) {
+            total if total > 0.0 => total,
+            _ => {
+                let price = number(&order.avg_deal_price);
+                filled
+                    * if price > 0.0 {
+                        price
+                    } else {
+                        number(&order.price)
+                    }
+            }
+        };
+        let open = order.status == "open";
+        let child = match self.orders.iter_mut().position(|c| c.id == order.id) {
+            Some(index) => &mut self.orders[index],
+            None => {
+                self.orders.push(ChildOrder {
+                    id: order.id.clone(),
+                    price: 0.0,
+                    filled: 0.0,
+                    value: 0.0,
+                    open,
+                });
+                self.orders.last_mut().expect("just pushed")
+            }
+        };
+        // Updates may arrive out of order; fills never shrink
+        if filled >= child.filled {
+            self.filled += filled - child.filled;
+            self.value += value - child.value;
+            child.filled = filled;
+            child.value = value;
+        }
+        child.price = number(&order.price);
+        child.open = open;
+    }
+
+    pub(crate) fn working(&self) -> impl Iterator<Item = &ChildOrder> {
+        self.orders.iter().filter(|c| c.open)
+    }
+
+    pub(crate) fn cancel_working(&self) -> Vec<AlgoAction> {
+        self.working()
+            .map(|c| AlgoAction::Cancel {
+                order_id: c.id.clone(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn progress(&self, status: AlgoStatus, target: f64) -> AlgoProgress {
+        AlgoProgress {
+            status,
+            target,
+            filled: self.filled,
+            avg_price: (self.filled > 0.0).then(|| self.value / self.filled),
+            child_orders: self.orders.len(),
+            working_orders: self.working().count(),
+        }
+    }
+}
+
+/// Actions taken by one step
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct StepReport {
+    /// Actions carried out, or only planned in dry-run mode
+    pub actions: Vec<AlgoAction>,
+    /// Progress after the step
+    pub progress: AlgoProgress,
+}
+
+/// Request carrying out an action
+enum Prepared {
+    Create(CreateOrder),
+    Amend(AmendOrder),
+    Cancel(CancelOrder),
+    /// Dry-run result
+    Simulated(Option<OrderDetails>),
+    /// Amount rounded to nothing
+    Skip,
+}
+
+/// Runs an algorithm against the exchange
+#[derive(Debug)]
+pub struct Execution<A> {
+    algo: A,
+    /// Currency pair of the child orders
+    pub currency_pair: String,
+    /// Account of the child orders
+    pub account: Option<String>,
+    /// Decimal places of child order amounts
+    pub amount_precision: usize,
+    /// Decimal places of child order prices
+    pub price_precision: usize,
+    /// Only report the actions, without sending any request
+    pub dry_run: bool,
+    simulated: usize,
+}
+
+impl<A: Algo> Execution<A> {
+    /// Creates an execution of `algo` on `currency_pair`
+    pub fn new(algo: A, currency_pair: &str) -> Self {
+        Self {
+            algo,
+            currency_pair: currency_pair.to_owned(),
+            account: None,
+            amount_precision: 8,
+            price_precision: 8,
+            dry_run: false,
+            simulated: 0,
+        }
+    }
+
+    /// Sets the account of the child orders
+    pub fn account(mut self, account: &str) -> Self {
+        self.account = Some(account.to_owned());
+        self
+    }
+
+    /// Sets the decimal places of child order amounts and prices
+    pub fn precision(mut self, amount_precision: usize, price_precision: usize) -> Self {
+        self.amount_precision = amount_precision;
+        self.price_precision = price_precision;
+        self
+    }
+
+    /// Enables dry-run mode
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// The algorithm and its state
+    pub fn algo(&self) -> &A {
+        &self.algo
+    }
+
+    /// Returns the algorithm and its state
+    pub fn into_algo(self) -> A {
+        self.algo
+    }
+
+    /// Progress so far
+    pub fn progress(&self) -> AlgoProgress {
+        self.algo.progress()
+    }
+
+    /// Checks an action against the limits and turns it into a request
+    fn prepare(&mut self, action: &AlgoAction, market: &Market) -> Result<Prepared, AlgoError> {
+        let limits = self.algo.child_limits();
+        let price = match action {
+            AlgoAction::Place(intent) => Some(intent.price),
+            AlgoAction::Amend { price, .. } => Some(*price),
+            AlgoAction::Cancel { .. } => None,
+        };
+        if let (Some(price), Some(band)) = (price, limits.price_band)
+            && market.last > 0.0
+            && ((price - market.last) / market.last).abs() > band
+        {
+            return Err(AlgoError::Limit(format!(
+                "price {} is more than {}% away from {}",
+                price,
+                band * 100.0,
+                market.last
+            )));
+        }
+        let price = price.map(|p| format!("{:.*}", self.price_precision, p));
+        match action {
+            AlgoAction::Place(intent) => {
+                if let Some(max) = limits.max_child_orders
+                    && self.algo.progress().child_orders >= max
+                {
+                    return Err(AlgoError::Limit(format!("{} child orders placed", max)));
+                }
+                if let Some(max) = limits.max_child_amount
+                    && intent.amount > max
+                {
+                    return Err(AlgoError::Limit(format!(
+                        "child amount {} above {}",
+                        intent.amount, max
+                    )));
+                }
+                let scale = 10f64.powi(self.amount_precision as i32);
+                let amount = (intent.amount * scale + 1e-9).floor() / scale;
+                if amount <= 0.0 {
+                    return Ok(Prepared::Skip);
+                }
+                let amount = format!("{:.*}", self.amount_precision, amount);
+                let price = price.unwrap_or_default();
+                if self.dry_run {
+                    self.simulated += 1;
+                    return Ok(Prepared::Simulated(Some(
+                        self.simulate(intent, &amount, &price),
+                    )));
+                }
+                let mut request = create_order(&self.currency_pair, &intent.side, &amount)
+                    .price(&price)
+                    .time_in_force(&intent.time_in_force);
+                if let Some(account) = &self.account {
+                    request = request.account(account);
+                }
+                Ok(Prepared::Create(request))
+            }
+            _ if self.dry_run => Ok(Prepared::Simulated(None)),
+            AlgoAction::Amend { order_id, .. } => {
+                let mut request =
+                    amend_order(order_id, &self.currency_pair).price(&price.unwrap_or_default());
+                if let Some(account) = &self.account {
+                    request = request.account(account);
+                }
+                Ok(Prepared::Amend(request))
+            }
+            AlgoAction::Cancel { order_id } => Ok(Prepared::Cancel(cancel_order(
+                order_id,
+                &self.currency_pair,
+            ))),
+        }
+    }
+
+    /// Child order filled at its price, as assumed in dry-run mode
+    fn simulate(&self, intent: &OrderIntent, amount: &str, price: &str) -> OrderDetails {
+        let total = number(amount) * number(price);
+        OrderDetails {
+            id: format!("dry-{}", self.simulated),
+            text: String::new(),
+            amend_text: String::new(),
+            create_time_ms: now_ms(),
+            update_time_ms: now_ms(),
+            status: "closed".into(),
+            currency_pair: self.currency_pair.clone(),
+            order_type: "limit".into(),
+            account: self.account.clone().unwrap_or_default(),
+            side: intent.side.clone(),
+            amount: amount.to_owned(),
+            price: price.to_owned(),
+            time_in_force: intent.time_in_force.clone(),
+            left: "0".into(),
+            filled_amount: amount.to_owned(),
+            filled_total: total.to_string(),
+            avg_deal_price: price.to_owned(),
+            fee: "0".into(),
+            fee_currency: String::new(),
+            finish_as: "filled".into(),
+        }
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<A: Algo> Execution<A> {
+    /// Refreshes the working child orders, then carries out the next actions
+    pub fn step<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+        market: &Market,
+    ) -> Result<StepReport, AlgoError> {
+        if !self.dry_run {
+            for id in self.algo.working_orders() {
+                let order = crate::utils::fetch(
+                    client,
+                    crate::api::spot::get_order(&id, &self.currency_pair),
+                )?;
+                self.algo.on_order(&order);
+            }
+        }
+        let actions = self.algo.next_actions(market, now_ms());
+        self.execute(client, &actions, market)?;
+        Ok(StepReport {
+            actions,
+            progress: self.algo.progress(),
+        })
+    }
+
+    /// Steps on a polled ticker every `interval` until the algorithm is done
+    pub fn run<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+        interval: std::time::Duration,
+    ) -> Result<AlgoProgress, AlgoError> {
+        loop {
+            let ticker = crate::api::spot::get_ticker().currency_pair(&self.currency_pair);
+            let body = client.send(ticker)?.into_body_str()?;
+            let report = self.step(client, &Market::from_ticker(&body)?)?;
+            if report.progress.status.is_terminal() {
+                return Ok(report.progress);
+            }
+            std::thread::sleep(interval);
+        }
+    }
+
+    /// Stops the algorithm and cancels its working child orders
+    pub fn stop<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+    ) -> Result<StepReport, AlgoError> {
+        let actions = self.algo.stop();
+        self.execute(client, &actions, &Market::new(0.0))?;
+        Ok(StepReport {
+            actions,
+            progress: self.algo.progress(),
+        })
+    }
+
+    fn execute<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+        actions: &[AlgoAction],
+        market: &Market,
+    ) -> Result<(), AlgoError> {
+        for action in actions {
+            let order = match self.prepare(action, market)? {
+                Prepared::Create(request) => Some(crate::utils::fetch(client, request)?),
+                Prepared::Amend(request) => Some(crate::utils::fetch(client, request)?),
+                Prepared::Cancel(request) => Some(crate::utils::fetch(client, request)?),
+                Prepared::Simulated(order) => order,
+                Prepared::Skip => None,
+            };
+            if let Some(order) = order {
+                self.algo.on_order(&order);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl<A: Algo> Execution<A> {
+    /// Refreshes the working child orders, then carries out the next actions
+    pub async fn step_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+        market: &Market,
+    ) -> Result<StepReport, AlgoError> {
+        if !self.dry_run {
+            for id in self.algo.working_orders() {
+                let request = crate::api::spot::get_order(&id, &self.currency_pair);
+                let order = crate::utils::fetch_async(client, request).await?;
+                self.algo.on_order(&order);
+            }
+        }
+        let actions = self.algo.next_actions(market, now_ms());
+        self.execute_async(client, &actions, market).await?;
+        Ok(StepReport {
+            actions,
+            progress: self.algo.progress(),
+        })
+    }
+
+    /// Steps on a polled ticker every `interval` until the algorithm is done
+    pub async fn run_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+        interval: std::time::Duration,
+    ) -> Result<AlgoProgress, AlgoError> {
+        loop {
+            let ticker = crate::api::spot::get_ticker().currency_pair(&self.currency_pair);
+            let body = client.send(ticker).await?.into_body_str().await?;
+            let report = self
+                .step_async(client, &Market::from_ticker(&body)?)
+                .await?;
+            if report.progress.status.is_terminal() {
+                return Ok(report.progress);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Stops the algorithm and cancels its working child orders
+    pub async fn stop_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<StepReport, AlgoError> {
+        let actions = self.algo.stop();
+        self.execute_async(client, &actions, &Market::new(0.0))
+            .await?;
+        Ok(StepReport {
+            actions,
+            progress: self.algo.progress(),
+        })
+    }
+
+    async fn execute_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+        actions: &[AlgoAction],
+        market: &Market,
+    ) -> Result<(), AlgoError> {
+        for action in actions {
+            let order = match self.prepare(action, market)? {
+                Prepared::Create(request) => {
+                    Some(crate::utils::fetch_async(client, request).await?)
+                }
+                Prepared::Amend(request) => Some(crate::utils::fetch_async(client, request).await?),
+                Prepared::Cancel(request) => {
+                    Some(crate::utils::fetch_async(client, request).await?)
+                }
+                Prepared::Simulated(order) => order,
+                Prepared::Skip => None,
+            };
+            if let Some(order) = order {
+                self.algo.on_order(&order);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error of an execution step
+#[derive(Debug)]
+pub enum AlgoError {
+    /// Request to the Gate API failed
+    Gate(Box<GateError>),
+    /// A child order broke the configured limits; nothing was sent for it
+    Limit(String),
+}
+
+impl fmt::Display for AlgoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlgoError::Gate(e) => write!(f, "Gate API error: {}", e),
+            AlgoError::Limit(e) => write!(f, "Limit exceeded: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AlgoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AlgoError::Gate(e) => Some(e.as_ref()),
+            AlgoError::Limit(_) => None,
+        }
+    }
+}
+
+impl From<GateError> for AlgoError {
+    fn from(err: GateError) -> Self {
+        AlgoError::Gate(Box::new(err))
+    }
+}
+
+impl From<Box<GateError>> for AlgoError {
+    fn from(err: Box<GateError>) -> Self {
+        AlgoError::Gate(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn market_from_ticker() {
+        let market = Market::from_ticker(
+            r#"[{"currency_pair":"BTC_USDT","last":"100","highest_bid":"99.5","lowest_ask":""}]"#,
+        )
+        .unwrap();
+        assert_eq!(market.last, 100.0);
+        assert_eq!(market.bid, Some(99.5));
+        assert_eq!(market.ask, None);
+    }
+
+    #[test]
+    fn ticker_without_positive_prices_is_invalid() {
+        for body in [
+            "[]",
+            r#"[{"last":""}]"#,
+            r#"[{"last":"abc"}]"#,
+            r#"[{"last":"0"}]"#,
+            r#"[{"last":"-1"}]"#,
+            r#"[{"last":"100","highest_bid":"0"}]"#,
+            r#"[{"last":"100","highest_bid":"abc"}]"#,
+            r#"[{"last":"100","lowest_ask":"-1"}]"#,
+            r#"[{"last":"100","lowest_ask":"NaN"}]"#,
+        ] {
+            assert!(
+                matches!(Market::from_ticker(body), Err(GateError::InvalidBody(_))),
+                "{body}"
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::{Algo, AlgoAction, AlgoLimits, AlgoProgress, AlgoStatus, Fills, Market, OrderIntent};
+use crate::api::spot::OrderDetails;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Executes an amount over a time window, following a volume profile.
+///
+/// The window is cut into buckets of equal length, each weighted by the
+/// profile: a flat profile gives a TWAP, a profile of the volume usually
+/// traded in each bucket gives a VWAP. At the start of each bucket, its share
+/// is released and sent as an immediate-or-cancel child order; whatever is
+/// not filled is carried over to the next step.
+///
+/// ```rust
+/// use gateio_rs::algo::{Algo, AlgoAction, Market, Slicer};
+/// use std::time::Duration;
+///
+/// // 3 BTC over 3 minutes, traded twice as much in the last minute
+/// let mut vwap = Slicer::vwap("sell", 3.0, 0, Duration::from_secs(180), vec![1.0, 1.0, 2.0]);
+/// assert_eq!(vwap.target_at(0), 0.75);
+/// assert_eq!(vwap.target_at(60_000), 1.5);
+/// assert_eq!(vwap.target_at(120_000), 3.0);
+///
+/// let actions = vwap.next_actions(&Market::new(30_000.0), 0);
+/// assert!(matches!(&actions[..], [AlgoAction::Place(order)] if order.amount == 0.75));
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Slicer {
+    /// Side of the child orders
+    pub side: String,
+    /// Amount to execute
+    pub amount: f64,
+    /// Start of the window, in milliseconds since the epoch
+    pub start_ms: i64,
+    /// End of the window, in milliseconds since the epoch
+    pub end_ms: i64,
+    /// Weight of each bucket of the window
+    pub profile: Vec<f64>,
+    /// Limits of the child orders
+    pub limits: AlgoLimits,
+    stopped: bool,
+    fills: Fills,
+}
+
+impl Slicer {
+    /// Creates a TWAP cutting `duration` into `slices` equal parts
+    pub fn twap(side: &str, amount: f64, start_ms: i64, duration: Duration, slices: usize) -> Self {
+        Self::vwap(side, amount, start_ms, duration, vec![1.0; slices.max(1)])
+    }
+
+    /// Creates a VWAP releasing the amount following `profile`
+    pub fn vwap(
+        side: &str,
+        amount: f64,
+        start_ms: i64,
+        duration: Duration,
+        profile: Vec<f64>,
+    ) -> Self {
+        let profile = if profile.iter().any(|w| *w > 0.0) {
+            profile.into_iter().map(|w| w.max(0.0)).collect()
+        } else {
+            vec![1.0]
+        };
+        Self {
+            side: side.to_owned(),
+            amount,
+            start_ms,
+            end_ms: start_ms + duration.as_millis() as i64,
+            profile,
+            limits: AlgoLimits::new(),
+            stopped: false,
+            fills: Fills::default(),
+        }
+    }
+
+    /// Sets the limits of the child orders
+    pub fn limits(mut self, limits: AlgoLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Amount released for execution by `now_ms`
+    pub fn target_at(&self, now_ms: i64) -> f64 {
+        if now_ms < self.start_ms {
+            return 0.0;
+        }
+        let buckets = self.profile.len();
+        let window = (self.end_ms - self.start_ms).max(1);
+        let bucket = ((now_ms - self.start_ms) as f64 / window as f64 * buckets as f64) as usize;
+        let total: f64 = self.profile.iter().sum();
+        let released: f64 = self.profile.iter().take(bucket + 1).sum();
+        self.amount * (released / total).min(1.0)
+    }
+
+    fn remaining(&self) -> f64 {
+        self.amount - self.fills.filled
+    }
+}
+
+impl Algo for Slicer {
+    fn next_actions(&mut self, market: &Market, now_ms: i64) -> Vec<AlgoAction> {
+        if self.stopped
+            || now_ms < self.start_ms
+            || now_ms >= self.end_ms
+            || self.fills.working().next().is_some()
+        {
+            return Vec::new();
+        }
+        let behind = self.target_at(now_ms) - self.fills.filled;
+        if self.limits.is_dust(behind, self.amount) {
+            return Vec::new();
+        }
+        vec![AlgoAction::Place(OrderIntent {
+            side: self.side.clone(),
+            amount: self.limits.cap(behind),
+            price: market.marketable_price(&self.side, self.limits.max_slippage),
+            time_in_force: "ioc".into(),
+        })]
+    }
+
+    fn on_order(&mut self, order: &OrderDetails) {
+        self.fills.record(order);
+    }
+
+    fn stop(&mut self) -> Vec<AlgoAction> {
+        self.stopped = true;
+        self.fills.cancel_working()
+    }
+
+    fn progress(&self) -> AlgoProgress {
+        let status = if self.limits.is_dust(self.remaining(), self.amount) {
+            AlgoStatus::Completed
+        } else if self.stopped {
+            AlgoStatus::Stopped
+        } else if crate::utils::now_ms() >= self.end_ms {
+            AlgoStatus::Expired
+        } else if crate::utils::now_ms() >= self.start_ms {
+            AlgoStatus::Running
+        } else {
+            AlgoStatus::Pending
+        };
+        self.fills.progress(status, self.amount)
+    }
+
+    fn child_limits(&self) -> &AlgoLimits {
+        &self.limits
+    }
+
+    fn working_orders(&self) -> Vec<String> {
+        self.fills.working().map(|c| c.id.clone()).collect()
+    }
+}
@@ -0,0 +1,171 @@
+use super::{Algo, AlgoAction, AlgoLimits, AlgoProgress, AlgoStatus, Fills, Market, OrderIntent};
+use crate::api::spot::OrderDetails;
+use serde::{Deserialize, Serialize};
+
+/// Distance between the best price and the stop
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Trail {
+    /// Fraction of the best price, e.g. `0.02` for 2%
+    Percent(f64),
+    /// Fixed price distance
+    Absolute(f64),
+}
+
+/// Stop following the price as it moves favourably.
+///
+/// A `sell` stop protects a long position: it tracks the highest price and
+/// sells once the price falls `trail` below it. A `buy` stop tracks the
+/// lowest price and buys once the price rises `trail` above it.
+///
+/// ```rust
+/// use gateio_rs::algo::{Algo, AlgoAction, Market, Trail, TrailingStop};
+///
+/// let mut stop = TrailingStop::new("sell", 1.0, Trail::Absolute(100.0));
+/// assert!(stop.next_actions(&Market::new(30_000.0), 0).is_empty());
+/// assert!(stop.next_actions(&Market::new(30_500.0), 0).is_empty());
+/// assert_eq!(stop.stop_price(), Some(30_400.0));
+///
+/// let actions = stop.next_actions(&Market::new(30_390.0), 0);
+/// assert!(matches!(&actions[..], [AlgoAction::Place(order)] if order.side == "sell"));
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TrailingStop {
+    /// Side of the stop order
+    pub side: String,
+    /// Amount to execute once triggered
+    pub amount: f64,
+    /// Distance between the best price and the stop
+    pub trail: Trail,
+    /// Price the trailing starts at, immediately when `None`
+    pub activation_price: Option<f64>,
+    /// Limits of the child orders
+    pub limits: AlgoLimits,
+    /// Best price since activation
+    best: Option<f64>,
+    triggered: bool,
+    stopped: bool,
+    fills: Fills,
+}
+
+impl TrailingStop {
+    /// Creates a trailing stop executing `amount` on `side`
+    pub fn new(side: &str, amount: f64, trail: Trail) -> Self {
+        Self {
+            side: side.to_owned(),
+            amount,
+            trail,
+            activation_price: None,
+            limits: AlgoLimits::new(),
+            best: None,
+            triggered: false,
+            stopped: false,
+            fills: Fills::default(),
+        }
+    }
+
+    /// Starts trailing once the price reaches `price`
+    pub fn activation_price(mut self, price: f64) -> Self {
+        self.activation_price = Some(price);
+        self
+    }
+
+    /// Sets the limits of the child orders
+    pub fn limits(mut self, limits: AlgoLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Current stop price, once trailing started
+    pub fn stop_price(&self) -> Option<f64> {
+        let best = self.best?;
+        let distance = match self.trail {
+            Trail::Percent(fraction) => best * fraction,
+            Trail::Absolute(distance) => distance,
+        };
+        Some(if self.side == "sell" {
+            best - distance
+        } else {
+            best + distance
+        })
+    }
+
+    /// Whether `price` is better than `than` for the position being protected
+    fn favours(&self, price: f64, than: f64) -> bool {
+        if self.side == "sell" {
+            price >= than
+        } else {
+            price <= than
+        }
+    }
+
+    fn remaining(&self) -> f64 {
+        self.amount - self.fills.filled
+    }
+}
+
+impl Algo for TrailingStop {
+    fn next_actions(&mut self, market: &Market, _now_ms: i64) -> Vec<AlgoAction> {
+        if self.stopped || self.limits.is_dust(self.remaining(), self.amount) {
+            return Vec::new();
+        }
+        if !self.triggered {
+            let activated = self.best.is_some()
+                || self
+                    .activation_price
+                    .is_none_or(|price| self.favours(market.last, price));
+            if !activated {
+                return Vec::new();
+            }
+            if self.best.is_none_or(|best| self.favours(market.last, best)) {
+                self.best = Some(market.last);
+            }
+            match self.stop_price() {
+                // The price fell back to the stop
+                Some(stop) if self.favours(stop, market.last) => {
+                    self.triggered = true;
+                }
+                _ => return Vec::new(),
+            }
+        }
+        // Triggered: execute what is left, retrying unfilled IOC remainders
+        if self.fills.working().next().is_some() {
+            return Vec::new();
+        }
+        vec![AlgoAction::Place(OrderIntent {
+            side: self.side.clone(),
+            amount: self.limits.cap(self.remaining()),
+            price: market.marketable_price(&self.side, self.limits.max_slippage),
+            time_in_force: "ioc".into(),
+        })]
+    }
+
+    fn on_order(&mut self, order: &OrderDetails) {
+        self.fills.record(order);
+    }
+
+    fn stop(&mut self) -> Vec<AlgoAction> {
+        self.stopped = true;
+        self.fills.cancel_working()
+    }
+
+    fn progress(&self) -> AlgoProgress {
+        let status = if self.limits.is_dust(self.remaining(), self.amount) {
+            AlgoStatus::Completed
+        } else if self.stopped {
+            AlgoStatus::Stopped
+        } else if self.best.is_some() {
+            AlgoStatus::Running
+        } else {
+            AlgoStatus::Pending
+        };
+        self.fills.progress(status, self.amount)
+    }
+
+    fn child_limits(&self) -> &AlgoLimits {
+        &self.limits
+    }
+
+    fn working_orders(&self) -> Vec<String> {
+        self.fills.working().map(|c| c.id.clone()).collect()
+    }
+}
@@ -0,0 +1,9 @@
+//! Endpoints and models of the Gate v4 API.
+//!
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+//!
+//! Only the operations of the spec are covered; the pinned
+//! `xtask/spec/openapi.yaml` is an excerpt with the public spot market endpoints.
+
+pub mod models;
+pub mod spot;
@@ -0,0 +1,141 @@
+//! Models of the Gate v4 API.
+//!
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use serde::{Deserialize, Serialize};
+
+/// Spot currency pair
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyPair {
+    /// Currency pair
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Base currency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Quote currency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+    /// Trading fee
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<String>,
+    /// Minimum amount of base currency to trade, `null` means no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_base_amount: Option<String>,
+    /// Minimum amount of quote currency to trade, `null` means no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_quote_amount: Option<String>,
+    /// Maximum amount of base currency to trade, `null` means no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_base_amount: Option<String>,
+    /// Maximum amount of quote currency to trade, `null` means no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_quote_amount: Option<String>,
+    /// Amount scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_precision: Option<i64>,
+    /// Price scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<i64>,
+    /// How currency pair can be traded
+    ///
+    /// - untradable: cannot be bought or sold
+    /// - buyable: can be bought
+    /// - sellable: can be sold
+    /// - tradable: can be bought or sold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_status: Option<String>,
+    /// Sell start unix timestamp in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sell_start: Option<i64>,
+    /// Buy start unix timestamp in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buy_start: Option<i64>,
+}
+
+/// `Ticker` model
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ticker {
+    /// Currency pair
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_pair: Option<String>,
+    /// Last trading price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last: Option<String>,
+    /// Recent lowest ask
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lowest_ask: Option<String>,
+    /// Recent highest bid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highest_bid: Option<String>,
+    /// Change percentage in the last 24h
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_percentage: Option<String>,
+    /// Base currency trade volume in the last 24h
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_volume: Option<String>,
+    /// Quote currency trade volume in the last 24h
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_volume: Option<String>,
+    /// Highest price in 24h
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_24h: Option<String>,
+    /// Lowest price in 24h
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_24h: Option<String>,
+}
+
+/// `OrderBook` model
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrderBook {
+    /// Order book ID, which is updated whenever the order book is changed. Valid only when `with_id` is set to `true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// The timestamp of the response data being generated (in milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<i64>,
+    /// The timestamp of when the orderbook last changed (in milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update: Option<i64>,
+    /// Asks order depth
+    pub asks: Vec<Vec<String>>,
+    /// Bids order depth
+    pub bids: Vec<Vec<String>>,
+}
+
+/// `Trade` model
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trade {
+    /// Trade ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Trading time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+    /// Trading time, with millisecond precision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create_time_ms: Option<String>,
+    /// Currency pair
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_pair: Option<String>,
+    /// Order side
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    /// Trade amount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    /// Order price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    /// Represents a unique and consecutive trade ID within a single market. It is used to track and identify trades in the specific market
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_id: Option<String>,
+}
+
+/// `SystemTime` model
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SystemTime {
+    /// Server current time(ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_time: Option<i64>,
+}
@@ -0,0 +1,58 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// Get details of a specific currency pair
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/currency_pairs/{currency_pair}`
+///
+/// Responds with [`GetCurrencyPairResponse`].
+#[derive(Clone, Debug)]
+pub struct GetCurrencyPair {
+    /// Currency pair
+    pub currency_pair: String,
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`GetCurrencyPair`]
+pub type GetCurrencyPairResponse = crate::api::generated::models::CurrencyPair;
+
+impl GetCurrencyPair {
+    /// Creates a new GetCurrencyPair request
+    pub fn new(currency_pair: &str) -> Self {
+        Self {
+            currency_pair: currency_pair.into(),
+            credentials: None,
+        }
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+/// Get details of a specific currency pair
+pub fn get_currency_pair(currency_pair: &str) -> GetCurrencyPair {
+    GetCurrencyPair::new(currency_pair)
+}
+
+impl From<GetCurrencyPair> for Request {
+    fn from(request: GetCurrencyPair) -> Request {
+        let params = Vec::new();
+
+        Request {
+            method: Method::Get,
+            path: format!("/api/v4/spot/currency_pairs/{}", request.currency_pair),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
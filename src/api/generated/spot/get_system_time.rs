@@ -0,0 +1,59 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// Get server current time
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/time`
+///
+/// Responds with [`GetSystemTimeResponse`].
+#[derive(Clone, Debug)]
+pub struct GetSystemTime {
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`GetSystemTime`]
+pub type GetSystemTimeResponse = crate::api::generated::models::SystemTime;
+
+impl GetSystemTime {
+    /// Creates a new GetSystemTime request
+    pub fn new() -> Self {
+        Self { credentials: None }
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+impl Default for GetSystemTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get server current time
+pub fn get_system_time() -> GetSystemTime {
+    GetSystemTime::new()
+}
+
+impl From<GetSystemTime> for Request {
+    fn from(request: GetSystemTime) -> Request {
+        let params = Vec::new();
+
+        Request {
+            method: Method::Get,
+            path: "/api/v4/spot/time".into(),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
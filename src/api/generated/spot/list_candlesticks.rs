@@ -0,0 +1,114 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// Market candlesticks
+///
+/// Maximum of 1000 points can be returned in a query. Be sure not to exceed the limit when specifying from, to and interval
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/candlesticks`
+///
+/// Responds with [`ListCandlesticksResponse`].
+#[derive(Clone, Debug)]
+pub struct ListCandlesticks {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Maximum recent data points to return. `limit` is conflicted with `from` and `to`. If either `from` or `to` is specified, request will be rejected.
+    pub limit: Option<i64>,
+    /// Start time of candlesticks, formatted in Unix timestamp in seconds. Default to`to - 100 * interval` if not specified
+    pub from: Option<i64>,
+    /// End time of candlesticks, formatted in Unix timestamp in seconds. Default to current time
+    pub to: Option<i64>,
+    /// Interval time between data points. Note that `30d` means 1 natual month, not 30 days
+    pub interval: Option<String>,
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`ListCandlesticks`]
+pub type ListCandlesticksResponse = Vec<Vec<String>>;
+
+impl ListCandlesticks {
+    /// Creates a new ListCandlesticks request
+    pub fn new(currency_pair: &str) -> Self {
+        Self {
+            currency_pair: currency_pair.into(),
+            limit: None,
+            from: None,
+            to: None,
+            interval: None,
+            credentials: None,
+        }
+    }
+
+    /// Maximum recent data points to return. `limit` is conflicted with `from` and `to`. If either `from` or `to` is specified, request will be rejected.
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Start time of candlesticks, formatted in Unix timestamp in seconds. Default to`to - 100 * interval` if not specified
+    pub fn from(mut self, from: i64) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// End time of candlesticks, formatted in Unix timestamp in seconds. Default to current time
+    pub fn to(mut self, to: i64) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Interval time between data points. Note that `30d` means 1 natual month, not 30 days
+    pub fn interval(mut self, interval: &str) -> Self {
+        self.interval = Some(interval.into());
+        self
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+/// Market candlesticks
+pub fn list_candlesticks(currency_pair: &str) -> ListCandlesticks {
+    ListCandlesticks::new(currency_pair)
+}
+
+impl From<ListCandlesticks> for Request {
+    fn from(request: ListCandlesticks) -> Request {
+        let mut params = Vec::new();
+        let value = request.currency_pair;
+        params.push(("currency_pair".into(), value));
+
+        if let Some(value) = request.limit {
+            params.push(("limit".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.from {
+            params.push(("from".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.to {
+            params.push(("to".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.interval {
+            params.push(("interval".into(), value));
+        }
+
+        Request {
+            method: Method::Get,
+            path: "/api/v4/spot/candlesticks".into(),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// List all currency pairs supported
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/currency_pairs`
+///
+/// Responds with [`ListCurrencyPairsResponse`].
+#[derive(Clone, Debug)]
+pub struct ListCurrencyPairs {
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`ListCurrencyPairs`]
+pub type ListCurrencyPairsResponse = Vec<crate::api::generated::models::CurrencyPair>;
+
+impl ListCurrencyPairs {
+    /// Creates a new ListCurrencyPairs request
+    pub fn new() -> Self {
+        Self { credentials: None }
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+impl Default for ListCurrencyPairs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// List all currency pairs supported
+pub fn list_currency_pairs() -> ListCurrencyPairs {
+    ListCurrencyPairs::new()
+}
+
+impl From<ListCurrencyPairs> for Request {
+    fn from(request: ListCurrencyPairs) -> Request {
+        let params = Vec::new();
+
+        Request {
+            method: Method::Get,
+            path: "/api/v4/spot/currency_pairs".into(),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
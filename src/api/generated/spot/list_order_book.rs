@@ -0,0 +1,101 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// Retrieve order book
+///
+/// Order book will be sorted by price from high to low on bids; low to high on asks
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/order_book`
+///
+/// Responds with [`ListOrderBookResponse`].
+#[derive(Clone, Debug)]
+pub struct ListOrderBook {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Order depth. 0 means no aggregation is applied. default to 0
+    pub interval: Option<String>,
+    /// Maximum number of order depth data in asks or bids
+    pub limit: Option<i64>,
+    /// Return order book ID
+    pub with_id: Option<bool>,
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`ListOrderBook`]
+pub type ListOrderBookResponse = crate::api::generated::models::OrderBook;
+
+impl ListOrderBook {
+    /// Creates a new ListOrderBook request
+    pub fn new(currency_pair: &str) -> Self {
+        Self {
+            currency_pair: currency_pair.into(),
+            interval: None,
+            limit: None,
+            with_id: None,
+            credentials: None,
+        }
+    }
+
+    /// Order depth. 0 means no aggregation is applied. default to 0
+    pub fn interval(mut self, interval: &str) -> Self {
+        self.interval = Some(interval.into());
+        self
+    }
+
+    /// Maximum number of order depth data in asks or bids
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Return order book ID
+    pub fn with_id(mut self, with_id: bool) -> Self {
+        self.with_id = Some(with_id);
+        self
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+/// Retrieve order book
+pub fn list_order_book(currency_pair: &str) -> ListOrderBook {
+    ListOrderBook::new(currency_pair)
+}
+
+impl From<ListOrderBook> for Request {
+    fn from(request: ListOrderBook) -> Request {
+        let mut params = Vec::new();
+        let value = request.currency_pair;
+        params.push(("currency_pair".into(), value));
+
+        if let Some(value) = request.interval {
+            params.push(("interval".into(), value));
+        }
+
+        if let Some(value) = request.limit {
+            params.push(("limit".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.with_id {
+            params.push(("with_id".into(), value.to_string()));
+        }
+
+        Request {
+            method: Method::Get,
+            path: "/api/v4/spot/order_book".into(),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
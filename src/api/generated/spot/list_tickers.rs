@@ -0,0 +1,89 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// Retrieve ticker information
+///
+/// Return only related data if `currency_pair` is specified; otherwise return all of them
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/tickers`
+///
+/// Responds with [`ListTickersResponse`].
+#[derive(Clone, Debug)]
+pub struct ListTickers {
+    /// Currency pair
+    pub currency_pair: Option<String>,
+    /// Timezone
+    pub timezone: Option<String>,
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`ListTickers`]
+pub type ListTickersResponse = Vec<crate::api::generated::models::Ticker>;
+
+impl ListTickers {
+    /// Creates a new ListTickers request
+    pub fn new() -> Self {
+        Self {
+            currency_pair: None,
+            timezone: None,
+            credentials: None,
+        }
+    }
+
+    /// Currency pair
+    pub fn currency_pair(mut self, currency_pair: &str) -> Self {
+        self.currency_pair = Some(currency_pair.into());
+        self
+    }
+
+    /// Timezone
+    pub fn timezone(mut self, timezone: &str) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+impl Default for ListTickers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Retrieve ticker information
+pub fn list_tickers() -> ListTickers {
+    ListTickers::new()
+}
+
+impl From<ListTickers> for Request {
+    fn from(request: ListTickers) -> Request {
+        let mut params = Vec::new();
+
+        if let Some(value) = request.currency_pair {
+            params.push(("currency_pair".into(), value));
+        }
+
+        if let Some(value) = request.timezone {
+            params.push(("timezone".into(), value));
+        }
+
+        Request {
+            method: Method::Get,
+            path: "/api/v4/spot/tickers".into(),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+use crate::http::{Credentials, Method, request::Request};
+
+/// Retrieve market trades
+///
+/// Supports `from` and `to` by time range query or page-turn query based on `last_id`.
+/// By default query by time range is the last 30 days.
+///
+/// # API Endpoint
+/// `GET /api/v4/spot/trades`
+///
+/// Responds with [`ListTradesResponse`].
+#[derive(Clone, Debug)]
+pub struct ListTrades {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Maximum number of records to be returned in a single list. Default 100, maximum 1000
+    pub limit: Option<i32>,
+    /// Specify list staring point using the `id` of last record in previous list-query results
+    pub last_id: Option<String>,
+    /// Whether the id of records to be retrieved should be less than the last_id specified. Default to false.
+    pub reverse: Option<bool>,
+    /// Start timestamp of the query
+    pub from: Option<i64>,
+    /// Time range ending, default to current time
+    pub to: Option<i64>,
+    /// Page number
+    pub page: Option<i32>,
+    /// API credentials for authentication
+    pub credentials: Option<Credentials>,
+}
+
+/// Response of [`ListTrades`]
+pub type ListTradesResponse = Vec<crate::api::generated::models::Trade>;
+
+impl ListTrades {
+    /// Creates a new ListTrades request
+    pub fn new(currency_pair: &str) -> Self {
+        Self {
+            currency_pair: currency_pair.into(),
+            limit: None,
+            last_id: None,
+            reverse: None,
+            from: None,
+            to: None,
+            page: None,
+            credentials: None,
+        }
+    }
+
+    /// Maximum number of records to be returned in a single list. Default 100, maximum 1000
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Specify list staring point using the `id` of last record in previous list-query results
+    pub fn last_id(mut self, last_id: &str) -> Self {
+        self.last_id = Some(last_id.into());
+        self
+    }
+
+    /// Whether the id of records to be retrieved should be less than the last_id specified. Default to false.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = Some(reverse);
+        self
+    }
+
+    /// Start timestamp of the query
+    pub fn from(mut self, from: i64) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Time range ending, default to current time
+    pub fn to(mut self, to: i64) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Page number
+    pub fn page(mut self, page: i32) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// Sets the API credentials for authentication
+    pub fn credentials(mut self, creds: Credentials) -> Self {
+        self.credentials = Some(creds);
+        self
+    }
+}
+
+/// Retrieve market trades
+pub fn list_trades(currency_pair: &str) -> ListTrades {
+    ListTrades::new(currency_pair)
+}
+
+impl From<ListTrades> for Request {
+    fn from(request: ListTrades) -> Request {
+        let mut params = Vec::new();
+        let value = request.currency_pair;
+        params.push(("currency_pair".into(), value));
+
+        if let Some(value) = request.limit {
+            params.push(("limit".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.last_id {
+            params.push(("last_id".into(), value));
+        }
+
+        if let Some(value) = request.reverse {
+            params.push(("reverse".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.from {
+            params.push(("from".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.to {
+            params.push(("to".into(), value.to_string()));
+        }
+
+        if let Some(value) = request.page {
+            params.push(("page".into(), value.to_string()));
+        }
+
+        Request {
+            method: Method::Get,
+            path: "/api/v4/spot/trades".into(),
+            params,
+            payload: "".to_string(),
+            x_gate_exp_time: None,
+            credentials: request.credentials,
+            sign: false,
+            timeout: None,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! `Spot` endpoints of the Gate v4 API.
+//!
+//! Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.
+
+mod get_currency_pair;
+pub use get_currency_pair::*;
+mod get_system_time;
+pub use get_system_time::*;
+mod list_candlesticks;
+pub use list_candlesticks::*;
+mod list_currency_pairs;
+pub use list_currency_pairs::*;
+mod list_order_book;
+pub use list_order_book::*;
+mod list_tickers;
+pub use list_tickers::*;
+mod list_trades;
+pub use list_trades::*;
@@ -1 +1,4 @@
 pub mod spot;
+
+/// Endpoints generated from the pinned OpenAPI spec by `cargo xtask codegen`
+pub mod generated;
@@ -1,5 +1,9 @@
+use super::client_order_id::ClientOrderId;
+use super::model::OrderDetails;
+use crate::batch::{Batch, BatchLimits};
 use crate::http::{Credentials, Method, request::Request};
 use serde::Serialize;
+use std::time::Duration;
 
 /// Single order amendment parameters
 #[derive(Debug, Clone, Serialize)]
@@ -19,7 +23,7 @@ pub struct OrderAmendment {
     pub price: Option<String>,
     /// Custom amendment text
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub amend_text: Option<String>,
+    pub amend_text: Option<ClientOrderId>,
 }
 
 impl OrderAmendment {
@@ -54,8 +58,8 @@ impl OrderAmendment {
     }
 
     /// Set amendment text
-    pub fn amend_text(mut self, amend_text: &str) -> Self {
-        self.amend_text = Some(amend_text.to_owned());
+    pub fn amend_text(mut self, amend_text: ClientOrderId) -> Self {
+        self.amend_text = Some(amend_text);
         self
     }
 }
@@ -66,6 +70,8 @@ pub struct AmendBatchOrders {
     pub orders: Vec<OrderAmendment>,
     /// Request expiration time
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials
     pub credentials: Option<Credentials>,
 }
@@ -76,6 +82,7 @@ impl AmendBatchOrders {
         Self {
             orders,
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -86,6 +93,12 @@ impl AmendBatchOrders {
         self
     }
 
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Set API credentials
     pub fn credentials(mut self, creds: Credentials) -> Self {
         self.credentials = Some(creds);
@@ -93,6 +106,32 @@ impl AmendBatchOrders {
     }
 }
 
+impl Batch for AmendBatchOrders {
+    type Item = OrderAmendment;
+    type Output = OrderDetails;
+
+    fn limits() -> BatchLimits {
+        BatchLimits::new(5)
+    }
+
+    fn take_items(&mut self) -> Vec<OrderAmendment> {
+        std::mem::take(&mut self.orders)
+    }
+
+    fn with_items(&self, orders: Vec<OrderAmendment>) -> Self {
+        Self {
+            orders,
+            x_gate_exp_time: self.x_gate_exp_time,
+            timeout: self.timeout,
+            credentials: self.credentials.clone(),
+        }
+    }
+
+    fn currency_pair(order: &OrderAmendment) -> &str {
+        &order.currency_pair
+    }
+}
+
 impl From<AmendBatchOrders> for Request {
     fn from(request: AmendBatchOrders) -> Request {
         let params = Vec::new();
@@ -106,6 +145,7 @@ impl From<AmendBatchOrders> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
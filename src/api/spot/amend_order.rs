@@ -1,5 +1,7 @@
+use super::client_order_id::ClientOrderId;
 use crate::http::{Credentials, Method, request::Request};
 use serde_json::{Map, Value, json};
+use std::time::Duration;
 
 /// Order amendment request
 pub struct AmendOrder {
@@ -14,11 +16,13 @@ pub struct AmendOrder {
     /// Order price
     pub price: Option<String>,
     /// Custom amendment text
-    pub amend_text: Option<String>,
+    pub amend_text: Option<ClientOrderId>,
     /// Processing mode
     pub action_mode: Option<String>,
     /// Request expiration time
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials
     pub credentials: Option<Credentials>,
 }
@@ -35,6 +39,7 @@ impl AmendOrder {
             amend_text: None,
             action_mode: None,
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -58,8 +63,8 @@ impl AmendOrder {
     }
 
     /// Set amendment text
-    pub fn amend_text(mut self, amend_text: &str) -> Self {
-        self.amend_text = Some(amend_text.into());
+    pub fn amend_text(mut self, amend_text: ClientOrderId) -> Self {
+        self.amend_text = Some(amend_text);
         self
     }
 
@@ -71,7 +76,13 @@ impl AmendOrder {
 
     /// Set expiration time
     pub fn x_gate_exp_time(mut self, x_gate_exp_time: u128) -> Self {
-        self.x_gate_exp_time = Some(x_gate_exp_time.into());
+        self.x_gate_exp_time = Some(x_gate_exp_time);
+        self
+    }
+
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
         self
     }
 
@@ -113,12 +124,13 @@ impl From<AmendOrder> for Request {
 
         Request {
             method: Method::Patch,
-            path: format!("/api/v4/spot/orders/{}", request.order_id).into(),
+            path: format!("/api/v4/spot/orders/{}", request.order_id),
             params,
             payload: payload_json.to_string(),
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
@@ -1,9 +1,12 @@
 use crate::http::{Credentials, Method, request::Request};
+use std::time::Duration;
 
 /// Cancel all open orders request
 pub struct CancelAllOpenOrders {
     /// Trading pair filter
     pub currency_pair: Option<String>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials
     pub credentials: Option<Credentials>,
 }
@@ -13,6 +16,7 @@ impl CancelAllOpenOrders {
     pub fn new() -> Self {
         Self {
             currency_pair: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -23,6 +27,12 @@ impl CancelAllOpenOrders {
         self
     }
 
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Set API credentials
     pub fn credentials(mut self, creds: Credentials) -> Self {
         self.credentials = Some(creds);
@@ -30,6 +40,12 @@ impl CancelAllOpenOrders {
     }
 }
 
+impl Default for CancelAllOpenOrders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<CancelAllOpenOrders> for Request {
     fn from(request: CancelAllOpenOrders) -> Request {
         let mut params = Vec::new();
@@ -46,6 +62,7 @@ impl From<CancelAllOpenOrders> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
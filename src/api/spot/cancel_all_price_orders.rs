@@ -63,6 +63,12 @@ impl CancelAllPriceOrders {
     }
 }
 
+impl Default for CancelAllPriceOrders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<CancelAllPriceOrders> for Request {
     fn from(request: CancelAllPriceOrders) -> Request {
         let mut params = Vec::new();
@@ -83,6 +89,7 @@ impl From<CancelAllPriceOrders> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
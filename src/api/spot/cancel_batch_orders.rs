@@ -1,5 +1,8 @@
+use super::model::CancelledOrder;
+use crate::batch::{Batch, BatchLimits};
 use crate::http::{Credentials, Method, request::Request};
 use serde::Serialize;
+use std::time::Duration;
 
 /// Single order cancellation parameters
 #[derive(Serialize)]
@@ -16,6 +19,8 @@ pub struct CancelBatchOrders {
     pub orders: Vec<CancelOrderRequest>,
     /// Request expiration time
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials
     pub credentials: Option<Credentials>,
 }
@@ -26,6 +31,7 @@ impl CancelBatchOrders {
         Self {
             orders,
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -36,6 +42,12 @@ impl CancelBatchOrders {
         self
     }
 
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Set API credentials
     pub fn credentials(mut self, creds: Credentials) -> Self {
         self.credentials = Some(creds);
@@ -43,6 +55,32 @@ impl CancelBatchOrders {
     }
 }
 
+impl Batch for CancelBatchOrders {
+    type Item = CancelOrderRequest;
+    type Output = CancelledOrder;
+
+    fn limits() -> BatchLimits {
+        BatchLimits::new(20)
+    }
+
+    fn take_items(&mut self) -> Vec<CancelOrderRequest> {
+        std::mem::take(&mut self.orders)
+    }
+
+    fn with_items(&self, orders: Vec<CancelOrderRequest>) -> Self {
+        Self {
+            orders,
+            x_gate_exp_time: self.x_gate_exp_time,
+            timeout: self.timeout,
+            credentials: self.credentials.clone(),
+        }
+    }
+
+    fn currency_pair(order: &CancelOrderRequest) -> &str {
+        &order.currency_pair
+    }
+}
+
 impl From<CancelBatchOrders> for Request {
     fn from(request: CancelBatchOrders) -> Request {
         let params = Vec::new();
@@ -56,6 +94,7 @@ impl From<CancelBatchOrders> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
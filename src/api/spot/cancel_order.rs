@@ -1,4 +1,5 @@
 use crate::http::{Credentials, Method, request::Request};
+use std::time::Duration;
 
 /// # Cancel a spot order
 ///
@@ -28,6 +29,8 @@ pub struct CancelOrder {
     pub action_mode: Option<String>,
     /// Request expiration time in milliseconds
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials for authentication
     pub credentials: Option<Credentials>,
 }
@@ -41,6 +44,7 @@ impl CancelOrder {
             account: None,
             action_mode: None,
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -60,7 +64,13 @@ impl CancelOrder {
     /// Specify the expiration time (milliseconds);<br/>
     /// If the GATE receives the request time greater than the expiration time, the request will be rejected
     pub fn x_gate_exp_time(mut self, x_gate_exp_time: u128) -> Self {
-        self.x_gate_exp_time = Some(x_gate_exp_time.into());
+        self.x_gate_exp_time = Some(x_gate_exp_time);
+        self
+    }
+
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
         self
     }
 
@@ -85,12 +95,13 @@ impl From<CancelOrder> for Request {
 
         Request {
             method: Method::Delete,
-            path: format!("/api/v4/spot/orders/{}", request.order_id).into(),
+            path: format!("/api/v4/spot/orders/{}", request.order_id),
             params,
             payload: "".to_string(),
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
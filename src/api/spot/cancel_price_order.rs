@@ -1,4 +1,5 @@
 use crate::http::{Credentials, Method, request::Request};
+use std::time::Duration;
 
 /// # Cancel a price-triggered order
 ///
@@ -20,6 +21,8 @@ pub struct CancelPriceOrder {
     pub order_id: String,
     /// Request expiration time in milliseconds
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials for authentication
     pub credentials: Option<Credentials>,
 }
@@ -30,6 +33,7 @@ impl CancelPriceOrder {
         Self {
             order_id: order_id.to_owned(),
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -41,6 +45,12 @@ impl CancelPriceOrder {
         self
     }
 
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Set API credentials for authentication
     pub fn credentials(mut self, creds: Credentials) -> Self {
         self.credentials = Some(creds);
@@ -54,12 +64,13 @@ impl From<CancelPriceOrder> for Request {
 
         Request {
             method: Method::Delete,
-            path: format!("/api/v4/spot/price_orders/{}", request.order_id).into(),
+            path: format!("/api/v4/spot/price_orders/{}", request.order_id),
             params,
             payload: "".to_string(),
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
@@ -0,0 +1,454 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum length of a client order id, excluding the `t-` prefix
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 28;
+
+/// Checks a client order id against the `text` rules of `CreateOrder`:
+/// prefixed with `t-`, at most 28 bytes after the prefix, made of
+/// `0-9`, `A-Z`, `a-z`, `_`, `-` and `.`
+pub fn validate_client_id(text: &str) -> Result<(), ClientOrderIdError> {
+    ClientOrderId::validate(text)
+}
+
+/// Custom order id (`text`) following Gate's rules.
+///
+/// A valid id is prefixed with `t-`, is at most 28 bytes long after the
+/// prefix and only contains `0-9`, `A-Z`, `a-z`, `_`, `-` and `.`.
+/// Builders taking a `ClientOrderId` cannot send an id Gate would refuse.
+///
+/// ```rust
+/// use gateio_rs::api::spot::ClientOrderId;
+///
+/// let id = ClientOrderId::new("t-grid.42")?;
+/// assert_eq!(id.as_str(), "t-grid.42");
+///
+/// assert!(ClientOrderId::new("grid-42").is_err());
+/// assert!(ClientOrderId::new("t-has space").is_err());
+/// assert!("t-0123456789012345678901234567890".parse::<ClientOrderId>().is_err());
+/// # Ok::<(), gateio_rs::api::spot::ClientOrderIdError>(())
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClientOrderId(String);
+
+impl ClientOrderId {
+    /// Validates `text` as a client order id
+    pub fn new(text: &str) -> Result<Self, ClientOrderIdError> {
+        Self::validate(text)?;
+        Ok(Self(text.to_owned()))
+    }
+
+    /// Checks `text` against the client order id rules
+    pub fn validate(text: &str) -> Result<(), ClientOrderIdError> {
+        let body = text
+            .strip_prefix("t-")
+            .ok_or(ClientOrderIdError::MissingPrefix)?;
+        if body.is_empty() {
+            return Err(ClientOrderIdError::Empty);
+        }
+        if body.len() > MAX_CLIENT_ORDER_ID_LEN {
+            return Err(ClientOrderIdError::TooLong(body.len()));
+        }
+        match body
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        {
+            Some(c) => Err(ClientOrderIdError::InvalidChar(c)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the id, including the `t-` prefix
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ClientOrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ClientOrderId {
+    type Err = ClientOrderIdError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::new(text)
+    }
+}
+
+impl TryFrom<String> for ClientOrderId {
+    type Error = ClientOrderIdError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::validate(&text)?;
+        Ok(Self(text))
+    }
+}
+
+impl TryFrom<&str> for ClientOrderId {
+    type Error = ClientOrderIdError;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        Self::new(text)
+    }
+}
+
+impl From<ClientOrderId> for String {
+    fn from(id: ClientOrderId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<str> for ClientOrderId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for ClientOrderId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ClientOrderId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Reason a client order id was refused
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ClientOrderIdError {
+    /// The id does not start with `t-`
+    MissingPrefix,
+    /// Nothing follows the `t-` prefix
+    Empty,
+    /// The id is longer than [`MAX_CLIENT_ORDER_ID_LEN`] bytes after the prefix
+    TooLong(usize),
+    /// The id contains a character outside `0-9 A-Z a-z _ - .`
+    InvalidChar(char),
+}
+
+impl fmt::Display for ClientOrderIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientOrderIdError::MissingPrefix => {
+                write!(f, "Client order id must start with \"t-\"")
+            }
+            ClientOrderIdError::Empty => write!(f, "Client order id is empty"),
+            ClientOrderIdError::TooLong(len) => write!(
+                f,
+                "Client order id is {} bytes long, at most {} allowed after \"t-\"",
+                len, MAX_CLIENT_ORDER_ID_LEN
+            ),
+            ClientOrderIdError::InvalidChar(c) => {
+                write!(f, "Client order id contains invalid character {:?}", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientOrderIdError {}
+
+/// Source of fresh client order ids.
+///
+/// Generators are shared between threads and never repeat an id
+/// within a process.
+pub trait ClientOrderIdGenerator: Send + Sync {
+    /// Returns a fresh id
+    fn next_id(&self) -> ClientOrderId;
+}
+
+/// Ids sorting in creation order: `t-<session>-<counter>`.
+///
+/// The session is the start time of the generator and the counter is
+/// zero-padded, so ids compare like the order they were generated in,
+/// also across restarts.
+///
+/// ```rust
+/// use gateio_rs::api::spot::{ClientOrderIdGenerator, MonotonicIds};
+///
+/// let ids = MonotonicIds::new();
+/// let (first, second) = (ids.next_id(), ids.next_id());
+/// assert!(first < second);
+/// ```
+#[derive(Debug)]
+pub struct MonotonicIds {
+    session: String,
+    counter: AtomicU64,
+}
+
+impl Default for MonotonicIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MonotonicIds {
+    /// Width of the zero-padded counter
+    const COUNTER_WIDTH: usize = 7;
+
+    /// Creates a generator whose session starts now
+    pub fn new() -> Self {
+        Self {
+            session: base36(crate::utils::now_ms() as u64),
+            counter: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ClientOrderIdGenerator for MonotonicIds {
+    fn next_id(&self) -> ClientOrderId {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        ClientOrderId(format!(
+            "t-{}-{:0>width$}",
+            self.session,
+            base36(counter),
+            width = Self::COUNTER_WIDTH
+        ))
+    }
+}
+
+/// Ids made of the current time and random bits: `t-<millis>-<random>`.
+///
+/// Independent processes sharing an account can use it without
+/// coordinating.
+///
+/// ```rust
+/// use gateio_rs::api::spot::{ClientOrderIdGenerator, RandomIds};
+///
+/// let ids = RandomIds::new();
+/// assert_ne!(ids.next_id(), ids.next_id());
+/// ```
+#[derive(Debug)]
+pub struct RandomIds {
+    state: RandomState,
+    counter: AtomicU64,
+}
+
+impl Default for RandomIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomIds {
+    /// Creates a generator with a random seed
+    pub fn new() -> Self {
+        Self {
+            state: RandomState::new(),
+            counter: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ClientOrderIdGenerator for RandomIds {
+    fn next_id(&self) -> ClientOrderId {
+        let mut hasher = self.state.build_hasher();
+        hasher.write_u64(self.counter.fetch_add(1, Ordering::Relaxed));
+        hasher.write_u128(now_nanos());
+        ClientOrderId(format!(
+            "t-{}-{}",
+            base36(crate::utils::now_ms() as u64),
+            base36(hasher.finish())
+        ))
+    }
+}
+
+/// Ids carrying a strategy tag: `t-<tag>.<session>-<counter>`.
+///
+/// Orders and fills can be attributed to a strategy from their `text`
+/// alone, with [`StrategyIds::tag_of`].
+///
+/// ```rust
+/// use gateio_rs::api::spot::{ClientOrderIdGenerator, StrategyIds};
+///
+/// let ids = StrategyIds::new("grid_btc")?;
+/// let id = ids.next_id();
+/// assert!(id.as_str().starts_with("t-grid_btc."));
+/// assert_eq!(StrategyIds::tag_of(id.as_str()), Some("grid_btc"));
+///
+/// // Tags leaving no room for the session and counter are refused
+/// assert!(StrategyIds::new("a_very_long_strategy").is_err());
+/// # Ok::<(), gateio_rs::api::spot::ClientOrderIdError>(())
+/// ```
+#[derive(Debug)]
+pub struct StrategyIds {
+    tag: String,
+    session: String,
+    counter: AtomicU64,
+}
+
+impl StrategyIds {
+    /// Creates a generator for `tag`, made of `0-9 A-Z a-z _ -`.
+    ///
+    /// Fails when the tag would produce invalid ids; tags of up to
+    /// 11 bytes are always accepted.
+    pub fn new(tag: &str) -> Result<Self, ClientOrderIdError> {
+        if let Some(c) = tag.chars().find(|c| *c == '.') {
+            return Err(ClientOrderIdError::InvalidChar(c));
+        }
+        let ids = Self::with_tag(tag);
+        // The longest id this generator produces must be valid
+        ClientOrderId::validate(&ids.format(u64::from(u32::MAX)))?;
+        Ok(ids)
+    }
+
+    /// Creates a generator for a tag known to satisfy the rules of [`StrategyIds::new`]
+    pub(crate) fn with_tag(tag: &str) -> Self {
+        Self {
+            tag: tag.to_owned(),
+            session: base36(crate::utils::now_ms() as u64),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the strategy tag of an id produced by a [`StrategyIds`]
+    pub fn tag_of(text: &str) -> Option<&str> {
+        let (tag, rest) = text.strip_prefix("t-")?.split_once('.')?;
+        (!tag.is_empty() && rest.contains('-')).then_some(tag)
+    }
+
+    fn format(&self, counter: u64) -> String {
+        format!("t-{}.{}-{}", self.tag, self.session, base36(counter))
+    }
+}
+
+impl ClientOrderIdGenerator for StrategyIds {
+    fn next_id(&self) -> ClientOrderId {
+        // Counters wrap at the width validated in `new`
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed) % u64::from(u32::MAX) + 1;
+        ClientOrderId(self.format(counter))
+    }
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default()
+}
+
+fn base36(mut value: u64) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut out = Vec::new();
+    loop {
+        out.push(DIGITS[(value % 36) as usize]);
+        value /= 36;
+        if value == 0 {
+            break;
+        }
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn accepts_ids_following_the_rules() {
+        let longest = format!("t-{}", "a".repeat(MAX_CLIENT_ORDER_ID_LEN));
+        for text in ["t-1", "t-Grid_01-a.b", longest.as_str()] {
+            assert_eq!(ClientOrderId::new(text).unwrap().as_str(), text);
+        }
+    }
+
+    #[test]
+    fn refuses_ids_breaking_the_rules() {
+        let too_long = format!("t-{}", "a".repeat(MAX_CLIENT_ORDER_ID_LEN + 1));
+        let cases = [
+            ("grid-1", ClientOrderIdError::MissingPrefix),
+            ("T-grid", ClientOrderIdError::MissingPrefix),
+            ("t-", ClientOrderIdError::Empty),
+            (too_long.as_str(), ClientOrderIdError::TooLong(29)),
+            ("t-a b", ClientOrderIdError::InvalidChar(' ')),
+            ("t-a/b", ClientOrderIdError::InvalidChar('/')),
+            ("t-é", ClientOrderIdError::InvalidChar('é')),
+        ];
+        for (text, err) in cases {
+            assert_eq!(ClientOrderId::new(text), Err(err), "{text}");
+        }
+    }
+
+    #[test]
+    fn deserializing_validates() {
+        let id: ClientOrderId = serde_json::from_str(r#""t-ok""#).unwrap();
+        assert_eq!(serde_json::to_string(&id).unwrap(), r#""t-ok""#);
+        assert!(serde_json::from_str::<ClientOrderId>(r#""no-prefix""#).is_err());
+    }
+
+    #[test]
+    fn monotonic_ids_are_valid_and_ordered() {
+        let ids = MonotonicIds::new();
+        let generated: Vec<ClientOrderId> = (0..1000).map(|_| ids.next_id()).collect();
+        for id in &generated {
+            ClientOrderId::validate(id).unwrap();
+        }
+        assert!(generated.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn random_ids_are_valid_and_distinct() {
+        let ids = RandomIds::new();
+        let generated: HashSet<ClientOrderId> = (0..1000).map(|_| ids.next_id()).collect();
+        assert_eq!(generated.len(), 1000);
+        for id in &generated {
+            ClientOrderId::validate(id).unwrap();
+        }
+    }
+
+    #[test]
+    fn strategy_ids_carry_their_tag() {
+        let ids = StrategyIds::new("grid_btc").unwrap();
+        let first = ids.next_id();
+        let second = ids.next_id();
+        assert_ne!(first, second);
+        ClientOrderId::validate(&first).unwrap();
+        assert_eq!(StrategyIds::tag_of(&first), Some("grid_btc"));
+        assert_eq!(StrategyIds::tag_of("t-plain-1"), None);
+        assert_eq!(StrategyIds::tag_of("t-.x-1"), None);
+    }
+
+    #[test]
+    fn strategy_ids_refuse_tags_producing_invalid_ids() {
+        assert!(StrategyIds::new(&"a".repeat(11)).is_ok());
+        assert_eq!(
+            StrategyIds::new("a.b").unwrap_err(),
+            ClientOrderIdError::InvalidChar('.')
+        );
+        assert_eq!(
+            StrategyIds::new("a b").unwrap_err(),
+            ClientOrderIdError::InvalidChar(' ')
+        );
+        assert!(matches!(
+            StrategyIds::new(&"a".repeat(20)),
+            Err(ClientOrderIdError::TooLong(_))
+        ));
+    }
+
+    #[test]
+    fn strategy_ids_wrap_within_the_validated_width() {
+        let ids = StrategyIds::new(&"a".repeat(11)).unwrap();
+        ids.counter
+            .store(u64::from(u32::MAX) - 1, Ordering::Relaxed);
+        for _ in 0..3 {
+            ClientOrderId::validate(&ids.next_id()).unwrap();
+        }
+    }
+}
@@ -67,6 +67,7 @@ impl From<CountdownCancelAll> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
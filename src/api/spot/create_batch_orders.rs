@@ -1,5 +1,8 @@
+use super::model::OrderDetails;
 use super::order::Order;
+use crate::batch::{Batch, BatchLimits};
 use crate::http::{Credentials, Method, request::Request};
+use std::time::Duration;
 
 /// # Create multiple spot orders in batch
 ///
@@ -12,12 +15,16 @@ use crate::http::{Credentials, Method, request::Request};
 /// - Orders are processed atomically (all or nothing)
 /// - Each order follows the same validation rules as individual orders
 ///
+/// Larger batches can be sent with `send_batch()`, see [`crate::batch`].
+///
 /// [Gate API Documentation](https://www.gate.com/docs/developers/apiv4/#create-a-batch-of-orders)
 pub struct CreateBatchOrders {
     /// List of orders to create (maximum 10)
     pub orders: Vec<Order>,
     /// Request expiration time in milliseconds
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials for authentication
     pub credentials: Option<Credentials>,
 }
@@ -28,6 +35,7 @@ impl CreateBatchOrders {
         Self {
             orders,
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -38,6 +46,12 @@ impl CreateBatchOrders {
         self
     }
 
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Set API credentials for authentication
     pub fn credentials(mut self, creds: Credentials) -> Self {
         self.credentials = Some(creds);
@@ -45,6 +59,37 @@ impl CreateBatchOrders {
     }
 }
 
+impl Batch for CreateBatchOrders {
+    type Item = Order;
+    type Output = OrderDetails;
+
+    fn limits() -> BatchLimits {
+        BatchLimits::new(10).max_pairs(4)
+    }
+
+    fn take_items(&mut self) -> Vec<Order> {
+        std::mem::take(&mut self.orders)
+    }
+
+    fn with_items(&self, orders: Vec<Order>) -> Self {
+        Self {
+            orders,
+            x_gate_exp_time: self.x_gate_exp_time,
+            timeout: self.timeout,
+            credentials: self.credentials.clone(),
+        }
+    }
+
+    fn currency_pair(order: &Order) -> &str {
+        &order.currency_pair
+    }
+
+    /// Spot and margin orders cannot be mixed
+    fn compatible(a: &Order, b: &Order) -> bool {
+        a.account == b.account
+    }
+}
+
 impl From<CreateBatchOrders> for Request {
     fn from(request: CreateBatchOrders) -> Request {
         let params = Vec::new();
@@ -58,6 +103,7 @@ impl From<CreateBatchOrders> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
@@ -1,3 +1,4 @@
+use super::client_order_id::ClientOrderId;
 use crate::http::{Credentials, Method, request::Request};
 use serde::Serialize;
 
@@ -16,7 +17,7 @@ pub struct CrossLiquidateOrder {
     pub price: String,
     /// Custom order text/label
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
+    pub text: Option<ClientOrderId>,
     /// Processing mode for the response
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action_mode: Option<String>,
@@ -35,8 +36,8 @@ impl CrossLiquidateOrder {
     }
 
     /// Set custom order text/label
-    pub fn text(mut self, text: &str) -> Self {
-        self.text = Some(text.to_owned());
+    pub fn text(mut self, text: ClientOrderId) -> Self {
+        self.text = Some(text);
         self
     }
 
@@ -104,6 +105,7 @@ impl From<CreateCrossLiquidateOrders> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -1,5 +1,7 @@
+use super::client_order_id::ClientOrderId;
 use crate::http::{Credentials, Method, request::Request};
 use serde_json::{Map, Value, json};
+use std::time::Duration;
 
 /// Request builder for creating trading orders.
 ///
@@ -16,7 +18,7 @@ use serde_json::{Map, Value, json};
 ///
 /// ```rust,no_run
 /// use gateio_rs::{
-///     api::spot::create_order,
+///     api::spot::{ClientOrderId, create_order},
 ///     http::Credentials,
 ///     ureq::GateHttpClient,
 /// };
@@ -29,14 +31,14 @@ use serde_json::{Map, Value, json};
 ///     .price("50000")
 ///     .order_type("limit")
 ///     .time_in_force("gtc")
-///     .text("t-my-order-123");
+///     .text(ClientOrderId::new("t-my-order-123")?);
 /// let response = client.send(request)?;
 ///
 /// // Market sell order
 /// let request = create_order("BTC_USDT", "sell", "0.001")
 ///     .order_type("market");
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// # Parameter Details
@@ -75,9 +77,12 @@ use serde_json::{Map, Value, json};
 /// - Be prefixed with `"t-"`
 /// - Be no longer than 28 bytes (excluding prefix)
 /// - Contain only: 0-9, A-Z, a-z, underscore, hyphen, or dot
+///
+/// `text` takes a [`ClientOrderId`], which is checked against these rules
+/// when built.
 pub struct CreateOrder {
     /// Custom order ID
-    pub text: Option<String>,
+    pub text: Option<ClientOrderId>,
     /// Trading pair
     pub currency_pair: String,
     /// Order type
@@ -104,6 +109,8 @@ pub struct CreateOrder {
     pub action_mode: Option<String>,
     /// Request expiration time
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials
     pub credentials: Option<Credentials>,
 }
@@ -126,13 +133,14 @@ impl CreateOrder {
             stp_act: None,
             action_mode: None,
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
 
     /// Set custom order ID
-    pub fn text(mut self, text: &str) -> Self {
-        self.text = Some(text.into());
+    pub fn text(mut self, text: ClientOrderId) -> Self {
+        self.text = Some(text);
         self
     }
 
@@ -168,13 +176,13 @@ impl CreateOrder {
 
     /// Enable auto borrow
     pub fn auto_borrow(mut self, auto_borrow: bool) -> Self {
-        self.auto_borrow = Some(auto_borrow.into());
+        self.auto_borrow = Some(auto_borrow);
         self
     }
 
     /// Enable auto repay
     pub fn auto_repay(mut self, auto_repay: bool) -> Self {
-        self.auto_repay = Some(auto_repay.into());
+        self.auto_repay = Some(auto_repay);
         self
     }
 
@@ -192,7 +200,13 @@ impl CreateOrder {
 
     /// Set expiration time
     pub fn x_gate_exp_time(mut self, x_gate_exp_time: u128) -> Self {
-        self.x_gate_exp_time = Some(x_gate_exp_time.into());
+        self.x_gate_exp_time = Some(x_gate_exp_time);
+        self
+    }
+
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
         self
     }
 
@@ -262,6 +276,7 @@ impl From<CreateOrder> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::http::{Credentials, Method, request::Request};
 use serde_json::{Map, Value, json};
+use std::time::Duration;
 
 /// # SpotPriceTrigger
 ///
@@ -147,6 +148,8 @@ pub struct CreatePriceOrder {
     pub market: String,
     /// Request expiration time in milliseconds
     pub x_gate_exp_time: Option<u128>,
+    /// Request timeout override
+    pub timeout: Option<Duration>,
     /// API credentials for authentication
     pub credentials: Option<Credentials>,
 }
@@ -166,6 +169,7 @@ impl CreatePriceOrder {
             put: SpotPricePutOrder::new("limit", order_side, order_price, order_amount),
             market: market.to_owned(),
             x_gate_exp_time: None,
+            timeout: None,
             credentials: None,
         }
     }
@@ -207,6 +211,12 @@ impl CreatePriceOrder {
         self
     }
 
+    /// Set request timeout override
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Set API credentials for authentication
     pub fn credentials(mut self, creds: Credentials) -> Self {
         self.credentials = Some(creds);
@@ -260,6 +270,7 @@ impl From<CreatePriceOrder> for Request {
             x_gate_exp_time: request.x_gate_exp_time,
             credentials: request.credentials,
             sign: true,
+            timeout: request.timeout,
         }
     }
 }
@@ -30,6 +30,12 @@ impl GetAccount {
     }
 }
 
+impl Default for GetAccount {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetAccount> for Request {
     fn from(request: GetAccount) -> Request {
         let mut params = Vec::new();
@@ -45,6 +51,7 @@ impl From<GetAccount> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
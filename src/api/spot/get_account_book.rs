@@ -1,6 +1,8 @@
 use crate::http::{Credentials, Method, request::Request};
+use crate::pagination::{Paginated, TimeWindowed};
 
 /// Request for retrieving spot account transaction history
+#[derive(Clone)]
 pub struct GetAccountBook {
     /// Currency to filter transactions by
     pub currency: Option<String>,
@@ -43,25 +45,25 @@ impl GetAccountBook {
 
     /// Sets the start timestamp for the date range filter
     pub fn from(mut self, from: i64) -> Self {
-        self.from = Some(from.into());
+        self.from = Some(from);
         self
     }
 
     /// Sets the end timestamp for the date range filter
     pub fn to(mut self, to: i64) -> Self {
-        self.to = Some(to.into());
+        self.to = Some(to);
         self
     }
 
     /// Sets the page number for pagination
     pub fn page(mut self, page: i32) -> Self {
-        self.page = Some(page.into());
+        self.page = Some(page);
         self
     }
 
     /// Sets the maximum number of records per page
     pub fn limit(mut self, limit: i64) -> Self {
-        self.limit = Some(limit.into());
+        self.limit = Some(limit);
         self
     }
 
@@ -84,6 +86,39 @@ impl GetAccountBook {
     }
 }
 
+impl Default for GetAccountBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Paginated for GetAccountBook {
+    const DEFAULT_LIMIT: u32 = 100;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(|limit| limit.max(0) as u32)
+    }
+
+    fn set_page_limit(&mut self, limit: u32) {
+        self.limit = Some(limit as i64);
+    }
+
+    fn start_page(&self) -> u32 {
+        self.page.map_or(1, |page| page.max(1) as u32)
+    }
+
+    fn set_page(&mut self, page: u32) {
+        self.page = Some(page as i32);
+    }
+}
+
+impl TimeWindowed for GetAccountBook {
+    fn set_window(&mut self, from: i64, to: i64) {
+        self.from = Some(from);
+        self.to = Some(to);
+    }
+}
+
 impl From<GetAccountBook> for Request {
     fn from(request: GetAccountBook) -> Request {
         let mut params = Vec::new();
@@ -124,6 +159,7 @@ impl From<GetAccountBook> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
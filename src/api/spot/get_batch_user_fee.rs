@@ -36,6 +36,7 @@ impl From<GetBatchUserFee> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
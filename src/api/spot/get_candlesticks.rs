@@ -31,19 +31,19 @@ impl GetCandlesticks {
 
     /// Sets the maximum number of candlesticks to return
     pub fn limit(mut self, limit: i64) -> Self {
-        self.limit = Some(limit.into());
+        self.limit = Some(limit);
         self
     }
 
     /// Sets the start timestamp for the data range
     pub fn from(mut self, from: i64) -> Self {
-        self.from = Some(from.into());
+        self.from = Some(from);
         self
     }
 
     /// Sets the end timestamp for the data range
     pub fn to(mut self, to: i64) -> Self {
-        self.to = Some(to.into());
+        self.to = Some(to);
         self
     }
 
@@ -88,6 +88,7 @@ impl From<GetCandlesticks> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
@@ -19,6 +19,12 @@ impl GetCurrencies {
     }
 }
 
+impl Default for GetCurrencies {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetCurrencies> for Request {
     fn from(request: GetCurrencies) -> Request {
         let params = Vec::new();
@@ -31,6 +37,7 @@ impl From<GetCurrencies> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
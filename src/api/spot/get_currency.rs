@@ -30,12 +30,13 @@ impl From<GetCurrency> for Request {
 
         Request {
             method: Method::Get,
-            path: format!("/api/v4/spot/currencies/{}", request.currency).into(),
+            path: format!("/api/v4/spot/currencies/{}", request.currency),
             params,
             payload: "".to_string(),
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
@@ -30,12 +30,13 @@ impl From<GetCurrencyPair> for Request {
 
         Request {
             method: Method::Get,
-            path: format!("/api/v4/spot/currency_pairs/{}", request.currency_pair).into(),
+            path: format!("/api/v4/spot/currency_pairs/{}", request.currency_pair),
             params,
             payload: "".to_string(),
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
@@ -19,6 +19,12 @@ impl GetCurrencyPairs {
     }
 }
 
+impl Default for GetCurrencyPairs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetCurrencyPairs> for Request {
     fn from(request: GetCurrencyPairs) -> Request {
         let params = Vec::new();
@@ -31,6 +37,7 @@ impl From<GetCurrencyPairs> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
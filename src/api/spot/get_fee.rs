@@ -30,6 +30,12 @@ impl GetFee {
     }
 }
 
+impl Default for GetFee {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetFee> for Request {
     fn from(request: GetFee) -> Request {
         let mut params = Vec::new();
@@ -45,6 +51,7 @@ impl From<GetFee> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -1,6 +1,8 @@
 use crate::http::{Credentials, Method, request::Request};
+use crate::pagination::{Paginated, TimeWindowed};
 
 /// Request for retrieving insurance history records
+#[derive(Clone)]
 pub struct GetInsuranceHistory {
     /// Business type for insurance history query
     pub business: String,
@@ -51,14 +53,42 @@ impl GetInsuranceHistory {
     }
 }
 
+impl Paginated for GetInsuranceHistory {
+    const DEFAULT_LIMIT: u32 = 30;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn set_page_limit(&mut self, limit: u32) {
+        self.limit = Some(limit);
+    }
+
+    fn start_page(&self) -> u32 {
+        self.page.map_or(1, |page| page.max(1))
+    }
+
+    fn set_page(&mut self, page: u32) {
+        self.page = Some(page);
+    }
+}
+
+impl TimeWindowed for GetInsuranceHistory {
+    fn set_window(&mut self, from: i64, to: i64) {
+        self.from = from;
+        self.to = to;
+    }
+}
+
 impl From<GetInsuranceHistory> for Request {
     fn from(req: GetInsuranceHistory) -> Request {
-        let mut params = Vec::new();
+        let mut params = vec![
+            ("business".to_owned(), req.business),
+            ("currency".to_owned(), req.currency),
+            ("from".to_owned(), req.from.to_string()),
+            ("to".to_owned(), req.to.to_string()),
+        ];
 
-        params.push(("business".to_owned(), req.business));
-        params.push(("currency".to_owned(), req.currency));
-        params.push(("from".to_owned(), req.from.to_string()));
-        params.push(("to".to_owned(), req.to.to_string()));
         if let Some(limit) = req.limit {
             params.push(("limit".to_owned(), limit.to_string()));
         }
@@ -74,6 +104,7 @@ impl From<GetInsuranceHistory> for Request {
             x_gate_exp_time: None,
             credentials: req.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -1,6 +1,8 @@
 use crate::http::{Credentials, Method, request::Request};
+use crate::pagination::{Paginated, TimeWindowed};
 
 /// Request for retrieving recent public trades for a currency pair
+#[derive(Clone)]
 pub struct GetMarketTrades {
     /// Currency pair to get trades for
     pub currency_pair: String,
@@ -37,7 +39,7 @@ impl GetMarketTrades {
 
     /// Sets the maximum number of trades to return
     pub fn limit(mut self, limit: i32) -> Self {
-        self.limit = Some(limit.into());
+        self.limit = Some(limit);
         self
     }
 
@@ -49,25 +51,25 @@ impl GetMarketTrades {
 
     /// Sets whether to reverse the order of results
     pub fn reverse(mut self, reverse: bool) -> Self {
-        self.reverse = Some(reverse.into());
+        self.reverse = Some(reverse);
         self
     }
 
     /// Sets the start timestamp for trade history range
     pub fn from(mut self, from: i64) -> Self {
-        self.from = Some(from.into());
+        self.from = Some(from);
         self
     }
 
     /// Sets the end timestamp for trade history range
     pub fn to(mut self, to: i64) -> Self {
-        self.to = Some(to.into());
+        self.to = Some(to);
         self
     }
 
     /// Sets the page number for pagination
     pub fn page(mut self, page: i32) -> Self {
-        self.page = Some(page.into());
+        self.page = Some(page);
         self
     }
 
@@ -78,6 +80,33 @@ impl GetMarketTrades {
     }
 }
 
+impl Paginated for GetMarketTrades {
+    const DEFAULT_LIMIT: u32 = 100;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(|limit| limit.max(0) as u32)
+    }
+
+    fn set_page_limit(&mut self, limit: u32) {
+        self.limit = Some(limit as i32);
+    }
+
+    fn start_page(&self) -> u32 {
+        self.page.map_or(1, |page| page.max(1) as u32)
+    }
+
+    fn set_page(&mut self, page: u32) {
+        self.page = Some(page as i32);
+    }
+}
+
+impl TimeWindowed for GetMarketTrades {
+    fn set_window(&mut self, from: i64, to: i64) {
+        self.from = Some(from);
+        self.to = Some(to);
+    }
+}
+
 impl From<GetMarketTrades> for Request {
     fn from(request: GetMarketTrades) -> Request {
         let mut params = vec![("currency_pair".to_owned(), request.currency_pair)];
@@ -114,6 +143,7 @@ impl From<GetMarketTrades> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
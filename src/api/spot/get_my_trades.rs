@@ -1,6 +1,8 @@
 use crate::http::{Credentials, Method, request::Request};
+use crate::pagination::{Paginated, TimeWindowed};
 
 /// Request for retrieving user's personal trading history
+#[derive(Clone)]
 pub struct GetMyTrades {
     /// Optional currency pair filter for trades
     pub currency_pair: Option<String>,
@@ -75,6 +77,39 @@ impl GetMyTrades {
     }
 }
 
+impl Default for GetMyTrades {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Paginated for GetMyTrades {
+    const DEFAULT_LIMIT: u32 = 100;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(|limit| limit.max(0) as u32)
+    }
+
+    fn set_page_limit(&mut self, limit: u32) {
+        self.limit = Some(limit as i32);
+    }
+
+    fn start_page(&self) -> u32 {
+        self.page.map_or(1, |page| page.max(1) as u32)
+    }
+
+    fn set_page(&mut self, page: u32) {
+        self.page = Some(page as i32);
+    }
+}
+
+impl TimeWindowed for GetMyTrades {
+    fn set_window(&mut self, from: i64, to: i64) {
+        self.from = Some(from);
+        self.to = Some(to);
+    }
+}
+
 impl From<GetMyTrades> for Request {
     fn from(request: GetMyTrades) -> Request {
         let mut params = Vec::new();
@@ -111,6 +146,7 @@ impl From<GetMyTrades> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -48,6 +48,12 @@ impl GetOpenOrders {
     }
 }
 
+impl Default for GetOpenOrders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetOpenOrders> for Request {
     fn from(request: GetOpenOrders) -> Request {
         let mut params = Vec::new();
@@ -72,6 +78,7 @@ impl From<GetOpenOrders> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -54,6 +54,7 @@ impl From<GetOrder> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -34,7 +34,7 @@ impl GetOrderbook {
 
     /// Sets the maximum depth of order book entries
     pub fn limit(mut self, limit: i64) -> Self {
-        self.limit = Some(limit.into());
+        self.limit = Some(limit);
         self
     }
 
@@ -75,6 +75,7 @@ impl From<GetOrderbook> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
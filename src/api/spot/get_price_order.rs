@@ -48,12 +48,13 @@ impl From<GetPriceOrder> for Request {
 
         Request {
             method: Method::Get,
-            path: format!("/api/v4/spot/price_orders/{}", request.order_id).into(),
+            path: format!("/api/v4/spot/price_orders/{}", request.order_id),
             params,
             payload: "".to_string(),
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
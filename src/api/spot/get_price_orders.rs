@@ -1,4 +1,5 @@
 use crate::http::{Credentials, Method, request::Request};
+use crate::pagination::Paginated;
 
 /// # Retrieve running auto order list
 ///
@@ -13,6 +14,7 @@ use crate::http::{Credentials, Method, request::Request};
 /// - "expired": Expired
 ///
 /// [Gate API Documentation](https://www.gate.com/docs/developers/apiv4/#retrieve-running-auto-order-list)
+#[derive(Clone)]
 pub struct GetPriceOrders {
     /// Order status filter
     pub status: Option<String>,
@@ -86,6 +88,31 @@ impl GetPriceOrders {
     }
 }
 
+impl Default for GetPriceOrders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Paginated for GetPriceOrders {
+    const DEFAULT_LIMIT: u32 = 100;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(|limit| limit.max(0) as u32)
+    }
+
+    fn set_page_limit(&mut self, limit: u32) {
+        self.limit = Some(limit as i32);
+    }
+
+    fn set_page(&mut self, page: u32) {
+        // The endpoint pages by offset, advance from the configured one
+        let limit = self.limit.unwrap_or(Self::DEFAULT_LIMIT as i32);
+        let skipped = (page.saturating_sub(1) as i32).saturating_mul(limit);
+        self.offset = Some(self.offset.unwrap_or(0).max(0).saturating_add(skipped));
+    }
+}
+
 impl From<GetPriceOrders> for Request {
     fn from(request: GetPriceOrders) -> Request {
         let mut params = Vec::new();
@@ -118,6 +145,7 @@ impl From<GetPriceOrders> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: true,
+            timeout: None,
         }
     }
 }
@@ -19,6 +19,12 @@ impl GetServerTime {
     }
 }
 
+impl Default for GetServerTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetServerTime> for Request {
     fn from(request: GetServerTime) -> Request {
         let params = Vec::new();
@@ -31,6 +37,7 @@ impl From<GetServerTime> for Request {
             x_gate_exp_time: None,
             credentials: request.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
@@ -24,7 +24,7 @@ use crate::http::{Credentials, Method, request::Request};
 /// // Get all tickers
 /// let request = get_ticker();
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// # Response Format
@@ -76,6 +76,12 @@ impl GetTicker {
     }
 }
 
+impl Default for GetTicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl From<GetTicker> for Request {
     fn from(g: GetTicker) -> Request {
         let mut params = Vec::new();
@@ -94,6 +100,7 @@ impl From<GetTicker> for Request {
             x_gate_exp_time: None,
             credentials: g.credentials,
             sign: false,
+            timeout: None,
         }
     }
 }
@@ -67,7 +67,7 @@
 //! let client = GateHttpClient::default().credentials(credentials);
 //! let order_req = create_order("BTC_USDT", "buy", "0.001").price("50000");
 //! let response = client.send(order_req)?;
-//! # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
 //! For detailed parameter documentation, see the [Gate.io API Documentation](https://www.gate.com/docs/developers/apiv4/#spot).
@@ -86,6 +86,8 @@ pub mod cancel_batch_orders;
 pub mod cancel_order;
 /// Cancel a price order
 pub mod cancel_price_order;
+/// Client order ids (`text`) and their generators
+pub mod client_order_id;
 /// Set countdown timer to cancel all orders
 pub mod countdown_cancel_all;
 /// Create multiple orders in batch
@@ -136,6 +138,8 @@ pub mod get_price_orders;
 pub mod get_server_time;
 /// Get ticker information
 pub mod get_ticker;
+/// Response data structures
+pub mod model;
 /// Order data structures
 pub mod order;
 
@@ -160,6 +164,10 @@ use cancel_batch_orders::CancelBatchOrders;
 pub use cancel_batch_orders::CancelOrderRequest;
 use cancel_order::CancelOrder;
 use cancel_price_order::CancelPriceOrder;
+pub use client_order_id::{
+    ClientOrderId, ClientOrderIdError, ClientOrderIdGenerator, MAX_CLIENT_ORDER_ID_LEN,
+    MonotonicIds, RandomIds, StrategyIds,
+};
 use countdown_cancel_all::CountdownCancelAll;
 use create_batch_orders::CreateBatchOrders;
 use create_cross_liquidate_orders::CreateCrossLiquidateOrders;
@@ -175,6 +183,10 @@ use get_orders::GetOrders;
 use get_price_order::GetPriceOrder;
 use get_price_orders::GetPriceOrders;
 use get_server_time::GetServerTime;
+pub use model::{
+    AccountBookEntry, CancelledOrder, OpenOrders, OrderDetails, PriceOrderDetails, SpotFee,
+    UserTrade,
+};
 pub use order::Order;
 
 /// List all currencies' details <br/>
@@ -224,7 +236,7 @@ pub fn get_currency_pair(currency_pair: &str) -> GetCurrencyPair {
 /// // Get all tickers
 /// let request = get_ticker();
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// [Gate API Documentation](https://www.gate.com/docs/developers/apiv4/#retrieve-ticker-information)
@@ -286,7 +298,7 @@ pub fn get_batch_user_fee(currency_pairs: &str) -> GetBatchUserFee {
 /// // Get specific currency balance
 /// let request = get_account().currency("BTC");
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// [Gate API Documentation](https://www.gate.com/docs/developers/apiv4/#list-spot-accounts)
@@ -346,7 +358,7 @@ pub fn get_open_orders() -> GetOpenOrders {
 /// let request = create_order("BTC_USDT", "sell", "0.001")
 ///     .order_type("market");
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// [Gate API Documentation](https://www.gate.com/docs/developers/apiv4/#create-an-order)
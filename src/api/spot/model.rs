@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+
+/// Spot order as returned by the order endpoints (`create_order`,
+/// `get_order`, `get_orders`, `amend_order`, `cancel_order`, ...).
+///
+/// Prices and amounts are kept as the decimal strings sent by Gate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderDetails {
+    /// Order ID
+    pub id: String,
+    /// Custom order ID set at creation
+    #[serde(default)]
+    pub text: String,
+    /// Custom ID set by the last amendment
+    #[serde(default)]
+    pub amend_text: String,
+    /// Creation time (UNIX milliseconds)
+    #[serde(default, with = "crate::utils::number_string")]
+    pub create_time_ms: i64,
+    /// Last update time (UNIX milliseconds)
+    #[serde(default, with = "crate::utils::number_string")]
+    pub update_time_ms: i64,
+    /// Order status: `open`, `closed` or `cancelled`
+    pub status: String,
+    /// Currency pair
+    pub currency_pair: String,
+    /// Order type
+    #[serde(rename = "type", default)]
+    pub order_type: String,
+    /// Account type
+    #[serde(default)]
+    pub account: String,
+    /// Order side, `buy` or `sell`
+    pub side: String,
+    /// Order amount
+    pub amount: String,
+    /// Order price
+    #[serde(default)]
+    pub price: String,
+    /// Time in force
+    #[serde(default)]
+    pub time_in_force: String,
+    /// Amount left to fill
+    #[serde(default)]
+    pub left: String,
+    /// Amount filled
+    #[serde(default)]
+    pub filled_amount: String,
+    /// Total filled in quote currency
+    #[serde(default)]
+    pub filled_total: String,
+    /// Average fill price
+    #[serde(default)]
+    pub avg_deal_price: String,
+    /// Fee deducted
+    #[serde(default)]
+    pub fee: String,
+    /// Fee currency
+    #[serde(default)]
+    pub fee_currency: String,
+    /// How the order was finished (`open`, `filled`, `cancelled`, `ioc`, `stp`, ...)
+    #[serde(default)]
+    pub finish_as: String,
+}
+
+/// Open orders of one currency pair, as returned by `get_open_orders`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenOrders {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Total number of open orders for the pair
+    #[serde(default)]
+    pub total: i64,
+    /// Open orders on the current page
+    #[serde(default)]
+    pub orders: Vec<OrderDetails>,
+}
+
+/// Entry of a `cancel_batch_orders` response for a cancelled order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelledOrder {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Order ID
+    pub id: String,
+    /// Custom order ID
+    #[serde(default)]
+    pub text: String,
+    /// Account type
+    #[serde(default)]
+    pub account: String,
+}
+
+/// Price-triggered order as returned by `get_price_order`, `get_price_orders`
+/// and `cancel_price_order`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceOrderDetails {
+    /// Price order ID
+    pub id: i64,
+    /// Currency pair
+    #[serde(default)]
+    pub market: String,
+    /// Status: `open`, `cancelled`, `finish`, `failed` or `expired`
+    pub status: String,
+    /// ID of the order placed when the trigger fired
+    #[serde(default)]
+    pub fired_order_id: Option<i64>,
+    /// Why the order finished
+    #[serde(default)]
+    pub reason: String,
+    /// Creation time (UNIX seconds)
+    #[serde(default)]
+    pub ctime: f64,
+    /// Finish time (UNIX seconds)
+    #[serde(default)]
+    pub ftime: f64,
+}
+
+/// Personal trade as returned by `get_my_trades`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserTrade {
+    /// Trade ID
+    pub id: String,
+    /// Trading time (UNIX milliseconds, with fractional part)
+    #[serde(default, with = "crate::utils::number_string")]
+    pub create_time_ms: f64,
+    /// Currency pair
+    pub currency_pair: String,
+    /// Order side, `buy` or `sell`
+    pub side: String,
+    /// Trade role, `taker` or `maker`
+    #[serde(default)]
+    pub role: String,
+    /// Trade amount
+    pub amount: String,
+    /// Trade price
+    pub price: String,
+    /// Related order ID
+    pub order_id: String,
+    /// Fee deducted
+    #[serde(default)]
+    pub fee: String,
+    /// Fee currency
+    #[serde(default)]
+    pub fee_currency: String,
+    /// Points used to deduct the fee
+    #[serde(default)]
+    pub point_fee: String,
+    /// GT used to deduct the fee
+    #[serde(default)]
+    pub gt_fee: String,
+    /// Custom ID of the related order
+    #[serde(default)]
+    pub text: String,
+}
+
+/// Spot account ledger entry as returned by `get_account_book`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountBookEntry {
+    /// Entry ID
+    pub id: String,
+    /// Time of the change (UNIX milliseconds)
+    #[serde(default, with = "crate::utils::number_string")]
+    pub time: i64,
+    /// Currency changed
+    pub currency: String,
+    /// Amount changed, negative when debited
+    pub change: String,
+    /// Balance after the change
+    #[serde(default)]
+    pub balance: String,
+    /// Kind of change (`order_fill`, `order_fee`, `deposit`, `withdraw`, ...)
+    #[serde(rename = "type", default)]
+    pub entry_type: String,
+    /// Code of the change, finer than the type
+    #[serde(default)]
+    pub code: String,
+    /// Additional information, such as the related order
+    #[serde(default)]
+    pub text: String,
+}
+
+/// Trading fee rates as returned by `get_fee` and `get_batch_user_fee`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpotFee {
+    /// User ID
+    #[serde(default)]
+    pub user_id: i64,
+    /// Currency pair the rates apply to, empty for account-wide rates
+    #[serde(default)]
+    pub currency_pair: String,
+    /// Taker fee rate
+    pub taker_fee: String,
+    /// Maker fee rate
+    pub maker_fee: String,
+    /// Whether fees are deducted in GT
+    #[serde(default)]
+    pub gt_discount: bool,
+    /// Taker fee rate when deducted in GT, `0` without GT deduction
+    #[serde(default)]
+    pub gt_taker_fee: String,
+    /// Maker fee rate when deducted in GT, `0` without GT deduction
+    #[serde(default)]
+    pub gt_maker_fee: String,
+    /// Loan fee rate of margin lending
+    #[serde(default)]
+    pub loan_fee: String,
+    /// Point card type, `0` for the original version and `1` for the new one
+    #[serde(default)]
+    pub point_type: String,
+    /// Rates applied: `1` GT deduction, `2` point card deduction, `3` VIP rates
+    #[serde(default)]
+    pub debit_fee: i32,
+}
@@ -1,3 +1,4 @@
+use super::client_order_id::ClientOrderId;
 use serde::Serialize;
 
 /// Order data structure for creating and managing spot orders
@@ -5,7 +6,7 @@ use serde::Serialize;
 pub struct Order {
     /// User-defined text information for the order
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
+    pub text: Option<ClientOrderId>,
     /// Currency pair for the order (e.g., "BTC_USDT")
     pub currency_pair: String,
     /// Order type ("limit", "market", "immediate_or_cancel", "fill_or_kill")
@@ -62,8 +63,8 @@ impl Order {
     }
 
     /// Sets user-defined text information for the order
-    pub fn text(mut self, text: &str) -> Self {
-        self.text = Some(text.into());
+    pub fn text(mut self, text: ClientOrderId) -> Self {
+        self.text = Some(text);
         self
     }
 
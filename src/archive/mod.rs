@@ -0,0 +1,437 @@
+//! Market trade tape archiver.
+//!
+//! [`TradeCrawler`] pulls the complete public trade history of a currency pair
+//! between two timestamps. The range is walked in time windows, oldest first;
+//! inside a window trades are followed backwards with `last_id` cursors until
+//! the window start is passed. Each window is then written in ascending id
+//! order, so the output has no duplicates and no holes between pages.
+//! Breaks in the trade sequence numbers sent by Gate are listed in the
+//! [`ArchiveReport`].
+//!
+//! Trades go to a [`TradeSink`]; CSV and JSON Lines sinks are built in.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::archive::{JsonLinesSink, TradeCrawler};
+//! use gateio_rs::ureq::GateHttpClient;
+//!
+//! let client = GateHttpClient::default();
+//! let mut sink = JsonLinesSink::create("btc_usdt_trades.jsonl")?;
+//!
+//! let report = TradeCrawler::new("BTC_USDT", 1_700_000_000, 1_700_086_400).run(&client, &mut sink)?;
+//! println!("{} trades in {} requests", report.trades, report.requests);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod sink;
+mod trade;
+
+pub use sink::*;
+pub use trade::Trade;
+
+use crate::api::spot::get_market_trades::GetMarketTrades;
+use crate::http::GateError;
+use crate::utils::parse;
+use std::fmt;
+use std::io;
+
+/// Maximum number of trades returned by one market trades call
+pub const MAX_LIMIT: u32 = 1000;
+
+/// Download of the trade tape of one currency pair
+#[derive(Clone, Debug)]
+pub struct TradeCrawler {
+    /// Currency pair to download
+    pub currency_pair: String,
+    /// Start of the range (UNIX seconds)
+    pub from: i64,
+    /// End of the range, inclusive (UNIX seconds)
+    pub to: i64,
+    /// Length of the time windows written at once, in seconds (at least 1)
+    pub window: i64,
+    /// Trades requested per call (1 to [`MAX_LIMIT`])
+    pub limit: u32,
+}
+
+impl TradeCrawler {
+    /// Creates a download of the trades between `from` and `to` (inclusive)
+    pub fn new(currency_pair: &str, from: i64, to: i64) -> Self {
+        Self {
+            currency_pair: currency_pair.to_owned(),
+            from,
+            to,
+            window: 3600,
+            limit: MAX_LIMIT,
+        }
+    }
+
+    /// Sets the length of the time windows, in seconds.
+    ///
+    /// The trades of a window are held in memory before being written.
+    pub fn window(mut self, window: i64) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Sets the number of trades requested per call, capped at [`MAX_LIMIT`]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit.clamp(1, MAX_LIMIT);
+        self
+    }
+
+    /// Downloads the range with the blocking client
+    #[cfg(feature = "enable-ureq")]
+    pub fn run<S: TradeSink>(
+        &self,
+        client: &crate::ureq::GateHttpClient,
+        sink: &mut S,
+    ) -> Result<ArchiveReport, ArchiveError> {
+        let mut walk = Walk::new(self);
+        while let Some(request) = walk.next_request() {
+            let body = client.send(request)?.into_body_str()?;
+            if let Some(trades) = walk.record(parse(&body)?) {
+                sink.write(&trades)?;
+                sink.flush()?;
+            }
+        }
+        Ok(walk.report)
+    }
+
+    /// Downloads the range with the async client
+    #[cfg(feature = "enable-hyper")]
+    pub async fn run_async<S: TradeSink>(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+        sink: &mut S,
+    ) -> Result<ArchiveReport, ArchiveError> {
+        let mut walk = Walk::new(self);
+        while let Some(request) = walk.next_request() {
+            let body = client.send(request).await?.into_body_str().await?;
+            if let Some(trades) = walk.record(parse(&body)?) {
+                sink.write(&trades)?;
+                sink.flush()?;
+            }
+        }
+        Ok(walk.report)
+    }
+}
+
+/// Cursor state of a crawl, shared by both clients
+struct Walk<'a> {
+    crawler: &'a TradeCrawler,
+    /// Window length and page size, clamped to valid values
+    span: i64,
+    limit: u32,
+    /// Current window, inclusive
+    window: (i64, i64),
+    /// Lowest id seen in the current window
+    cursor: Option<u64>,
+    /// Trades of the current window
+    buffer: Vec<Trade>,
+    /// Highest id written so far
+    last_written: Option<u64>,
+    /// Id and sequence number of the last written trade carrying one
+    last_sequence: Option<(u64, u64)>,
+    report: ArchiveReport,
+}
+
+impl<'a> Walk<'a> {
+    fn new(crawler: &'a TradeCrawler) -> Self {
+        let span = crawler.window.max(1);
+        Self {
+            crawler,
+            span,
+            limit: crawler.limit.clamp(1, MAX_LIMIT),
+            window: (
+                crawler.from,
+                crawler.to.min(crawler.from.saturating_add(span - 1)),
+            ),
+            cursor: None,
+            buffer: Vec::new(),
+            last_written: None,
+            last_sequence: None,
+            report: ArchiveReport::default(),
+        }
+    }
+
+    fn next_request(&self) -> Option<GetMarketTrades> {
+        if self.window.0 > self.crawler.to {
+            return None;
+        }
+        let request = GetMarketTrades::new(&self.crawler.currency_pair).limit(self.limit as i32);
+        Some(match self.cursor {
+            // The first page of a window holds its newest trades
+            None => request.from(self.window.0).to(self.window.1),
+            Some(cursor) => request.last_id(&cursor.to_string()).reverse(true),
+        })
+    }
+
+    /// Records a page, returning the trades of the window once it is complete
+    fn record(&mut self, mut page: Vec<Trade>) -> Option<Vec<Trade>> {
+        self.report.requests += 1;
+        let full = page.len() >= self.limit as usize;
+
+        // Anything at or above the cursor was already seen
+        if let Some(cursor) = self.cursor {
+            let before = page.len();
+            page.retain(|trade| trade.id < cursor);
+            self.report.duplicates += (before - page.len()) as u64;
+        }
+
+        let oldest = page.iter().min_by_key(|trade| trade.id);
+        let passed_start = oldest.is_none_or(|trade| trade.create_time < self.window.0);
+        self.cursor = oldest.map(|trade| trade.id).or(self.cursor);
+
+        let (from, to) = self.window;
+        self.buffer.extend(
+            page.into_iter()
+                .filter(|t| from <= t.create_time && t.create_time <= to),
+        );
+
+        if full && !passed_start {
+            return None;
+        }
+
+        // Window complete: move on and hand out its trades in id order
+        let mut trades = std::mem::take(&mut self.buffer);
+        trades.sort_by_key(|trade| trade.id);
+        let fetched = trades.len();
+        trades.dedup_by_key(|trade| trade.id);
+        if let Some(last) = self.last_written {
+            trades.retain(|trade| trade.id > last);
+        }
+        self.report.duplicates += (fetched - trades.len()) as u64;
+        self.report.trades += trades.len() as u64;
+        if let Some(trade) = trades.last() {
+            self.last_written = Some(trade.id);
+            self.report.last_id = Some(trade.id);
+        }
+        if let Some(trade) = trades.first() {
+            self.report.first_id.get_or_insert(trade.id);
+        }
+        self.check_sequence(&trades);
+
+        let start = to.saturating_add(1);
+        self.window = (
+            start,
+            self.crawler.to.min(start.saturating_add(self.span - 1)),
+        );
+        self.cursor = None;
+        Some(trades)
+    }
+
+    /// Records the breaks in the sequence numbers of trades written in id order
+    fn check_sequence(&mut self, trades: &[Trade]) {
+        for trade in trades {
+            let Some(sequence) = trade.sequence_id.as_deref().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            if let Some((after_id, last)) = self.last_sequence
+                && sequence > last + 1
+            {
+                self.report.gaps.push(TradeGap {
+                    after_id,
+                    before_id: trade.id,
+                    missing: sequence - last - 1,
+                });
+            }
+            self.last_sequence = Some((trade.id, sequence));
+        }
+    }
+}
+
+/// Trades missing from the tape, detected from their sequence numbers
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TradeGap {
+    /// Id of the last trade written before the gap
+    pub after_id: u64,
+    /// Id of the first trade written after the gap
+    pub before_id: u64,
+    /// Number of sequence numbers skipped
+    pub missing: u64,
+}
+
+/// Summary of a finished crawl
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ArchiveReport {
+    /// Trades written
+    pub trades: u64,
+    /// Requests sent
+    pub requests: u64,
+    /// Trades returned more than once and dropped
+    pub duplicates: u64,
+    /// Id of the first trade written
+    pub first_id: Option<u64>,
+    /// Id of the last trade written
+    pub last_id: Option<u64>,
+    /// Breaks in the trade sequence, within or between pages and windows.
+    ///
+    /// Only trades carrying a `sequence_id` are checked.
+    pub gaps: Vec<TradeGap>,
+}
+
+/// Error of a trade crawl
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// Request to the Gate API failed
+    Gate(Box<GateError>),
+    /// Writing trades failed
+    Io(io::Error),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Gate(e) => write!(f, "Gate API error: {}", e),
+            ArchiveError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArchiveError::Gate(e) => Some(e.as_ref()),
+            ArchiveError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<GateError> for ArchiveError {
+    fn from(err: GateError) -> Self {
+        ArchiveError::Gate(Box::new(err))
+    }
+}
+
+impl From<Box<GateError>> for ArchiveError {
+    fn from(err: Box<GateError>) -> Self {
+        ArchiveError::Gate(err)
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(err: io::Error) -> Self {
+        ArchiveError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::request::Request;
+
+    fn trade(id: u64, create_time: i64) -> Trade {
+        Trade {
+            id,
+            create_time,
+            create_time_ms: String::new(),
+            currency_pair: "BTC_USDT".into(),
+            side: "buy".into(),
+            amount: "1".into(),
+            price: "100".into(),
+            sequence_id: Some(id.to_string()),
+        }
+    }
+
+    fn param(request: GetMarketTrades, key: &str) -> Option<String> {
+        let request: Request = request.into();
+        request
+            .params
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn ids(trades: &[Trade]) -> Vec<u64> {
+        trades.iter().map(|trade| trade.id).collect()
+    }
+
+    #[test]
+    fn follows_cursor_until_window_start() {
+        let crawler = TradeCrawler::new("BTC_USDT", 100, 199).window(100).limit(2);
+        let mut walk = Walk::new(&crawler);
+
+        let first = walk.next_request().unwrap();
+        assert_eq!(param(first.clone(), "from").as_deref(), Some("100"));
+        assert_eq!(param(first, "to").as_deref(), Some("199"));
+        assert!(walk.record(vec![trade(5, 150), trade(4, 140)]).is_none());
+
+        let next = walk.next_request().unwrap();
+        assert_eq!(param(next.clone(), "last_id").as_deref(), Some("4"));
+        assert_eq!(param(next, "reverse").as_deref(), Some("true"));
+        // The page overlaps the cursor and reaches before the window
+        let trades = walk.record(vec![trade(4, 140), trade(3, 120), trade(2, 90)]);
+        assert_eq!(ids(&trades.unwrap()), vec![3, 4, 5]);
+        assert!(walk.next_request().is_none());
+        assert_eq!(walk.report.duplicates, 1);
+        assert_eq!(walk.report.trades, 3);
+        assert_eq!(walk.report.requests, 2);
+        assert_eq!(
+            (walk.report.first_id, walk.report.last_id),
+            (Some(3), Some(5))
+        );
+    }
+
+    #[test]
+    fn drops_trades_written_in_previous_window() {
+        let crawler = TradeCrawler::new("BTC_USDT", 0, 19).window(10);
+        let mut walk = Walk::new(&crawler);
+        assert_eq!(
+            ids(&walk.record(vec![trade(2, 9), trade(1, 5)]).unwrap()),
+            vec![1, 2]
+        );
+        // A trade on the boundary returned again by the next window
+        assert_eq!(
+            ids(&walk.record(vec![trade(3, 10), trade(2, 10)]).unwrap()),
+            vec![3]
+        );
+        assert_eq!(walk.report.duplicates, 1);
+        assert!(walk.next_request().is_none());
+    }
+
+    #[test]
+    fn reports_sequence_gaps_between_windows() {
+        let crawler = TradeCrawler::new("BTC_USDT", 0, 29).window(10);
+        let mut walk = Walk::new(&crawler);
+        walk.record(vec![trade(2, 5), trade(1, 1)]);
+        walk.record(vec![]);
+        walk.record(vec![trade(6, 25), trade(3, 21)]);
+        assert_eq!(
+            walk.report.gaps,
+            vec![TradeGap {
+                after_id: 3,
+                before_id: 6,
+                missing: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn trades_without_sequence_are_not_checked() {
+        let crawler = TradeCrawler::new("BTC_USDT", 0, 9);
+        let mut walk = Walk::new(&crawler);
+        let mut old = trade(1, 1);
+        old.sequence_id = None;
+        walk.record(vec![trade(9, 2), old]);
+        assert!(walk.report.gaps.is_empty());
+    }
+
+    #[test]
+    fn zero_window_and_limit_are_clamped() {
+        let mut crawler = TradeCrawler::new("BTC_USDT", 0, 1);
+        crawler.window = 0;
+        crawler.limit = 0;
+        let mut walk = Walk::new(&crawler);
+        let request = walk.next_request().unwrap();
+        assert_eq!(param(request.clone(), "to").as_deref(), Some("0"));
+        assert_eq!(param(request, "limit").as_deref(), Some("1"));
+        assert!(walk.record(vec![]).is_some());
+        assert_eq!(
+            param(walk.next_request().unwrap(), "from").as_deref(),
+            Some("1")
+        );
+        assert!(walk.record(vec![]).is_some());
+        assert!(walk.next_request().is_none());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::archive::Trade;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Destination of archived trades.
+///
+/// Trades are written in ascending id order; [`TradeSink::flush`] is called
+/// after every completed time window.
+pub trait TradeSink {
+    /// Writes a batch of trades
+    fn write(&mut self, trades: &[Trade]) -> io::Result<()>;
+
+    /// Persists everything written so far
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TradeSink for Vec<Trade> {
+    fn write(&mut self, trades: &[Trade]) -> io::Result<()> {
+        self.extend_from_slice(trades);
+        Ok(())
+    }
+}
+
+fn append(path: &Path) -> io::Result<(BufWriter<File>, bool)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let is_empty = file.metadata()?.len() == 0;
+    Ok((BufWriter::new(file), is_empty))
+}
+
+/// Appends trades to a CSV file, writing the header to new files
+pub struct CsvSink {
+    writer: BufWriter<File>,
+}
+
+impl CsvSink {
+    /// Opens `path` for appending
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (mut writer, is_empty) = append(path.as_ref())?;
+        if is_empty {
+            writeln!(
+                writer,
+                "id,create_time,create_time_ms,currency_pair,side,amount,price"
+            )?;
+        }
+        Ok(Self { writer })
+    }
+}
+
+impl TradeSink for CsvSink {
+    fn write(&mut self, trades: &[Trade]) -> io::Result<()> {
+        for trade in trades {
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{}",
+                trade.id,
+                trade.create_time,
+                trade.create_time_ms,
+                trade.currency_pair,
+                trade.side,
+                trade.amount,
+                trade.price
+            )?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Appends trades to a JSON Lines file, one JSON object per line
+pub struct JsonLinesSink {
+    writer: BufWriter<File>,
+}
+
+impl JsonLinesSink {
+    /// Opens `path` for appending
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (writer, _) = append(path.as_ref())?;
+        Ok(Self { writer })
+    }
+}
+
+impl TradeSink for JsonLinesSink {
+    fn write(&mut self, trades: &[Trade]) -> io::Result<()> {
+        for trade in trades {
+            serde_json::to_writer(&mut self.writer, trade)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// Public market trade as returned by the market trades endpoint.
+///
+/// Prices and amounts are kept as the decimal strings sent by Gate.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Trade {
+    /// Trade ID, increasing over time for a currency pair
+    #[serde(with = "crate::utils::number_string")]
+    pub id: u64,
+    /// Trading time (UNIX seconds)
+    #[serde(with = "crate::utils::number_string")]
+    pub create_time: i64,
+    /// Trading time with millisecond precision
+    #[serde(default)]
+    pub create_time_ms: String,
+    /// Currency pair
+    pub currency_pair: String,
+    /// Taker side, `buy` or `sell`
+    pub side: String,
+    /// Trade amount in base currency
+    pub amount: String,
+    /// Trade price
+    pub price: String,
+    /// Consecutive trade sequence number within the pair, when provided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_id: Option<String>,
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Candlestick interval accepted by the candlesticks endpoint.
+///
+/// The monthly `30d` interval is not included: its bars follow calendar
+/// months and cannot be split into fixed-size windows.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub enum Interval {
+    /// 10 seconds
+    #[serde(rename = "10s")]
+    Sec10,
+    /// 1 minute
+    #[serde(rename = "1m")]
+    Min1,
+    /// 5 minutes
+    #[serde(rename = "5m")]
+    Min5,
+    /// 15 minutes
+    #[serde(rename = "15m")]
+    Min15,
+    /// 30 minutes
+    #[serde(rename = "30m")]
+    Min30,
+    /// 1 hour
+    #[serde(rename = "1h")]
+    Hour1,
+    /// 4 hours
+    #[serde(rename = "4h")]
+    Hour4,
+    /// 8 hours
+    #[serde(rename = "8h")]
+    Hour8,
+    /// 1 day
+    #[serde(rename = "1d")]
+    Day1,
+    /// 7 days
+    #[serde(rename = "7d")]
+    Day7,
+}
+
+impl Interval {
+    /// Returns the value sent as the `interval` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interval::Sec10 => "10s",
+            Interval::Min1 => "1m",
+            Interval::Min5 => "5m",
+            Interval::Min15 => "15m",
+            Interval::Min30 => "30m",
+            Interval::Hour1 => "1h",
+            Interval::Hour4 => "4h",
+            Interval::Hour8 => "8h",
+            Interval::Day1 => "1d",
+            Interval::Day7 => "7d",
+        }
+    }
+
+    /// Returns the length of one bar in seconds
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Interval::Sec10 => 10,
+            Interval::Min1 => 60,
+            Interval::Min5 => 5 * 60,
+            Interval::Min15 => 15 * 60,
+            Interval::Min30 => 30 * 60,
+            Interval::Hour1 => 3600,
+            Interval::Hour4 => 4 * 3600,
+            Interval::Hour8 => 8 * 3600,
+            Interval::Day1 => 86_400,
+            Interval::Day7 => 7 * 86_400,
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "10s" => Interval::Sec10,
+            "1m" => Interval::Min1,
+            "5m" => Interval::Min5,
+            "15m" => Interval::Min15,
+            "30m" => Interval::Min30,
+            "1h" => Interval::Hour1,
+            "4h" => Interval::Hour4,
+            "8h" => Interval::Hour8,
+            "1d" => Interval::Day1,
+            "7d" => Interval::Day7,
+            other => return Err(format!("unsupported interval: {}", other)),
+        })
+    }
+}
+
+/// One candlestick bar.
+///
+/// Deserialized from the array rows returned by the candlesticks endpoint:
+/// `[timestamp, quote volume, close, high, low, open, base volume, closed]`.
+#[derive(PartialEq, Clone, Debug, Deserialize)]
+#[serde(try_from = "Vec<Value>")]
+pub struct Candle {
+    /// Bar start time (UNIX seconds)
+    pub timestamp: i64,
+    /// Trading volume in quote currency
+    pub quote_volume: f64,
+    /// Close price
+    pub close: f64,
+    /// Highest price
+    pub high: f64,
+    /// Lowest price
+    pub low: f64,
+    /// Open price
+    pub open: f64,
+    /// Trading volume in base currency
+    pub base_volume: f64,
+    /// Whether the bar is final
+    pub closed: bool,
+}
+
+impl TryFrom<Vec<Value>> for Candle {
+    type Error = String;
+
+    fn try_from(row: Vec<Value>) -> Result<Self, Self::Error> {
+        let field = |index: usize| -> Result<f64, String> {
+            match row.get(index) {
+                Some(Value::String(value)) => value
+                    .parse()
+                    .map_err(|_| format!("invalid candlestick field {}: {}", index, value)),
+                Some(Value::Number(value)) => value
+                    .as_f64()
+                    .ok_or_else(|| format!("invalid candlestick field {}", index)),
+                _ => Err(format!("missing candlestick field {}", index)),
+            }
+        };
+
+        Ok(Candle {
+            timestamp: field(0)? as i64,
+            quote_volume: field(1)?,
+            close: field(2)?,
+            high: field(3)?,
+            low: field(4)?,
+            open: field(5)?,
+            // Older responses end after the close price
+            base_volume: field(6).unwrap_or(0.0),
+            closed: match row.get(7) {
+                Some(Value::Bool(closed)) => *closed,
+                Some(Value::String(closed)) => closed != "false",
+                _ => true,
+            },
+        })
+    }
+}
+
+/// Range of missing bars, given as inclusive bar start times
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Gap {
+    /// Start time of the first missing bar
+    pub from: i64,
+    /// Start time of the last missing bar
+    pub to: i64,
+}
+
+impl Gap {
+    /// Returns the number of missing bars
+    pub fn bars(&self, interval: Interval) -> i64 {
+        (self.to - self.from) / interval.seconds() + 1
+    }
+}
+
+/// Splits `from..=to` into consecutive windows of at most `max_points` bars.
+///
+/// Window boundaries are aligned to the interval, except that the first window
+/// starts at `from`. Each window is an inclusive `(from, to)` pair that can be
+/// passed straight to `GetCandlesticks`.
+///
+/// # Examples
+///
+/// ```rust
+/// use gateio_rs::backfill::{Interval, windows};
+///
+/// assert_eq!(
+///     windows(0, 7_199, Interval::Min1, 50),
+///     vec![(0, 2_999), (3_000, 5_999), (6_000, 7_199)]
+/// );
+/// assert_eq!(
+///     windows(90, 3_599, Interval::Min1, 50),
+///     vec![(90, 3_059), (3_060, 3_599)]
+/// );
+/// ```
+pub fn windows(from: i64, to: i64, interval: Interval, max_points: u32) -> Vec<(i64, i64)> {
+    let step = interval.seconds();
+    let span = step * i64::from(max_points.max(1));
+    let mut start = from.div_euclid(step) * step;
+    let mut windows = Vec::new();
+    while start <= to {
+        windows.push((start.max(from), to.min(start + span - 1)));
+        start += span;
+    }
+    windows
+}
+
+/// Finds the bars missing from `candles` within `from..=to`.
+///
+/// `candles` must be sorted by timestamp.
+///
+/// # Examples
+///
+/// ```rust
+/// use gateio_rs::backfill::{Candle, Gap, Interval, gaps};
+///
+/// let candle = |timestamp| Candle {
+///     timestamp,
+///     quote_volume: 0.0,
+///     close: 1.0,
+///     high: 1.0,
+///     low: 1.0,
+///     open: 1.0,
+///     base_volume: 0.0,
+///     closed: true,
+/// };
+/// let candles = [candle(60), candle(240)];
+/// assert_eq!(
+///     gaps(&candles, 0, 299, Interval::Min1),
+///     vec![Gap { from: 0, to: 0 }, Gap { from: 120, to: 180 }]
+/// );
+/// ```
+pub fn gaps(candles: &[Candle], from: i64, to: i64, interval: Interval) -> Vec<Gap> {
+    let step = interval.seconds();
+    let mut expected = from.div_euclid(step) * step;
+    if expected < from {
+        expected += step;
+    }
+
+    let mut gaps = Vec::new();
+    for candle in candles {
+        if candle.timestamp - expected >= step {
+            gaps.push(Gap {
+                from: expected,
+                to: expected + (candle.timestamp - expected - 1) / step * step,
+            });
+        }
+        expected = expected.max(candle.timestamp + step);
+    }
+    if expected <= to {
+        gaps.push(Gap {
+            from: expected,
+            to: expected + (to - expected) / step * step,
+        });
+    }
+    gaps
+}
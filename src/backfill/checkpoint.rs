@@ -0,0 +1,41 @@
+use crate::backfill::Interval;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Progress of a backfill, persisted after every written window
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Currency pair being downloaded
+    pub currency_pair: String,
+    /// Candlestick interval
+    pub interval: Interval,
+    /// Start of the requested range
+    pub from: i64,
+    /// Start of the first window not yet written
+    pub next_from: i64,
+    /// Number of candles written so far
+    pub candles: u64,
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint, returning `None` when the file does not exist
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .map(Some)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Atomically replaces the checkpoint file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(tmp, path)
+    }
+}
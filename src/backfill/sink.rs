@@ -0,0 +1,181 @@
+use crate::backfill::Candle;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Destination of downloaded candles.
+///
+/// Candles are written window by window in ascending timestamp order;
+/// [`CandleSink::flush`] is called before the checkpoint moves forward.
+pub trait CandleSink {
+    /// Writes a batch of candles
+    fn write(&mut self, candles: &[Candle]) -> io::Result<()>;
+
+    /// Persists everything written so far
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CandleSink for Vec<Candle> {
+    fn write(&mut self, candles: &[Candle]) -> io::Result<()> {
+        self.extend_from_slice(candles);
+        Ok(())
+    }
+}
+
+const CSV_HEADER: &str = "timestamp,open,high,low,close,base_volume,quote_volume";
+
+/// Appends candles to a CSV file.
+///
+/// When the file already exists, candles at or before its last row are
+/// skipped, so a window rewritten after an interruption is not duplicated.
+pub struct CsvSink {
+    writer: BufWriter<File>,
+    last: Option<i64>,
+}
+
+impl CsvSink {
+    /// Opens `path` for appending, writing the header to new files
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let last = match File::open(path) {
+            Ok(file) => last_timestamp(file)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+        if is_empty {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+        Ok(Self { writer, last })
+    }
+}
+
+impl CandleSink for CsvSink {
+    fn write(&mut self, candles: &[Candle]) -> io::Result<()> {
+        for candle in candles {
+            if self.last.is_some_and(|last| candle.timestamp <= last) {
+                continue;
+            }
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{}",
+                candle.timestamp,
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                candle.base_volume,
+                candle.quote_volume
+            )?;
+            self.last = Some(candle.timestamp);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()
+    }
+}
+
+fn last_timestamp(file: File) -> io::Result<Option<i64>> {
+    let mut last = None;
+    for line in BufReader::new(file).lines() {
+        if let Some(Ok(timestamp)) = line?.split(',').next().map(str::parse) {
+            last = Some(timestamp);
+        }
+    }
+    Ok(last)
+}
+
+/// Writes every batch of candles to its own Parquet file.
+///
+/// Files are named `<prefix>-<first timestamp>.parquet` inside the target
+/// directory, so a window rewritten after an interruption replaces its
+/// earlier file. Requires the `enable-parquet` feature.
+#[cfg(feature = "enable-parquet")]
+pub struct ParquetSink {
+    dir: std::path::PathBuf,
+    prefix: String,
+}
+
+#[cfg(feature = "enable-parquet")]
+impl ParquetSink {
+    const SCHEMA: &'static str = "message candle {
+        required int64 timestamp;
+        required double open;
+        required double high;
+        required double low;
+        required double close;
+        required double base_volume;
+        required double quote_volume;
+    }";
+
+    /// Writes files named `<prefix>-<timestamp>.parquet` into `dir`, creating it if needed
+    pub fn create(dir: impl AsRef<Path>, prefix: &str) -> io::Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_owned(),
+            prefix: prefix.to_owned(),
+        })
+    }
+
+    fn write_file(&self, path: &Path, candles: &[Candle]) -> parquet::errors::Result<()> {
+        use parquet::data_type::{DoubleType, Int64Type};
+        use parquet::file::writer::SerializedFileWriter;
+        use std::sync::Arc;
+
+        let schema = Arc::new(parquet::schema::parser::parse_message_type(Self::SCHEMA)?);
+        let mut writer =
+            SerializedFileWriter::new(File::create(path)?, schema, Default::default())?;
+        let mut row_group = writer.next_row_group()?;
+
+        let timestamps: Vec<i64> = candles.iter().map(|candle| candle.timestamp).collect();
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<Int64Type>()
+                .write_batch(&timestamps, None, None)?;
+            column.close()?;
+        }
+
+        let fields: [fn(&Candle) -> f64; 6] = [
+            |candle| candle.open,
+            |candle| candle.high,
+            |candle| candle.low,
+            |candle| candle.close,
+            |candle| candle.base_volume,
+            |candle| candle.quote_volume,
+        ];
+        for field in fields {
+            let values: Vec<f64> = candles.iter().map(field).collect();
+            if let Some(mut column) = row_group.next_column()? {
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&values, None, None)?;
+                column.close()?;
+            }
+        }
+
+        row_group.close()?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "enable-parquet")]
+impl CandleSink for ParquetSink {
+    fn write(&mut self, candles: &[Candle]) -> io::Result<()> {
+        let Some(first) = candles.first() else {
+            return Ok(());
+        };
+        let path = self
+            .dir
+            .join(format!("{}-{}.parquet", self.prefix, first.timestamp));
+        self.write_file(&path, candles).map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,403 @@
+//! Batch requests split to fit Gate's batch limits.
+//!
+//! Gate caps the size of batch endpoints (e.g. 10 orders over at most 4
+//! currency pairs for `create_batch_orders`) and answers with one entry per
+//! item, each carrying its own `succeeded`/`label`/`message`. Requests
+//! implementing [`Batch`] can be sent with `send_batch()` on either client:
+//!
+//! - the items are packed into as few compliant requests as possible
+//! - the requests are sent one after the other with the ureq client, in
+//!   parallel with the hyper client
+//! - one result is returned per item, in the order the items were given;
+//!   refused items and failed requests are reported as [`BatchItemError`]
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::api::spot::{ClientOrderIdGenerator, MonotonicIds, Order, create_batch_orders};
+//! use gateio_rs::{http::Credentials, ureq::GateHttpClient};
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//! let ids = MonotonicIds::new();
+//!
+//! let orders = (0..25)
+//!     .map(|i| {
+//!         Order::new("BTC_USDT", "buy", "0.001")
+//!             .price(&(30_000 - i * 10).to_string())
+//!             .text(ids.next_id())
+//!     })
+//!     .collect();
+//!
+//! // Sent as three requests of 10, 10 and 5 orders
+//! for (i, result) in client.send_batch(create_batch_orders(orders)).into_iter().enumerate() {
+//!     match result {
+//!         Ok(order) => println!("order {} placed as {}", i, order.id),
+//!         Err(err) => println!("order {} failed: {}", i, err),
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::{GateError, GateErrorLabel, request::Request};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// Size limits of a batch endpoint
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BatchLimits {
+    /// Maximum number of items per request
+    pub max_items: usize,
+    /// Maximum number of distinct currency pairs per request
+    pub max_pairs: Option<usize>,
+    /// Maximum number of requests in flight (async client only)
+    pub concurrency: usize,
+}
+
+impl BatchLimits {
+    /// Creates limits of `max_items` items per request
+    pub fn new(max_items: usize) -> Self {
+        Self {
+            max_items: max_items.max(1),
+            max_pairs: None,
+            concurrency: 4,
+        }
+    }
+
+    /// Sets the maximum number of distinct currency pairs per request
+    pub fn max_pairs(mut self, max_pairs: usize) -> Self {
+        self.max_pairs = Some(max_pairs.max(1));
+        self
+    }
+
+    /// Sets the maximum number of requests in flight (async client only)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+/// Request builder of a batch endpoint
+pub trait Batch: Into<Request> {
+    /// Item of the batch
+    type Item;
+    /// Response entry of a successful item
+    type Output: DeserializeOwned;
+
+    /// Limits Gate documents for the endpoint
+    fn limits() -> BatchLimits;
+
+    /// Takes the items out of the request
+    fn take_items(&mut self) -> Vec<Self::Item>;
+
+    /// Creates a request with the same options carrying `items`
+    fn with_items(&self, items: Vec<Self::Item>) -> Self;
+
+    /// Currency pair of an item
+    fn currency_pair(item: &Self::Item) -> &str;
+
+    /// Whether two items may share a request
+    fn compatible(_a: &Self::Item, _b: &Self::Item) -> bool {
+        true
+    }
+}
+
+/// Failure of a single batch item
+#[derive(Clone, Debug)]
+pub enum BatchItemError {
+    /// Gate refused the item
+    Rejected {
+        /// Error label
+        label: GateErrorLabel,
+        /// Error message
+        message: String,
+    },
+    /// The request carrying the item failed; shared by every item it carried
+    Request(Arc<GateError>),
+    /// The response had no entry for the item
+    Missing,
+    /// The response entry of the item could not be decoded
+    InvalidEntry(String),
+}
+
+impl fmt::Display for BatchItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchItemError::Rejected { label, message } => {
+                write!(f, "Rejected: {} {}", label.as_str(), message)
+            }
+            BatchItemError::Request(e) => write!(f, "Batch request failed: {}", e),
+            BatchItemError::Missing => write!(f, "No response entry for the item"),
+            BatchItemError::InvalidEntry(e) => write!(f, "Invalid response entry: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BatchItemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BatchItemError::Request(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Result of one batch item
+pub type BatchResult<T> = Result<T, BatchItemError>;
+
+/// Request being filled by [`split`]
+struct Chunk<I> {
+    /// Positions of the items in the original batch
+    indices: Vec<usize>,
+    items: Vec<I>,
+    pairs: Vec<String>,
+}
+
+impl<I> Chunk<I> {
+    fn fits<B: Batch<Item = I>>(&self, item: &I, limits: &BatchLimits) -> bool {
+        let pair = B::currency_pair(item);
+        self.items.len() < limits.max_items
+            && self
+                .items
+                .first()
+                .is_none_or(|first| B::compatible(first, item))
+            && (self.pairs.iter().any(|p| p == pair)
+                || limits.max_pairs.is_none_or(|max| self.pairs.len() < max))
+    }
+}
+
+/// Packs the items of `batch` into compliant requests, first fit.
+///
+/// Each request comes with the positions of its items in the original batch.
+pub(crate) fn split<B: Batch>(mut batch: B, limits: &BatchLimits) -> Vec<(Vec<usize>, B)> {
+    let mut chunks: Vec<Chunk<B::Item>> = Vec::new();
+    for (index, item) in batch.take_items().into_iter().enumerate() {
+        let position = match chunks.iter().position(|c| c.fits::<B>(&item, limits)) {
+            Some(position) => position,
+            None => {
+                chunks.push(Chunk {
+                    indices: Vec::new(),
+                    items: Vec::new(),
+                    pairs: Vec::new(),
+                });
+                chunks.len() - 1
+            }
+        };
+        let chunk = &mut chunks[position];
+        let pair = B::currency_pair(&item);
+        if !chunk.pairs.iter().any(|p| p == pair) {
+            chunk.pairs.push(pair.to_owned());
+        }
+        chunk.indices.push(index);
+        chunk.items.push(item);
+    }
+    chunks
+        .into_iter()
+        .map(|chunk| (chunk.indices, batch.with_items(chunk.items)))
+        .collect()
+}
+
+/// Response entry of a batch endpoint
+#[derive(Deserialize)]
+struct Entry {
+    #[serde(default = "succeeded")]
+    succeeded: bool,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    message: String,
+    #[serde(flatten)]
+    fields: Value,
+}
+
+fn succeeded() -> bool {
+    true
+}
+
+/// Results of a batch, put back in the original item order
+pub(crate) struct BatchResults<T> {
+    results: Vec<Option<BatchResult<T>>>,
+}
+
+impl<T: DeserializeOwned> BatchResults<T> {
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            results: (0..len).map(|_| None).collect(),
+        }
+    }
+
+    /// Records the response to the request carrying the items at `indices`
+    pub(crate) fn record(&mut self, indices: &[usize], response: Result<String, GateError>) {
+        let entries = response.and_then(|body| {
+            serde_json::from_str::<Vec<Entry>>(&body)
+                .map_err(|err| GateError::InvalidBody(Box::new(err)))
+        });
+        match entries {
+            Ok(entries) => {
+                let mut entries = entries.into_iter();
+                for &index in indices {
+                    self.results[index] = Some(match entries.next() {
+                        Some(entry) => parse_entry(entry),
+                        None => Err(BatchItemError::Missing),
+                    });
+                }
+            }
+            Err(err) => {
+                let err = Arc::new(err);
+                for &index in indices {
+                    self.results[index] = Some(Err(BatchItemError::Request(err.clone())));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<BatchResult<T>> {
+        self.results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(BatchItemError::Missing)))
+            .collect()
+    }
+}
+
+fn parse_entry<T: DeserializeOwned>(entry: Entry) -> BatchResult<T> {
+    if !entry.succeeded {
+        return Err(BatchItemError::Rejected {
+            label: GateErrorLabel::from(entry.label.as_str()),
+            message: entry.message,
+        });
+    }
+    serde_json::from_value(entry.fields)
+        .map_err(|err| BatchItemError::InvalidEntry(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::spot::create_batch_orders::CreateBatchOrders;
+    use crate::api::spot::{Order, create_batch_orders};
+
+    fn orders(pairs: &[&str]) -> CreateBatchOrders {
+        create_batch_orders(
+            pairs
+                .iter()
+                .map(|pair| Order::new(pair, "buy", "1"))
+                .collect(),
+        )
+    }
+
+    fn layout(chunks: &[(Vec<usize>, CreateBatchOrders)]) -> Vec<Vec<usize>> {
+        chunks.iter().map(|(indices, _)| indices.clone()).collect()
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Placed {
+        id: String,
+    }
+
+    fn id(result: &BatchResult<Placed>) -> Option<&str> {
+        result.as_ref().ok().map(|placed| placed.id.as_str())
+    }
+
+    #[test]
+    fn splits_by_item_count() {
+        let chunks = split(orders(&["BTC_USDT"; 25]), &CreateBatchOrders::limits());
+        let sizes: Vec<usize> = chunks.iter().map(|(_, b)| b.orders.len()).collect();
+        assert_eq!(sizes, vec![10, 10, 5]);
+        assert_eq!(chunks[2].0, (20..25).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn exact_limit_fits_one_request() {
+        let chunks = split(orders(&["BTC_USDT"; 10]), &CreateBatchOrders::limits());
+        assert_eq!(layout(&chunks), vec![(0..10).collect::<Vec<_>>()]);
+    }
+
+    #[test]
+    fn empty_batch_sends_nothing() {
+        assert!(split(orders(&[]), &CreateBatchOrders::limits()).is_empty());
+    }
+
+    #[test]
+    fn splits_by_pair_count_first_fit() {
+        let pairs = ["A_USDT", "B_USDT", "C_USDT", "D_USDT", "E_USDT", "A_USDT"];
+        let chunks = split(orders(&pairs), &CreateBatchOrders::limits());
+        // The fifth pair opens a request, the repeated first pair still fits the first
+        assert_eq!(layout(&chunks), vec![vec![0, 1, 2, 3, 5], vec![4]]);
+    }
+
+    #[test]
+    fn incompatible_items_are_kept_apart() {
+        let batch = create_batch_orders(vec![
+            Order::new("BTC_USDT", "buy", "1").account("spot"),
+            Order::new("BTC_USDT", "buy", "1").account("margin"),
+            Order::new("BTC_USDT", "buy", "1").account("spot"),
+        ]);
+        let chunks = split(batch, &CreateBatchOrders::limits());
+        assert_eq!(layout(&chunks), vec![vec![0, 2], vec![1]]);
+    }
+
+    #[test]
+    fn results_are_realigned_to_item_order() {
+        let mut results = BatchResults::<Placed>::new(4);
+        results.record(&[1, 3], Ok(r#"[{"id":"b"},{"id":"d"}]"#.into()));
+        results.record(&[0, 2], Ok(r#"[{"id":"a"},{"id":"c"}]"#.into()));
+        let results = results.finish();
+        assert_eq!(id(&results[0]), Some("a"));
+        assert_eq!(id(&results[1]), Some("b"));
+        assert_eq!(id(&results[2]), Some("c"));
+        assert_eq!(id(&results[3]), Some("d"));
+    }
+
+    #[test]
+    fn rejected_and_invalid_entries() {
+        let mut results = BatchResults::<Placed>::new(3);
+        let body = r#"[
+            {"id":"a","succeeded":true},
+            {"succeeded":false,"label":"BALANCE_NOT_ENOUGH","message":"top up"},
+            {"succeeded":true}
+        ]"#;
+        results.record(&[0, 1, 2], Ok(body.into()));
+        let results = results.finish();
+        assert_eq!(id(&results[0]), Some("a"));
+        match &results[1] {
+            Err(BatchItemError::Rejected { label, message }) => {
+                assert_eq!(label, &GateErrorLabel::BalanceNotEnough);
+                assert_eq!(message, "top up");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(results[2], Err(BatchItemError::InvalidEntry(_))));
+    }
+
+    #[test]
+    fn short_response_leaves_items_missing() {
+        let mut results = BatchResults::<Placed>::new(3);
+        results.record(&[0, 1], Ok(r#"[{"id":"a"}]"#.into()));
+        let results = results.finish();
+        assert_eq!(id(&results[0]), Some("a"));
+        assert!(matches!(results[1], Err(BatchItemError::Missing)));
+        // Never recorded
+        assert!(matches!(results[2], Err(BatchItemError::Missing)));
+    }
+
+    #[test]
+    fn failed_request_is_shared_by_its_items() {
+        let mut results = BatchResults::<Placed>::new(3);
+        results.record(&[0, 2], Err(GateError::Timeout));
+        results.record(&[1], Ok("not json".into()));
+        let results = results.finish();
+        match (&results[0], &results[2]) {
+            (Err(BatchItemError::Request(a)), Err(BatchItemError::Request(b))) => {
+                assert!(Arc::ptr_eq(a, b));
+                assert!(matches!(**a, GateError::Timeout));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            &results[1],
+            Err(BatchItemError::Request(err)) if matches!(**err, GateError::InvalidBody(_))
+        ));
+    }
+}
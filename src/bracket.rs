@@ -0,0 +1,888 @@
+//! Client-side bracket and OCO orders.
+//!
+//! Gate spot has single price-triggered orders but no one-cancels-other
+//! link between them. A [`Bracket`] emulates it:
+//!
+//! 1. an optional entry order is placed with `create_order`
+//! 2. once it is filled, a take-profit and a stop-loss price order are placed
+//!    for the filled amount with `create_price_order`
+//! 3. when one of them fires, the other one is cancelled with
+//!    `cancel_price_order`
+//!
+//! [`ActiveBracket::poll`] drives these steps by polling `get_order` and
+//! `get_price_order`; [`ActiveBracket::run`] polls until the bracket is done.
+//! Updates received elsewhere (e.g. from a WebSocket order feed) can be fed
+//! in with [`ActiveBracket::apply_order`] and
+//! [`ActiveBracket::apply_price_order`], followed by
+//! [`ActiveBracket::advance`] to act on them without polling.
+//!
+//! The emulation runs in this process: while it is not running, nothing
+//! cancels the sibling leg and both legs may fire.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::bracket::{Bracket, Leg};
+//! use gateio_rs::{http::Credentials, ureq::GateHttpClient};
+//! use std::time::Duration;
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//!
+//! // Buy 0.01 BTC at 30000, take profit at 33000, stop out at 29000
+//! let mut bracket = Bracket::new(
+//!     "BTC_USDT",
+//!     "buy",
+//!     "0.01",
+//!     Leg::new("33000", "32990"),
+//!     Leg::new("29000", "28900"),
+//! )
+//! .entry_price("30000")
+//! .place(&client)?;
+//!
+//! let outcome = bracket.run(&client, Duration::from_secs(2))?;
+//! println!("bracket finished: {:?}", outcome);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::api::spot::{
+    ClientOrderId, OrderDetails, PriceOrderDetails, cancel_order, cancel_price_order, create_order,
+    create_order::CreateOrder, create_price_order, create_price_order::CreatePriceOrder, get_order,
+    get_price_order,
+};
+use crate::http::GateError;
+#[cfg(feature = "enable-ureq")]
+use crate::utils::fetch;
+#[cfg(feature = "enable-hyper")]
+use crate::utils::fetch_async;
+use crate::utils::{format_number, number, split_pair};
+use serde::Deserialize;
+use std::fmt;
+
+/// Exit of a bracket, placed as a price-triggered limit order
+#[derive(Clone, Debug)]
+pub struct Leg {
+    /// Price triggering the exit
+    pub trigger_price: String,
+    /// Limit price of the exit order
+    pub order_price: String,
+    /// Time in force of the exit order
+    pub time_in_force: Option<String>,
+}
+
+impl Leg {
+    /// Creates an exit placing a limit order at `order_price` once `trigger_price` is reached
+    pub fn new(trigger_price: &str, order_price: &str) -> Self {
+        Self {
+            trigger_price: trigger_price.to_owned(),
+            order_price: order_price.to_owned(),
+            time_in_force: None,
+        }
+    }
+
+    /// Sets the time in force of the exit order
+    pub fn time_in_force(mut self, time_in_force: &str) -> Self {
+        self.time_in_force = Some(time_in_force.to_owned());
+        self
+    }
+}
+
+/// One of the two exits of a bracket
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum BracketLeg {
+    /// Exit in profit
+    TakeProfit,
+    /// Exit at a loss
+    StopLoss,
+}
+
+impl BracketLeg {
+    fn index(self) -> usize {
+        match self {
+            BracketLeg::TakeProfit => 0,
+            BracketLeg::StopLoss => 1,
+        }
+    }
+}
+
+/// Entry order of a bracket
+#[derive(Clone, Debug)]
+pub struct Entry {
+    /// Entry side, `buy` or `sell`
+    pub side: String,
+    /// Limit price, `None` for a market order
+    pub price: Option<String>,
+    /// Custom order ID
+    pub text: Option<ClientOrderId>,
+}
+
+/// Entry order with linked take-profit and stop-loss exits
+#[derive(Clone, Debug)]
+pub struct Bracket {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Side of the exit orders
+    pub exit_side: String,
+    /// Entry amount, or exit amount without entry
+    pub amount: String,
+    /// Entry order, `None` for a plain OCO pair
+    pub entry: Option<Entry>,
+    /// Take-profit exit
+    pub take_profit: Leg,
+    /// Stop-loss exit
+    pub stop_loss: Leg,
+    /// Account of the orders
+    pub account: Option<String>,
+    /// Lifetime of the exit triggers, in seconds
+    pub expiration: Option<i64>,
+}
+
+impl Bracket {
+    /// Creates a bracket entering with a market order on `side`.
+    ///
+    /// As for any market buy, `amount` is in quote currency for a buy entry;
+    /// the exits cover the amount actually filled.
+    pub fn new(
+        currency_pair: &str,
+        side: &str,
+        amount: &str,
+        take_profit: Leg,
+        stop_loss: Leg,
+    ) -> Self {
+        let exit_side = if side == "buy" { "sell" } else { "buy" };
+        Self {
+            currency_pair: currency_pair.to_owned(),
+            exit_side: exit_side.to_owned(),
+            amount: amount.to_owned(),
+            entry: Some(Entry {
+                side: side.to_owned(),
+                price: None,
+                text: None,
+            }),
+            take_profit,
+            stop_loss,
+            account: None,
+            expiration: None,
+        }
+    }
+
+    /// Creates a one-cancels-other pair of exits for an existing position
+    pub fn oco(
+        currency_pair: &str,
+        exit_side: &str,
+        amount: &str,
+        take_profit: Leg,
+        stop_loss: Leg,
+    ) -> Self {
+        Self {
+            currency_pair: currency_pair.to_owned(),
+            exit_side: exit_side.to_owned(),
+            amount: amount.to_owned(),
+            entry: None,
+            take_profit,
+            stop_loss,
+            account: None,
+            expiration: None,
+        }
+    }
+
+    /// Enters with a limit order at `price` instead of a market order
+    pub fn entry_price(mut self, price: &str) -> Self {
+        if let Some(entry) = &mut self.entry {
+            entry.price = Some(price.to_owned());
+        }
+        self
+    }
+
+    /// Sets the custom order ID of the entry order
+    pub fn entry_text(mut self, text: ClientOrderId) -> Self {
+        if let Some(entry) = &mut self.entry {
+            entry.text = Some(text);
+        }
+        self
+    }
+
+    /// Sets the account of the orders (`spot`, `margin` or `unified`)
+    pub fn account(mut self, account: &str) -> Self {
+        self.account = Some(account.to_owned());
+        self
+    }
+
+    /// Sets the lifetime of the exit triggers, in seconds
+    pub fn expiration(mut self, expiration: i64) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    fn entry_request(&self) -> Option<CreateOrder> {
+        let entry = self.entry.as_ref()?;
+        let mut request = create_order(&self.currency_pair, &entry.side, &self.amount);
+        request = match &entry.price {
+            Some(price) => request.price(price),
+            None => request.order_type("market").time_in_force("ioc"),
+        };
+        if let Some(text) = &entry.text {
+            request = request.text(text.clone());
+        }
+        if let Some(account) = &self.account {
+            request = request.account(account);
+        }
+        Some(request)
+    }
+
+    fn leg_request(&self, leg: BracketLeg, amount: &str) -> CreatePriceOrder {
+        let (exit, rises) = match leg {
+            BracketLeg::TakeProfit => (&self.take_profit, self.exit_side == "sell"),
+            BracketLeg::StopLoss => (&self.stop_loss, self.exit_side == "buy"),
+        };
+        let rule = if rises { ">=" } else { "<=" };
+        let mut request = create_price_order(
+            &self.currency_pair,
+            &exit.trigger_price,
+            rule,
+            &self.exit_side,
+            &exit.order_price,
+            amount,
+        );
+        if let Some(time_in_force) = &exit.time_in_force {
+            request = request.time_in_force(time_in_force);
+        }
+        if let Some(expiration) = self.expiration {
+            request = request.trigger_expiration(expiration);
+        }
+        // Price orders name the plain spot account `normal`
+        match self.account.as_deref() {
+            Some("spot") | None => request.account("normal"),
+            Some(account) => request.account(account),
+        }
+    }
+}
+
+/// Progress of an [`ActiveBracket`]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BracketState {
+    /// Waiting for the entry to fill or for the exits to be placed
+    Entering,
+    /// Both exits wait for their trigger
+    Armed,
+    /// The take-profit fired and the stop-loss was cancelled
+    TookProfit,
+    /// The stop-loss fired and the take-profit was cancelled
+    StoppedOut,
+    /// Both exits fired before either could be cancelled
+    BothFired,
+    /// An exit ended without firing (failed, expired or cancelled
+    /// elsewhere) and the other one was cancelled
+    Broken(BracketLeg),
+    /// Cancelled before any exit fired
+    Cancelled,
+}
+
+impl BracketState {
+    /// Whether the bracket is done
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, BracketState::Entering | BracketState::Armed)
+    }
+}
+
+/// Next step of a bracket
+#[derive(PartialEq, Debug)]
+enum Action {
+    /// Nothing to do until the next update
+    Wait,
+    /// Place the missing exits for the amount
+    PlaceLegs(String),
+    /// Cancel an exit
+    Cancel(BracketLeg),
+    /// The bracket reached a final state
+    Finish(BracketState),
+}
+
+/// Response of `create_price_order`
+#[derive(Deserialize)]
+struct Created {
+    id: i64,
+}
+
+/// Placed bracket, advanced by polling or by feeding order updates
+#[derive(Clone, Debug)]
+pub struct ActiveBracket {
+    /// The bracket
+    pub bracket: Bracket,
+    /// Current state
+    pub state: BracketState,
+    /// Entry order, once placed
+    pub entry_order: Option<OrderDetails>,
+    /// IDs of the take-profit and stop-loss price orders, once placed
+    pub leg_ids: [Option<i64>; 2],
+    /// Last known status of the take-profit and stop-loss price orders
+    pub leg_status: [Option<String>; 2],
+    /// Exit whose loss broke the bracket
+    lost: Option<BracketLeg>,
+}
+
+impl ActiveBracket {
+    fn new(bracket: Bracket) -> Self {
+        Self {
+            bracket,
+            state: BracketState::Entering,
+            entry_order: None,
+            leg_ids: [None, None],
+            leg_status: [None, None],
+            lost: None,
+        }
+    }
+
+    /// ID of the price order of `leg`, once placed
+    pub fn leg_id(&self, leg: BracketLeg) -> Option<i64> {
+        self.leg_ids[leg.index()]
+    }
+
+    /// Applies an update of the entry order.
+    ///
+    /// Returns `false` when the order is not the entry of this bracket.
+    pub fn apply_order(&mut self, order: &OrderDetails) -> bool {
+        match &self.entry_order {
+            Some(entry) if entry.id != order.id => false,
+            _ => {
+                self.entry_order = Some(order.clone());
+                true
+            }
+        }
+    }
+
+    /// Applies an update of an exit price order.
+    ///
+    /// Returns `false` when the order is not an exit of this bracket.
+    pub fn apply_price_order(&mut self, order: &PriceOrderDetails) -> bool {
+        match self.leg_ids.iter().position(|id| *id == Some(order.id)) {
+            Some(index) => {
+                self.leg_status[index] = Some(order.status.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Amount the exits cover once the entry filled: the filled amount,
+    /// less the fee when it was paid in the base currency
+    fn exit_amount(&self) -> Option<Result<String, BracketState>> {
+        if self.bracket.entry.is_none() {
+            return Some(Ok(self.bracket.amount.clone()));
+        }
+        let entry = self.entry_order.as_ref()?;
+        if !matches!(entry.status.as_str(), "closed" | "cancelled") {
+            return None;
+        }
+        let mut held = number(&entry.filled_amount);
+        let (base, _) = split_pair(&self.bracket.currency_pair);
+        if entry.fee_currency.eq_ignore_ascii_case(&base) {
+            held -= number(&entry.fee);
+        }
+        if held > 0.0 {
+            Some(Ok(format_number(held)))
+        } else {
+            Some(Err(BracketState::Cancelled))
+        }
+    }
+
+    fn next_action(&mut self) -> Action {
+        match self.state {
+            BracketState::Entering => match self.exit_amount() {
+                Some(Ok(amount)) => Action::PlaceLegs(amount),
+                Some(Err(state)) => Action::Finish(state),
+                None => Action::Wait,
+            },
+            BracketState::Armed => {
+                let [tp, sl] = &self.leg_status;
+                match (tp.as_deref(), sl.as_deref()) {
+                    (Some("finish"), Some("finish")) => Action::Finish(BracketState::BothFired),
+                    (Some("finish"), Some("open")) => Action::Cancel(BracketLeg::StopLoss),
+                    (Some("finish"), _) => Action::Finish(BracketState::TookProfit),
+                    (Some("open"), Some("finish")) => Action::Cancel(BracketLeg::TakeProfit),
+                    (_, Some("finish")) => Action::Finish(BracketState::StoppedOut),
+                    (Some("open"), Some("open")) => Action::Wait,
+                    (Some("open"), _) => {
+                        self.lost = Some(BracketLeg::StopLoss);
+                        Action::Cancel(BracketLeg::TakeProfit)
+                    }
+                    (_, Some("open")) => {
+                        self.lost = Some(BracketLeg::TakeProfit);
+                        Action::Cancel(BracketLeg::StopLoss)
+                    }
+                    _ => Action::Finish(BracketState::Broken(
+                        self.lost.unwrap_or(BracketLeg::TakeProfit),
+                    )),
+                }
+            }
+            _ => Action::Wait,
+        }
+    }
+
+    /// Records a placed exit, arming the bracket once both are placed
+    fn leg_placed(&mut self, leg: BracketLeg, id: i64) {
+        self.leg_ids[leg.index()] = Some(id);
+        self.leg_status[leg.index()] = Some("open".into());
+        if self.leg_ids.iter().all(Option::is_some) {
+            self.state = BracketState::Armed;
+        }
+    }
+
+    fn missing_legs(&self) -> Vec<BracketLeg> {
+        [BracketLeg::TakeProfit, BracketLeg::StopLoss]
+            .into_iter()
+            .filter(|leg| self.leg_ids[leg.index()].is_none())
+            .collect()
+    }
+
+    /// Exits still waiting for their trigger
+    fn open_legs(&self) -> Vec<i64> {
+        (0..2)
+            .filter(|&index| self.leg_status[index].as_deref() == Some("open"))
+            .filter_map(|index| self.leg_ids[index])
+            .collect()
+    }
+
+    fn entry_pending(&self) -> Option<(String, String)> {
+        let entry = self.entry_order.as_ref()?;
+        (entry.status == "open").then(|| (entry.id.clone(), entry.currency_pair.clone()))
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl Bracket {
+    /// Places the entry order, or the exits when there is no entry
+    pub fn place<S: crate::ureq::SendRequest>(
+        self,
+        client: &S,
+    ) -> Result<ActiveBracket, BracketError> {
+        let mut active = ActiveBracket::new(self);
+        if let Some(request) = active.bracket.entry_request() {
+            let order: OrderDetails = fetch(client, request)?;
+            active.apply_order(&order);
+        }
+        active.advance(client)?;
+        Ok(active)
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl ActiveBracket {
+    /// Queries the orders of the bracket, then acts on their state
+    pub fn poll<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+    ) -> Result<BracketState, BracketError> {
+        match self.state {
+            BracketState::Entering => {
+                if let Some((id, pair)) = self.entry_pending() {
+                    let order: OrderDetails = fetch(client, get_order(&id, &pair))?;
+                    self.apply_order(&order);
+                }
+            }
+            BracketState::Armed => {
+                for id in self.open_legs() {
+                    let order: PriceOrderDetails = fetch(client, get_price_order(&id.to_string()))?;
+                    self.apply_price_order(&order);
+                }
+            }
+            _ => {}
+        }
+        self.advance(client)
+    }
+
+    /// Acts on the known state: places the exits once the entry filled and
+    /// cancels an exit once the other one fired
+    pub fn advance<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+    ) -> Result<BracketState, BracketError> {
+        loop {
+            match self.next_action() {
+                Action::Wait => return Ok(self.state),
+                Action::Finish(state) => self.state = state,
+                Action::PlaceLegs(amount) => {
+                    for leg in self.missing_legs() {
+                        let created: Created =
+                            fetch(client, self.bracket.leg_request(leg, &amount))?;
+                        self.leg_placed(leg, created.id);
+                    }
+                }
+                Action::Cancel(leg) => {
+                    let id = self
+                        .leg_id(leg)
+                        .ok_or(BracketError::MissingLeg(leg))?
+                        .to_string();
+                    match fetch::<PriceOrderDetails, _, _>(client, cancel_price_order(&id)) {
+                        Ok(order) => {
+                            self.apply_price_order(&order);
+                        }
+                        // The exit may have fired meanwhile
+                        Err(err) => {
+                            let order: PriceOrderDetails = fetch(client, get_price_order(&id))?;
+                            self.apply_price_order(&order);
+                            if order.status == "open" {
+                                return Err(err.into());
+                            }
+                        }
+                    }
+                    // A successful cancel must not leave the exit looking open
+                    if self.leg_status[leg.index()].as_deref() == Some("open") {
+                        self.leg_status[leg.index()] = Some("cancelled".into());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Polls every `interval` until the bracket is done
+    pub fn run<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+        interval: std::time::Duration,
+    ) -> Result<BracketState, BracketError> {
+        loop {
+            let state = self.poll(client)?;
+            if state.is_terminal() {
+                return Ok(state);
+            }
+            std::thread::sleep(interval);
+        }
+    }
+
+    /// Cancels the pending entry and the open exits
+    pub fn cancel<S: crate::ureq::SendRequest>(&mut self, client: &S) -> Result<(), BracketError> {
+        if let Some((id, pair)) = self.entry_pending() {
+            let order: OrderDetails = fetch(client, cancel_order(&id, &pair))?;
+            self.apply_order(&order);
+        }
+        for id in self.open_legs() {
+            let order: PriceOrderDetails = fetch(client, cancel_price_order(&id.to_string()))?;
+            self.apply_price_order(&order);
+        }
+        if !self.state.is_terminal() {
+            self.state = BracketState::Cancelled;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl Bracket {
+    /// Places the entry order, or the exits when there is no entry
+    pub async fn place_async(
+        self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<ActiveBracket, BracketError> {
+        let mut active = ActiveBracket::new(self);
+        if let Some(request) = active.bracket.entry_request() {
+            let order: OrderDetails = fetch_async(client, request).await?;
+            active.apply_order(&order);
+        }
+        active.advance_async(client).await?;
+        Ok(active)
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl ActiveBracket {
+    /// Queries the orders of the bracket, then acts on their state
+    pub async fn poll_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<BracketState, BracketError> {
+        match self.state {
+            BracketState::Entering => {
+                if let Some((id, pair)) = self.entry_pending() {
+                    let order: OrderDetails = fetch_async(client, get_order(&id, &pair)).await?;
+                    self.apply_order(&order);
+                }
+            }
+            BracketState::Armed => {
+                for id in self.open_legs() {
+                    let order: PriceOrderDetails =
+                        fetch_async(client, get_price_order(&id.to_string())).await?;
+                    self.apply_price_order(&order);
+                }
+            }
+            _ => {}
+        }
+        self.advance_async(client).await
+    }
+
+    /// Acts on the known state: places the exits once the entry filled and
+    /// cancels an exit once the other one fired
+    pub async fn advance_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<BracketState, BracketError> {
+        loop {
+            match self.next_action() {
+                Action::Wait => return Ok(self.state),
+                Action::Finish(state) => self.state = state,
+                Action::PlaceLegs(amount) => {
+                    for leg in self.missing_legs() {
+                        let created: Created =
+                            fetch_async(client, self.bracket.leg_request(leg, &amount)).await?;
+                        self.leg_placed(leg, created.id);
+                    }
+                }
+                Action::Cancel(leg) => {
+                    let id = self
+                        .leg_id(leg)
+                        .ok_or(BracketError::MissingLeg(leg))?
+                        .to_string();
+                    match fetch_async::<PriceOrderDetails, _>(client, cancel_price_order(&id)).await
+                    {
+                        Ok(order) => {
+                            self.apply_price_order(&order);
+                        }
+                        // The exit may have fired meanwhile
+                        Err(err) => {
+                            let order: PriceOrderDetails =
+                                fetch_async(client, get_price_order(&id)).await?;
+                            self.apply_price_order(&order);
+                            if order.status == "open" {
+                                return Err(err.into());
+                            }
+                        }
+                    }
+                    if self.leg_status[leg.index()].as_deref() == Some("open") {
+                        self.leg_status[leg.index()] = Some("cancelled".into());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Polls every `interval` until the bracket is done
+    pub async fn run_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+        interval: std::time::Duration,
+    ) -> Result<BracketState, BracketError> {
+        loop {
+            let state = self.poll_async(client).await?;
+            if state.is_terminal() {
+                return Ok(state);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Cancels the pending entry and the open exits
+    pub async fn cancel_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<(), BracketError> {
+        if let Some((id, pair)) = self.entry_pending() {
+            let order: OrderDetails = fetch_async(client, cancel_order(&id, &pair)).await?;
+            self.apply_order(&order);
+        }
+        for id in self.open_legs() {
+            let order: PriceOrderDetails =
+                fetch_async(client, cancel_price_order(&id.to_string())).await?;
+            self.apply_price_order(&order);
+        }
+        if !self.state.is_terminal() {
+            self.state = BracketState::Cancelled;
+        }
+        Ok(())
+    }
+}
+
+/// Error of a bracket operation
+#[derive(Debug)]
+pub enum BracketError {
+    /// Request to the Gate API failed
+    Gate(Box<GateError>),
+    /// The exit to cancel has no price order id, e.g. after `leg_ids` was edited
+    MissingLeg(BracketLeg),
+}
+
+impl fmt::Display for BracketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BracketError::Gate(e) => write!(f, "Gate API error: {}", e),
+            BracketError::MissingLeg(leg) => write!(f, "No price order for the {:?} exit", leg),
+        }
+    }
+}
+
+impl std::error::Error for BracketError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BracketError::Gate(e) => Some(e.as_ref()),
+            BracketError::MissingLeg(_) => None,
+        }
+    }
+}
+
+impl From<GateError> for BracketError {
+    fn from(err: GateError) -> Self {
+        BracketError::Gate(Box::new(err))
+    }
+}
+
+impl From<Box<GateError>> for BracketError {
+    fn from(err: Box<GateError>) -> Self {
+        BracketError::Gate(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bracket() -> ActiveBracket {
+        let bracket = Bracket::new(
+            "BTC_USDT",
+            "buy",
+            "1",
+            Leg::new("110", "109"),
+            Leg::new("90", "89"),
+        )
+        .entry_price("100");
+        ActiveBracket::new(bracket)
+    }
+
+    fn entry(status: &str, filled: &str, fee: &str, fee_currency: &str) -> OrderDetails {
+        serde_json::from_value(json!({
+            "id": "1",
+            "status": status,
+            "currency_pair": "BTC_USDT",
+            "side": "buy",
+            "amount": "1",
+            "filled_amount": filled,
+            "fee": fee,
+            "fee_currency": fee_currency,
+        }))
+        .unwrap()
+    }
+
+    fn armed(take_profit: &str, stop_loss: &str) -> ActiveBracket {
+        let mut active = bracket();
+        active.apply_order(&entry("closed", "1", "0", "USDT"));
+        active.leg_placed(BracketLeg::TakeProfit, 10);
+        active.leg_placed(BracketLeg::StopLoss, 11);
+        active.leg_status = [Some(take_profit.into()), Some(stop_loss.into())];
+        active
+    }
+
+    fn price_order(id: i64, status: &str) -> PriceOrderDetails {
+        serde_json::from_value(json!({"id": id, "status": status})).unwrap()
+    }
+
+    #[test]
+    fn waits_for_entry_fill() {
+        let mut active = bracket();
+        assert_eq!(active.next_action(), Action::Wait);
+        active.apply_order(&entry("open", "0.5", "0", "BTC"));
+        assert_eq!(active.next_action(), Action::Wait);
+    }
+
+    #[test]
+    fn exits_net_of_base_fee() {
+        let mut active = bracket();
+        active.apply_order(&entry("closed", "1", "0.002", "BTC"));
+        assert_eq!(active.next_action(), Action::PlaceLegs("0.998".into()));
+    }
+
+    #[test]
+    fn exits_ignore_other_fees() {
+        let mut active = bracket();
+        active.apply_order(&entry("closed", "1", "0.2", "USDT"));
+        assert_eq!(active.next_action(), Action::PlaceLegs("1".into()));
+        active.apply_order(&entry("closed", "1", "0.01", "GT"));
+        assert_eq!(active.next_action(), Action::PlaceLegs("1".into()));
+    }
+
+    #[test]
+    fn cancelled_entry() {
+        let mut active = bracket();
+        active.apply_order(&entry("cancelled", "0.4", "0", "USDT"));
+        assert_eq!(active.next_action(), Action::PlaceLegs("0.4".into()));
+        active.apply_order(&entry("cancelled", "0", "0", "USDT"));
+        assert_eq!(
+            active.next_action(),
+            Action::Finish(BracketState::Cancelled)
+        );
+    }
+
+    #[test]
+    fn other_orders_are_ignored() {
+        let mut active = bracket();
+        active.apply_order(&entry("open", "0", "0", "USDT"));
+        let mut other = entry("closed", "1", "0", "USDT");
+        other.id = "2".into();
+        assert!(!active.apply_order(&other));
+        assert!(!active.apply_price_order(&price_order(99, "finish")));
+    }
+
+    #[test]
+    fn oco_places_exits_for_amount() {
+        let bracket = Bracket::oco(
+            "BTC_USDT",
+            "sell",
+            "0.5",
+            Leg::new("110", "109"),
+            Leg::new("90", "89"),
+        );
+        let mut active = ActiveBracket::new(bracket);
+        assert_eq!(active.next_action(), Action::PlaceLegs("0.5".into()));
+        active.leg_placed(BracketLeg::TakeProfit, 10);
+        assert_eq!(active.state, BracketState::Entering);
+        assert_eq!(active.missing_legs(), vec![BracketLeg::StopLoss]);
+        active.leg_placed(BracketLeg::StopLoss, 11);
+        assert_eq!(active.state, BracketState::Armed);
+    }
+
+    #[test]
+    fn fired_exit_cancels_sibling() {
+        let mut active = armed("open", "open");
+        assert_eq!(active.next_action(), Action::Wait);
+        assert!(active.apply_price_order(&price_order(10, "finish")));
+        assert_eq!(active.next_action(), Action::Cancel(BracketLeg::StopLoss));
+        active.leg_status[1] = Some("cancelled".into());
+        assert_eq!(
+            active.next_action(),
+            Action::Finish(BracketState::TookProfit)
+        );
+
+        let mut active = armed("open", "finish");
+        assert_eq!(active.next_action(), Action::Cancel(BracketLeg::TakeProfit));
+    }
+
+    #[test]
+    fn both_fired() {
+        let mut active = armed("finish", "finish");
+        assert_eq!(
+            active.next_action(),
+            Action::Finish(BracketState::BothFired)
+        );
+    }
+
+    #[test]
+    fn lost_exit_breaks_bracket() {
+        let mut active = armed("failed", "open");
+        assert_eq!(active.next_action(), Action::Cancel(BracketLeg::StopLoss));
+        active.leg_status[1] = Some("cancelled".into());
+        assert_eq!(
+            active.next_action(),
+            Action::Finish(BracketState::Broken(BracketLeg::TakeProfit))
+        );
+    }
+
+    #[cfg(feature = "enable-ureq")]
+    #[test]
+    fn cancelling_unplaced_exit_is_an_error() {
+        let mut active = armed("finish", "open");
+        active.leg_ids[1] = None;
+        // Fails before any request is sent
+        let client = crate::ureq::GateHttpClient::with_url("http://127.0.0.1:9");
+        assert!(matches!(
+            active.advance(&client),
+            Err(BracketError::MissingLeg(BracketLeg::StopLoss))
+        ));
+    }
+}
@@ -0,0 +1,797 @@
+//! Record-and-replay HTTP cassettes.
+//!
+//! A [`Recorder`] wraps a client and writes every request sent through it,
+//! with its response, into a [`Cassette`] that can be saved as JSON. A
+//! [`Replayer`] answers the same requests from the cassette without touching
+//! the network, so tests built on real interactions run deterministically in
+//! CI.
+//!
+//! Requests are recorded from the SDK's own [`Request`], before signing: the
+//! `KEY`, `Timestamp` and `SIGN` headers never reach the cassette, only
+//! whether the request was signed, and replay matching is not affected by the
+//! changing timestamps and signatures. Cassettes recorded with the ureq client
+//! replay with the hyper client and the other way round.
+//!
+//! Further values are kept out of cassettes with a [`Redaction`]: query
+//! parameters, JSON fields of payloads and bodies, and response headers are
+//! replaced by `"[REDACTED]"`. The same redaction is applied to live requests
+//! before matching them against the cassette.
+//!
+//! # Examples
+//!
+//! Recording, once, against the exchange:
+//!
+//! ```rust,no_run
+//! use gateio_rs::api::spot::{get_account, get_ticker};
+//! use gateio_rs::cassette::{Cassette, Recorder};
+//! use gateio_rs::{http::Credentials, ureq::GateHttpClient};
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//! let recorder = Recorder::new(client, Cassette::new().redact_field("user_id"));
+//! recorder.send(get_ticker().currency_pair("BTC_USDT"))?.into_body_str()?;
+//! recorder.send(get_account())?.into_body_str()?;
+//! recorder.save("tests/cassettes/account.json")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Replaying in CI:
+//!
+//! ```rust
+//! use gateio_rs::api::spot::get_ticker;
+//! use gateio_rs::cassette::{Cassette, Replayer};
+//!
+//! let cassette = Cassette::parse(r#"{
+//!     "interactions": [{
+//!         "request": {"method": "GET", "path": "/api/v4/spot/tickers",
+//!                     "params": [["currency_pair", "BTC_USDT"]]},
+//!         "response": {"status": 200, "body": "[{\"last\":\"30000\"}]"}
+//!     }]
+//! }"#)?;
+//! let replayer = Replayer::new(cassette);
+//! let body = replayer.send(get_ticker().currency_pair("BTC_USDT"))?.into_body_str()?;
+//! assert_eq!(body, r#"[{"last":"30000"}]"#);
+//! assert_eq!(replayer.remaining(), 0);
+//!
+//! // Requests missing from the cassette fail instead of reaching the network
+//! assert!(replayer.send(get_ticker().currency_pair("ETH_USDT")).is_err());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::request::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Placeholder written in place of redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+const FRAMING_HEADERS: &[&str] = &["content-length", "transfer-encoding", "connection"];
+
+/// Values kept out of cassettes
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Redaction {
+    /// Query parameters whose values are redacted
+    #[serde(default)]
+    pub params: Vec<String>,
+    /// JSON fields redacted at any depth of payloads and response bodies
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Response headers left out, compared case-insensitively
+    #[serde(default)]
+    pub headers: Vec<String>,
+}
+
+impl Redaction {
+    fn params(&self, params: &[(String, String)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(k, v)| {
+                let v = if self.params.contains(k) { REDACTED } else { v };
+                (k.clone(), v.to_owned())
+            })
+            .collect()
+    }
+
+    fn body(&self, body: &str) -> String {
+        if self.fields.is_empty() {
+            return body.to_owned();
+        }
+        match serde_json::from_str::<Value>(body) {
+            Ok(mut value) => {
+                if self.redact_value(&mut value) {
+                    value.to_string()
+                } else {
+                    body.to_owned()
+                }
+            }
+            Err(_) => body.to_owned(),
+        }
+    }
+
+    /// Redacts the configured fields of `value`, telling whether any was found
+    fn redact_value(&self, value: &mut Value) -> bool {
+        match value {
+            Value::Object(map) => map.iter_mut().fold(false, |found, (k, v)| {
+                if self.fields.contains(k) {
+                    *v = Value::String(REDACTED.to_owned());
+                    true
+                } else {
+                    self.redact_value(v) || found
+                }
+            }),
+            Value::Array(items) => {
+                let mut found = false;
+                for item in items {
+                    found |= self.redact_value(item);
+                }
+                found
+            }
+            _ => false,
+        }
+    }
+
+    fn keeps_header(&self, name: &str) -> bool {
+        !self.headers.iter().any(|h| h.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Request as stored in a cassette
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct CassetteRequest {
+    /// HTTP method, e.g. `GET`
+    pub method: String,
+    /// Path, e.g. `/api/v4/spot/tickers`
+    pub path: String,
+    /// Query parameters, redacted
+    #[serde(default)]
+    pub params: Vec<(String, String)>,
+    /// JSON payload, redacted
+    #[serde(default)]
+    pub payload: String,
+    /// Whether the request was signed
+    #[serde(default)]
+    pub signed: bool,
+}
+
+impl CassetteRequest {
+    fn from_request(request: &Request, redaction: &Redaction) -> Self {
+        Self {
+            method: request.method().as_ref().to_owned(),
+            path: request.path().to_owned(),
+            params: redaction.params(request.params()),
+            payload: redaction.body(request.payload()),
+            signed: *request.sign(),
+        }
+    }
+
+    /// Whether both requests are the same, ignoring the order of parameters
+    /// and the formatting of payloads
+    pub fn matches(&self, other: &CassetteRequest) -> bool {
+        let sorted = |params: &[(String, String)]| {
+            let mut params = params.to_vec();
+            params.sort();
+            params
+        };
+        let same_payload = match (
+            serde_json::from_str::<Value>(&self.payload),
+            serde_json::from_str::<Value>(&other.payload),
+        ) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => self.payload == other.payload,
+        };
+        self.method == other.method
+            && self.path == other.path
+            && self.signed == other.signed
+            && sorted(&self.params) == sorted(&other.params)
+            && same_payload
+    }
+}
+
+/// Response as stored in a cassette
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct CassetteResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers, redacted
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Response body, redacted
+    pub body: String,
+}
+
+impl CassetteResponse {
+    fn from_parts(parts: &http::response::Parts, body: &str, redaction: &Redaction) -> Self {
+        Self {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .filter(|(name, _)| {
+                    // Framing headers no longer hold once the body is redacted
+                    !FRAMING_HEADERS.contains(&name.as_str())
+                        && redaction.keeps_header(name.as_str())
+                })
+                .map(|(name, value)| {
+                    let value = value.to_str().unwrap_or_default();
+                    (name.as_str().to_owned(), value.to_owned())
+                })
+                .collect(),
+            body: redaction.body(body),
+        }
+    }
+
+    fn to_http<B>(&self, body: B) -> Result<http::Response<B>, http::Error> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder.body(body)
+    }
+}
+
+/// Request sent and response received
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Interaction {
+    /// Request sent
+    pub request: CassetteRequest,
+    /// Response received
+    pub response: CassetteResponse,
+}
+
+/// Recorded interactions, in the order they happened
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Values kept out of the cassette
+    #[serde(default)]
+    pub redaction: Redaction,
+    /// Interactions, oldest first
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self {
+            redaction: Redaction {
+                headers: vec!["set-cookie".to_owned()],
+                ..Redaction::default()
+            },
+            interactions: Vec::new(),
+        }
+    }
+}
+
+impl Cassette {
+    /// Creates an empty cassette leaving out `Set-Cookie` headers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redacts the value of a query parameter
+    pub fn redact_param(mut self, name: &str) -> Self {
+        self.redaction.params.push(name.to_owned());
+        self
+    }
+
+    /// Redacts a JSON field of payloads and response bodies
+    pub fn redact_field(mut self, name: &str) -> Self {
+        self.redaction.fields.push(name.to_owned());
+        self
+    }
+
+    /// Leaves a response header out
+    pub fn redact_header(mut self, name: &str) -> Self {
+        self.redaction.headers.push(name.to_owned());
+        self
+    }
+
+    /// Parses a cassette from JSON, e.g. embedded with `include_str!`
+    pub fn parse(json: &str) -> Result<Self, CassetteError> {
+        serde_json::from_str(json).map_err(CassetteError::Format)
+    }
+
+    /// Serializes the cassette to pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Loads a cassette file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        Self::parse(&std::fs::read_to_string(path).map_err(CassetteError::Io)?)
+    }
+
+    /// Saves the cassette to a file, creating the parent directories
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CassetteError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(CassetteError::Io)?;
+        }
+        std::fs::write(path, self.to_json() + "\n").map_err(CassetteError::Io)
+    }
+
+    fn record(&mut self, request: CassetteRequest, parts: &http::response::Parts, body: &str) {
+        let response = CassetteResponse::from_parts(parts, body, &self.redaction);
+        self.interactions.push(Interaction { request, response });
+    }
+}
+
+/// Cassette error
+#[derive(Debug)]
+pub enum CassetteError {
+    /// The cassette file could not be read or written
+    Io(std::io::Error),
+    /// The cassette is not valid JSON
+    Format(serde_json::Error),
+    /// No interaction of the cassette matches the request
+    Unmatched(CassetteRequest),
+}
+
+impl std::fmt::Display for CassetteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CassetteError::Io(err) => write!(f, "cassette file error: {}", err),
+            CassetteError::Format(err) => write!(f, "invalid cassette: {}", err),
+            CassetteError::Unmatched(request) => write!(
+                f,
+                "no recorded interaction for {} {}",
+                request.method, request.path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CassetteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CassetteError::Io(err) => Some(err),
+            CassetteError::Format(err) => Some(err),
+            CassetteError::Unmatched(_) => None,
+        }
+    }
+}
+
+/// Client wrapper recording every interaction into a cassette
+#[derive(Clone)]
+pub struct Recorder<C> {
+    client: C,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<C> Recorder<C> {
+    /// Records the requests sent with `client` into `cassette`
+    pub fn new(client: C, cassette: Cassette) -> Self {
+        Self {
+            client,
+            cassette: Arc::new(Mutex::new(cassette)),
+        }
+    }
+
+    /// Wrapped client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Interactions recorded so far
+    pub fn cassette(&self) -> Cassette {
+        self.lock().clone()
+    }
+
+    /// Saves the interactions recorded so far
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CassetteError> {
+        self.lock().save(path)
+    }
+
+    fn request(&self, request: &Request) -> CassetteRequest {
+        CassetteRequest::from_request(request, &self.lock().redaction)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cassette> {
+        self.cassette.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<S: crate::ureq::SendRequest> Recorder<S> {
+    /// Sends a request with the wrapped client and records the interaction
+    pub fn send<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::ureq::Response, Box<crate::ureq::Error>> {
+        let request = request.into();
+        let recorded = self.request(&request);
+        let (parts, body) = self.client.send(request)?.into_raw()?;
+        self.lock().record(recorded, &parts, &body);
+        Ok(rebuild_ureq(parts, body))
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<S: crate::ureq::SendRequest> crate::ureq::SendRequest for Recorder<S> {
+    fn send<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::ureq::Response, Box<crate::ureq::Error>> {
+        Recorder::send(self, request)
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl Recorder<crate::hyper::GateHttpClient> {
+    /// Sends a request with the wrapped async client and records the interaction
+    pub async fn send_async<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::hyper::Response, crate::hyper::Error> {
+        let request = request.into();
+        let recorded = self.request(&request);
+        let (parts, body) = self.client.send(request).await?.into_raw().await?;
+        self.lock().record(recorded, &parts, &body);
+        Ok(rebuild_hyper(parts, body))
+    }
+}
+
+/// Client answering requests from a cassette, without network access
+///
+/// Each request is answered by the first interaction not replayed yet that
+/// matches it, so repeated requests replay their recorded responses in order.
+#[derive(Clone)]
+pub struct Replayer {
+    state: Arc<Mutex<Replay>>,
+}
+
+struct Replay {
+    cassette: Cassette,
+    played: Vec<bool>,
+    allow_repeats: bool,
+}
+
+impl Replayer {
+    /// Replays `cassette`
+    pub fn new(cassette: Cassette) -> Self {
+        let played = vec![false; cassette.interactions.len()];
+        Self {
+            state: Arc::new(Mutex::new(Replay {
+                cassette,
+                played,
+                allow_repeats: false,
+            })),
+        }
+    }
+
+    /// Answers requests whose interactions were all replayed with the last one
+    pub fn allow_repeats(self) -> Self {
+        self.lock().allow_repeats = true;
+        self
+    }
+
+    /// Number of interactions not replayed yet
+    pub fn remaining(&self) -> usize {
+        self.lock().played.iter().filter(|played| !**played).count()
+    }
+
+    /// Interactions not replayed yet, e.g. to assert that a test made every call
+    pub fn unplayed(&self) -> Vec<Interaction> {
+        let state = self.lock();
+        state
+            .cassette
+            .interactions
+            .iter()
+            .zip(&state.played)
+            .filter(|(_, played)| !**played)
+            .map(|(interaction, _)| interaction.clone())
+            .collect()
+    }
+
+    fn replay(&self, request: &Request) -> Result<CassetteResponse, CassetteError> {
+        let mut state = self.lock();
+        let request = CassetteRequest::from_request(request, &state.cassette.redaction);
+        let matching: Vec<usize> = (0..state.cassette.interactions.len())
+            .filter(|&i| state.cassette.interactions[i].request.matches(&request))
+            .collect();
+        let index = match matching.iter().find(|&&i| !state.played[i]) {
+            Some(&index) => index,
+            None if state.allow_repeats => match matching.last() {
+                Some(&index) => index,
+                None => return Err(CassetteError::Unmatched(request)),
+            },
+            None => return Err(CassetteError::Unmatched(request)),
+        };
+        state.played[index] = true;
+        Ok(state.cassette.interactions[index].response.clone())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Replay> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl Replayer {
+    /// Answers a request from the cassette
+    pub fn send<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::ureq::Response, Box<crate::ureq::Error>> {
+        let response = self
+            .replay(&request.into())
+            .map_err(|err| Box::new(crate::ureq::Error::Send(Box::new(err))))?;
+        let body = ::ureq::Body::builder().data(response.body.clone());
+        Ok(response.to_http(body)?.into())
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl crate::ureq::SendRequest for Replayer {
+    fn send<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::ureq::Response, Box<crate::ureq::Error>> {
+        Replayer::send(self, request)
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl Replayer {
+    /// Answers a request from the cassette, like the async client would
+    pub async fn send_async<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::hyper::Response, crate::hyper::Error> {
+        let response = self
+            .replay(&request.into())
+            .map_err(|err| crate::hyper::Error::Send(Box::new(err)))?;
+        let body = http_body_util::Full::new(bytes::Bytes::from(response.body.clone()));
+        Ok(response.to_http(body)?.into())
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+pub(crate) fn rebuild_ureq(parts: http::response::Parts, body: String) -> crate::ureq::Response {
+    http::Response::from_parts(parts, ::ureq::Body::builder().data(body)).into()
+}
+
+#[cfg(feature = "enable-hyper")]
+pub(crate) fn rebuild_hyper(parts: http::response::Parts, body: String) -> crate::hyper::Response {
+    let body = http_body_util::Full::new(bytes::Bytes::from(body));
+    http::Response::from_parts(parts, body).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use crate::http::request::RequestBuilder;
+
+    fn request(method: Method, params: &[(&str, &str)], payload: &str) -> Request {
+        RequestBuilder::new(method, "/api/v4/spot/orders")
+            .params(params.iter().copied())
+            .payload(payload)
+            .sign()
+            .into()
+    }
+
+    fn recorded(request: &Request, redaction: &Redaction) -> CassetteRequest {
+        CassetteRequest::from_request(request, redaction)
+    }
+
+    #[test]
+    fn redacts_params_and_nested_fields() {
+        let redaction = Redaction {
+            params: vec!["text".into()],
+            fields: vec!["user_id".into()],
+            headers: Vec::new(),
+        };
+        let request = request(
+            Method::Post,
+            &[("currency_pair", "BTC_USDT"), ("text", "t-secret")],
+            r#"{"user_id":7,"orders":[{"user_id":8,"amount":"1"}]}"#,
+        );
+        let recorded = recorded(&request, &redaction);
+        assert_eq!(
+            recorded.params,
+            vec![
+                ("currency_pair".to_owned(), "BTC_USDT".to_owned()),
+                ("text".to_owned(), REDACTED.to_owned()),
+            ]
+        );
+        let payload: Value = serde_json::from_str(&recorded.payload).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "user_id": REDACTED,
+                "orders": [{"user_id": REDACTED, "amount": "1"}],
+            })
+        );
+        assert!(recorded.signed);
+    }
+
+    #[test]
+    fn keeps_bodies_without_redacted_fields_verbatim() {
+        let redaction = Redaction {
+            fields: vec!["user_id".into()],
+            ..Redaction::default()
+        };
+        // Formatting is kept when nothing was redacted, and text is left alone
+        assert_eq!(redaction.body(r#"{ "a" : 1 }"#), r#"{ "a" : 1 }"#);
+        assert_eq!(redaction.body("user_id=7"), "user_id=7");
+        assert_eq!(
+            Redaction::default().body(r#"{"user_id":7}"#),
+            r#"{"user_id":7}"#
+        );
+    }
+
+    #[test]
+    fn leaves_out_redacted_and_framing_headers() {
+        let (parts, ()) = http::Response::builder()
+            .status(200)
+            .header("Content-Length", "12")
+            .header("Set-Cookie", "session=1")
+            .header("X-Gate-Trace-Id", "abc")
+            .header("X-Gate-RateLimit-Requests-Remain", "9")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let redaction = Cassette::new().redact_header("x-gate-trace-id").redaction;
+        let response = CassetteResponse::from_parts(&parts, r#"{"user_id":1}"#, &redaction);
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.headers.into_iter().collect::<Vec<_>>(),
+            vec![(
+                "x-gate-ratelimit-requests-remain".to_owned(),
+                "9".to_owned()
+            )]
+        );
+        assert_eq!(response.body, r#"{"user_id":1}"#);
+    }
+
+    #[test]
+    fn matches_ignore_param_order_and_payload_formatting() {
+        let redaction = Redaction::default();
+        let base = recorded(
+            &request(
+                Method::Post,
+                &[("a", "1"), ("b", "2")],
+                r#"{"x":1,"y":[1,2]}"#,
+            ),
+            &redaction,
+        );
+        let reordered = recorded(
+            &request(
+                Method::Post,
+                &[("b", "2"), ("a", "1")],
+                "{ \"y\": [1, 2],\n  \"x\": 1 }",
+            ),
+            &redaction,
+        );
+        assert!(base.matches(&reordered));
+
+        let other_payload = recorded(
+            &request(
+                Method::Post,
+                &[("a", "1"), ("b", "2")],
+                r#"{"x":2,"y":[1,2]}"#,
+            ),
+            &redaction,
+        );
+        let other_param = recorded(
+            &request(
+                Method::Post,
+                &[("a", "1"), ("b", "3")],
+                r#"{"x":1,"y":[1,2]}"#,
+            ),
+            &redaction,
+        );
+        let other_method = recorded(
+            &request(
+                Method::Put,
+                &[("a", "1"), ("b", "2")],
+                r#"{"x":1,"y":[1,2]}"#,
+            ),
+            &redaction,
+        );
+        let unsigned = CassetteRequest {
+            signed: false,
+            ..base.clone()
+        };
+        for other in [other_payload, other_param, other_method, unsigned] {
+            assert!(!base.matches(&other), "{other:?}");
+        }
+
+        // Payloads that are not JSON are compared as text
+        let text = |payload: &str| recorded(&request(Method::Post, &[], payload), &redaction);
+        assert!(text("a=1").matches(&text("a=1")));
+        assert!(!text("a=1").matches(&text("a=2")));
+    }
+
+    #[test]
+    fn live_requests_are_redacted_before_matching() {
+        let cassette = Cassette::new().redact_param("text");
+        let stored = recorded(
+            &request(Method::Get, &[("text", "t-recorded")], ""),
+            &cassette.redaction,
+        );
+        let interaction = Interaction {
+            request: stored,
+            response: CassetteResponse {
+                status: 200,
+                headers: BTreeMap::new(),
+                body: "[]".into(),
+            },
+        };
+        let replayer = Replayer::new(Cassette {
+            interactions: vec![interaction],
+            ..cassette
+        });
+        let live = request(Method::Get, &[("text", "t-live")], "");
+        assert!(replayer.replay(&live).is_ok());
+        assert!(matches!(
+            replayer.replay(&live),
+            Err(CassetteError::Unmatched(_))
+        ));
+    }
+
+    #[test]
+    fn replays_repeated_requests_in_order() {
+        let get = request(Method::Get, &[], "");
+        let interaction = |body: &str| Interaction {
+            request: recorded(&get, &Redaction::default()),
+            response: CassetteResponse {
+                status: 200,
+                headers: BTreeMap::new(),
+                body: body.into(),
+            },
+        };
+        let cassette = Cassette {
+            interactions: vec![interaction("first"), interaction("second")],
+            ..Cassette::new()
+        };
+
+        let replayer = Replayer::new(cassette.clone());
+        assert_eq!(replayer.replay(&get).unwrap().body, "first");
+        assert_eq!(replayer.remaining(), 1);
+        assert_eq!(replayer.unplayed()[0].response.body, "second");
+        assert_eq!(replayer.replay(&get).unwrap().body, "second");
+        assert!(replayer.replay(&get).is_err());
+
+        let replayer = Replayer::new(cassette).allow_repeats();
+        replayer.replay(&get).unwrap();
+        replayer.replay(&get).unwrap();
+        assert_eq!(replayer.replay(&get).unwrap().body, "second");
+    }
+
+    #[cfg(feature = "enable-ureq")]
+    #[test]
+    fn recorder_round_trips_through_json() {
+        use crate::api::spot::get_ticker;
+        use crate::paper::RecordedMarket;
+
+        let market = RecordedMarket::new().fixture(
+            "/api/v4/spot/tickers",
+            &[("currency_pair", "BTC_USDT")],
+            r#"[{"currency_pair":"BTC_USDT","last":"30000","user_id":5}]"#,
+        );
+        let recorder = Recorder::new(market, Cassette::new().redact_field("user_id"));
+        let body = recorder
+            .send(get_ticker().currency_pair("BTC_USDT"))
+            .unwrap()
+            .into_body_str()
+            .unwrap();
+        // The caller gets the live body, the cassette the redacted one
+        assert!(body.contains(r#""user_id":5"#));
+
+        let cassette = Cassette::parse(&recorder.cassette().to_json()).unwrap();
+        assert_eq!(cassette, recorder.cassette());
+        assert!(cassette.interactions[0].response.body.contains(REDACTED));
+
+        let replayer = Replayer::new(cassette);
+        let replayed = replayer
+            .send(get_ticker().currency_pair("BTC_USDT"))
+            .unwrap()
+            .into_body_str()
+            .unwrap();
+        assert!(replayed.contains(REDACTED));
+        assert_eq!(replayer.remaining(), 0);
+    }
+}
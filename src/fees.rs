@@ -0,0 +1,808 @@
+//! Fee-tier aware cost estimation for spot orders.
+//!
+//! A [`FeeModel`] caches the rates of `get_fee` / `get_batch_user_fee` per
+//! currency pair, then:
+//!
+//! - [`FeeModel::estimate`] prices the fee of a proposed [`CreateOrder`]:
+//!   maker or taker rate, deduction in the received currency, in GT or in
+//!   points, and the net proceeds of the order
+//! - [`FeeModel::compare`] checks the fees reported by `get_my_trades`
+//!   against the rates, exposing fills charged more than expected
+//!
+//! Fees deducted in GT or points are valued with the last prices given to
+//! [`FeeModel::price`], through USDT when needed; points are worth one USDT
+//! each. The deduction is assumed to cover the whole fee, as when the GT or
+//! point balance suffices.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::api::spot::create_order;
+//! use gateio_rs::fees::{FeeModel, Liquidity};
+//! use gateio_rs::paper::RecordedMarket;
+//!
+//! let gate = RecordedMarket::new().fixture(
+//!     "/api/v4/spot/batch_fee",
+//!     &[("currency_pairs", "BTC_USDT")],
+//!     r#"{"BTC_USDT":{"taker_fee":"0.002","maker_fee":"0.001","debit_fee":3}}"#,
+//! );
+//! let mut fees = FeeModel::new();
+//! fees.refresh(&gate, &["BTC_USDT"])?;
+//!
+//! // Resting limit sell: maker rate, fee taken from the USDT received
+//! let order = create_order("BTC_USDT", "sell", "0.5").price("30000");
+//! let estimate = fees.estimate(&order)?;
+//! assert_eq!(estimate.liquidity, Liquidity::Maker);
+//! assert_eq!(estimate.fee, Some(15.0));
+//! assert_eq!(estimate.net_proceeds, 14_985.0);
+//!
+//! // The same order below the last price crosses the book and pays taker
+//! let estimate = fees.estimate_at(&order, 31_000.0)?;
+//! assert_eq!(estimate.liquidity, Liquidity::Taker);
+//! assert_eq!(estimate.fee_value, 30.0);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::api::spot::create_order::CreateOrder;
+use crate::api::spot::{SpotFee, UserTrade};
+use crate::http::GateError;
+use crate::reporting::{GT_CURRENCY, POINT_CURRENCY};
+use crate::utils::{number, parse, split_pair};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Currency points are valued in
+const POINT_VALUE_CURRENCY: &str = "USDT";
+
+/// Most currency pairs `get_batch_user_fee` accepts at once
+const BATCH_FEE_LIMIT: usize = 50;
+
+/// Side of the book an order fills against
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    /// Resting on the book
+    Maker,
+    /// Taking liquidity from the book
+    Taker,
+}
+
+impl Liquidity {
+    /// Lowercase name, as in the `role` of trades
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Liquidity::Maker => "maker",
+            Liquidity::Taker => "taker",
+        }
+    }
+}
+
+impl fmt::Display for Liquidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Currency a fee is paid in
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Deduction {
+    /// Taken from the currency received
+    Received,
+    /// Deducted in GT at the GT rates
+    Gt,
+    /// Deducted from point card points
+    Points,
+}
+
+/// Estimated cost of an order filled in full
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Liquidity the order is expected to fill with
+    pub liquidity: Liquidity,
+    /// How the fee is paid
+    pub deduction: Deduction,
+    /// Fee rate applied
+    pub rate: f64,
+    /// Price the order fills at
+    pub price: f64,
+    /// Amount of base currency traded
+    pub amount: f64,
+    /// Amount of quote currency traded
+    pub notional: f64,
+    /// Fee in `fee_currency`, `None` when no price converts it
+    pub fee: Option<f64>,
+    /// Currency the fee is paid in
+    pub fee_currency: String,
+    /// Fee in quote currency
+    pub fee_value: f64,
+    /// Currency received: base for buys, quote for sells
+    pub received_currency: String,
+    /// Amount received, net of fees taken from it
+    pub net_proceeds: f64,
+}
+
+/// Fee of a trade checked against the rates
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct FeeCheck {
+    /// Trade ID
+    pub trade_id: String,
+    /// Currency pair
+    pub currency_pair: String,
+    /// Role of the trade
+    pub liquidity: Liquidity,
+    /// Amount of quote currency traded
+    pub notional: f64,
+    /// Fee the rates imply, in quote currency; `None` without rates for the pair
+    pub expected: Option<f64>,
+    /// Fees reported by the trade, in quote currency; `None` when unconvertible
+    pub actual: Option<f64>,
+}
+
+impl FeeCheck {
+    /// Actual minus expected fee, when both are known
+    pub fn difference(&self) -> Option<f64> {
+        Some(self.actual? - self.expected?)
+    }
+
+    /// Actual fee as a fraction of the notional
+    pub fn actual_rate(&self) -> Option<f64> {
+        self.actual
+            .filter(|_| self.notional > 0.0)
+            .map(|actual| actual / self.notional)
+    }
+}
+
+/// Error of a [`FeeModel`] estimate
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum FeeError {
+    /// No rates are cached for the pair
+    UnknownPair(String),
+    /// The order has no price and no last price was given
+    MissingPrice(String),
+    /// The order amount or price is not a positive number
+    InvalidOrder(String),
+}
+
+impl fmt::Display for FeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeError::UnknownPair(pair) => write!(f, "No fee rates for {}", pair),
+            FeeError::MissingPrice(pair) => write!(f, "No price to estimate {} order", pair),
+            FeeError::InvalidOrder(reason) => write!(f, "Invalid order: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FeeError {}
+
+/// Cached fee rates, see the [module documentation](self)
+#[derive(Clone, Debug, Default)]
+pub struct FeeModel {
+    /// Rates by currency pair
+    pub rates: HashMap<String, SpotFee>,
+    /// Account-wide rates, used for pairs without their own
+    pub default: Option<SpotFee>,
+    /// Last prices by currency pair, valuing GT and point deductions
+    pub prices: HashMap<String, f64>,
+}
+
+impl FeeModel {
+    /// Creates a model without rates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches `fee`, as the account-wide rates when it has no currency pair
+    pub fn insert(&mut self, fee: SpotFee) {
+        if fee.currency_pair.is_empty() {
+            self.default = Some(fee);
+        } else {
+            self.rates.insert(fee.currency_pair.to_uppercase(), fee);
+        }
+    }
+
+    /// Sets the last price of `currency_pair`, e.g. `GT_USDT`
+    pub fn price(mut self, currency_pair: &str, last: f64) -> Self {
+        self.set_price(currency_pair, last);
+        self
+    }
+
+    /// Updates the last price of `currency_pair`
+    pub fn set_price(&mut self, currency_pair: &str, last: f64) {
+        self.prices.insert(currency_pair.to_uppercase(), last);
+    }
+
+    /// Rates of `currency_pair`, falling back to the account-wide ones
+    pub fn rates(&self, currency_pair: &str) -> Option<&SpotFee> {
+        self.rates
+            .get(&currency_pair.to_uppercase())
+            .or(self.default.as_ref())
+    }
+
+    /// Pairs among `currency_pairs` without cached rates of their own
+    pub fn missing(&self, currency_pairs: &[&str]) -> Vec<String> {
+        let mut missing: Vec<String> = currency_pairs
+            .iter()
+            .map(|pair| pair.to_uppercase())
+            .filter(|pair| !self.rates.contains_key(pair))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+
+    /// Estimates the fee of `order`, priced at its limit price
+    pub fn estimate(&self, order: &CreateOrder) -> Result<FeeEstimate, FeeError> {
+        self.estimate_with(order, None)
+    }
+
+    /// Estimates the fee of `order` with the `last` price of its pair.
+    ///
+    /// Market orders fill at `last`; limit orders crossing it pay the taker rate.
+    pub fn estimate_at(&self, order: &CreateOrder, last: f64) -> Result<FeeEstimate, FeeError> {
+        self.estimate_with(order, Some(last))
+    }
+
+    fn estimate_with(
+        &self,
+        order: &CreateOrder,
+        last: Option<f64>,
+    ) -> Result<FeeEstimate, FeeError> {
+        let pair = order.currency_pair.to_uppercase();
+        let rates = self
+            .rates(&pair)
+            .ok_or_else(|| FeeError::UnknownPair(pair.clone()))?;
+        let (base, quote) = split_pair(&pair);
+        let buy = order.side.eq_ignore_ascii_case("buy");
+        let kind = order.order_type.as_deref().unwrap_or("limit");
+        let time_in_force = order.time_in_force.as_deref().unwrap_or("gtc");
+        let market = kind == "market";
+
+        let limit = order.price.as_deref().map(number).filter(|p| *p > 0.0);
+        let price = if market { last } else { limit }
+            .filter(|p| *p > 0.0)
+            .ok_or_else(|| FeeError::MissingPrice(pair.clone()))?;
+        let crosses = match (limit, last) {
+            (Some(limit), Some(last)) => {
+                if buy {
+                    limit >= last
+                } else {
+                    limit <= last
+                }
+            }
+            _ => false,
+        };
+        let liquidity = if market
+            || matches!(kind, "ioc" | "fok")
+            || matches!(time_in_force, "ioc" | "fok")
+            || (crosses && kind != "poc" && time_in_force != "poc")
+        {
+            Liquidity::Taker
+        } else {
+            Liquidity::Maker
+        };
+
+        let ordered = number(&order.amount);
+        if ordered <= 0.0 {
+            return Err(FeeError::InvalidOrder(format!(
+                "amount {:?} is not positive",
+                order.amount
+            )));
+        }
+        // Market buys are sized in quote currency
+        let (amount, notional) = if market && buy {
+            (ordered / price, ordered)
+        } else {
+            (ordered, ordered * price)
+        };
+
+        let deduction = deduction(rates);
+        let rate = rate(rates, liquidity, deduction);
+        let fee_value = notional * rate;
+        let (received, received_currency) = if buy {
+            (amount, base)
+        } else {
+            (notional, quote.clone())
+        };
+        let (fee, fee_currency) = match deduction {
+            Deduction::Received => (Some(received * rate), received_currency.clone()),
+            Deduction::Gt => (
+                self.convert(fee_value, &quote, GT_CURRENCY),
+                GT_CURRENCY.to_owned(),
+            ),
+            Deduction::Points => (
+                self.convert(fee_value, &quote, POINT_CURRENCY),
+                POINT_CURRENCY.to_owned(),
+            ),
+        };
+        let net_proceeds = match deduction {
+            Deduction::Received => received - received * rate,
+            Deduction::Gt | Deduction::Points => received,
+        };
+
+        Ok(FeeEstimate {
+            currency_pair: pair,
+            liquidity,
+            deduction,
+            rate,
+            price,
+            amount,
+            notional,
+            fee,
+            fee_currency,
+            fee_value,
+            received_currency,
+            net_proceeds,
+        })
+    }
+
+    /// Checks the fees reported by `trades` against the cached rates
+    pub fn compare(&self, trades: &[UserTrade]) -> Vec<FeeCheck> {
+        trades.iter().map(|trade| self.check(trade)).collect()
+    }
+
+    fn check(&self, trade: &UserTrade) -> FeeCheck {
+        let pair = trade.currency_pair.to_uppercase();
+        let (base, quote) = split_pair(&pair);
+        let price = number(&trade.price);
+        let notional = number(&trade.amount) * price;
+        let liquidity = if trade.role == "maker" {
+            Liquidity::Maker
+        } else {
+            Liquidity::Taker
+        };
+        let fee_currency = trade.fee_currency.to_uppercase();
+
+        let mut paid = vec![(fee_currency.clone(), number(&trade.fee))];
+        for (currency, deducted) in [
+            (POINT_CURRENCY, &trade.point_fee),
+            (GT_CURRENCY, &trade.gt_fee),
+        ] {
+            if fee_currency != currency {
+                paid.push((currency.to_owned(), number(deducted)));
+            }
+        }
+        let actual = paid
+            .iter()
+            .filter(|(_, amount)| *amount != 0.0)
+            .map(|(currency, amount)| {
+                if *currency == base {
+                    Some(amount * price)
+                } else {
+                    self.convert(*amount, currency, &quote)
+                }
+            })
+            .sum::<Option<f64>>();
+
+        let paid_in = |currency: &str| paid.iter().any(|(c, a)| c == currency && *a != 0.0);
+        let deduction = if paid_in(GT_CURRENCY) {
+            Deduction::Gt
+        } else if paid_in(POINT_CURRENCY) {
+            Deduction::Points
+        } else {
+            Deduction::Received
+        };
+        let expected = self
+            .rates(&pair)
+            .map(|rates| notional * rate(rates, liquidity, deduction));
+
+        FeeCheck {
+            trade_id: trade.id.clone(),
+            currency_pair: pair,
+            liquidity,
+            notional,
+            expected,
+            actual,
+        }
+    }
+
+    /// Converts `amount` of `from` into `to` with the cached last prices,
+    /// bridging through USDT when no pair joins them
+    fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        let usdt = |currency: &str| {
+            if currency == POINT_CURRENCY {
+                POINT_VALUE_CURRENCY.to_owned()
+            } else {
+                currency.to_owned()
+            }
+        };
+        let (from, to) = (usdt(from), usdt(to));
+        self.rate_between(&from, &to)
+            .or_else(|| {
+                Some(
+                    self.rate_between(&from, POINT_VALUE_CURRENCY)?
+                        * self.rate_between(POINT_VALUE_CURRENCY, &to)?,
+                )
+            })
+            .map(|rate| amount * rate)
+    }
+
+    /// Units of `to` one unit of `from` is worth, from a direct or inverse pair
+    fn rate_between(&self, from: &str, to: &str) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        if let Some(last) = self.prices.get(&format!("{}_{}", from, to)) {
+            return Some(*last);
+        }
+        self.prices
+            .get(&format!("{}_{}", to, from))
+            .filter(|last| **last > 0.0)
+            .map(|last| 1.0 / last)
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl FeeModel {
+    /// Fetches and caches the rates of `currency_pairs` with `get_batch_user_fee`
+    pub fn refresh<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+        currency_pairs: &[&str],
+    ) -> Result<(), GateError> {
+        for chunk in currency_pairs.chunks(BATCH_FEE_LIMIT) {
+            let request = crate::api::spot::get_batch_user_fee(&chunk.join(","));
+            let body = client
+                .send(request)
+                .and_then(|response| response.into_body_str())
+                .map_err(|err| *err)?;
+            self.insert_batch(&body)?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the rates of the pairs not cached yet
+    pub fn ensure<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+        currency_pairs: &[&str],
+    ) -> Result<(), GateError> {
+        let missing = self.missing(currency_pairs);
+        let missing: Vec<&str> = missing.iter().map(String::as_str).collect();
+        self.refresh(client, &missing)
+    }
+
+    /// Fetches and caches the account-wide rates with `get_fee`
+    pub fn refresh_default<S: crate::ureq::SendRequest>(
+        &mut self,
+        client: &S,
+    ) -> Result<(), GateError> {
+        let body = client
+            .send(crate::api::spot::get_fee())
+            .and_then(|response| response.into_body_str())
+            .map_err(|err| *err)?;
+        let mut fee: SpotFee = parse(&body)?;
+        fee.currency_pair.clear();
+        self.insert(fee);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl FeeModel {
+    /// Fetches and caches the rates of `currency_pairs` with `get_batch_user_fee`
+    pub async fn refresh_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+        currency_pairs: &[&str],
+    ) -> Result<(), GateError> {
+        for chunk in currency_pairs.chunks(BATCH_FEE_LIMIT) {
+            let request = crate::api::spot::get_batch_user_fee(&chunk.join(","));
+            let body = client.send(request).await?.into_body_str().await?;
+            self.insert_batch(&body)?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the rates of the pairs not cached yet
+    pub async fn ensure_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+        currency_pairs: &[&str],
+    ) -> Result<(), GateError> {
+        let missing = self.missing(currency_pairs);
+        let missing: Vec<&str> = missing.iter().map(String::as_str).collect();
+        self.refresh_async(client, &missing).await
+    }
+
+    /// Fetches and caches the account-wide rates with `get_fee`
+    pub async fn refresh_default_async(
+        &mut self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<(), GateError> {
+        let body = client
+            .send(crate::api::spot::get_fee())
+            .await?
+            .into_body_str()
+            .await?;
+        let mut fee: SpotFee = parse(&body)?;
+        fee.currency_pair.clear();
+        self.insert(fee);
+        Ok(())
+    }
+}
+
+impl FeeModel {
+    /// Caches a `get_batch_user_fee` response, keyed by currency pair
+    fn insert_batch(&mut self, body: &str) -> Result<(), GateError> {
+        let batch: HashMap<String, SpotFee> = parse(body)?;
+        for (pair, mut fee) in batch {
+            fee.currency_pair = pair;
+            self.insert(fee);
+        }
+        Ok(())
+    }
+}
+
+/// How `rates` deduct fees
+fn deduction(rates: &SpotFee) -> Deduction {
+    match rates.debit_fee {
+        1 => Deduction::Gt,
+        2 => Deduction::Points,
+        3 => Deduction::Received,
+        _ if rates.gt_discount => Deduction::Gt,
+        _ => Deduction::Received,
+    }
+}
+
+/// Fee rate of `liquidity` paid with `deduction`
+fn rate(rates: &SpotFee, liquidity: Liquidity, deduction: Deduction) -> f64 {
+    let (regular, gt) = match liquidity {
+        Liquidity::Maker => (&rates.maker_fee, &rates.gt_maker_fee),
+        Liquidity::Taker => (&rates.taker_fee, &rates.gt_taker_fee),
+    };
+    match (deduction, number(gt)) {
+        (Deduction::Gt, gt) if gt > 0.0 => gt,
+        _ => number(regular),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::spot::create_order;
+
+    fn rates(fee: serde_json::Value) -> SpotFee {
+        serde_json::from_value(fee).unwrap()
+    }
+
+    fn model(fee: serde_json::Value) -> FeeModel {
+        let mut model = FeeModel::new();
+        model.insert(rates(fee));
+        model
+    }
+
+    fn regular() -> FeeModel {
+        model(serde_json::json!({
+            "currency_pair": "BTC_USDT",
+            "taker_fee": "0.002",
+            "maker_fee": "0.001",
+            "debit_fee": 3,
+        }))
+    }
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    fn trade(role: &str, amount: &str, price: &str, fee: &str, fee_currency: &str) -> UserTrade {
+        UserTrade {
+            id: "1".into(),
+            create_time_ms: 0.0,
+            currency_pair: "BTC_USDT".into(),
+            side: "buy".into(),
+            role: role.into(),
+            amount: amount.into(),
+            price: price.into(),
+            order_id: "1".into(),
+            fee: fee.into(),
+            fee_currency: fee_currency.into(),
+            point_fee: "0".into(),
+            gt_fee: "0".into(),
+            text: String::new(),
+        }
+    }
+
+    #[test]
+    fn buys_pay_in_the_base_currency() {
+        let fees = regular();
+        let order = create_order("btc_usdt", "buy", "2").price("100");
+
+        let resting = fees.estimate(&order).unwrap();
+        assert_eq!(resting.liquidity, Liquidity::Maker);
+        assert_eq!(resting.deduction, Deduction::Received);
+        assert_eq!(resting.fee_currency, "BTC");
+        assert_eq!(resting.received_currency, "BTC");
+        assert!(close(resting.fee.unwrap(), 0.002));
+        assert!(close(resting.fee_value, 0.2));
+        assert!(close(resting.net_proceeds, 1.998));
+
+        // Bidding above the last price takes the ask
+        let crossing = fees.estimate_at(&order, 90.0).unwrap();
+        assert_eq!(crossing.liquidity, Liquidity::Taker);
+        assert!(close(crossing.fee.unwrap(), 0.004));
+        assert!(close(crossing.fee_value, 0.4));
+    }
+
+    #[test]
+    fn order_types_decide_the_liquidity() {
+        let fees = regular();
+        let limit = || create_order("BTC_USDT", "sell", "1").price("100");
+        let cases = [
+            (limit(), 90.0, Liquidity::Maker),
+            // Asking below the last price hits the bid
+            (limit(), 110.0, Liquidity::Taker),
+            (limit().time_in_force("poc"), 110.0, Liquidity::Maker),
+            (limit().time_in_force("ioc"), 90.0, Liquidity::Taker),
+            (limit().time_in_force("fok"), 90.0, Liquidity::Taker),
+        ];
+        for (order, last, liquidity) in cases {
+            assert_eq!(fees.estimate_at(&order, last).unwrap().liquidity, liquidity);
+        }
+    }
+
+    #[test]
+    fn market_buys_are_sized_in_quote_currency() {
+        let fees = regular();
+        let order = create_order("BTC_USDT", "buy", "200").order_type("market");
+        assert_eq!(
+            fees.estimate(&order),
+            Err(FeeError::MissingPrice("BTC_USDT".into()))
+        );
+
+        let estimate = fees.estimate_at(&order, 100.0).unwrap();
+        assert_eq!(estimate.liquidity, Liquidity::Taker);
+        assert_eq!(estimate.amount, 2.0);
+        assert_eq!(estimate.notional, 200.0);
+        assert!(close(estimate.fee.unwrap(), 0.004));
+        assert!(close(estimate.net_proceeds, 1.996));
+    }
+
+    #[test]
+    fn negative_maker_rates_are_rebates() {
+        let fees = model(serde_json::json!({
+            "currency_pair": "BTC_USDT",
+            "taker_fee": "0.0005",
+            "maker_fee": "-0.0005",
+        }));
+        let order = create_order("BTC_USDT", "sell", "1").price("1000");
+        let estimate = fees.estimate(&order).unwrap();
+        assert_eq!(estimate.rate, -0.0005);
+        assert!(close(estimate.fee.unwrap(), -0.5));
+        assert!(close(estimate.fee_value, -0.5));
+        assert!(close(estimate.net_proceeds, 1000.5));
+
+        let mut trade = trade("maker", "1", "1000", "-0.5", "USDT");
+        trade.side = "sell".into();
+        let check = &fees.compare(&[trade])[0];
+        assert!(close(check.difference().unwrap(), 0.0));
+        assert!(close(check.actual_rate().unwrap(), -0.0005));
+    }
+
+    #[test]
+    fn gt_deductions_use_the_gt_rates() {
+        let fees = model(serde_json::json!({
+            "currency_pair": "BTC_USDT",
+            "taker_fee": "0.002",
+            "maker_fee": "0.001",
+            "gt_taker_fee": "0.0015",
+            "gt_maker_fee": "0.0008",
+            "debit_fee": 1,
+        }));
+        let order = create_order("BTC_USDT", "sell", "1").price("1000");
+
+        // Without a GT price the fee cannot be expressed in GT
+        let estimate = fees.estimate(&order).unwrap();
+        assert_eq!(estimate.deduction, Deduction::Gt);
+        assert_eq!(estimate.fee, None);
+
+        let fees = fees.price("GT_USDT", 10.0);
+        let estimate = fees.estimate(&order).unwrap();
+        assert_eq!(estimate.rate, 0.0008);
+        assert_eq!(estimate.fee_currency, GT_CURRENCY);
+        assert!(close(estimate.fee.unwrap(), 0.08));
+        assert!(close(estimate.fee_value, 0.8));
+        assert_eq!(estimate.net_proceeds, 1000.0);
+    }
+
+    #[test]
+    fn deduction_follows_the_debit_setting() {
+        let with = |debit_fee: i32, gt_discount: bool| {
+            deduction(&rates(serde_json::json!({
+                "taker_fee": "0.002",
+                "maker_fee": "0.001",
+                "debit_fee": debit_fee,
+                "gt_discount": gt_discount,
+            })))
+        };
+        assert_eq!(with(1, false), Deduction::Gt);
+        assert_eq!(with(2, true), Deduction::Points);
+        assert_eq!(with(3, true), Deduction::Received);
+        assert_eq!(with(0, true), Deduction::Gt);
+        assert_eq!(with(0, false), Deduction::Received);
+
+        // GT deductions without GT rates pay the regular ones
+        let fee = rates(serde_json::json!({"taker_fee": "0.002", "maker_fee": "0.001"}));
+        assert_eq!(rate(&fee, Liquidity::Taker, Deduction::Gt), 0.002);
+    }
+
+    #[test]
+    fn points_are_worth_one_usdt() {
+        let fees = model(serde_json::json!({
+            "taker_fee": "0.002",
+            "maker_fee": "0.001",
+            "debit_fee": 2,
+        }))
+        .price("BTC_USDT", 50_000.0);
+
+        let estimate = fees
+            .estimate(&create_order("BTC_USDT", "buy", "1").price("100"))
+            .unwrap();
+        assert_eq!(estimate.fee_currency, POINT_CURRENCY);
+        assert!(close(estimate.fee.unwrap(), 0.1));
+        assert_eq!(estimate.net_proceeds, 1.0);
+
+        // Fees in BTC are bridged through BTC_USDT
+        let estimate = fees
+            .estimate(&create_order("ETH_BTC", "sell", "1").price("0.05"))
+            .unwrap();
+        assert!(close(estimate.fee_value, 0.00005));
+        assert!(close(estimate.fee.unwrap(), 2.5));
+    }
+
+    #[test]
+    fn refuses_orders_it_cannot_price() {
+        let fees = regular();
+        assert_eq!(
+            fees.estimate(&create_order("ETH_USDT", "buy", "1").price("10")),
+            Err(FeeError::UnknownPair("ETH_USDT".into()))
+        );
+        assert_eq!(
+            fees.estimate(&create_order("BTC_USDT", "buy", "1")),
+            Err(FeeError::MissingPrice("BTC_USDT".into()))
+        );
+        assert!(matches!(
+            fees.estimate(&create_order("BTC_USDT", "buy", "0").price("10")),
+            Err(FeeError::InvalidOrder(_))
+        ));
+        assert_eq!(
+            fees.missing(&["btc_usdt", "eth_usdt", "ETH_USDT"]),
+            ["ETH_USDT"]
+        );
+    }
+
+    #[test]
+    fn compares_trade_fees_in_quote_currency() {
+        let fees = regular().price("GT_USDT", 10.0);
+        let mut gt = trade("maker", "1", "100", "0", "USDT");
+        gt.gt_fee = "0.02".into();
+        let mut other = trade("taker", "1", "100", "0.2", "USDT");
+        other.currency_pair = "ETH_USDT".into();
+        let checks = fees.compare(&[
+            trade("maker", "2", "100", "0.002", "BTC"),
+            trade("taker", "1", "100", "0.3", "USDT"),
+            gt,
+            trade("maker", "1", "100", "1", "DOGE"),
+            other,
+        ]);
+
+        // Base currency fees are valued at the trade price
+        assert!(close(checks[0].actual.unwrap(), 0.2));
+        assert!(close(checks[0].difference().unwrap(), 0.0));
+        // Overcharged taker fill
+        assert_eq!(checks[1].liquidity, Liquidity::Taker);
+        assert!(close(checks[1].difference().unwrap(), 0.1));
+        assert!(close(checks[1].actual_rate().unwrap(), 0.003));
+        // GT deductions valued through GT_USDT
+        assert!(close(checks[2].actual.unwrap(), 0.2));
+        assert!(close(checks[2].expected.unwrap(), 0.1));
+        // No price for DOGE, no rates for ETH_USDT
+        assert_eq!(checks[3].actual, None);
+        assert_eq!(checks[3].difference(), None);
+        assert_eq!(checks[4].expected, None);
+    }
+}
@@ -0,0 +1,792 @@
+//! Dead-man's switch around `countdown_cancel_all`.
+//!
+//! Gate cancels every open spot order of a currency pair when a countdown set
+//! with `countdown_cancel_all` runs out. [`Heartbeat`] arms that countdown and
+//! keeps refreshing it in the background (a thread with the ureq client, a
+//! task with the hyper client) well before it expires:
+//!
+//! - if the process dies or hangs, refreshes stop and Gate cancels the orders
+//! - failed refreshes are retried quickly and reported as [`HeartbeatAlert`]s,
+//!   including when the countdown may have run out
+//! - [`HeartbeatHandle::health`] reports the state of every countdown
+//! - [`HeartbeatHandle::stop`] disarms the countdowns (timeout 0) on a clean
+//!   shutdown; dropping the handle only stops the refreshes and leaves the
+//!   countdowns to expire
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::heartbeat::Heartbeat;
+//! use gateio_rs::{http::Credentials, ureq::GateHttpClient};
+//! use std::time::Duration;
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//! let heartbeat = Heartbeat::new(Duration::from_secs(30))
+//!     .currency_pair("BTC_USDT")
+//!     .currency_pair("ETH_USDT")
+//!     .start(&client)?;
+//! let alerts = heartbeat.subscribe();
+//!
+//! // ... trade ...
+//! for alert in alerts.try_iter() {
+//!     eprintln!("heartbeat: {}", alert);
+//! }
+//!
+//! heartbeat.stop()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::api::spot::countdown_cancel_all::CountdownCancelAll;
+use crate::http::GateError;
+use std::fmt;
+use std::io;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+/// Shortest countdown Gate accepts
+pub const MIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Configuration of a dead-man's switch
+#[derive(Clone, Debug)]
+pub struct Heartbeat {
+    /// Countdown set on every refresh
+    pub timeout: Duration,
+    /// Fraction of the timeout between two refreshes
+    pub refresh_fraction: f64,
+    /// Currency pairs to protect; empty protects all pairs with one countdown
+    pub currency_pairs: Vec<String>,
+}
+
+impl Heartbeat {
+    /// Creates a switch cancelling all orders `timeout` after the last refresh.
+    ///
+    /// The timeout is rounded down to whole seconds, with a minimum of
+    /// [`MIN_TIMEOUT`]. Refreshes happen every half timeout.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout: Duration::from_secs(timeout.as_secs()).max(MIN_TIMEOUT),
+            refresh_fraction: 0.5,
+            currency_pairs: Vec::new(),
+        }
+    }
+
+    /// Sets the fraction of the timeout between two refreshes, within `0.05..=0.9`
+    pub fn refresh_fraction(mut self, refresh_fraction: f64) -> Self {
+        self.refresh_fraction = refresh_fraction.clamp(0.05, 0.9);
+        self
+    }
+
+    /// Adds a currency pair with its own countdown
+    pub fn currency_pair(mut self, currency_pair: &str) -> Self {
+        self.currency_pairs.push(currency_pair.to_owned());
+        self
+    }
+
+    /// Time between two refreshes
+    pub fn interval(&self) -> Duration {
+        self.timeout.mul_f64(self.refresh_fraction)
+    }
+
+    /// Time before retrying after a failed refresh
+    fn retry_interval(&self) -> Duration {
+        (self.interval() / 4).max(Duration::from_secs(1))
+    }
+
+    /// Countdowns to maintain: one per pair, or a single one for all pairs
+    fn targets(&self) -> Vec<Option<String>> {
+        if self.currency_pairs.is_empty() {
+            vec![None]
+        } else {
+            self.currency_pairs.iter().cloned().map(Some).collect()
+        }
+    }
+
+    fn request(&self, currency_pair: &Option<String>, timeout: Duration) -> CountdownCancelAll {
+        let request = CountdownCancelAll::new(timeout.as_secs() as i64);
+        match currency_pair {
+            Some(pair) => request.currency_pair(pair),
+            None => request,
+        }
+    }
+}
+
+/// State of one countdown
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CountdownHealth {
+    /// Protected currency pair, `None` for all pairs
+    pub currency_pair: Option<String>,
+    /// Time of the last successful refresh
+    pub last_refresh: Option<SystemTime>,
+    /// Time Gate will cancel the orders (UNIX milliseconds), as of the last refresh
+    pub trigger_time: Option<i64>,
+    /// Refreshes failed since the last success
+    pub consecutive_failures: u32,
+    /// Error of the last failed refresh
+    pub last_error: Option<String>,
+}
+
+impl CountdownHealth {
+    /// Whether the last refresh succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.last_refresh.is_some() && self.consecutive_failures == 0
+    }
+}
+
+/// Problem reported by a running heartbeat
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum HeartbeatAlert {
+    /// A refresh failed; it is retried shortly
+    RefreshFailed {
+        /// Protected currency pair, `None` for all pairs
+        currency_pair: Option<String>,
+        /// Refreshes failed since the last success
+        consecutive_failures: u32,
+        /// Error returned by the refresh
+        error: String,
+    },
+    /// The countdown ran out without refresh: the orders were likely cancelled
+    Expired {
+        /// Protected currency pair, `None` for all pairs
+        currency_pair: Option<String>,
+    },
+    /// A refresh succeeded after failures
+    Recovered {
+        /// Protected currency pair, `None` for all pairs
+        currency_pair: Option<String>,
+    },
+}
+
+impl fmt::Display for HeartbeatAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pair = |pair: &Option<String>| pair.clone().unwrap_or_else(|| "all pairs".into());
+        match self {
+            HeartbeatAlert::RefreshFailed {
+                currency_pair,
+                consecutive_failures,
+                error,
+            } => write!(
+                f,
+                "Countdown refresh for {} failed ({} in a row): {}",
+                pair(currency_pair),
+                consecutive_failures,
+                error
+            ),
+            HeartbeatAlert::Expired { currency_pair } => {
+                write!(f, "Countdown for {} expired", pair(currency_pair))
+            }
+            HeartbeatAlert::Recovered { currency_pair } => {
+                write!(f, "Countdown refresh for {} recovered", pair(currency_pair))
+            }
+        }
+    }
+}
+
+/// Health and subscribers shared with the background refresher
+#[derive(Clone)]
+struct Monitor {
+    timeout: Duration,
+    inner: Arc<Mutex<MonitorState>>,
+}
+
+struct MonitorState {
+    health: Vec<CountdownHealth>,
+    /// Countdowns already reported as expired in the current outage
+    expired: Vec<bool>,
+    subscribers: Vec<Sender<HeartbeatAlert>>,
+}
+
+impl Monitor {
+    fn new(heartbeat: &Heartbeat) -> Self {
+        let health: Vec<_> = heartbeat
+            .targets()
+            .into_iter()
+            .map(|currency_pair| CountdownHealth {
+                currency_pair,
+                last_refresh: None,
+                trigger_time: None,
+                consecutive_failures: 0,
+                last_error: None,
+            })
+            .collect();
+        Self {
+            timeout: heartbeat.timeout,
+            inner: Arc::new(Mutex::new(MonitorState {
+                expired: vec![false; health.len()],
+                health,
+                subscribers: Vec::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MonitorState> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn subscribe(&self) -> Receiver<HeartbeatAlert> {
+        let (sender, receiver) = channel();
+        self.lock().subscribers.push(sender);
+        receiver
+    }
+
+    fn health(&self) -> Vec<CountdownHealth> {
+        self.lock().health.clone()
+    }
+
+    /// Records the outcome of a refresh, alerting subscribers
+    fn record(&self, index: usize, result: &Result<Option<i64>, GateError>) {
+        let mut state = self.lock();
+        let now = SystemTime::now();
+        let health = &mut state.health[index];
+        let currency_pair = health.currency_pair.clone();
+        let mut alerts = Vec::new();
+        match result {
+            Ok(trigger_time) => {
+                if health.consecutive_failures > 0 {
+                    alerts.push(HeartbeatAlert::Recovered { currency_pair });
+                }
+                health.last_refresh = Some(now);
+                health.trigger_time = *trigger_time;
+                health.consecutive_failures = 0;
+                health.last_error = None;
+                state.expired[index] = false;
+            }
+            Err(err) => {
+                health.consecutive_failures += 1;
+                health.last_error = Some(err.to_string());
+                alerts.push(HeartbeatAlert::RefreshFailed {
+                    currency_pair: currency_pair.clone(),
+                    consecutive_failures: health.consecutive_failures,
+                    error: err.to_string(),
+                });
+                let expired = health
+                    .last_refresh
+                    .is_some_and(|last| last + self.timeout <= now);
+                if expired && !state.expired[index] {
+                    state.expired[index] = true;
+                    alerts.push(HeartbeatAlert::Expired { currency_pair });
+                }
+            }
+        }
+        for alert in alerts {
+            state
+                .subscribers
+                .retain(|subscriber| subscriber.send(alert.clone()).is_ok());
+        }
+    }
+}
+
+/// Reads the trigger time from a `countdown_cancel_all` response
+fn trigger_time(body: &str) -> Option<i64> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("triggerTime")?
+        .as_i64()
+}
+
+#[cfg(feature = "enable-ureq")]
+impl Heartbeat {
+    /// Arms the countdowns and refreshes them from a background thread.
+    ///
+    /// Fails, leaving no countdown armed, when the first refresh fails.
+    pub fn start<S>(&self, client: &S) -> Result<HeartbeatHandle<S>, HeartbeatError>
+    where
+        S: crate::ureq::SendRequest + Clone + Send + 'static,
+    {
+        let monitor = Monitor::new(self);
+        if let Err(err) = self.refresh_all(client, &monitor) {
+            let _ = self.disarm(client);
+            return Err(err);
+        }
+
+        let (stop, stopped) = channel::<()>();
+        let heartbeat = self.clone();
+        let thread_client = client.clone();
+        let thread_monitor = monitor.clone();
+        let thread = std::thread::Builder::new()
+            .name("gate-heartbeat".into())
+            .spawn(move || {
+                let mut wait = heartbeat.interval();
+                // Runs until `stop` is signalled or dropped
+                while let Err(std::sync::mpsc::RecvTimeoutError::Timeout) =
+                    stopped.recv_timeout(wait)
+                {
+                    wait = match heartbeat.refresh_all(&thread_client, &thread_monitor) {
+                        Ok(()) => heartbeat.interval(),
+                        Err(_) => heartbeat.retry_interval(),
+                    };
+                }
+            })?;
+
+        Ok(HeartbeatHandle {
+            heartbeat: self.clone(),
+            client: client.clone(),
+            monitor,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    /// Refreshes every countdown, returning the last error
+    fn refresh_all<S: crate::ureq::SendRequest>(
+        &self,
+        client: &S,
+        monitor: &Monitor,
+    ) -> Result<(), HeartbeatError> {
+        let mut outcome = Ok(());
+        for (index, currency_pair) in self.targets().iter().enumerate() {
+            let result = client
+                .send(self.request(currency_pair, self.timeout))
+                .and_then(|response| response.into_body_str())
+                .map(|body| trigger_time(&body))
+                .map_err(|err| *err);
+            monitor.record(index, &result);
+            if let Err(err) = result {
+                outcome = Err(err.into());
+            }
+        }
+        outcome
+    }
+
+    /// Sets every countdown to 0, returning the last error
+    fn disarm<S: crate::ureq::SendRequest>(&self, client: &S) -> Result<(), HeartbeatError> {
+        let mut outcome = Ok(());
+        for currency_pair in self.targets() {
+            let request = self.request(&currency_pair, Duration::ZERO);
+            if let Err(err) = client
+                .send(request)
+                .and_then(|response| response.into_body_str())
+            {
+                outcome = Err(err.into());
+            }
+        }
+        outcome
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl Heartbeat {
+    /// Arms the countdowns and refreshes them from a Tokio task.
+    ///
+    /// Fails, leaving no countdown armed, when the first refresh fails.
+    pub async fn start_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<AsyncHeartbeatHandle, HeartbeatError> {
+        let monitor = Monitor::new(self);
+        if let Err(err) = self.refresh_all_async(client, &monitor).await {
+            let _ = self.disarm_async(client).await;
+            return Err(err);
+        }
+
+        let (stop, mut stopped) = tokio::sync::oneshot::channel::<()>();
+        let heartbeat = self.clone();
+        let task_client = client.clone();
+        let task_monitor = monitor.clone();
+        let task = tokio::spawn(async move {
+            let mut wait = heartbeat.interval();
+            // Runs until `stop` is signalled or dropped; refreshes are not interrupted
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = tokio::time::sleep(wait) => {}
+                }
+                wait = match heartbeat
+                    .refresh_all_async(&task_client, &task_monitor)
+                    .await
+                {
+                    Ok(()) => heartbeat.interval(),
+                    Err(_) => heartbeat.retry_interval(),
+                };
+            }
+        });
+
+        Ok(AsyncHeartbeatHandle {
+            heartbeat: self.clone(),
+            client: client.clone(),
+            monitor,
+            stop: Some(stop),
+            task,
+        })
+    }
+
+    /// Refreshes every countdown, returning the last error
+    async fn refresh_all_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+        monitor: &Monitor,
+    ) -> Result<(), HeartbeatError> {
+        let mut outcome = Ok(());
+        for (index, currency_pair) in self.targets().iter().enumerate() {
+            let result = match client.send(self.request(currency_pair, self.timeout)).await {
+                Ok(response) => response
+                    .into_body_str()
+                    .await
+                    .map(|body| trigger_time(&body)),
+                Err(err) => Err(err),
+            };
+            monitor.record(index, &result);
+            if let Err(err) = result {
+                outcome = Err(err.into());
+            }
+        }
+        outcome
+    }
+
+    /// Sets every countdown to 0, returning the last error
+    async fn disarm_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<(), HeartbeatError> {
+        let mut outcome = Ok(());
+        for currency_pair in self.targets() {
+            let request = self.request(&currency_pair, Duration::ZERO);
+            let result = match client.send(request).await {
+                Ok(response) => response.into_body_str().await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                outcome = Err(err.into());
+            }
+        }
+        outcome
+    }
+}
+
+/// Running dead-man's switch refreshed by a background thread
+#[cfg(feature = "enable-ureq")]
+pub struct HeartbeatHandle<S = crate::ureq::GateHttpClient> {
+    heartbeat: Heartbeat,
+    client: S,
+    monitor: Monitor,
+    stop: Option<Sender<()>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<S> HeartbeatHandle<S> {
+    /// Returns the state of every countdown
+    pub fn health(&self) -> Vec<CountdownHealth> {
+        self.monitor.health()
+    }
+
+    /// Whether the last refresh of every countdown succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.health().iter().all(CountdownHealth::is_healthy)
+    }
+
+    /// Returns a receiver of every alert raised from now on
+    pub fn subscribe(&self) -> Receiver<HeartbeatAlert> {
+        self.monitor.subscribe()
+    }
+
+    /// Stops the refreshes and disarms every countdown
+    pub fn stop(mut self) -> Result<(), HeartbeatError>
+    where
+        S: crate::ureq::SendRequest,
+    {
+        self.halt();
+        self.heartbeat.disarm(&self.client)
+    }
+
+    fn halt(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<S> Drop for HeartbeatHandle<S> {
+    /// Stops the refreshes, leaving the countdowns to expire
+    fn drop(&mut self) {
+        self.halt();
+    }
+}
+
+/// Running dead-man's switch refreshed by a Tokio task
+#[cfg(feature = "enable-hyper")]
+pub struct AsyncHeartbeatHandle {
+    heartbeat: Heartbeat,
+    client: crate::hyper::GateHttpClient,
+    monitor: Monitor,
+    stop: Option<tokio::sync::oneshot::Sender<()>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[cfg(feature = "enable-hyper")]
+impl AsyncHeartbeatHandle {
+    /// Returns the state of every countdown
+    pub fn health(&self) -> Vec<CountdownHealth> {
+        self.monitor.health()
+    }
+
+    /// Whether the last refresh of every countdown succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.health().iter().all(CountdownHealth::is_healthy)
+    }
+
+    /// Returns a receiver of every alert raised from now on
+    pub fn subscribe(&self) -> Receiver<HeartbeatAlert> {
+        self.monitor.subscribe()
+    }
+
+    /// Stops the refreshes and disarms every countdown
+    pub async fn stop(mut self) -> Result<(), HeartbeatError> {
+        // Let an in-flight refresh finish before disarming
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+        self.heartbeat.disarm_async(&self.client).await
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl Drop for AsyncHeartbeatHandle {
+    /// Stops the refreshes, leaving the countdowns to expire
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Error of a heartbeat
+#[derive(Debug)]
+pub enum HeartbeatError {
+    /// Request to the Gate API failed
+    Gate(Box<GateError>),
+    /// The background thread could not be started
+    Io(io::Error),
+}
+
+impl fmt::Display for HeartbeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeartbeatError::Gate(e) => write!(f, "Gate API error: {}", e),
+            HeartbeatError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HeartbeatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HeartbeatError::Gate(e) => Some(e.as_ref()),
+            HeartbeatError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<GateError> for HeartbeatError {
+    fn from(err: GateError) -> Self {
+        HeartbeatError::Gate(Box::new(err))
+    }
+}
+
+impl From<Box<GateError>> for HeartbeatError {
+    fn from(err: Box<GateError>) -> Self {
+        HeartbeatError::Gate(err)
+    }
+}
+
+impl From<io::Error> for HeartbeatError {
+    fn from(err: io::Error) -> Self {
+        HeartbeatError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> Result<Option<i64>, GateError> {
+        Err(GateError::Timeout)
+    }
+
+    #[test]
+    fn timeout_is_rounded_and_clamped() {
+        assert_eq!(
+            Heartbeat::new(Duration::from_millis(30_900)).timeout,
+            Duration::from_secs(30)
+        );
+        assert_eq!(Heartbeat::new(Duration::from_secs(1)).timeout, MIN_TIMEOUT);
+        let heartbeat = Heartbeat::new(Duration::from_secs(20)).refresh_fraction(2.0);
+        assert_eq!(heartbeat.interval(), Duration::from_secs(18));
+        assert_eq!(heartbeat.retry_interval(), Duration::from_millis(4500));
+        let heartbeat = Heartbeat::new(MIN_TIMEOUT).refresh_fraction(0.0);
+        assert_eq!(heartbeat.retry_interval(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn one_countdown_without_pairs() {
+        assert_eq!(Heartbeat::new(MIN_TIMEOUT).targets(), vec![None]);
+        let heartbeat = Heartbeat::new(MIN_TIMEOUT).currency_pair("BTC_USDT");
+        assert_eq!(heartbeat.targets(), vec![Some("BTC_USDT".to_owned())]);
+    }
+
+    #[test]
+    fn reads_trigger_time() {
+        assert_eq!(
+            trigger_time(r#"{"triggerTime":1660039145000}"#),
+            Some(1660039145000)
+        );
+        assert_eq!(trigger_time(r#"{"triggerTime":"soon"}"#), None);
+        assert_eq!(trigger_time("not json"), None);
+    }
+
+    #[test]
+    fn failures_are_counted_and_recovered() {
+        let monitor = Monitor::new(&Heartbeat::new(MIN_TIMEOUT).currency_pair("BTC_USDT"));
+        let alerts = monitor.subscribe();
+        monitor.record(0, &Ok(Some(1)));
+        assert!(monitor.health()[0].is_healthy());
+
+        monitor.record(0, &failure());
+        monitor.record(0, &failure());
+        let health = &monitor.health()[0];
+        assert!(!health.is_healthy());
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(health.trigger_time, Some(1));
+
+        monitor.record(0, &Ok(Some(2)));
+        assert!(monitor.health()[0].is_healthy());
+        let alerts: Vec<_> = alerts.try_iter().collect();
+        assert_eq!(alerts.len(), 3);
+        assert!(matches!(
+            alerts[1],
+            HeartbeatAlert::RefreshFailed {
+                consecutive_failures: 2,
+                ..
+            }
+        ));
+        assert_eq!(
+            alerts[2],
+            HeartbeatAlert::Recovered {
+                currency_pair: Some("BTC_USDT".into())
+            }
+        );
+    }
+
+    #[test]
+    fn expiry_is_reported_once_per_outage() {
+        let monitor = Monitor::new(&Heartbeat::new(MIN_TIMEOUT));
+        monitor.record(0, &Ok(None));
+        // Pretend the last refresh is older than the countdown
+        monitor.lock().health[0].last_refresh = Some(SystemTime::now() - MIN_TIMEOUT * 2);
+        let alerts = monitor.subscribe();
+        monitor.record(0, &failure());
+        monitor.record(0, &failure());
+        let expired = alerts
+            .try_iter()
+            .filter(|alert| matches!(alert, HeartbeatAlert::Expired { .. }))
+            .count();
+        assert_eq!(expired, 1);
+    }
+
+    #[test]
+    fn never_refreshed_does_not_expire() {
+        let monitor = Monitor::new(&Heartbeat::new(MIN_TIMEOUT));
+        let alerts = monitor.subscribe();
+        monitor.record(0, &failure());
+        assert_eq!(alerts.try_iter().count(), 1);
+    }
+
+    #[cfg(all(feature = "testing", feature = "enable-ureq"))]
+    mod mock {
+        use super::*;
+        use crate::http::{GateErrorLabel, Method};
+        use crate::testing::{MockGate, MockResponse};
+        use crate::ureq::GateHttpClient;
+
+        const PATH: &str = "/api/v4/spot/countdown_cancel_all";
+
+        fn timeouts(mock: &MockGate) -> Vec<i64> {
+            mock.requests_to(PATH)
+                .iter()
+                .filter_map(|request| request.json()?.get("timeout")?.as_i64())
+                .collect()
+        }
+
+        #[test]
+        fn stop_disarms_and_reports_error_statuses() {
+            let mock = MockGate::start().unwrap();
+            mock.respond(
+                Method::Post,
+                PATH,
+                MockResponse::json(r#"{"triggerTime":1}"#),
+            );
+            let client = GateHttpClient::with_url(&mock.url());
+            let heartbeat = Heartbeat::new(Duration::from_secs(60))
+                .start(&client)
+                .unwrap();
+            assert!(heartbeat.is_healthy());
+
+            mock.respond(
+                Method::Post,
+                PATH,
+                MockResponse::error(500, GateErrorLabel::ServerError, "down"),
+            );
+            assert!(matches!(heartbeat.stop(), Err(HeartbeatError::Gate(_))));
+            assert_eq!(timeouts(&mock), vec![60, 0]);
+        }
+
+        #[test]
+        fn failed_start_disarms() {
+            let mock = MockGate::start().unwrap();
+            mock.respond(
+                Method::Post,
+                PATH,
+                MockResponse::error(400, GateErrorLabel::InvalidParamValue, "bad"),
+            );
+            let client = GateHttpClient::with_url(&mock.url());
+            let started = Heartbeat::new(MIN_TIMEOUT)
+                .currency_pair("BTC_USDT")
+                .start(&client);
+            assert!(started.is_err());
+            assert_eq!(timeouts(&mock), vec![5, 0]);
+        }
+    }
+
+    #[cfg(all(feature = "testing", feature = "enable-hyper"))]
+    mod mock_async {
+        use super::*;
+        use crate::http::Method;
+        use crate::hyper::GateHttpClient;
+        use crate::testing::{MockGate, MockResponse};
+
+        const PATH: &str = "/api/v4/spot/countdown_cancel_all";
+
+        #[tokio::test]
+        async fn stop_waits_for_refresh_then_disarms() {
+            let mock = MockGate::start().unwrap();
+            mock.respond(
+                Method::Post,
+                PATH,
+                MockResponse::json(r#"{"triggerTime":1}"#),
+            );
+            let client = GateHttpClient::with_url(&mock.url());
+            let heartbeat = Heartbeat::new(MIN_TIMEOUT)
+                .refresh_fraction(0.05)
+                .start_async(&client)
+                .await
+                .unwrap();
+
+            // Let a refresh start, slowed down by the mock, then stop during it
+            mock.respond(
+                Method::Post,
+                PATH,
+                MockResponse::json(r#"{"triggerTime":2}"#).delay(Duration::from_millis(400)),
+            );
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            heartbeat.stop().await.unwrap();
+
+            let requests = mock.requests_to(PATH);
+            let last = requests.last().unwrap().json().unwrap();
+            assert_eq!(last.get("timeout").and_then(|t| t.as_i64()), Some(0));
+            // Every refresh was answered before the disarm was sent
+            assert!(requests.iter().all(|request| request.status == 200));
+            assert!(requests.len() >= 3);
+        }
+    }
+}
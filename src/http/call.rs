@@ -0,0 +1,367 @@
+use crate::http::request::{Request, RequestBuilder};
+use crate::http::{Credentials, GateError, Method};
+use crate::utils::parse;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Typed call to any Gate v4 endpoint, including those the SDK does not wrap.
+///
+/// Created with `call::<T>(method, path)` on either client: the query and the
+/// body are serialized from any `Serialize` type, the request is signed like
+/// the wrapped endpoints when [`Call::sign`] is set, and the response body is
+/// deserialized into `T`. An empty body deserializes as JSON `null`, so `()`
+/// and `Option<_>` fit endpoints answering without content.
+///
+/// Query types must serialize to a flat map: `None` fields are left out,
+/// strings are sent as is, numbers and booleans in their JSON form and arrays
+/// joined with commas.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use gateio_rs::http::{Credentials, Method};
+/// use gateio_rs::ureq::GateHttpClient;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize)]
+/// struct Query<'a> {
+///     currency: &'a str,
+///     limit: Option<u32>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Entry {
+///     id: String,
+///     change: String,
+/// }
+///
+/// let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+/// let entries: Vec<Entry> = client
+///     .call(Method::Get, "/api/v4/spot/account_book")
+///     .query(&Query { currency: "USDT", limit: Some(10) })
+///     .sign()
+///     .send()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Any [`SendRequest`](crate::ureq::SendRequest) implementation can be called
+/// through, e.g. recorded market data:
+///
+/// ```rust
+/// use gateio_rs::http::{Call, Method};
+/// use gateio_rs::paper::RecordedMarket;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct ServerTime {
+///     server_time: i64,
+/// }
+///
+/// let market = RecordedMarket::new().fixture("/api/v4/spot/time", &[], r#"{"server_time":1700000000000}"#);
+/// let time: ServerTime = Call::new(&market, Method::Get, "/api/v4/spot/time").send()?;
+/// assert_eq!(time.server_time, 1_700_000_000_000);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[must_use = "a call does nothing until sent"]
+pub struct Call<'a, C, T> {
+    client: &'a C,
+    builder: RequestBuilder,
+    error: Option<GateError>,
+    output: PhantomData<fn() -> T>,
+}
+
+impl<'a, C, T> Call<'a, C, T> {
+    /// Creates a call to `path` sent with `client`
+    pub fn new(client: &'a C, method: Method, path: &str) -> Self {
+        Self {
+            client,
+            builder: RequestBuilder::new(method, path),
+            error: None,
+            output: PhantomData,
+        }
+    }
+
+    /// Adds the fields of `query` to the query string
+    pub fn query<Q: Serialize + ?Sized>(mut self, query: &Q) -> Self {
+        match query_params(query) {
+            Ok(params) => {
+                let params = params.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+                self.builder = self.builder.params(params);
+            }
+            Err(err) => self.error = Some(err),
+        }
+        self
+    }
+
+    /// Adds a query parameter
+    pub fn param(mut self, key: &str, value: &str) -> Self {
+        self.builder = self.builder.params([(key, value)]);
+        self
+    }
+
+    /// Sends `body` as the JSON payload
+    pub fn body<B: Serialize + ?Sized>(mut self, body: &B) -> Self {
+        match serde_json::to_string(body) {
+            Ok(payload) => self.builder = self.builder.payload(&payload),
+            Err(_) => self.error = Some(GateError::PayloadSerializationError),
+        }
+        self
+    }
+
+    /// Signs the request, as required by private endpoints
+    pub fn sign(mut self) -> Self {
+        self.builder = self.builder.sign();
+        self
+    }
+
+    /// Signs with `credentials` instead of those of the client
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.builder = self.builder.credentials(credentials);
+        self
+    }
+
+    /// Overrides the client timeout for this call
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.builder = self.builder.timeout(timeout);
+        self
+    }
+
+    fn into_request(self) -> Result<(&'a C, Request), GateError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok((self.client, self.builder.into())),
+        }
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<S: crate::ureq::SendRequest, T: DeserializeOwned> Call<'_, S, T> {
+    /// Sends the call and deserializes the response
+    pub fn send(self) -> Result<T, Box<GateError>> {
+        let (client, request) = self.into_request()?;
+        let body = client.send(request)?.into_body_str()?;
+        Ok(parse(&body)?)
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl<T: DeserializeOwned> Call<'_, crate::hyper::GateHttpClient, T> {
+    /// Sends the call and deserializes the response
+    pub async fn send(self) -> Result<T, GateError> {
+        let (client, request) = self.into_request()?;
+        let body = client.send(request).await?.into_body_str().await?;
+        parse(&body)
+    }
+}
+
+/// Flattens a serializable value into query parameters
+fn query_params<Q: Serialize + ?Sized>(query: &Q) -> Result<Vec<(String, String)>, GateError> {
+    let scalar = |value: &Value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    };
+    let fields = match serde_json::to_value(query) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(Value::Null) => return Ok(Vec::new()),
+        _ => return Err(GateError::PayloadSerializationError),
+    };
+    let mut params = Vec::with_capacity(fields.len());
+    for (key, value) in fields {
+        let value = match &value {
+            Value::Null => continue,
+            Value::Array(items) => items
+                .iter()
+                .map(scalar)
+                .collect::<Option<Vec<_>>>()
+                .map(|items| items.join(",")),
+            value => scalar(value),
+        };
+        params.push((key, value.ok_or(GateError::PayloadSerializationError)?));
+    }
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Query<'a> {
+        currency: &'a str,
+        limit: Option<u32>,
+        page: Option<u32>,
+        reverse: bool,
+        ids: Vec<u64>,
+    }
+
+    #[test]
+    fn flattens_queries() {
+        let query = Query {
+            currency: "USDT",
+            limit: Some(10),
+            page: None,
+            reverse: true,
+            ids: vec![1, 2],
+        };
+        let params = query_params(&query).unwrap();
+        // Field order follows the JSON map, sorted unless `preserve_order` is enabled
+        let mut params: Vec<(&str, &str)> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        params.sort();
+        assert_eq!(
+            params,
+            vec![
+                ("currency", "USDT"),
+                ("ids", "1,2"),
+                ("limit", "10"),
+                ("reverse", "true"),
+            ]
+        );
+        assert!(query_params(&()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn refuses_queries_that_are_not_flat_maps() {
+        let nested = serde_json::json!({"range": {"from": 1}});
+        let nested_list = serde_json::json!({"ids": [[1]]});
+        for query in [nested, nested_list, serde_json::json!("currency=USDT")] {
+            assert!(matches!(
+                query_params(&query),
+                Err(GateError::PayloadSerializationError)
+            ));
+        }
+    }
+
+    #[test]
+    fn builds_the_request() {
+        #[derive(Serialize)]
+        struct Body {
+            currency: &'static str,
+            amount: String,
+        }
+
+        let call: Call<'_, (), ()> = Call::new(&(), Method::Post, "/api/v4/wallet/transfers")
+            .param("a", "1")
+            .body(&Body {
+                currency: "USDT",
+                amount: "10".into(),
+            })
+            .sign()
+            .timeout(Duration::from_secs(3));
+        let (_, request) = call.into_request().unwrap();
+        assert_eq!(request.path(), "/api/v4/wallet/transfers");
+        assert_eq!(request.params(), &[("a".to_owned(), "1".to_owned())]);
+        assert_eq!(request.payload(), r#"{"currency":"USDT","amount":"10"}"#);
+        assert!(*request.sign());
+        assert_eq!(*request.timeout(), Some(Duration::from_secs(3)));
+
+        let call: Call<'_, (), ()> =
+            Call::new(&(), Method::Get, "/api/v4/spot/tickers").query(&"currency_pair");
+        assert!(matches!(
+            call.into_request(),
+            Err(GateError::PayloadSerializationError)
+        ));
+    }
+
+    #[cfg(all(feature = "testing", feature = "enable-ureq"))]
+    mod mock {
+        use super::*;
+        use crate::testing::{MockGate, MockResponse};
+        use crate::ureq::GateHttpClient;
+        use serde::Deserialize;
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Entry {
+            id: String,
+            change: String,
+        }
+
+        fn gate() -> (MockGate, GateHttpClient) {
+            let credentials = Credentials::new("key", "secret");
+            let mock = MockGate::start().unwrap();
+            mock.credentials(credentials.clone());
+            let client = GateHttpClient::with_url(&mock.url()).credentials(credentials);
+            (mock, client)
+        }
+
+        #[test]
+        fn signs_queries_and_parses_the_response() {
+            let (mock, client) = gate();
+            mock.respond(
+                Method::Get,
+                "/api/v4/spot/account_book",
+                MockResponse::json(r#"[{"id":"1","change":"-5"}]"#),
+            );
+            let entries: Vec<Entry> = client
+                .call(Method::Get, "/api/v4/spot/account_book")
+                .query(&Query {
+                    currency: "USDT",
+                    limit: Some(10),
+                    page: None,
+                    reverse: false,
+                    ids: Vec::new(),
+                })
+                .sign()
+                .send()
+                .unwrap();
+            assert_eq!(
+                entries,
+                vec![Entry {
+                    id: "1".into(),
+                    change: "-5".into()
+                }]
+            );
+
+            let request = &mock.requests_to("/api/v4/spot/account_book")[0];
+            assert!(request.signed);
+            assert_eq!(request.param("currency"), Some("USDT"));
+            assert_eq!(request.param("limit"), Some("10"));
+            assert_eq!(request.param("page"), None);
+            assert_eq!(request.param("ids"), Some(""));
+        }
+
+        #[test]
+        fn signs_bodies_and_accepts_empty_responses() {
+            let (mock, client) = gate();
+            mock.respond(
+                Method::Delete,
+                "/api/v4/spot/orders",
+                MockResponse::new(204, ""),
+            );
+            let body = serde_json::json!({"currency_pair": "BTC_USDT", "side": "buy"});
+            let () = client
+                .call(Method::Delete, "/api/v4/spot/orders")
+                .body(&body)
+                .sign()
+                .send()
+                .unwrap();
+
+            let request = &mock.requests_to("/api/v4/spot/orders")[0];
+            assert!(request.signed);
+            assert_eq!(request.json(), Some(body));
+        }
+
+        #[test]
+        fn unsigned_private_calls_are_refused() {
+            let (mock, client) = gate();
+            mock.respond(
+                Method::Get,
+                "/api/v4/spot/accounts",
+                MockResponse::json("[]"),
+            );
+            let err = client
+                .call::<Vec<Entry>>(Method::Get, "/api/v4/spot/accounts")
+                .send()
+                .unwrap_err();
+            assert_eq!(err.status_code(), Some(401));
+            assert!(!mock.requests()[0].signed);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+/// Default upper bound on response bodies read into memory (10 MiB)
+pub const DEFAULT_MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// Transport configuration shared by every HTTP client backend.
+///
+/// Unset options fall back to the defaults of the underlying HTTP library.
+///
+/// # Examples
+///
+/// ```rust
+/// use gateio_rs::http::ClientConfig;
+/// use std::time::Duration;
+///
+/// let config = ClientConfig::new()
+///     .connect_timeout(Duration::from_secs(3))
+///     .timeout(Duration::from_secs(10))
+///     .proxy("socks5://127.0.0.1:1080")
+///     .pool_max_idle_per_host(8)
+///     .tcp_keepalive(Duration::from_secs(60));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ClientConfig {
+    /// Timeout for establishing the TCP/TLS connection
+    pub connect_timeout: Option<Duration>,
+    /// Timeout for receiving the response, including the body
+    pub read_timeout: Option<Duration>,
+    /// Overall timeout for a request, from sending to the fully read response
+    pub timeout: Option<Duration>,
+    /// Proxy URL (`http://`, `https://`, `socks4://` or `socks5://`)
+    pub proxy: Option<String>,
+    /// How long an idle pooled connection is kept alive
+    pub pool_idle_timeout: Option<Duration>,
+    /// Maximum number of idle connections kept per host
+    pub pool_max_idle_per_host: Option<usize>,
+    /// TCP keepalive interval (ignored by backends without keepalive support)
+    pub tcp_keepalive: Option<Duration>,
+    /// Maximum response body size read into memory, [`DEFAULT_MAX_BODY_SIZE`] if unset
+    pub max_body_size: Option<usize>,
+}
+
+impl ClientConfig {
+    /// Creates a configuration using the backend defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the connect timeout
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Sets the response read timeout
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    /// Sets the overall request timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Routes all requests through an HTTP or SOCKS proxy
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_owned());
+        self
+    }
+
+    /// Sets how long idle pooled connections are kept
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections per host
+    pub fn pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = Some(pool_max_idle_per_host);
+        self
+    }
+
+    /// Sets the TCP keepalive interval
+    pub fn tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+
+    /// Sets the maximum response body size read into memory
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+}
@@ -1,6 +1,147 @@
+use http::{Error as HttpCrateError, uri::InvalidUri};
 use serde::Deserialize;
 use std::collections::HashMap;
 
+/// Communication error with the Gate API, shared by every client backend.
+#[derive(Debug)]
+pub enum GateError {
+    /// 4XX error from the server.
+    Client(ClientError),
+    /// 5XX error from the server.
+    Server(ClientError),
+    /// The format of the API secret is invalid.
+    InvalidApiSecret,
+    /// The configured proxy URL is invalid.
+    InvalidProxy(String),
+    /// Error serializing request payload to JSON
+    PayloadSerializationError,
+    /// The request did not complete within the configured timeout.
+    Timeout,
+    /// The system clock is set before the UNIX epoch, so requests cannot be signed.
+    Clock,
+    /// The response body exceeded the configured size limit (in bytes).
+    BodyTooLarge(usize),
+    /// The response body could not be decoded.
+    InvalidBody(Box<dyn std::error::Error + Send + Sync>),
+    /// Error parsing HTTP request or response
+    Parse(HttpCrateError),
+    /// Error sending HTTP request
+    Send(Box<dyn std::error::Error + Send + Sync>),
+    /// The request was refused before sending by a pre-trade check, such as a
+    /// [`RiskGate`](crate::risk::RiskGate) limit.
+    Risk(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl GateError {
+    /// Returns the error response when the server answered
+    pub fn response(&self) -> Option<&ClientError> {
+        match self {
+            GateError::Client(e) | GateError::Server(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP status code when the server answered
+    pub fn status_code(&self) -> Option<u16> {
+        self.response().map(|e| e.status_code())
+    }
+
+    /// Returns the Gate error label when the server answered with one
+    pub fn label(&self) -> Option<&GateErrorLabel> {
+        self.response().and_then(|e| e.label())
+    }
+
+    /// Returns the raw response body when the server answered
+    pub fn body(&self) -> Option<&str> {
+        self.response().map(|e| e.body())
+    }
+
+    /// Returns the response headers when the server answered
+    pub fn headers(&self) -> Option<&HashMap<String, String>> {
+        self.response().map(|e| e.headers())
+    }
+
+    /// Whether sending the same request again may succeed.
+    ///
+    /// True for transport failures, timeouts, 5XX responses, 429 responses
+    /// and labels signalling server overload.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            GateError::Timeout | GateError::Send(_) | GateError::Server(_) => true,
+            GateError::Client(_) => {
+                self.status_code() == Some(429) || self.label().is_some_and(|l| l.is_retryable())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the request was rejected because of authentication or permissions.
+    pub fn is_auth(&self) -> bool {
+        match self {
+            GateError::InvalidApiSecret => true,
+            GateError::Client(_) => {
+                matches!(self.status_code(), Some(401) | Some(403))
+                    || self.label().is_some_and(|l| l.is_auth())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<InvalidUri> for GateError {
+    fn from(err: InvalidUri) -> GateError {
+        GateError::Parse(err.into())
+    }
+}
+
+impl From<InvalidUri> for Box<GateError> {
+    fn from(err: InvalidUri) -> Box<GateError> {
+        Box::new(err.into())
+    }
+}
+
+impl From<HttpCrateError> for GateError {
+    fn from(err: HttpCrateError) -> GateError {
+        GateError::Parse(err)
+    }
+}
+
+impl From<HttpCrateError> for Box<GateError> {
+    fn from(err: HttpCrateError) -> Box<GateError> {
+        Box::new(err.into())
+    }
+}
+
+impl std::fmt::Display for GateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GateError::Client(e) => write!(f, "Client error {}", e),
+            GateError::Server(e) => write!(f, "Server error {}", e),
+            GateError::InvalidApiSecret => write!(f, "Invalid API secret"),
+            GateError::InvalidProxy(proxy) => write!(f, "Invalid proxy: {}", proxy),
+            GateError::PayloadSerializationError => write!(f, "Payload serialization error"),
+            GateError::Timeout => write!(f, "Request timed out"),
+            GateError::Clock => write!(f, "System clock is before the UNIX epoch"),
+            GateError::BodyTooLarge(limit) => {
+                write!(f, "Response body exceeds the limit of {} bytes", limit)
+            }
+            GateError::InvalidBody(e) => write!(f, "Invalid response body: {}", e),
+            GateError::Parse(e) => write!(f, "Parse error: {}", e),
+            GateError::Send(e) => write!(f, "Send error: {}", e),
+            GateError::Risk(e) => write!(f, "Refused by risk checks: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GateError::Risk(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 /// Unsuccessful response from the Gate API.
 #[derive(Debug)]
 pub enum ClientError {
@@ -19,6 +160,8 @@ pub struct HttpError<T> {
     pub data: T,
     /// Response headers
     pub headers: HashMap<String, String>,
+    /// Raw response body, preserved even when `data` was parsed from it
+    pub body: String,
 }
 
 impl<T> HttpError<T> {
@@ -28,6 +171,75 @@ impl<T> HttpError<T> {
             status_code,
             data,
             headers,
+            body: String::new(),
+        }
+    }
+
+    /// Sets the raw response body
+    pub fn with_body(mut self, body: String) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+impl HttpError<String> {
+    /// Creates a new HTTP error whose data is the raw body
+    pub fn raw(status_code: u16, body: String, headers: HashMap<String, String>) -> Self {
+        Self::new(status_code, body.clone(), headers).with_body(body)
+    }
+}
+
+impl ClientError {
+    /// Classifies an error response body, keeping the raw body in both cases
+    pub fn from_body(status_code: u16, body: String, headers: HashMap<String, String>) -> Self {
+        match serde_json::from_str::<GateApiError>(&body) {
+            Ok(err) => {
+                ClientError::Structured(HttpError::new(status_code, err, headers).with_body(body))
+            }
+            Err(_) => ClientError::Raw(HttpError::raw(status_code, body, headers)),
+        }
+    }
+
+    /// HTTP status code of the response
+    pub fn status_code(&self) -> u16 {
+        match self {
+            ClientError::Structured(e) => e.status_code,
+            ClientError::Raw(e) => e.status_code,
+        }
+    }
+
+    /// Gate error label, when the body complied with the error schema
+    pub fn label(&self) -> Option<&GateErrorLabel> {
+        match self {
+            ClientError::Structured(e) => Some(&e.data.label),
+            ClientError::Raw(_) => None,
+        }
+    }
+
+    /// Raw response body
+    pub fn body(&self) -> &str {
+        match self {
+            ClientError::Structured(e) => &e.body,
+            ClientError::Raw(e) => &e.body,
+        }
+    }
+
+    /// Response headers
+    pub fn headers(&self) -> &HashMap<String, String> {
+        match self {
+            ClientError::Structured(e) => &e.headers,
+            ClientError::Raw(e) => &e.headers,
+        }
+    }
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Structured(e) => {
+                write!(f, "{}: {} {}", e.status_code, e.data.label, e.data.message)
+            }
+            ClientError::Raw(e) => write!(f, "{}: {}", e.status_code, e.body),
         }
     }
 }
@@ -35,15 +247,222 @@ impl<T> HttpError<T> {
 /// Structured Gate server error
 #[derive(Deserialize, Debug)]
 pub struct GateApiError {
-    /// Error code
+    /// Error label
     ///
-    /// [API Documentation](https://www.gate.com/docs/developers/apiv4/#accountbook-code)
-
-    /// non-2xx status code
-    #[serde(rename(deserialize = "label"))]
-    pub label: String,
+    /// [API Documentation](https://www.gate.com/docs/developers/apiv4/#error-response)
+    pub label: GateErrorLabel,
 
     /// detailed error message
-    #[serde(rename(deserialize = "message"))]
+    #[serde(default)]
     pub message: String,
 }
+
+macro_rules! error_labels {
+    ($($(#[$doc:meta])* $variant:ident => $label:literal,)*) => {
+        /// Documented Gate API error labels.
+        ///
+        /// Labels not known to this crate are kept verbatim in [`GateErrorLabel::Other`].
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+        #[serde(from = "String")]
+        pub enum GateErrorLabel {
+            $($(#[$doc])* $variant,)*
+            /// Label not covered by the variants above
+            Other(String),
+        }
+
+        impl GateErrorLabel {
+            /// Returns the label as sent by Gate
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(GateErrorLabel::$variant => $label,)*
+                    GateErrorLabel::Other(label) => label,
+                }
+            }
+        }
+
+        impl From<&str> for GateErrorLabel {
+            fn from(label: &str) -> Self {
+                match label {
+                    $($label => GateErrorLabel::$variant,)*
+                    other => GateErrorLabel::Other(other.to_owned()),
+                }
+            }
+        }
+    };
+}
+
+error_labels! {
+    /// Invalid parameter value
+    InvalidParamValue => "INVALID_PARAM_VALUE",
+    /// Invalid protocol
+    InvalidProtocol => "INVALID_PROTOCOL",
+    /// Invalid argument
+    InvalidArgument => "INVALID_ARGUMENT",
+    /// Invalid request body
+    InvalidRequestBody => "INVALID_REQUEST_BODY",
+    /// Missing required parameter
+    MissingRequiredParam => "MISSING_REQUIRED_PARAM",
+    /// Bad request
+    BadRequest => "BAD_REQUEST",
+    /// Invalid `Content-Type` header
+    InvalidContentType => "INVALID_CONTENT_TYPE",
+    /// Invalid `Accept` header
+    NotAcceptable => "NOT_ACCEPTABLE",
+    /// Request method not allowed
+    MethodNotAllowed => "METHOD_NOT_ALLOWED",
+    /// Request URL not found
+    NotFound => "NOT_FOUND",
+    /// Invalid credentials provided
+    InvalidCredentials => "INVALID_CREDENTIALS",
+    /// Invalid API key
+    InvalidKey => "INVALID_KEY",
+    /// Request IP not in whitelist
+    IpForbidden => "IP_FORBIDDEN",
+    /// API key is read-only
+    ReadOnly => "READ_ONLY",
+    /// Invalid signature
+    InvalidSignature => "INVALID_SIGNATURE",
+    /// Missing required authentication header
+    MissingRequiredHeader => "MISSING_REQUIRED_HEADER",
+    /// Request timestamp too far from server time
+    RequestExpired => "REQUEST_EXPIRED",
+    /// Account locked by the administrator
+    AccountLocked => "ACCOUNT_LOCKED",
+    /// No permission for the operation
+    Forbidden => "FORBIDDEN",
+    /// Invalid precision
+    InvalidPrecision => "INVALID_PRECISION",
+    /// Invalid currency
+    InvalidCurrency => "INVALID_CURRENCY",
+    /// Invalid currency pair
+    InvalidCurrencyPair => "INVALID_CURRENCY_PAIR",
+    /// POC order would be filled immediately
+    PocFillImmediately => "POC_FILL_IMMEDIATELY",
+    /// Order not found
+    OrderNotFound => "ORDER_NOT_FOUND",
+    /// Order already closed
+    OrderClosed => "ORDER_CLOSED",
+    /// Order already cancelled
+    OrderCancelled => "ORDER_CANCELLED",
+    /// Amount is not enough
+    QuantityNotEnough => "QUANTITY_NOT_ENOUGH",
+    /// Balance is not enough
+    BalanceNotEnough => "BALANCE_NOT_ENOUGH",
+    /// Margin trading not supported for the pair
+    MarginNotSupported => "MARGIN_NOT_SUPPORTED",
+    /// Margin balance is not enough
+    MarginBalanceNotEnough => "MARGIN_BALANCE_NOT_ENOUGH",
+    /// Amount below the minimum
+    AmountTooLittle => "AMOUNT_TOO_LITTLE",
+    /// Amount above the maximum
+    AmountTooMuch => "AMOUNT_TOO_MUCH",
+    /// Repeated creation
+    RepeatedCreation => "REPEATED_CREATION",
+    /// Too many currency pairs in a batch request
+    TooManyCurrencyPairs => "TOO_MANY_CURRENCY_PAIRS",
+    /// Too many orders in a batch request
+    TooManyOrders => "TOO_MANY_ORDERS",
+    /// More than one account type used in a batch request
+    MixedAccountType => "MIXED_ACCOUNT_TYPE",
+    /// Auto borrow exceeds the limit
+    AutoBorrowTooMuch => "AUTO_BORROW_TOO_MUCH",
+    /// Trading restricted for the account
+    TradeRestricted => "TRADE_RESTRICTED",
+    /// FOK order could not be filled completely
+    FokNotFill => "FOK_NOT_FILL",
+    /// Order book not found
+    OrderBookNotFound => "ORDER_BOOK_NOT_FOUND",
+    /// Order cancellation failed
+    CancelFail => "CANCEL_FAIL",
+    /// Amendment did not change the order
+    NoChange => "NO_CHANGE",
+    /// Too many requests, rate limited
+    TooManyRequests => "TOO_MANY_REQUESTS",
+    /// Internal server error
+    Internal => "INTERNAL",
+    /// Internal server error
+    ServerError => "SERVER_ERROR",
+    /// Server is too busy
+    TooBusy => "TOO_BUSY",
+}
+
+impl GateErrorLabel {
+    /// Whether the label signals a transient condition worth retrying
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            GateErrorLabel::TooManyRequests
+                | GateErrorLabel::Internal
+                | GateErrorLabel::ServerError
+                | GateErrorLabel::TooBusy
+        )
+    }
+
+    /// Whether the label signals an authentication or permission failure
+    pub fn is_auth(&self) -> bool {
+        matches!(
+            self,
+            GateErrorLabel::InvalidCredentials
+                | GateErrorLabel::InvalidKey
+                | GateErrorLabel::IpForbidden
+                | GateErrorLabel::ReadOnly
+                | GateErrorLabel::InvalidSignature
+                | GateErrorLabel::MissingRequiredHeader
+                | GateErrorLabel::RequestExpired
+                | GateErrorLabel::AccountLocked
+                | GateErrorLabel::Forbidden
+        )
+    }
+}
+
+impl From<String> for GateErrorLabel {
+    fn from(label: String) -> Self {
+        GateErrorLabel::from(label.as_str())
+    }
+}
+
+impl std::fmt::Display for GateErrorLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(status: u16, body: &str) -> GateError {
+        let response = ClientError::from_body(status, body.to_owned(), HashMap::new());
+        if 500 <= status {
+            GateError::Server(response)
+        } else {
+            GateError::Client(response)
+        }
+    }
+
+    #[test]
+    fn server_error_labels_are_parsed() {
+        let err = error(503, r#"{"label":"TOO_BUSY","message":"busy"}"#);
+        assert_eq!(err.label(), Some(&GateErrorLabel::TooBusy));
+        assert_eq!(err.status_code(), Some(503));
+        assert!(err.is_retryable());
+        assert_eq!(err.to_string(), "Server error 503: TOO_BUSY busy");
+    }
+
+    #[test]
+    fn raw_server_error_keeps_body() {
+        let err = error(502, "Bad Gateway");
+        assert_eq!(err.label(), None);
+        assert_eq!(err.body(), Some("Bad Gateway"));
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn client_error_retryable_by_label_only() {
+        assert!(error(400, r#"{"label":"TOO_MANY_REQUESTS"}"#).is_retryable());
+        assert!(error(429, "").is_retryable());
+        let err = error(400, r#"{"label":"BALANCE_NOT_ENOUGH","message":""}"#);
+        assert!(!err.is_retryable());
+        assert_eq!(err.label(), Some(&GateErrorLabel::BalanceNotEnough));
+    }
+}
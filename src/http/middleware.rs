@@ -0,0 +1,534 @@
+//! Request/response middleware.
+//!
+//! Middleware registered on a client runs around every attempt of every
+//! request: [`Middleware::before_send`] in registration order once the
+//! [`Request`] is built and before it is signed, [`Middleware::after_receive`]
+//! in reverse order once the response head (or the transport error) is back.
+//!
+//! A middleware can:
+//!
+//! - add headers or change the query and payload of the request (it is signed
+//!   afterwards)
+//! - short-circuit the request with a [`CannedResponse`], in which case no
+//!   network call is made and the later middleware is skipped
+//! - observe the status, headers and latency of each attempt, and the
+//!   [`GateError`] of error responses, whose bodies are buffered for it
+//! - ask for another attempt with [`Next::Retry`]; the client waits, signs the
+//!   request again and sends it
+//!
+//! [`Middleware`] is blocking-free and works with both clients;
+//! [`AsyncMiddleware`] can await in its hooks and is accepted by the hyper
+//! client only. [`LoggingLayer`] and [`RetryLayer`] are provided.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::http::middleware::{LoggingLayer, Middleware, Outgoing, RetryLayer};
+//! use gateio_rs::http::middleware::CannedResponse;
+//! use gateio_rs::ureq::GateHttpClient;
+//! use gateio_rs::api::spot::get_server_time;
+//!
+//! /// Tags every request and answers the server time locally
+//! struct Local;
+//!
+//! impl Middleware for Local {
+//!     fn before_send(&self, request: &mut Outgoing) -> Option<CannedResponse> {
+//!         request.set_header("X-Desk", "arb");
+//!         (request.path() == "/api/v4/spot/time")
+//!             .then(|| CannedResponse::json(r#"{"server_time":1700000000000}"#))
+//!     }
+//! }
+//!
+//! let client = GateHttpClient::default()
+//!     .middleware(LoggingLayer::new(|line| println!("{}", line)))
+//!     .middleware(RetryLayer::new(3))
+//!     .middleware(Local);
+//! let body = client.send(get_server_time())?.into_body_str()?;
+//! assert_eq!(body, r#"{"server_time":1700000000000}"#);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::error::ClientError;
+use crate::http::{GateError, Method, request::Request};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Request about to be sent, as seen by middleware
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Outgoing {
+    request: Request,
+    /// Extra headers sent with the request
+    pub headers: Vec<(String, String)>,
+    /// Attempt number, 0 for the first one
+    pub attempt: u32,
+}
+
+impl Outgoing {
+    pub(crate) fn new(request: Request, attempt: u32) -> Self {
+        Self {
+            request,
+            headers: Vec::new(),
+            attempt,
+        }
+    }
+
+    /// Request as it will be signed and sent
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// HTTP method
+    pub fn method(&self) -> &Method {
+        &self.request.method
+    }
+
+    /// API path, e.g. `/api/v4/spot/orders`
+    pub fn path(&self) -> &str {
+        &self.request.path
+    }
+
+    /// Query parameters
+    pub fn params(&self) -> &[(String, String)] {
+        &self.request.params
+    }
+
+    /// JSON payload
+    pub fn payload(&self) -> &str {
+        &self.request.payload
+    }
+
+    /// Whether the request will be signed
+    pub fn signed(&self) -> bool {
+        self.request.sign
+    }
+
+    /// Value of an extra header, looked up case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Sets an extra header, replacing a previous value
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_owned(), value.to_owned()));
+    }
+
+    /// Sets a query parameter, replacing a previous value
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        self.request.params.retain(|(k, _)| k != name);
+        self.request
+            .params
+            .push((name.to_owned(), value.to_owned()));
+    }
+
+    /// Replaces the JSON payload
+    pub fn set_payload(&mut self, payload: &str) {
+        self.request.payload = payload.to_owned();
+    }
+
+    pub(crate) fn into_parts(self) -> (Request, Vec<(String, String)>) {
+        (self.request, self.headers)
+    }
+}
+
+/// Response returned by middleware instead of calling the API
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CannedResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers
+    pub headers: Vec<(String, String)>,
+    /// Response body
+    pub body: String,
+}
+
+impl CannedResponse {
+    /// Creates a response with the given status and body
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.into(),
+        }
+    }
+
+    /// Creates a `200 OK` response with a JSON body
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new(200, body)
+    }
+
+    /// Adds a response header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub(crate) fn to_http<B>(&self, body: B) -> Result<http::Response<B>, http::Error> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder.body(body)
+    }
+}
+
+/// Outcome of an attempt, as seen by middleware
+#[derive(Debug)]
+pub struct Received<'a> {
+    /// HTTP status code, `None` when no response came back
+    pub status: Option<u16>,
+    /// Response headers, with lowercase names
+    pub headers: &'a HashMap<String, String>,
+    /// Error of an error response, or the transport error
+    pub error: Option<&'a GateError>,
+    /// Time from sending the request to receiving the response head
+    pub latency: Duration,
+    /// Whether the response was canned by a middleware
+    pub canned: bool,
+}
+
+impl Received<'_> {
+    /// Value of a response header
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// What to do once an attempt completed
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Next {
+    /// Hand the response, or the error, to the caller
+    Continue,
+    /// Send the request again after the given delay
+    Retry(Duration),
+}
+
+/// Hooks run by the clients around every attempt of every request
+pub trait Middleware: Send + Sync {
+    /// Inspects or changes the request, or answers it without calling the API
+    fn before_send(&self, request: &mut Outgoing) -> Option<CannedResponse> {
+        let _ = request;
+        None
+    }
+
+    /// Observes the outcome of an attempt and decides whether to retry
+    fn after_receive(&self, request: &Outgoing, received: &Received<'_>) -> Next {
+        let _ = (request, received);
+        Next::Continue
+    }
+}
+
+/// Hooks that can await, run by the hyper client
+///
+/// Every [`Middleware`] is an `AsyncMiddleware`.
+#[async_trait]
+pub trait AsyncMiddleware: Send + Sync {
+    /// Inspects or changes the request, or answers it without calling the API
+    async fn before_send(&self, request: &mut Outgoing) -> Option<CannedResponse>;
+
+    /// Observes the outcome of an attempt and decides whether to retry
+    async fn after_receive(&self, request: &Outgoing, received: &Received<'_>) -> Next;
+}
+
+#[async_trait]
+impl<M: Middleware> AsyncMiddleware for M {
+    async fn before_send(&self, request: &mut Outgoing) -> Option<CannedResponse> {
+        Middleware::before_send(self, request)
+    }
+
+    async fn after_receive(&self, request: &Outgoing, received: &Received<'_>) -> Next {
+        Middleware::after_receive(self, request, received)
+    }
+}
+
+/// Writes a line per attempt: method, path, status or error, latency
+///
+/// ```text
+/// GET /api/v4/spot/tickers?currency_pair=BTC_USDT -> 200 in 41ms
+/// POST /api/v4/spot/orders -> 429 TOO_MANY_REQUESTS in 12ms (attempt 2)
+/// ```
+#[derive(Clone)]
+pub struct LoggingLayer {
+    sink: Arc<dyn Fn(&str) + Send + Sync>,
+}
+
+impl LoggingLayer {
+    /// Logs with `sink`
+    pub fn new(sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Logs to standard error
+    pub fn stderr() -> Self {
+        Self::new(|line| eprintln!("{}", line))
+    }
+}
+
+impl Middleware for LoggingLayer {
+    fn after_receive(&self, request: &Outgoing, received: &Received<'_>) -> Next {
+        let mut line = format!("{} {}", request.method().as_ref(), request.path());
+        let query = request.request().query_string();
+        if !query.is_empty() {
+            line.push('?');
+            line.push_str(&query);
+        }
+        line.push_str(" -> ");
+        match (received.status, received.error) {
+            (Some(status), error) => {
+                line.push_str(&status.to_string());
+                if let Some(label) = error.and_then(|e| e.label()) {
+                    line.push(' ');
+                    line.push_str(label.as_str());
+                }
+            }
+            (None, Some(error)) => line.push_str(&error.to_string()),
+            (None, None) => line.push_str("no response"),
+        }
+        line.push_str(&format!(" in {}ms", received.latency.as_millis()));
+        if received.canned {
+            line.push_str(" (canned)");
+        }
+        if request.attempt > 0 {
+            line.push_str(&format!(" (attempt {})", request.attempt + 1));
+        }
+        (self.sink)(&line);
+        Next::Continue
+    }
+}
+
+/// Retries transient failures with exponential backoff
+///
+/// Timeouts, transport errors, 5xx responses, 429s and retryable Gate labels
+/// (see [`GateError::is_retryable`]) are retried. Requests that may have
+/// reached the exchange are only retried when idempotent (`GET`, `PUT` and
+/// `DELETE`) unless [`RetryLayer::retry_all_methods`] is set; a 429 is retried
+/// for every method since Gate did not process the request.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RetryLayer {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each retry
+    pub base_delay: Duration,
+    /// Upper bound of the delay
+    pub max_delay: Duration,
+    /// Whether `POST` and `PATCH` requests are retried as well
+    pub all_methods: bool,
+}
+
+impl RetryLayer {
+    /// Retries up to `max_retries` times, from 200ms up to 5s apart
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            all_methods: false,
+        }
+    }
+
+    /// Sets the delay before the first retry
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the upper bound of the delay
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Retries `POST` and `PATCH` requests too, at the risk of duplicates
+    pub fn retry_all_methods(mut self) -> Self {
+        self.all_methods = true;
+        self
+    }
+
+    /// Delay before retry number `attempt` (0 for the first retry)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.min(16));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Middleware for RetryLayer {
+    fn after_receive(&self, request: &Outgoing, received: &Received<'_>) -> Next {
+        let Some(error) = received.error else {
+            return Next::Continue;
+        };
+        if received.canned || request.attempt >= self.max_retries || !error.is_retryable() {
+            return Next::Continue;
+        }
+        let idempotent = matches!(request.method(), Method::Get | Method::Put | Method::Delete);
+        if !(idempotent || self.all_methods || received.status == Some(429)) {
+            return Next::Continue;
+        }
+        Next::Retry(self.delay(request.attempt))
+    }
+}
+
+/// Builds the error handed to middleware for an error response
+pub(crate) fn response_error(
+    status: u16,
+    content: String,
+    headers: HashMap<String, String>,
+) -> GateError {
+    if 500 <= status {
+        GateError::Server(ClientError::from_body(status, content, headers))
+    } else {
+        GateError::Client(ClientError::from_body(status, content, headers))
+    }
+}
+
+/// Combines the decisions of the middleware, retrying after the longest delay asked
+pub(crate) fn combine(decisions: impl IntoIterator<Item = Next>) -> Next {
+    decisions
+        .into_iter()
+        .fold(Next::Continue, |next, decision| match (next, decision) {
+            (Next::Retry(a), Next::Retry(b)) => Next::Retry(a.max(b)),
+            (Next::Retry(a), Next::Continue) | (Next::Continue, Next::Retry(a)) => Next::Retry(a),
+            (Next::Continue, Next::Continue) => Next::Continue,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::request::RequestBuilder;
+
+    fn outgoing(method: Method, attempt: u32) -> Outgoing {
+        Outgoing::new(
+            RequestBuilder::new(method, "/api/v4/spot/orders").into(),
+            attempt,
+        )
+    }
+
+    fn decide(retry: &RetryLayer, request: &Outgoing, error: Option<&GateError>) -> Next {
+        let headers = HashMap::new();
+        let received = Received {
+            status: error.and_then(GateError::status_code),
+            headers: &headers,
+            error,
+            latency: Duration::ZERO,
+            canned: false,
+        };
+        Middleware::after_receive(retry, request, &received)
+    }
+
+    fn error(status: u16, body: &str) -> GateError {
+        response_error(status, body.to_owned(), HashMap::new())
+    }
+
+    #[test]
+    fn retries_transient_failures_of_idempotent_requests() {
+        let retry = RetryLayer::new(3);
+        let get = outgoing(Method::Get, 0);
+        let first = Next::Retry(Duration::from_millis(200));
+        assert_eq!(decide(&retry, &get, Some(&GateError::Timeout)), first);
+        assert_eq!(decide(&retry, &get, Some(&error(503, ""))), first);
+        assert_eq!(
+            decide(&retry, &get, Some(&error(400, r#"{"label":"TOO_BUSY"}"#))),
+            first
+        );
+        assert_eq!(
+            decide(&retry, &outgoing(Method::Delete, 1), Some(&error(502, ""))),
+            Next::Retry(Duration::from_millis(400))
+        );
+    }
+
+    #[test]
+    fn does_not_retry_successes_or_permanent_errors() {
+        let retry = RetryLayer::new(3);
+        let get = outgoing(Method::Get, 0);
+        assert_eq!(decide(&retry, &get, None), Next::Continue);
+        let refused = error(400, r#"{"label":"INVALID_PARAM_VALUE"}"#);
+        assert_eq!(decide(&retry, &get, Some(&refused)), Next::Continue);
+        assert_eq!(decide(&retry, &get, Some(&error(401, ""))), Next::Continue);
+    }
+
+    #[test]
+    fn retries_non_idempotent_requests_only_on_429_or_when_allowed() {
+        let retry = RetryLayer::new(3);
+        let post = outgoing(Method::Post, 0);
+        assert_eq!(
+            decide(&retry, &post, Some(&GateError::Timeout)),
+            Next::Continue
+        );
+        assert_eq!(decide(&retry, &post, Some(&error(500, ""))), Next::Continue);
+        assert_eq!(
+            decide(&retry, &post, Some(&error(429, ""))),
+            Next::Retry(Duration::from_millis(200))
+        );
+
+        let retry = retry.retry_all_methods();
+        assert_eq!(
+            decide(&retry, &post, Some(&GateError::Timeout)),
+            Next::Retry(Duration::from_millis(200))
+        );
+    }
+
+    #[test]
+    fn stops_after_max_retries_and_skips_canned_responses() {
+        let retry = RetryLayer::new(2);
+        assert_eq!(
+            decide(&retry, &outgoing(Method::Get, 2), Some(&GateError::Timeout)),
+            Next::Continue
+        );
+
+        let headers = HashMap::new();
+        let failure = error(503, "");
+        let canned = Received {
+            status: Some(503),
+            headers: &headers,
+            error: Some(&failure),
+            latency: Duration::ZERO,
+            canned: true,
+        };
+        assert_eq!(
+            Middleware::after_receive(&retry, &outgoing(Method::Get, 0), &canned),
+            Next::Continue
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let retry = RetryLayer::new(10)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(700));
+        let delays: Vec<u128> = (0..5).map(|n| retry.delay(n).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 700, 700]);
+        assert_eq!(retry.delay(u32::MAX), Duration::from_millis(700));
+    }
+
+    #[test]
+    fn combine_retries_after_the_longest_delay() {
+        let ms = Duration::from_millis;
+        assert_eq!(combine([]), Next::Continue);
+        assert_eq!(combine([Next::Continue, Next::Continue]), Next::Continue);
+        assert_eq!(
+            combine([Next::Continue, Next::Retry(ms(5))]),
+            Next::Retry(ms(5))
+        );
+        assert_eq!(
+            combine([Next::Retry(ms(5)), Next::Continue, Next::Retry(ms(3))]),
+            Next::Retry(ms(5))
+        );
+    }
+
+    #[test]
+    fn response_errors_are_classified_by_status() {
+        assert!(matches!(error(502, ""), GateError::Server(_)));
+        let client = error(400, r#"{"label":"BALANCE_NOT_ENOUGH","message":"no"}"#);
+        assert!(matches!(client, GateError::Client(_)));
+        assert_eq!(client.status_code(), Some(400));
+    }
+}
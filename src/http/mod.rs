@@ -1,10 +1,21 @@
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+mod call;
+mod config;
 mod credentials;
 mod method;
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+pub(crate) mod trace;
 
 /// HTTP error types
 pub mod error;
+/// Request/response middleware
+pub mod middleware;
 /// HTTP request structures
 pub mod request;
 
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+pub use call::Call;
+pub use config::{ClientConfig, DEFAULT_MAX_BODY_SIZE};
 pub use credentials::Credentials;
+pub use error::{GateError, GateErrorLabel};
 pub use method::Method;
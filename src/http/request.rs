@@ -1,7 +1,8 @@
-use crate::http::{Credentials, Method};
+use crate::http::{Credentials, GateError, Method};
+use std::time::Duration;
 
 /// HTTP request representation for Gate.io API calls
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Request {
     pub(crate) method: Method,
     pub(crate) path: String,
@@ -10,6 +11,7 @@ pub struct Request {
     pub(crate) x_gate_exp_time: Option<u128>,
     pub(crate) credentials: Option<Credentials>,
     pub(crate) sign: bool,
+    pub(crate) timeout: Option<Duration>,
 }
 
 impl Request {
@@ -41,6 +43,68 @@ impl Request {
     pub fn sign(&self) -> &bool {
         &self.sign
     }
+    /// Returns the per-request timeout override if set
+    pub fn timeout(&self) -> &Option<Duration> {
+        &self.timeout
+    }
+
+    /// Returns the query string exactly as it is sent and signed.
+    ///
+    /// Keys and values are percent-encoded per RFC 3986, leaving only
+    /// unreserved characters (`A-Z a-z 0-9 - . _ ~`) as is.
+    ///
+    /// ```rust
+    /// use gateio_rs::http::{Method, request::{Request, RequestBuilder}};
+    ///
+    /// let request: Request = RequestBuilder::new(Method::Get, "/api/v4/spot/orders")
+    ///     .params([("currency_pair", "BTC_USDT,ETH_USDT"), ("text", "t-a b&c")])
+    ///     .into();
+    /// assert_eq!(
+    ///     request.query_string(),
+    ///     "currency_pair=BTC_USDT%2CETH_USDT&text=t-a%20b%26c"
+    /// );
+    /// ```
+    pub fn query_string(&self) -> String {
+        crate::utils::encode_query(&self.params)
+    }
+
+    /// Computes the `SIGN` header Gate expects for this request at `timestamp`.
+    ///
+    /// The signature covers the encoded [`query_string`](Request::query_string),
+    /// so it matches what Gate computes from the URL it receives.
+    ///
+    /// ```rust
+    /// use gateio_rs::http::{Method, request::{Request, RequestBuilder}};
+    ///
+    /// let request: Request = RequestBuilder::new(Method::Get, "/api/v4/spot/orders")
+    ///     .params([
+    ///         ("currency_pair", "BTC_USDT,ETH_USDT"),
+    ///         ("text", "t-a b&c=d/é"),
+    ///         ("from", "1700000000"),
+    ///     ])
+    ///     .into();
+    /// assert_eq!(
+    ///     request.query_string(),
+    ///     "currency_pair=BTC_USDT%2CETH_USDT&text=t-a%20b%26c%3Dd%2F%C3%A9&from=1700000000"
+    /// );
+    /// assert_eq!(
+    ///     request.signature("secret", "1700000000")?,
+    ///     "f3112d08432ac5e7f2b7604e8dc92b39fed1b1638bb4d0404dcaab426945c8f2\
+    ///      96e196aed32b1b438da0a7fd9ad8b0e687baf55bcc132b64ad70ab7dd3614276"
+    /// );
+    /// # Ok::<(), gateio_rs::http::GateError>(())
+    /// ```
+    pub fn signature(&self, api_secret: &str, timestamp: &str) -> Result<String, GateError> {
+        crate::utils::sign_hmac(
+            self.method.as_ref(),
+            &self.path,
+            &self.query_string(),
+            &self.payload,
+            timestamp,
+            api_secret,
+        )
+        .map_err(|_| GateError::InvalidApiSecret)
+    }
 }
 
 /// API HTTP Request
@@ -55,6 +119,7 @@ pub struct RequestBuilder {
     credentials: Option<Credentials>,
     x_gate_exp_time: Option<u128>,
     sign: bool,
+    timeout: Option<Duration>,
 }
 
 impl RequestBuilder {
@@ -68,6 +133,7 @@ impl RequestBuilder {
             x_gate_exp_time: None,
             credentials: None,
             sign: false,
+            timeout: None,
         }
     }
 
@@ -105,6 +171,12 @@ impl RequestBuilder {
         self.sign = true;
         self
     }
+
+    /// Overrides the client timeout for this request only
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 impl From<RequestBuilder> for Request {
@@ -117,6 +189,7 @@ impl From<RequestBuilder> for Request {
             x_gate_exp_time: builder.x_gate_exp_time,
             credentials: builder.credentials,
             sign: builder.sign,
+            timeout: builder.timeout,
         }
     }
 }
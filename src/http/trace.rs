@@ -0,0 +1,298 @@
+//! `tracing` instrumentation of the clients (`enable-tracing` feature).
+//!
+//! Each request gets an `INFO` span named `gate_request` covering every
+//! attempt, with the method, path, endpoint and endpoint class, query string
+//! and payload when it starts, and the status, Gate label, latency, number of
+//! retries and rate-limit headers once it completes. Payload values are
+//! redacted unless their field was revealed to the client, and the `KEY` and
+//! `SIGN` headers are only ever recorded redacted.
+//!
+//! Without the feature, [`Trace`] does nothing and compiles away.
+
+use crate::http::middleware::{Outgoing, Received};
+use crate::http::request::Request;
+#[cfg(feature = "enable-hyper")]
+use std::future::Future;
+
+#[cfg(feature = "enable-tracing")]
+const REDACTED: &str = "[REDACTED]";
+
+/// Span of a request, from the first attempt to the response handed back
+pub(crate) struct Trace {
+    #[cfg(feature = "enable-tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "enable-tracing")]
+    started: std::time::Instant,
+}
+
+impl Trace {
+    /// Whether requests are traced at all
+    pub(crate) const ENABLED: bool = cfg!(feature = "enable-tracing");
+
+    /// Opens the span of `request`, payload values redacted except `reveal`
+    #[cfg_attr(not(feature = "enable-tracing"), allow(unused_variables))]
+    pub(crate) fn start(request: &Request, reveal: &[String]) -> Self {
+        Self {
+            #[cfg(feature = "enable-tracing")]
+            span: tracing::info_span!(
+                "gate_request",
+                method = request.method().as_ref(),
+                path = request.path(),
+                endpoint = %crate::utils::endpoint(request.path()),
+                class = crate::utils::endpoint_class(request.path()),
+                query = %request.query_string(),
+                payload = %redact_payload(request.payload(), reveal),
+                signed = request.sign,
+                headers = tracing::field::Empty,
+                status = tracing::field::Empty,
+                label = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                retries = tracing::field::Empty,
+                ratelimit_remain = tracing::field::Empty,
+                ratelimit_limit = tracing::field::Empty,
+                ratelimit_reset = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+            #[cfg(feature = "enable-tracing")]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    /// Runs `f` inside the span
+    #[cfg(feature = "enable-ureq")]
+    pub(crate) fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "enable-tracing")]
+        return self.span.in_scope(f);
+        #[cfg(not(feature = "enable-tracing"))]
+        f()
+    }
+
+    /// Runs `future` inside the span
+    #[cfg(feature = "enable-hyper")]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        #[cfg(feature = "enable-tracing")]
+        return tracing::Instrument::instrument(future, self.span.clone());
+        #[cfg(not(feature = "enable-tracing"))]
+        future
+    }
+
+    /// Records the outcome of the last attempt
+    #[cfg_attr(not(feature = "enable-tracing"), allow(unused_variables))]
+    pub(crate) fn finish(&self, outgoing: &Outgoing, received: &Received<'_>) {
+        #[cfg(feature = "enable-tracing")]
+        {
+            let span = &self.span;
+            span.record("headers", redact_headers(outgoing).as_str());
+            if let Some(status) = received.status {
+                span.record("status", status);
+            }
+            if let Some(label) = received.error.and_then(|err| err.label()) {
+                span.record("label", label.as_str());
+            }
+            span.record("latency_ms", self.started.elapsed().as_millis() as u64);
+            span.record("retries", outgoing.attempt);
+            for (field, header) in [
+                ("ratelimit_remain", "x-gate-ratelimit-requests-remain"),
+                ("ratelimit_limit", "x-gate-ratelimit-limit"),
+                ("ratelimit_reset", "x-gate-ratelimit-reset-timestamp"),
+            ] {
+                if let Some(value) = received.header(header) {
+                    span.record(field, value);
+                }
+            }
+            match received.error {
+                Some(error) => {
+                    span.record("error", tracing::field::display(error));
+                    tracing::warn!(parent: span, "gate request failed");
+                }
+                None => tracing::debug!(parent: span, "gate request completed"),
+            }
+        }
+    }
+}
+
+/// Replaces the values of a JSON payload by a placeholder, except `reveal`
+#[cfg(feature = "enable-tracing")]
+fn redact_payload(payload: &str, reveal: &[String]) -> String {
+    use serde_json::Value;
+
+    fn redact(value: &mut Value, reveal: &[String]) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if field.is_object() || field.is_array() {
+                        redact(field, reveal);
+                    } else if !reveal.contains(key) {
+                        *field = Value::String(REDACTED.to_owned());
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| redact(item, reveal)),
+            _ => *value = Value::String(REDACTED.to_owned()),
+        }
+    }
+
+    if payload.is_empty() {
+        return String::new();
+    }
+    match serde_json::from_str::<Value>(payload) {
+        Ok(mut value) => {
+            redact(&mut value, reveal);
+            value.to_string()
+        }
+        Err(_) => REDACTED.to_owned(),
+    }
+}
+
+/// Lists the headers added to the request, credentials redacted
+#[cfg(feature = "enable-tracing")]
+fn redact_headers(outgoing: &Outgoing) -> String {
+    let secret = |name: &str| ["KEY", "SIGN"].iter().any(|s| s.eq_ignore_ascii_case(name));
+    let mut headers: Vec<String> = outgoing
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if secret(name) { REDACTED } else { value };
+            format!("{}={}", name, value)
+        })
+        .collect();
+    if outgoing.signed() {
+        headers.push(format!("KEY={}", REDACTED));
+        headers.push(format!("SIGN={}", REDACTED));
+    }
+    headers.join(" ")
+}
+
+#[cfg(all(test, feature = "enable-tracing"))]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use crate::http::request::RequestBuilder;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Subscriber keeping the last value recorded for every span field
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Capture {
+        fn field(&self, name: &str) -> Option<String> {
+            self.0.lock().unwrap().get(name).cloned()
+        }
+    }
+
+    impl Visit for Capture {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            let mut fields = self.0.lock().unwrap();
+            fields.insert(field.name().to_owned(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let mut fields = self.0.lock().unwrap();
+            fields.insert(field.name().to_owned(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn signed_order() -> Request {
+        RequestBuilder::new(Method::Post, "/api/v4/spot/orders")
+            .payload(r#"{"text":"t-1","amount":"0.5","price":"30000"}"#)
+            .sign()
+            .into()
+    }
+
+    #[test]
+    fn redacts_payload_values_except_revealed_fields() {
+        let reveal = vec!["currency_pair".to_owned()];
+        let payload =
+            r#"{"currency_pair":"BTC_USDT","amount":"1","orders":[{"price":"2"}],"ids":[3]}"#;
+        let redacted: serde_json::Value =
+            serde_json::from_str(&redact_payload(payload, &reveal)).unwrap();
+        assert_eq!(
+            redacted,
+            serde_json::json!({
+                "currency_pair": "BTC_USDT",
+                "amount": REDACTED,
+                "orders": [{"price": REDACTED}],
+                "ids": [REDACTED],
+            })
+        );
+        assert_eq!(redact_payload("", &reveal), "");
+        assert_eq!(redact_payload("amount=1", &reveal), REDACTED);
+    }
+
+    #[test]
+    fn redacts_credential_headers() {
+        let mut outgoing = Outgoing::new(signed_order(), 0);
+        outgoing.set_header("X-Client", "bot");
+        outgoing.set_header("sign", "deadbeef");
+        let headers = redact_headers(&outgoing);
+        assert!(headers.contains("X-Client=bot"));
+        assert!(!headers.contains("deadbeef"));
+        assert!(headers.contains("KEY=[REDACTED]"));
+        assert!(headers.contains("SIGN=[REDACTED]"));
+
+        let unsigned = RequestBuilder::new(Method::Get, "/api/v4/spot/tickers").into();
+        assert_eq!(redact_headers(&Outgoing::new(unsigned, 0)), "");
+    }
+
+    #[test]
+    fn spans_never_carry_secrets() {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), || {
+            let request = signed_order();
+            let trace = Trace::start(&request, &["text".to_owned()]);
+            let mut outgoing = Outgoing::new(request, 1);
+            outgoing.set_header("KEY", "api-key");
+            let headers = HashMap::from([(
+                "x-gate-ratelimit-requests-remain".to_owned(),
+                "7".to_owned(),
+            )]);
+            let received = Received {
+                status: Some(201),
+                headers: &headers,
+                error: None,
+                latency: std::time::Duration::ZERO,
+                canned: false,
+            };
+            trace.finish(&outgoing, &received);
+        });
+
+        let payload = capture.field("payload").unwrap();
+        assert!(payload.contains(r#""text":"t-1""#));
+        assert!(!payload.contains("0.5") && !payload.contains("30000"));
+        let headers = capture.field("headers").unwrap();
+        assert!(!headers.contains("api-key"));
+        assert!(headers.contains("SIGN=[REDACTED]"));
+        assert_eq!(capture.field("status").as_deref(), Some("201"));
+        assert_eq!(capture.field("retries").as_deref(), Some("1"));
+        assert_eq!(capture.field("ratelimit_remain").as_deref(), Some("7"));
+        assert_eq!(capture.field("method").as_deref(), Some("POST"));
+    }
+}
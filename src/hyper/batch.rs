@@ -0,0 +1,36 @@
+use crate::batch::{Batch, BatchLimits, BatchResult, BatchResults, split};
+use crate::hyper::GateHttpClient;
+use futures_util::stream::{self, StreamExt};
+
+impl GateHttpClient {
+    /// Sends a batch of any size, split to fit the endpoint's limits.
+    ///
+    /// The requests are sent in parallel; one result is returned per item,
+    /// in the order of the items in `batch`.
+    pub async fn send_batch<B: Batch>(&self, batch: B) -> Vec<BatchResult<B::Output>> {
+        self.send_batch_with(batch, B::limits()).await
+    }
+
+    /// Sends a batch of any size, split to fit `limits`
+    pub async fn send_batch_with<B: Batch>(
+        &self,
+        batch: B,
+        limits: BatchLimits,
+    ) -> Vec<BatchResult<B::Output>> {
+        let chunks = split(batch, &limits);
+        let mut results = BatchResults::new(chunks.iter().map(|(indices, _)| indices.len()).sum());
+        let mut responses = stream::iter(chunks)
+            .map(|(indices, chunk)| async move {
+                let response = match self.send(chunk).await {
+                    Ok(response) => response.into_body_str().await,
+                    Err(err) => Err(err),
+                };
+                (indices, response)
+            })
+            .buffer_unordered(limits.concurrency);
+        while let Some((indices, response)) = responses.next().await {
+            results.record(&indices, response);
+        }
+        results.finish()
+    }
+}
@@ -1,12 +1,19 @@
-use crate::http::{Credentials, Method, request::Request};
+use crate::http::middleware::{self, AsyncMiddleware, CannedResponse, Next, Outgoing, Received};
+use crate::http::trace::Trace;
+use crate::http::{
+    Call, ClientConfig, Credentials, DEFAULT_MAX_BODY_SIZE, Method, request::Request,
+};
+use crate::hyper::connector::Connector;
 use crate::hyper::{Error, Response};
 use crate::version::VERSION;
 use bytes::Bytes;
 use http_body_util::Full;
 use hyper::Uri;
 use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::{Client, connect::HttpConnector};
-use std::time::{SystemTime, UNIX_EPOCH};
+use hyper_util::client::legacy::Client;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Asynchronous HTTP client for Gate.io API using hyper.
 ///
@@ -94,34 +101,192 @@ use std::time::{SystemTime, UNIX_EPOCH};
 ///     Ok(())
 /// }
 /// ```
+///
+/// ## Custom Configuration
+///
+/// ```rust
+/// use gateio_rs::{http::ClientConfig, hyper::GateHttpClient};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), gateio_rs::hyper::Error> {
+/// let config = ClientConfig::new()
+///     .connect_timeout(Duration::from_secs(3))
+///     .timeout(Duration::from_secs(10))
+///     .pool_max_idle_per_host(8);
+/// let client = GateHttpClient::with_config("https://api.gateio.ws", &config)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
 pub struct GateHttpClient {
-    client: Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
+    client: Client<HttpsConnector<Connector>, Full<Bytes>>,
     base_url: String,
     credentials: Option<Credentials>,
+    config: ClientConfig,
+    middleware: Vec<Arc<dyn AsyncMiddleware>>,
+    trace_fields: Vec<String>,
 }
 
-impl GateHttpClient {
-    pub fn default() -> Self {
+impl Default for GateHttpClient {
+    /// Creates a new client with default settings and Gate.io production URL
+    fn default() -> Self {
         Self::with_url("https://api.gateio.ws")
     }
+}
 
+impl GateHttpClient {
+    /// Creates a new client with a custom base URL
     pub fn with_url(url: &str) -> Self {
+        let config = ClientConfig::default();
+        let connector = Connector::Direct(Connector::direct(&config));
+        Self::build(url, config, connector)
+    }
+
+    /// Creates a new client with a custom base URL and transport configuration.
+    ///
+    /// On this backend `read_timeout` and `timeout` bound the wait for the
+    /// response headers; the body is read when the response is consumed.
+    pub fn with_config(url: &str, config: &ClientConfig) -> Result<Self, Error> {
+        let connector = Connector::new(config)?;
+        Ok(Self::build(url, config.clone(), connector))
+    }
+
+    fn build(url: &str, config: ClientConfig, connector: Connector) -> Self {
         use hyper_util::rt::TokioExecutor;
 
-        let https = HttpsConnector::new();
+        let https = HttpsConnector::new_with_connector(connector);
+        let mut builder = Client::builder(TokioExecutor::new());
+        if let Some(pool_idle_timeout) = config.pool_idle_timeout {
+            builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = config.pool_max_idle_per_host {
+            builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+
         Self {
-            client: Client::builder(TokioExecutor::new()).build(https),
+            client: builder.build(https),
             base_url: url.to_string(),
             credentials: None,
+            config,
+            middleware: Vec::new(),
+            trace_fields: Vec::new(),
         }
     }
 
+    /// Sets the default API credentials for all requests
     pub fn credentials(mut self, credentials: Credentials) -> Self {
         self.credentials = Some(credentials);
         self
     }
 
+    /// Registers a middleware, run after those registered before it.
+    ///
+    /// Accepts both [`AsyncMiddleware`] and blocking-free
+    /// [`Middleware`](crate::http::middleware::Middleware) implementations.
+    pub fn middleware(mut self, middleware: impl AsyncMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Reveals payload fields in the `tracing` spans of requests
+    #[cfg(feature = "enable-tracing")]
+    pub fn trace_fields(mut self, fields: &[&str]) -> Self {
+        self.trace_fields = fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    /// Starts a typed call to any endpoint, see [`Call`]
+    pub fn call<T>(&self, method: Method, path: &str) -> Call<'_, Self, T> {
+        Call::new(self, method, path)
+    }
+
+    /// Sends an HTTP request to the Gate.io API
     pub async fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Error> {
+        let request = request.into();
+        if self.middleware.is_empty() && !Trace::ENABLED {
+            return self.execute(request, &[]).await;
+        }
+        let trace = Trace::start(&request, &self.trace_fields);
+        trace.instrument(self.dispatch(request, &trace)).await
+    }
+
+    /// Runs the attempts of a request through the middleware
+    async fn dispatch(&self, request: Request, trace: &Trace) -> Result<Response, Error> {
+        let max_body_size = self.config.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE);
+
+        let mut attempt = 0;
+        loop {
+            let mut outgoing = Outgoing::new(request.clone(), attempt);
+            let mut canned = None;
+            let mut ran = 0;
+            for middleware in &self.middleware {
+                ran += 1;
+                canned = middleware.before_send(&mut outgoing).await;
+                if canned.is_some() {
+                    break;
+                }
+            }
+
+            let started = Instant::now();
+            let result = match &canned {
+                Some(canned) => canned_response(canned),
+                None => {
+                    let (request, headers) = outgoing.clone().into_parts();
+                    self.execute(request, &headers).await
+                }
+            };
+            let latency = started.elapsed();
+
+            // Error bodies are buffered so that middleware sees the Gate error
+            let (result, status, headers, error) = match result {
+                Ok(response) if 400 <= response.status() => {
+                    let status = response.status();
+                    let (parts, body) = response.into_raw().await?;
+                    let headers = crate::utils::header_map(&parts.headers);
+                    let error = middleware::response_error(status, body.clone(), headers.clone());
+                    let response = http::Response::from_parts(parts, Full::new(Bytes::from(body)));
+                    let response = Response::from(response).max_body_size(max_body_size);
+                    (Ok(response), Some(status), headers, Some(error))
+                }
+                Ok(response) => {
+                    let headers = crate::utils::header_map(response.headers());
+                    let status = response.status();
+                    (Ok(response), Some(status), headers, None)
+                }
+                Err(err) => (Err(err), None, HashMap::new(), None),
+            };
+            let received = Received {
+                status,
+                headers: &headers,
+                error: error.as_ref().or(result.as_ref().err()),
+                latency,
+                canned: canned.is_some(),
+            };
+            let mut decisions = Vec::with_capacity(ran);
+            for middleware in self.middleware[..ran].iter().rev() {
+                decisions.push(middleware.after_receive(&outgoing, &received).await);
+            }
+
+            match middleware::combine(decisions) {
+                Next::Retry(delay) => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Next::Continue => {
+                    trace.finish(&outgoing, &received);
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Signs and sends a request with extra headers
+    async fn execute(
+        &self,
+        request: Request,
+        extra_headers: &[(String, String)],
+    ) -> Result<Response, Error> {
         let Request {
             method,
             path,
@@ -130,17 +295,18 @@ impl GateHttpClient {
             x_gate_exp_time,
             credentials,
             sign,
-        } = request.into();
+            timeout,
+        } = request;
 
-        // Map query parameters
-        let query_string = params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect::<Vec<String>>()
-            .join("&");
+        // Percent-encode query parameters; the same string is signed
+        let query_string = crate::utils::encode_query(&params);
 
         // Build URL
-        let full_url = format!("{}{}?{}", self.base_url, path, query_string);
+        let full_url = if query_string.is_empty() {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}{}?{}", self.base_url, path, query_string)
+        };
         let uri: Uri = full_url.parse()?;
 
         // Create request builder
@@ -150,6 +316,10 @@ impl GateHttpClient {
             .header("Accept", "application/json")
             .header("Content-Type", "application/json");
 
+        for (key, value) in extra_headers {
+            req_builder = req_builder.header(key, value);
+        }
+
         // Handle credentials and signing
         let client_credentials = self.credentials.as_ref();
         let request_credentials = credentials.as_ref();
@@ -158,37 +328,36 @@ impl GateHttpClient {
             api_key,
             api_secret,
         }) = request_credentials.or(client_credentials)
+            && sign
         {
-            if sign {
-                // Use system clock
-                let timestamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Clock may have gone backwards")
-                    .as_secs();
-
-                // Set API-Key and Timestamp in header
-                req_builder = req_builder
-                    .header("KEY", api_key)
-                    .header("Timestamp", timestamp.to_string());
-
-                // Set x-gate-exptime header
-                if let Some(exp_time_ms) = x_gate_exp_time {
-                    req_builder = req_builder.header("x-gate-exptime", exp_time_ms.to_string());
-                }
+            // Use system clock
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| Error::Clock)?
+                .as_secs();
 
-                // Generate signature
-                let signature = crate::utils::sign_hmac(
-                    method.as_ref(),
-                    &path.to_string(),
-                    &query_string,
-                    &payload,
-                    &timestamp.to_string(),
-                    api_secret,
-                )
-                .map_err(|_| Error::InvalidApiSecret)?;
-
-                req_builder = req_builder.header("SIGN", signature);
+            // Set API-Key and Timestamp in header
+            req_builder = req_builder
+                .header("KEY", api_key)
+                .header("Timestamp", timestamp.to_string());
+
+            // Set x-gate-exptime header
+            if let Some(exp_time_ms) = x_gate_exp_time {
+                req_builder = req_builder.header("x-gate-exptime", exp_time_ms.to_string());
             }
+
+            // Generate signature
+            let signature = crate::utils::sign_hmac(
+                method.as_ref(),
+                &path.to_string(),
+                &query_string,
+                &payload,
+                &timestamp.to_string(),
+                api_secret,
+            )
+            .map_err(|_| Error::InvalidApiSecret)?;
+
+            req_builder = req_builder.header("SIGN", signature);
         }
 
         // Set method
@@ -207,15 +376,61 @@ impl GateHttpClient {
             Full::new(Bytes::from(payload))
         };
 
-        let request = req_builder.body(body).expect("Failed to build request");
+        let request = req_builder.body(body)?;
+
+        // Send request, bounded by the tightest of the configured timeouts
+        let response = self.client.request(request);
+        let response = match response_timeout(&self.config, timeout) {
+            Some(timeout) => tokio::time::timeout(timeout, response)
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => response.await,
+        }
+        .map_err(|e| Error::Send(Box::new(e)))?;
+
+        Ok(Response::from(response)
+            .max_body_size(self.config.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE)))
+    }
+}
+
+/// Resolves the bound on waiting for a response.
+///
+/// A per-request timeout replaces the configured overall `timeout`, as on the
+/// ureq backend; `read_timeout` still applies, so the shorter of the two wins.
+fn response_timeout(config: &ClientConfig, timeout: Option<Duration>) -> Option<Duration> {
+    match (timeout.or(config.timeout), config.read_timeout) {
+        (Some(timeout), Some(read_timeout)) => Some(timeout.min(read_timeout)),
+        (timeout, read_timeout) => timeout.or(read_timeout),
+    }
+}
+
+fn canned_response(canned: &CannedResponse) -> Result<Response, Error> {
+    let body = Full::new(Bytes::from(canned.body.clone()));
+    Ok(Response::from(canned.to_http(body)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_timeout_takes_the_tightest_bound() {
+        let secs = Duration::from_secs;
+        let config = ClientConfig::new();
+        assert_eq!(response_timeout(&config, None), None);
+        assert_eq!(response_timeout(&config, Some(secs(5))), Some(secs(5)));
+
+        let config = ClientConfig::new().read_timeout(secs(2));
+        assert_eq!(response_timeout(&config, None), Some(secs(2)));
+        assert_eq!(response_timeout(&config, Some(secs(5))), Some(secs(2)));
+        assert_eq!(response_timeout(&config, Some(secs(1))), Some(secs(1)));
 
-        // Send request
-        let response = self
-            .client
-            .request(request)
-            .await
-            .map_err(|e| Error::Send(Box::new(e)))?;
+        let config = ClientConfig::new().timeout(secs(10)).read_timeout(secs(3));
+        assert_eq!(response_timeout(&config, None), Some(secs(3)));
+        assert_eq!(response_timeout(&config, Some(secs(20))), Some(secs(3)));
 
-        Ok(Response::from(response))
+        let config = ClientConfig::new().timeout(secs(10));
+        assert_eq!(response_timeout(&config, None), Some(secs(10)));
+        assert_eq!(response_timeout(&config, Some(secs(20))), Some(secs(20)));
     }
 }
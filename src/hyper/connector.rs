@@ -0,0 +1,128 @@
+use crate::http::ClientConfig;
+use crate::hyper::Error;
+use hyper::Uri;
+use hyper::header::HeaderValue;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::connect::proxy::{SocksV4, SocksV5, Tunnel};
+use hyper_util::rt::TokioIo;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// TCP connector that optionally routes through an HTTP or SOCKS proxy.
+#[derive(Clone)]
+pub(crate) enum Connector {
+    Direct(HttpConnector),
+    Http {
+        proxy: Uri,
+        auth: Option<HeaderValue>,
+        inner: HttpConnector,
+    },
+    Socks4(SocksV4<HttpConnector>),
+    Socks5(SocksV5<HttpConnector>),
+}
+
+impl Connector {
+    /// Builds a direct connector, ignoring any configured proxy
+    pub(crate) fn direct(config: &ClientConfig) -> HttpConnector {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(config.connect_timeout);
+        http.set_keepalive(config.tcp_keepalive);
+        http
+    }
+
+    /// Builds the connector described by `config`
+    pub(crate) fn new(config: &ClientConfig) -> Result<Self, Error> {
+        let http = Self::direct(config);
+        let Some(proxy) = &config.proxy else {
+            return Ok(Connector::Direct(http));
+        };
+
+        let invalid = || Error::InvalidProxy(proxy.clone());
+        let proxy_uri: Uri = proxy.parse().map_err(|_| invalid())?;
+        let credentials = proxy_uri
+            .authority()
+            .and_then(|authority| authority.as_str().rsplit_once('@'))
+            .map(|(userinfo, _)| match userinfo.split_once(':') {
+                Some((user, pass)) => (user.to_owned(), pass.to_owned()),
+                None => (userinfo.to_owned(), String::new()),
+            });
+
+        match proxy_uri.scheme_str() {
+            Some("http") | Some("https") => {
+                let auth = match credentials {
+                    Some((user, pass)) => {
+                        use base64::Engine;
+                        let token = base64::engine::general_purpose::STANDARD
+                            .encode(format!("{}:{}", user, pass));
+                        Some(format!("Basic {}", token).parse().map_err(|_| invalid())?)
+                    }
+                    None => None,
+                };
+                Ok(Connector::Http {
+                    proxy: proxy_uri,
+                    auth,
+                    inner: http,
+                })
+            }
+            Some("socks4") | Some("socks4a") => {
+                Ok(Connector::Socks4(SocksV4::new(proxy_uri.clone(), http)))
+            }
+            Some("socks5") | Some("socks5h") => {
+                let mut socks = SocksV5::new(proxy_uri.clone(), http)
+                    .local_dns(proxy_uri.scheme_str() == Some("socks5"));
+                if let Some((user, pass)) = credentials {
+                    socks = socks.with_auth(user, pass);
+                }
+                Ok(Connector::Socks5(socks))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Service<Uri> for Connector {
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self {
+            Connector::Direct(inner) => inner.poll_ready(cx).map_err(Into::into),
+            Connector::Http { inner, .. } => inner.poll_ready(cx).map_err(Into::into),
+            Connector::Socks4(inner) => inner.poll_ready(cx).map_err(Into::into),
+            Connector::Socks5(inner) => inner.poll_ready(cx).map_err(Into::into),
+        }
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        match self {
+            Connector::Direct(inner) => {
+                let fut = inner.call(dst);
+                Box::pin(async move { fut.await.map_err(Into::into) })
+            }
+            Connector::Http { proxy, auth, inner } => {
+                // `Tunnel` is not `Clone`, so one is built for every connection
+                let mut tunnel = Tunnel::new(proxy.clone(), inner.clone());
+                if let Some(auth) = auth {
+                    tunnel = tunnel.with_auth(auth.clone());
+                }
+                let fut = tunnel.call(dst);
+                Box::pin(async move { fut.await.map_err(Into::into) })
+            }
+            Connector::Socks4(inner) => {
+                let fut = inner.call(dst);
+                Box::pin(async move { fut.await.map_err(Into::into) })
+            }
+            Connector::Socks5(inner) => {
+                let fut = inner.call(dst);
+                Box::pin(async move { fut.await.map_err(Into::into) })
+            }
+        }
+    }
+}
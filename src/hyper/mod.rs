@@ -38,8 +38,11 @@
 //! }
 //! ```
 
+mod batch;
 mod client;
+mod connector;
 mod error;
+mod pagination;
 mod response;
 
 pub use client::*;
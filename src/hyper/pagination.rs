@@ -0,0 +1,101 @@
+use crate::hyper::{Error, GateHttpClient};
+use crate::pagination::{Pager, Paginated, TimeWindowed};
+use futures_util::Stream;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+
+struct State<'a, R, T> {
+    client: &'a GateHttpClient,
+    pager: Pager<R>,
+    buffer: VecDeque<T>,
+}
+
+async fn fetch<R, T>(client: &GateHttpClient, request: R) -> Result<Vec<T>, Error>
+where
+    R: Paginated,
+    T: DeserializeOwned,
+{
+    let body = client.send(request).await?.into_body_str().await?;
+    serde_json::from_str(&body).map_err(|err| Error::InvalidBody(Box::new(err)))
+}
+
+fn records<'a, R, T>(client: &'a GateHttpClient, pager: Pager<R>) -> BoxStream<'a, Result<T, Error>>
+where
+    R: Paginated + Send + 'a,
+    T: DeserializeOwned + Send + 'a,
+{
+    let state = State {
+        client,
+        pager,
+        buffer: VecDeque::new(),
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(record) = state.buffer.pop_front() {
+                return Some((Ok(record), state));
+            }
+            let request = state.pager.next_request()?;
+            match fetch(state.client, request).await {
+                Ok(records) => {
+                    state.pager.record(records.len());
+                    state.buffer.extend(records);
+                }
+                Err(err) => {
+                    state.pager.finish();
+                    return Some((Err(err), state));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+impl GateHttpClient {
+    /// Streams every record of a paged endpoint, fetching pages on demand.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures_util::TryStreamExt;
+    /// use gateio_rs::{api::spot::get_orders, http::Credentials, hyper::GateHttpClient};
+    /// use serde_json::Value;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+    ///     let orders: Vec<Value> = client
+    ///         .paginate(get_orders().currency_pair("BTC_USDT").status("finished"))
+    ///         .try_collect()
+    ///         .await?;
+    ///     println!("{} orders", orders.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn paginate<'a, T, R>(
+        &'a self,
+        request: R,
+    ) -> impl Stream<Item = Result<T, Error>> + Send + 'a
+    where
+        T: DeserializeOwned + Send + 'a,
+        R: Paginated + Send + 'a,
+    {
+        records(self, Pager::new(request))
+    }
+
+    /// Streams every record between `from` and `to` (inclusive, UNIX
+    /// seconds), querying at most `window` seconds at a time
+    pub fn paginate_windows<'a, T, R>(
+        &'a self,
+        request: R,
+        from: i64,
+        to: i64,
+        window: i64,
+    ) -> impl Stream<Item = Result<T, Error>> + Send + 'a
+    where
+        T: DeserializeOwned + Send + 'a,
+        R: TimeWindowed + Send + 'a,
+    {
+        records(self, Pager::windows(request, from, to, window))
+    }
+}
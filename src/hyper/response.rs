@@ -1,50 +1,74 @@
-use crate::http::error::{ClientError, GateApiError, HttpError};
+use crate::http::DEFAULT_MAX_BODY_SIZE;
+use crate::http::error::ClientError;
 use crate::hyper::Error;
 use bytes::Bytes;
-use http_body_util::BodyExt;
+use futures_util::{Stream, TryStreamExt};
+use http_body_util::{BodyExt, BodyStream};
 use std::collections::HashMap;
 
+type BoxBody =
+    http_body_util::combinators::BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
 /// REST Response
 #[derive(Debug)]
 pub struct Response {
-    inner_response: hyper::Response<
-        http_body_util::combinators::BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>,
-    >,
+    inner_response: hyper::Response<BoxBody>,
+    max_body_size: usize,
 }
 
 impl Response {
-    pub async fn into_body_str(self) -> Result<String, Error> {
-        let status = self.inner_response.status().as_u16();
-        if 400 <= status {
-            let headers: HashMap<String, String> =
-                self.inner_response
-                    .headers()
-                    .iter()
-                    .fold(HashMap::new(), |mut headers, (k, v)| {
-                        headers.entry(k.as_str().to_owned()).or_insert_with(|| {
-                            // Assume all Gate response headers can convert to String.
-                            v.to_str()
-                                .expect("Failed to convert response header value to string")
-                                .to_owned()
-                        });
-                        headers
-                    });
+    /// Sets the maximum body size read by [`Response::into_body_str`]
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
 
-            let content = hyper_body_to_string(self.inner_response.into_body()).await?;
-            if 500 <= status {
-                Err(Error::Server(HttpError::new(status, content, headers)))
-            } else {
-                let client_error = match serde_json::from_str::<GateApiError>(&content) {
-                    Ok(err) => ClientError::Structured(HttpError::new(status, err, headers)),
-                    Err(_) => ClientError::Raw(HttpError::new(status, content, headers)),
-                };
+    /// Returns the HTTP status code
+    pub fn status(&self) -> u16 {
+        self.inner_response.status().as_u16()
+    }
 
-                Err(Error::Client(client_error))
-            }
+    /// Returns the response headers
+    pub(crate) fn headers(&self) -> &http::HeaderMap {
+        self.inner_response.headers()
+    }
+
+    /// Reads the head and body, leaving error statuses to the caller
+    pub(crate) async fn into_raw(self) -> Result<(http::response::Parts, String), Error> {
+        let (parts, body) = self.inner_response.into_parts();
+        Ok((parts, read_limited(body, self.max_body_size).await?))
+    }
+
+    /// Fetch the data received from the API.
+    pub async fn into_body_str(self) -> Result<String, Error> {
+        let status = self.status();
+        let headers = crate::utils::header_map(self.inner_response.headers());
+        let content = read_limited(self.inner_response.into_body(), self.max_body_size).await?;
+
+        if 400 <= status {
+            Err(error_response(status, content, headers))
         } else {
-            Ok(hyper_body_to_string(self.inner_response.into_body()).await?)
+            Ok(content)
         }
     }
+
+    /// Streams the body of a successful response without a size limit.
+    ///
+    /// Error responses are read (within the size limit) and returned as errors.
+    pub async fn into_body_stream(
+        self,
+    ) -> Result<impl Stream<Item = Result<Bytes, Error>> + Send, Error> {
+        let status = self.status();
+        if 400 <= status {
+            let headers = crate::utils::header_map(self.inner_response.headers());
+            let content = read_limited(self.inner_response.into_body(), self.max_body_size).await?;
+            return Err(error_response(status, content, headers));
+        }
+
+        let frames = BodyStream::new(self.inner_response.into_body());
+        Ok(TryStreamExt::map_err(frames, Error::Send)
+            .try_filter_map(|frame| async move { Ok(frame.into_data().ok()) }))
+    }
 }
 
 impl<B> From<hyper::Response<B>> for Response
@@ -57,24 +81,31 @@ where
         let boxed_body = body.map_err(|e| e.into()).boxed();
         Response {
             inner_response: hyper::Response::from_parts(parts, boxed_body),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
         }
     }
 }
 
-async fn hyper_body_to_string<B>(body: B) -> Result<String, Error>
-where
-    B: http_body::Body + Send + 'static,
-    B::Data: Send,
-    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
-{
-    use http_body_util::BodyExt;
+fn error_response(status: u16, content: String, headers: HashMap<String, String>) -> Error {
+    if 500 <= status {
+        Error::Server(ClientError::from_body(status, content, headers))
+    } else {
+        Error::Client(ClientError::from_body(status, content, headers))
+    }
+}
 
-    // Collect body bytes
-    let collected = body.collect().await.map_err(|e| Error::Send(e.into()))?;
-    let bytes = collected.to_bytes();
+async fn read_limited(mut body: BoxBody, limit: usize) -> Result<String, Error> {
+    // Collect body bytes, refusing bodies above the limit
+    let mut bytes = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame.map_err(Error::Send)?.into_data() {
+            if bytes.len() + data.len() > limit {
+                return Err(Error::BodyTooLarge(limit));
+            }
+            bytes.extend_from_slice(&data);
+        }
+    }
 
     // Convert to string
-    let content = String::from_utf8(bytes.to_vec()).map_err(|e| Error::Send(Box::new(e)))?;
-
-    Ok(content)
+    String::from_utf8(bytes).map_err(|e| Error::InvalidBody(Box::new(e)))
 }
@@ -18,7 +18,7 @@
 //! // Get ticker data
 //! let request = get_ticker().currency_pair("BTC_USDT");
 //! let response = client.send(request)?;
-//! # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
 //! ## Features
@@ -27,6 +27,9 @@
 //!
 //! * `enable-ureq` (default): Synchronous HTTP client powered by [`ureq`](https://docs.rs/ureq/)
 //! * `enable-hyper`: Asynchronous HTTP client powered by [`hyper`](https://docs.rs/hyper/)
+//! * `enable-tracing`: [`tracing`](https://docs.rs/tracing/) spans for every request, secrets redacted
+//! * `enable-metrics`: API usage metrics through the [`metrics`](https://docs.rs/metrics/) facade
+//! * `testing`: Local mock Gate server for integration tests
 //!
 //! ## Architecture
 //!
@@ -37,14 +40,55 @@
 //!
 
 #![warn(missing_docs)]
+// `GateError` deliberately carries the full error response (status, headers, body)
+#![allow(clippy::result_large_err)]
 
 mod utils;
 mod version;
 
+/// Client-side execution algorithms
+pub mod algo;
 /// Spot trading API endpoints
 pub mod api;
+/// Market trade tape archiver
+pub mod archive;
+/// Historical candlestick backfill
+pub mod backfill;
+/// Batch requests split to fit Gate's batch limits
+pub mod batch;
+/// Client-side bracket and OCO orders
+pub mod bracket;
+/// Record-and-replay HTTP cassettes
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+pub mod cassette;
+/// Fee-tier aware cost estimation for spot orders
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+pub mod fees;
+/// Dead-man's switch around `countdown_cancel_all`
+pub mod heartbeat;
 /// HTTP client abstractions and utilities
 pub mod http;
+/// API usage metrics through the `metrics` facade
+#[cfg(feature = "enable-metrics")]
+pub mod metrics;
+/// Order lifecycle management
+pub mod orders;
+/// Automatic pagination for paged endpoints
+pub mod pagination;
+/// Paper trading client simulating the trading endpoints
+#[cfg(feature = "enable-ureq")]
+pub mod paper;
+/// Balances of every wallet valued in one quote currency
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+pub mod portfolio;
+/// Account book reconciliation and realized PnL reports
+pub mod reporting;
+/// Pre-trade risk checks wrapping a client
+#[cfg(any(feature = "enable-ureq", feature = "enable-hyper"))]
+pub mod risk;
+/// Local mock Gate server for integration tests
+#[cfg(feature = "testing")]
+pub mod testing;
 
 #[cfg(feature = "enable-hyper")]
 pub mod hyper;
@@ -0,0 +1,430 @@
+//! API usage metrics through the [`metrics`](https://docs.rs/metrics/) facade.
+//!
+//! [`MetricsLayer`] is a [`Middleware`] registered on either client. It
+//! records every attempt with the recorder installed by the application, so
+//! any exporter works: Prometheus, OpenTelemetry, StatsD...
+//!
+//! | Metric | Kind | Labels |
+//! |---|---|---|
+//! | `gateio_requests_total` | counter | `method`, `endpoint`, `class`, `status` |
+//! | `gateio_errors_total` | counter | `endpoint`, `label` |
+//! | `gateio_retries_total` | counter | `endpoint` |
+//! | `gateio_request_duration_seconds` | histogram | `method`, `endpoint` |
+//! | `gateio_ratelimit_remaining` | gauge | `endpoint` |
+//! | `gateio_ratelimit_limit` | gauge | `endpoint` |
+//! | `gateio_ws_messages_total` | counter | `channel` |
+//! | `gateio_ws_message_lag_seconds` | histogram | `channel` |
+//! | `gateio_ws_reconnects_total` | counter | `channel` |
+//!
+//! Endpoints are paths with ids replaced by `{id}`, classes are `market`,
+//! `account` or `trading`. `status` is `error` when no response came back,
+//! and `label` is the Gate label, `HTTP_<status>` for error responses without
+//! one, or the kind of transport error. The rate-limit gauges follow the
+//! `X-Gate-RateLimit-Requests-Remain` and `X-Gate-RateLimit-Limit` headers.
+//!
+//! The SDK has no WebSocket client: feeds report their messages and
+//! reconnects with [`MetricsLayer::ws_message`] and
+//! [`MetricsLayer::ws_reconnect`] on a clone of the layer.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::metrics::MetricsLayer;
+//! use gateio_rs::ureq::GateHttpClient;
+//!
+//! // Install a recorder first, e.g. `metrics_exporter_prometheus::PrometheusBuilder`
+//! let metrics = MetricsLayer::new().label("account", "main");
+//! let client = GateHttpClient::default().middleware(metrics.clone());
+//!
+//! // From the WebSocket feed
+//! metrics.ws_reconnect("spot.orders");
+//! metrics.ws_message("spot.orders", 1_700_000_000_000);
+//! ```
+
+use crate::http::GateError;
+use crate::http::middleware::{Middleware, Next, Outgoing, Received};
+use ::metrics::Label;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Middleware recording API usage metrics, see the [module documentation](self)
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MetricsLayer {
+    /// Prefix of the metric names, `gateio` by default
+    pub prefix: String,
+    /// Labels added to every metric
+    pub labels: Vec<(String, String)>,
+}
+
+impl Default for MetricsLayer {
+    fn default() -> Self {
+        Self {
+            prefix: "gateio".to_owned(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl MetricsLayer {
+    /// Creates a layer recording `gateio_*` metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prefix of the metric names
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_owned();
+        self
+    }
+
+    /// Adds a label to every metric, e.g. the account or the strategy
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Records a WebSocket message of `channel` sent by Gate at `event_time_ms`
+    pub fn ws_message(&self, channel: &str, event_time_ms: i64) {
+        let labels = self.labels(&[("channel", channel.to_owned())]);
+        ::metrics::counter!(self.name("ws_messages_total"), labels.clone()).increment(1);
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+        let lag = Duration::from_millis(now_ms.saturating_sub(event_time_ms).max(0) as u64);
+        ::metrics::histogram!(self.name("ws_message_lag_seconds"), labels).record(lag);
+    }
+
+    /// Records a reconnection of the WebSocket feed of `channel`
+    pub fn ws_reconnect(&self, channel: &str) {
+        let labels = self.labels(&[("channel", channel.to_owned())]);
+        ::metrics::counter!(self.name("ws_reconnects_total"), labels).increment(1);
+    }
+
+    fn name(&self, metric: &str) -> String {
+        if self.prefix.is_empty() {
+            metric.to_owned()
+        } else {
+            format!("{}_{}", self.prefix, metric)
+        }
+    }
+
+    fn labels(&self, labels: &[(&'static str, String)]) -> Vec<Label> {
+        self.labels
+            .iter()
+            .map(|(k, v)| Label::new(k.clone(), v.clone()))
+            .chain(labels.iter().map(|(k, v)| Label::new(*k, v.clone())))
+            .collect()
+    }
+}
+
+impl Middleware for MetricsLayer {
+    fn after_receive(&self, request: &Outgoing, received: &Received<'_>) -> Next {
+        if received.canned {
+            return Next::Continue;
+        }
+        let method = request.method().as_ref().to_owned();
+        let endpoint = crate::utils::endpoint(request.path());
+        let class = crate::utils::endpoint_class(request.path());
+        let status = received
+            .status
+            .map_or_else(|| "error".to_owned(), |s| s.to_string());
+
+        let by_endpoint = self.labels(&[("endpoint", endpoint.clone())]);
+        ::metrics::counter!(
+            self.name("requests_total"),
+            self.labels(&[
+                ("method", method.clone()),
+                ("endpoint", endpoint.clone()),
+                ("class", class.to_owned()),
+                ("status", status),
+            ])
+        )
+        .increment(1);
+        ::metrics::histogram!(
+            self.name("request_duration_seconds"),
+            self.labels(&[("method", method), ("endpoint", endpoint.clone())])
+        )
+        .record(received.latency);
+
+        if let Some(error) = received.error {
+            let label = error_label(error);
+            ::metrics::counter!(
+                self.name("errors_total"),
+                self.labels(&[("endpoint", endpoint.clone()), ("label", label)])
+            )
+            .increment(1);
+        }
+        if request.attempt > 0 {
+            ::metrics::counter!(self.name("retries_total"), by_endpoint.clone()).increment(1);
+        }
+        let header = |name: &str| received.header(name).and_then(|v| v.parse::<f64>().ok());
+        if let Some(remain) = header("x-gate-ratelimit-requests-remain") {
+            ::metrics::gauge!(self.name("ratelimit_remaining"), by_endpoint.clone()).set(remain);
+        }
+        if let Some(limit) = header("x-gate-ratelimit-limit") {
+            ::metrics::gauge!(self.name("ratelimit_limit"), by_endpoint).set(limit);
+        }
+        Next::Continue
+    }
+}
+
+/// Label of an error: the Gate label, the status or the kind of failure
+fn error_label(error: &GateError) -> String {
+    if let Some(label) = error.label() {
+        return label.as_str().to_owned();
+    }
+    if let Some(status) = error.status_code() {
+        return format!("HTTP_{}", status);
+    }
+    match error {
+        GateError::Timeout => "TIMEOUT",
+        GateError::Send(_) => "SEND_ERROR",
+        GateError::BodyTooLarge(_) => "BODY_TOO_LARGE",
+        GateError::InvalidBody(_) => "INVALID_BODY",
+        GateError::Risk(_) => "RISK_REFUSED",
+        _ => "CLIENT_ERROR",
+    }
+    .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use crate::http::error::ClientError;
+    use crate::http::request::RequestBuilder;
+    use ::metrics::{
+        Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata,
+        Recorder, SharedString, Unit,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Metric name, its labels as `key=value`, and the value recorded
+    type Sample = (String, Vec<String>, f64);
+
+    /// Recorder keeping every update in order
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<Sample>>>);
+
+    struct Handle {
+        key: Key,
+        samples: Capture,
+    }
+
+    impl Handle {
+        fn push(&self, value: f64) {
+            let labels = self
+                .key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            let mut samples = self.samples.0.lock().unwrap();
+            samples.push((self.key.name().to_owned(), labels, value));
+        }
+    }
+
+    impl CounterFn for Handle {
+        fn increment(&self, value: u64) {
+            self.push(value as f64);
+        }
+
+        fn absolute(&self, value: u64) {
+            self.push(value as f64);
+        }
+    }
+
+    impl GaugeFn for Handle {
+        fn increment(&self, value: f64) {
+            self.push(value);
+        }
+
+        fn decrement(&self, value: f64) {
+            self.push(-value);
+        }
+
+        fn set(&self, value: f64) {
+            self.push(value);
+        }
+    }
+
+    impl HistogramFn for Handle {
+        fn record(&self, value: f64) {
+            self.push(value);
+        }
+    }
+
+    impl Capture {
+        fn handle(&self, key: &Key) -> Arc<Handle> {
+            Arc::new(Handle {
+                key: key.clone(),
+                samples: self.clone(),
+            })
+        }
+
+        fn samples(&self, name: &str) -> Vec<(Vec<String>, f64)> {
+            let samples = self.0.lock().unwrap();
+            samples
+                .iter()
+                .filter(|(metric, _, _)| metric == name)
+                .map(|(_, labels, value)| (labels.clone(), *value))
+                .collect()
+        }
+    }
+
+    impl Recorder for Capture {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.handle(key))
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.handle(key))
+        }
+    }
+
+    fn record(
+        layer: &MetricsLayer,
+        attempt: u32,
+        status: Option<u16>,
+        headers: &[(&str, &str)],
+        error: Option<&GateError>,
+    ) -> Capture {
+        let request = RequestBuilder::new(Method::Get, "/api/v4/spot/orders/12345").into();
+        let outgoing = Outgoing::new(request, attempt);
+        let headers: HashMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let received = Received {
+            status,
+            headers: &headers,
+            error,
+            latency: Duration::from_millis(250),
+            canned: false,
+        };
+        let capture = Capture::default();
+        ::metrics::with_local_recorder(&capture, || layer.after_receive(&outgoing, &received));
+        capture
+    }
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_rate_limit_headers_into_gauges() {
+        let capture = record(
+            &MetricsLayer::new(),
+            0,
+            Some(200),
+            &[
+                ("x-gate-ratelimit-requests-remain", "9"),
+                ("x-gate-ratelimit-limit", "10"),
+            ],
+            None,
+        );
+        let endpoint = labels(&["endpoint=/api/v4/spot/orders/{id}"]);
+        assert_eq!(
+            capture.samples("gateio_ratelimit_remaining"),
+            vec![(endpoint.clone(), 9.0)]
+        );
+        assert_eq!(
+            capture.samples("gateio_ratelimit_limit"),
+            vec![(endpoint, 10.0)]
+        );
+    }
+
+    #[test]
+    fn ignores_missing_or_malformed_rate_limit_headers() {
+        let capture = record(
+            &MetricsLayer::new(),
+            0,
+            Some(200),
+            &[("x-gate-ratelimit-requests-remain", "many")],
+            None,
+        );
+        assert!(capture.samples("gateio_ratelimit_remaining").is_empty());
+        assert!(capture.samples("gateio_ratelimit_limit").is_empty());
+    }
+
+    #[test]
+    fn counts_requests_with_their_labels() {
+        let layer = MetricsLayer::new().prefix("gate").label("account", "main");
+        let capture = record(&layer, 0, Some(200), &[], None);
+        assert_eq!(
+            capture.samples("gate_requests_total"),
+            vec![(
+                labels(&[
+                    "account=main",
+                    "method=GET",
+                    "endpoint=/api/v4/spot/orders/{id}",
+                    "class=trading",
+                    "status=200",
+                ]),
+                1.0
+            )]
+        );
+        assert_eq!(
+            capture.samples("gate_request_duration_seconds"),
+            vec![(
+                labels(&[
+                    "account=main",
+                    "method=GET",
+                    "endpoint=/api/v4/spot/orders/{id}"
+                ]),
+                0.25
+            )]
+        );
+        assert!(capture.samples("gate_errors_total").is_empty());
+        assert!(capture.samples("gate_retries_total").is_empty());
+    }
+
+    #[test]
+    fn counts_errors_and_retries() {
+        let error = GateError::Client(ClientError::from_body(
+            400,
+            r#"{"label":"ORDER_NOT_FOUND","message":"no"}"#.to_owned(),
+            HashMap::new(),
+        ));
+        let capture = record(&MetricsLayer::new(), 2, Some(400), &[], Some(&error));
+        let endpoint = "endpoint=/api/v4/spot/orders/{id}";
+        assert_eq!(
+            capture.samples("gateio_errors_total"),
+            vec![(labels(&[endpoint, "label=ORDER_NOT_FOUND"]), 1.0)]
+        );
+        assert_eq!(
+            capture.samples("gateio_retries_total"),
+            vec![(labels(&[endpoint]), 1.0)]
+        );
+
+        let capture = record(
+            &MetricsLayer::new(),
+            0,
+            None,
+            &[],
+            Some(&GateError::Timeout),
+        );
+        assert_eq!(
+            capture.samples("gateio_requests_total")[0].0[3],
+            "status=error"
+        );
+    }
+
+    #[test]
+    fn labels_errors_by_label_status_or_kind() {
+        let raw = GateError::Server(ClientError::from_body(502, "bad".into(), HashMap::new()));
+        assert_eq!(error_label(&raw), "HTTP_502");
+        assert_eq!(error_label(&GateError::Timeout), "TIMEOUT");
+        assert_eq!(error_label(&GateError::BodyTooLarge(1)), "BODY_TOO_LARGE");
+    }
+}
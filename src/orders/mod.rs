@@ -0,0 +1,939 @@
+//! Order lifecycle management.
+//!
+//! [`OrderManager`] places, amends and cancels orders on behalf of the
+//! application while keeping a local view of every order it created:
+//!
+//! - every order gets a [`ClientOrderId`] (`text`), generated by a
+//!   [`ClientOrderIdGenerator`] unless the request carries one
+//! - local states ([`OrderState`]) follow the exchange responses, with
+//!   pending states while a creation or amendment is in flight
+//! - [`OrderManager::reconcile`] catches up with the exchange using
+//!   `get_open_orders`, `get_orders` and the personal trade feed, resolving
+//!   orders whose creation response was lost
+//! - every change is published as an [`OrderEvent`] to subscribers
+//!
+//! State updates from other sources (e.g. a WebSocket feed) can be fed in
+//! with [`OrderManager::apply_order`] and [`OrderManager::apply_trade`].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::{api::spot::create_order, http::Credentials, ureq::GateHttpClient};
+//! use gateio_rs::orders::{OrderEvent, OrderManager};
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//! let manager = OrderManager::new();
+//! let events = manager.subscribe();
+//!
+//! let order = manager.create(&client, create_order("BTC_USDT", "buy", "0.001").price("30000"))?;
+//! println!("{} is {:?}", order.client_id, order.state);
+//!
+//! manager.reconcile(&client)?;
+//! for event in events.try_iter() {
+//!     if let OrderEvent::StateChanged { previous, order } = event {
+//!         println!("{}: {:?} -> {:?}", order.client_id, previous, order.state);
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod state;
+
+pub use crate::api::spot::client_order_id::validate_client_id;
+pub use state::{ManagedOrder, OrderEvent, OrderState};
+
+use crate::api::spot::amend_order::AmendOrder;
+use crate::api::spot::client_order_id::{
+    ClientOrderId, ClientOrderIdError, ClientOrderIdGenerator, StrategyIds,
+};
+use crate::api::spot::create_order::CreateOrder;
+use crate::api::spot::model::{OrderDetails, UserTrade};
+use crate::http::GateError;
+use crate::utils::{now_ms, number};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Mutex, MutexGuard};
+
+/// Tracks the orders it places and publishes their state changes.
+///
+/// All methods take `&self`; the manager can be shared between threads.
+pub struct OrderManager {
+    ids: Box<dyn ClientOrderIdGenerator>,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Orders by client id
+    orders: HashMap<ClientOrderId, ManagedOrder>,
+    /// Client id by exchange order id
+    order_ids: HashMap<String, ClientOrderId>,
+    /// Trades already applied
+    trade_ids: HashSet<String>,
+    /// Fill sources by client id
+    fills: HashMap<ClientOrderId, Fills>,
+    subscribers: Vec<Sender<OrderEvent>>,
+}
+
+/// Filled amount of an order as seen by each source
+#[derive(Clone, Copy, Default)]
+struct Fills {
+    /// Highest filled amount reported by order snapshots
+    reported: f64,
+    /// Sum of the applied trades
+    traded: f64,
+}
+
+impl Fills {
+    /// Snapshots and the trade feed both lag, the larger one is the most recent
+    fn total(&self) -> f64 {
+        self.reported.max(self.traded)
+    }
+}
+
+impl Inner {
+    fn emit(&mut self, event: OrderEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    fn client_id_of(&self, order_id: &str, text: &str) -> Option<ClientOrderId> {
+        self.order_ids
+            .get(order_id)
+            .or_else(|| self.orders.get_key_value(text).map(|(id, _)| id))
+            .cloned()
+    }
+
+    /// Moves an order to `state`, publishing the change
+    fn transition(&mut self, client_id: &ClientOrderId, state: OrderState) {
+        let Some(order) = self.orders.get_mut(client_id) else {
+            return;
+        };
+        let previous = order.state;
+        if previous == state {
+            return;
+        }
+        order.state = state;
+        order.updated_at = now_ms();
+        let order = order.clone();
+        self.emit(OrderEvent::StateChanged { previous, order });
+    }
+}
+
+impl Default for OrderManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderManager {
+    /// Creates a manager generating client ids tagged `om`
+    pub fn new() -> Self {
+        Self::with_generator(StrategyIds::with_tag("om"))
+    }
+
+    /// Creates a manager generating client ids tagged with `prefix`, see [`StrategyIds`].
+    ///
+    /// Fails when the prefix would produce ids breaking the `text` rules.
+    pub fn with_prefix(prefix: &str) -> Result<Self, OrderManagerError> {
+        Ok(Self::with_generator(StrategyIds::new(prefix)?))
+    }
+
+    /// Creates a manager taking client ids from `ids`
+    pub fn with_generator<G: ClientOrderIdGenerator + 'static>(ids: G) -> Self {
+        Self {
+            ids: Box::new(ids),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns a fresh client order id
+    pub fn next_client_id(&self) -> ClientOrderId {
+        self.ids.next_id()
+    }
+
+    /// Returns a receiver of every event published from now on
+    pub fn subscribe(&self) -> Receiver<OrderEvent> {
+        let (sender, receiver) = channel();
+        self.lock().subscribers.push(sender);
+        receiver
+    }
+
+    /// Returns the order with the given client id
+    pub fn get(&self, client_id: &str) -> Option<ManagedOrder> {
+        self.lock().orders.get(client_id).cloned()
+    }
+
+    /// Returns every tracked order
+    pub fn orders(&self) -> Vec<ManagedOrder> {
+        self.lock().orders.values().cloned().collect()
+    }
+
+    /// Returns the orders not yet closed, cancelled or rejected
+    pub fn active_orders(&self) -> Vec<ManagedOrder> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|order| !order.state.is_terminal())
+            .cloned()
+            .collect()
+    }
+
+    /// Drops closed, cancelled and rejected orders from the local view
+    pub fn prune(&self) {
+        let mut inner = self.lock();
+        inner.orders.retain(|_, order| !order.state.is_terminal());
+        let Inner {
+            orders,
+            order_ids,
+            fills,
+            ..
+        } = &mut *inner;
+        order_ids.retain(|_, client_id| orders.contains_key(client_id));
+        fills.retain(|client_id, _| orders.contains_key(client_id));
+    }
+
+    /// Registers an order about to be sent, assigning a client id when it has none.
+    ///
+    /// The returned request carries the client id in `text`.
+    pub fn register(&self, request: CreateOrder) -> Result<CreateOrder, OrderManagerError> {
+        self.register_id(request).map(|(request, _)| request)
+    }
+
+    fn register_id(
+        &self,
+        mut request: CreateOrder,
+    ) -> Result<(CreateOrder, ClientOrderId), OrderManagerError> {
+        let client_id = match &request.text {
+            Some(text) => text.clone(),
+            None => self.next_client_id(),
+        };
+
+        let mut inner = self.lock();
+        if inner.orders.contains_key(&client_id) {
+            return Err(OrderManagerError::DuplicateClientId(client_id.into()));
+        }
+        let now = now_ms();
+        let order = ManagedOrder {
+            client_id: client_id.clone(),
+            order_id: None,
+            currency_pair: request.currency_pair.clone(),
+            side: request.side.clone(),
+            amount: request.amount.clone(),
+            price: request.price.clone(),
+            state: OrderState::PendingNew,
+            filled_amount: 0.0,
+            finish_as: None,
+            created_at: now,
+            updated_at: now,
+        };
+        inner.orders.insert(client_id.clone(), order.clone());
+        inner.emit(OrderEvent::Submitted(order));
+
+        request.text = Some(client_id.clone());
+        Ok((request, client_id))
+    }
+
+    /// Applies an order snapshot returned by Gate.
+    ///
+    /// Returns `false` when the order is not tracked by this manager.
+    pub fn apply_order(&self, details: &OrderDetails) -> bool {
+        let mut inner = self.lock();
+        let Some(client_id) = inner.client_id_of(&details.id, &details.text) else {
+            return false;
+        };
+        inner
+            .order_ids
+            .insert(details.id.clone(), client_id.clone());
+
+        if inner
+            .orders
+            .get(&client_id)
+            .is_none_or(|order| order.state.is_terminal())
+        {
+            // Terminal states are final, late snapshots cannot reopen an order
+            return inner.orders.contains_key(&client_id);
+        }
+        let fills = inner.fills.entry(client_id.clone()).or_default();
+        fills.reported = fills.reported.max(number(&details.filled_amount));
+        let filled = fills.total();
+
+        let Some(order) = inner.orders.get_mut(&client_id) else {
+            return false;
+        };
+        order.order_id = Some(details.id.clone());
+        order.filled_amount = filled;
+        if !details.price.is_empty() {
+            order.price = Some(details.price.clone());
+        }
+        if !details.amount.is_empty() {
+            order.amount = details.amount.clone();
+        }
+        if !details.finish_as.is_empty() {
+            order.finish_as = Some(details.finish_as.clone());
+        }
+        order.updated_at = now_ms();
+
+        // A snapshot older than the applied trades must not hide their fills
+        let next = match OrderState::from_details(details) {
+            OrderState::Open if filled > 0.0 => OrderState::PartiallyFilled,
+            next => next,
+        };
+        inner.transition(&client_id, next);
+        true
+    }
+
+    /// Applies a personal trade, publishing a fill the first time it is seen.
+    ///
+    /// Returns `false` for trades of untracked orders and already applied trades.
+    pub fn apply_trade(&self, trade: &UserTrade) -> bool {
+        let mut inner = self.lock();
+        let Some(client_id) = inner.client_id_of(&trade.order_id, &trade.text) else {
+            return false;
+        };
+        if !inner.trade_ids.insert(trade.id.clone()) {
+            return false;
+        }
+        inner
+            .order_ids
+            .insert(trade.order_id.clone(), client_id.clone());
+
+        if !inner.orders.contains_key(&client_id) {
+            return false;
+        }
+        let fills = inner.fills.entry(client_id.clone()).or_default();
+        fills.traded += number(&trade.amount);
+        let filled = fills.total();
+
+        let Some(order) = inner.orders.get_mut(&client_id) else {
+            return false;
+        };
+        order.order_id = Some(trade.order_id.clone());
+        order.filled_amount = filled;
+        order.updated_at = now_ms();
+        // Only a `closed` snapshot closes an order: the requested amount of
+        // a market buy is in quote currency, and a resting order can be
+        // fully filled on paper while its snapshot is still catching up
+        let next = match order.state {
+            state if state.is_terminal() || state == OrderState::AmendPending => state,
+            _ => OrderState::PartiallyFilled,
+        };
+        let order = order.clone();
+        inner.emit(OrderEvent::Fill {
+            trade: Box::new(trade.clone()),
+            order,
+        });
+        inner.transition(&client_id, next);
+        true
+    }
+
+    /// Marks a creation as refused by Gate
+    fn reject(&self, client_id: &ClientOrderId, reason: String) {
+        let mut inner = self.lock();
+        inner.transition(client_id, OrderState::Rejected);
+        if let Some(order) = inner.orders.get(client_id).cloned() {
+            inner.emit(OrderEvent::Rejected { reason, order });
+        }
+    }
+
+    /// Looks up the tracked order an amendment or cancellation refers to,
+    /// by exchange order id or client id
+    fn resolve(&self, order_ref: &str) -> Result<ManagedOrder, OrderManagerError> {
+        let inner = self.lock();
+        inner
+            .client_id_of(order_ref, order_ref)
+            .and_then(|client_id| inner.orders.get(&client_id).cloned())
+            .ok_or_else(|| OrderManagerError::UnknownOrder(order_ref.to_owned()))
+    }
+
+    /// Marks an amendment as in flight and points it at the tracked order
+    fn begin_amend(
+        &self,
+        mut request: AmendOrder,
+    ) -> Result<(AmendOrder, ClientOrderId), OrderManagerError> {
+        let order = self.resolve(&request.order_id)?;
+        request.order_id = order.order_ref().to_owned();
+        request.currency_pair = order.currency_pair.clone();
+        self.lock()
+            .transition(&order.client_id, OrderState::AmendPending);
+        Ok((request, order.client_id))
+    }
+
+    /// Applies the outcome of a creation request
+    fn finish_create(
+        &self,
+        client_id: &ClientOrderId,
+        result: Result<OrderDetails, GateError>,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        match result {
+            Ok(details) => {
+                self.apply_order(&details);
+            }
+            // Gate refused the order; other failures leave it pending until reconciled
+            Err(err @ GateError::Client(_)) => {
+                self.reject(client_id, err.to_string());
+                return Err(err.into());
+            }
+            Err(err) => return Err(err.into()),
+        }
+        self.get(client_id.as_str())
+            .ok_or_else(|| OrderManagerError::UnknownOrder(client_id.to_string()))
+    }
+
+    /// Applies the outcome of an amendment or cancellation request
+    fn finish_update(
+        &self,
+        client_id: &ClientOrderId,
+        result: Result<OrderDetails, GateError>,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        match result {
+            Ok(details) => {
+                self.apply_order(&details);
+            }
+            Err(err) => {
+                let mut inner = self.lock();
+                if let Some(order) = inner.orders.get(client_id)
+                    && order.state == OrderState::AmendPending
+                {
+                    let state = order.resting_state();
+                    inner.transition(client_id, state);
+                }
+                return Err(err.into());
+            }
+        }
+        self.get(client_id.as_str())
+            .ok_or_else(|| OrderManagerError::UnknownOrder(client_id.to_string()))
+    }
+
+    /// Pairs with active orders, with the earliest creation time (UNIX seconds)
+    fn active_pairs(&self) -> BTreeMap<String, i64> {
+        let mut pairs = BTreeMap::new();
+        for order in self.active_orders() {
+            let since = pairs.entry(order.currency_pair.clone()).or_insert(i64::MAX);
+            // Allow for clock skew between this host and Gate
+            *since = (*since).min(order.created_at / 1000 - 60);
+        }
+        pairs
+    }
+
+    fn unresolved(&self) -> Vec<ClientOrderId> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|order| order.state == OrderState::PendingNew)
+            .map(|order| order.client_id.clone())
+            .collect()
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl OrderManager {
+    /// Places an order, assigning a client id when `request` has none
+    pub fn create<S: crate::ureq::SendRequest>(
+        &self,
+        client: &S,
+        request: CreateOrder,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        let (request, client_id) = self.register_id(request)?;
+        let result = crate::utils::fetch(client, request);
+        self.finish_create(&client_id, result)
+    }
+
+    /// Amends a tracked order; `request.order_id` may be its order id or client id
+    pub fn amend<S: crate::ureq::SendRequest>(
+        &self,
+        client: &S,
+        request: AmendOrder,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        let (request, client_id) = self.begin_amend(request)?;
+        let result = crate::utils::fetch(client, request);
+        self.finish_update(&client_id, result)
+    }
+
+    /// Cancels a tracked order by order id or client id
+    pub fn cancel<S: crate::ureq::SendRequest>(
+        &self,
+        client: &S,
+        order_ref: &str,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        let order = self.resolve(order_ref)?;
+        let request = crate::api::spot::cancel_order(order.order_ref(), &order.currency_pair);
+        let result = crate::utils::fetch(client, request);
+        self.finish_update(&order.client_id, result)
+    }
+
+    /// Brings every active order up to date with the exchange
+    pub fn reconcile<S: crate::ureq::SendRequest>(
+        &self,
+        client: &S,
+    ) -> Result<ReconcileReport, OrderManagerError> {
+        use crate::api::spot::{get_my_trades, get_open_orders, get_orders, model::OpenOrders};
+        use crate::ureq::Pages;
+
+        let mut report = ReconcileReport::default();
+        let pairs = self.active_pairs();
+        if pairs.is_empty() {
+            return Ok(report);
+        }
+
+        for page in 1.. {
+            let request = get_open_orders().page(page).limit(OPEN_ORDERS_LIMIT);
+            let open: Vec<OpenOrders> = crate::utils::fetch(client, request)?;
+            report.updated += self.apply_open_orders(&open);
+            if !has_more(&open) {
+                break;
+            }
+        }
+
+        for (pair, since) in &pairs {
+            let finished = get_orders()
+                .currency_pair(pair)
+                .status("finished")
+                .from(*since);
+            for order in Pages::<_, OrderDetails, _>::new(client, finished) {
+                report.updated += usize::from(self.apply_order(&order?));
+            }
+
+            let trades = get_my_trades().currency_pair(pair).from(*since);
+            for trade in Pages::<_, UserTrade, _>::new(client, trades) {
+                report.fills += usize::from(self.apply_trade(&trade?));
+            }
+        }
+
+        report.unresolved = self.unresolved();
+        Ok(report)
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl OrderManager {
+    /// Places an order, assigning a client id when `request` has none
+    pub async fn create_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+        request: CreateOrder,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        let (request, client_id) = self.register_id(request)?;
+        let result = crate::utils::fetch_async(client, request).await;
+        self.finish_create(&client_id, result)
+    }
+
+    /// Amends a tracked order; `request.order_id` may be its order id or client id
+    pub async fn amend_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+        request: AmendOrder,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        let (request, client_id) = self.begin_amend(request)?;
+        let result = crate::utils::fetch_async(client, request).await;
+        self.finish_update(&client_id, result)
+    }
+
+    /// Cancels a tracked order by order id or client id
+    pub async fn cancel_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+        order_ref: &str,
+    ) -> Result<ManagedOrder, OrderManagerError> {
+        let order = self.resolve(order_ref)?;
+        let request = crate::api::spot::cancel_order(order.order_ref(), &order.currency_pair);
+        let result = crate::utils::fetch_async(client, request).await;
+        self.finish_update(&order.client_id, result)
+    }
+
+    /// Brings every active order up to date with the exchange
+    pub async fn reconcile_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<ReconcileReport, OrderManagerError> {
+        use crate::api::spot::{get_my_trades, get_open_orders, get_orders, model::OpenOrders};
+        use futures_util::TryStreamExt;
+
+        let mut report = ReconcileReport::default();
+        let pairs = self.active_pairs();
+        if pairs.is_empty() {
+            return Ok(report);
+        }
+
+        for page in 1.. {
+            let request = get_open_orders().page(page).limit(OPEN_ORDERS_LIMIT);
+            let open: Vec<OpenOrders> = crate::utils::fetch_async(client, request).await?;
+            report.updated += self.apply_open_orders(&open);
+            if !has_more(&open) {
+                break;
+            }
+        }
+
+        for (pair, since) in &pairs {
+            let finished = get_orders()
+                .currency_pair(pair)
+                .status("finished")
+                .from(*since);
+            let mut orders = std::pin::pin!(client.paginate::<OrderDetails, _>(finished));
+            while let Some(order) = orders.try_next().await? {
+                report.updated += usize::from(self.apply_order(&order));
+            }
+
+            let trades = get_my_trades().currency_pair(pair).from(*since);
+            let mut trades = std::pin::pin!(client.paginate::<UserTrade, _>(trades));
+            while let Some(trade) = trades.try_next().await? {
+                report.fills += usize::from(self.apply_trade(&trade));
+            }
+        }
+
+        report.unresolved = self.unresolved();
+        Ok(report)
+    }
+}
+
+impl OrderManager {
+    fn apply_open_orders(&self, open: &[crate::api::spot::model::OpenOrders]) -> usize {
+        open.iter()
+            .flat_map(|pair| &pair.orders)
+            .filter(|order| self.apply_order(order))
+            .count()
+    }
+}
+
+/// Page size used when listing open orders
+const OPEN_ORDERS_LIMIT: i32 = 100;
+
+/// Whether another page of open orders may exist
+fn has_more(open: &[crate::api::spot::model::OpenOrders]) -> bool {
+    open.iter()
+        .any(|pair| pair.orders.len() >= OPEN_ORDERS_LIMIT as usize)
+}
+
+/// Outcome of a reconciliation
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ReconcileReport {
+    /// Order snapshots applied to tracked orders
+    pub updated: usize,
+    /// New fills found in the trade feed
+    pub fills: usize,
+    /// Client ids of orders still not acknowledged by Gate
+    pub unresolved: Vec<ClientOrderId>,
+}
+
+/// Error of an [`OrderManager`] operation
+#[derive(Debug)]
+pub enum OrderManagerError {
+    /// The client order id breaks the `text` rules
+    InvalidClientId(ClientOrderIdError),
+    /// The client order id is already used by a tracked order
+    DuplicateClientId(String),
+    /// No tracked order has this order id or client id
+    UnknownOrder(String),
+    /// Request to the Gate API failed
+    Gate(Box<GateError>),
+}
+
+impl fmt::Display for OrderManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderManagerError::InvalidClientId(e) => write!(f, "Invalid client order id: {}", e),
+            OrderManagerError::DuplicateClientId(id) => {
+                write!(f, "Client order id already in use: {}", id)
+            }
+            OrderManagerError::UnknownOrder(id) => write!(f, "Unknown order: {}", id),
+            OrderManagerError::Gate(e) => write!(f, "Gate API error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for OrderManagerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OrderManagerError::InvalidClientId(e) => Some(e),
+            OrderManagerError::Gate(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ClientOrderIdError> for OrderManagerError {
+    fn from(err: ClientOrderIdError) -> Self {
+        OrderManagerError::InvalidClientId(err)
+    }
+}
+
+impl From<GateError> for OrderManagerError {
+    fn from(err: GateError) -> Self {
+        OrderManagerError::Gate(Box::new(err))
+    }
+}
+
+impl From<Box<GateError>> for OrderManagerError {
+    fn from(err: Box<GateError>) -> Self {
+        OrderManagerError::Gate(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::spot::create_order;
+    use crate::http::error::ClientError;
+    use serde_json::json;
+
+    fn details(id: &str, text: &str, status: &str, amount: &str, filled: &str) -> OrderDetails {
+        serde_json::from_value(json!({
+            "id": id,
+            "text": text,
+            "status": status,
+            "currency_pair": "BTC_USDT",
+            "side": "buy",
+            "amount": amount,
+            "price": "100",
+            "filled_amount": filled,
+        }))
+        .unwrap()
+    }
+
+    fn trade(id: &str, order_id: &str, amount: &str) -> UserTrade {
+        UserTrade {
+            id: id.into(),
+            create_time_ms: 0.0,
+            currency_pair: "BTC_USDT".into(),
+            side: "buy".into(),
+            role: "maker".into(),
+            amount: amount.into(),
+            price: "100".into(),
+            order_id: order_id.into(),
+            fee: "0".into(),
+            fee_currency: "BTC".into(),
+            point_fee: "0".into(),
+            gt_fee: "0".into(),
+            text: String::new(),
+        }
+    }
+
+    fn placed(manager: &OrderManager, amount: &str) -> ClientOrderId {
+        let request = manager
+            .register(create_order("BTC_USDT", "buy", amount).price("100"))
+            .unwrap();
+        let client_id = request.text.unwrap();
+        manager.apply_order(&details("1", client_id.as_str(), "open", amount, "0"));
+        client_id
+    }
+
+    fn state(manager: &OrderManager, client_id: &ClientOrderId) -> (OrderState, f64) {
+        let order = manager.get(client_id.as_str()).unwrap();
+        (order.state, order.filled_amount)
+    }
+
+    #[test]
+    fn register_assigns_client_id() {
+        let manager = OrderManager::new();
+        let events = manager.subscribe();
+        let request = manager
+            .register(create_order("BTC_USDT", "buy", "1"))
+            .unwrap();
+        let client_id = request.text.unwrap();
+        assert!(client_id.as_str().starts_with("t-om"));
+        assert_eq!(state(&manager, &client_id).0, OrderState::PendingNew);
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Submitted(_))));
+    }
+
+    #[test]
+    fn duplicate_client_id_is_refused() {
+        let manager = OrderManager::new();
+        let request = manager
+            .register(create_order("BTC_USDT", "buy", "1"))
+            .unwrap();
+        assert!(matches!(
+            manager.register(request),
+            Err(OrderManagerError::DuplicateClientId(_))
+        ));
+    }
+
+    #[test]
+    fn snapshot_and_trades_are_not_counted_twice() {
+        let manager = OrderManager::new();
+        let client_id = placed(&manager, "2");
+
+        // Reconcile order: open orders first, then the trade feed
+        manager.apply_order(&details("1", "", "open", "2", "1"));
+        assert!(manager.apply_trade(&trade("t1", "1", "1")));
+        assert_eq!(
+            state(&manager, &client_id),
+            (OrderState::PartiallyFilled, 1.0)
+        );
+
+        assert!(manager.apply_trade(&trade("t2", "1", "0.5")));
+        assert_eq!(
+            state(&manager, &client_id),
+            (OrderState::PartiallyFilled, 1.5)
+        );
+    }
+
+    #[test]
+    fn trades_never_close_an_order() {
+        let manager = OrderManager::new();
+        let client_id = placed(&manager, "1");
+        manager.apply_trade(&trade("t1", "1", "1"));
+        assert_eq!(
+            state(&manager, &client_id),
+            (OrderState::PartiallyFilled, 1.0)
+        );
+
+        manager.apply_order(&details("1", "", "closed", "1", "1"));
+        assert_eq!(state(&manager, &client_id), (OrderState::Closed, 1.0));
+    }
+
+    #[test]
+    fn market_buy_quote_amount_does_not_close() {
+        let manager = OrderManager::new();
+        // 100 USDT market buy, filled with 0.5 and 0.5 BTC
+        let client_id = placed(&manager, "100");
+        manager.apply_trade(&trade("t1", "1", "0.5"));
+        manager.apply_trade(&trade("t2", "1", "0.5"));
+        assert_eq!(
+            state(&manager, &client_id),
+            (OrderState::PartiallyFilled, 1.0)
+        );
+    }
+
+    #[test]
+    fn stale_snapshot_keeps_trade_fills() {
+        let manager = OrderManager::new();
+        let client_id = placed(&manager, "2");
+        manager.apply_trade(&trade("t1", "1", "1"));
+        // Snapshot taken before the trade
+        manager.apply_order(&details("1", "", "open", "2", "0"));
+        assert_eq!(
+            state(&manager, &client_id),
+            (OrderState::PartiallyFilled, 1.0)
+        );
+    }
+
+    #[test]
+    fn duplicate_and_unknown_trades_are_ignored() {
+        let manager = OrderManager::new();
+        let client_id = placed(&manager, "2");
+        assert!(manager.apply_trade(&trade("t1", "1", "1")));
+        assert!(!manager.apply_trade(&trade("t1", "1", "1")));
+        assert!(!manager.apply_trade(&trade("t2", "other", "1")));
+        assert_eq!(state(&manager, &client_id).1, 1.0);
+    }
+
+    #[test]
+    fn terminal_states_are_final() {
+        let manager = OrderManager::new();
+        let client_id = placed(&manager, "2");
+        manager.apply_order(&details("1", "", "cancelled", "2", "0"));
+        manager.apply_order(&details("1", "", "open", "2", "0"));
+        assert_eq!(state(&manager, &client_id).0, OrderState::Cancelled);
+        // Late trades still count, without reopening the order
+        manager.apply_trade(&trade("t1", "1", "0.5"));
+        assert_eq!(state(&manager, &client_id), (OrderState::Cancelled, 0.5));
+    }
+
+    #[test]
+    fn refused_creation_is_rejected() {
+        let manager = OrderManager::new();
+        let request = manager
+            .register(create_order("BTC_USDT", "buy", "1"))
+            .unwrap();
+        let client_id = request.text.unwrap();
+        let body = r#"{"label":"BALANCE_NOT_ENOUGH","message":""}"#.to_owned();
+        let err = GateError::Client(ClientError::from_body(400, body, HashMap::new()));
+        assert!(manager.finish_create(&client_id, Err(err)).is_err());
+        assert_eq!(state(&manager, &client_id).0, OrderState::Rejected);
+    }
+
+    #[test]
+    fn lost_creation_stays_pending() {
+        let manager = OrderManager::new();
+        let request = manager
+            .register(create_order("BTC_USDT", "buy", "1"))
+            .unwrap();
+        let client_id = request.text.unwrap();
+        assert!(
+            manager
+                .finish_create(&client_id, Err(GateError::Timeout))
+                .is_err()
+        );
+        assert_eq!(state(&manager, &client_id).0, OrderState::PendingNew);
+        assert_eq!(manager.unresolved(), vec![client_id.clone()]);
+
+        // Found later by client id in an order snapshot
+        manager.apply_order(&details("7", client_id.as_str(), "open", "1", "0"));
+        assert_eq!(state(&manager, &client_id).0, OrderState::Open);
+        assert!(manager.unresolved().is_empty());
+    }
+
+    #[test]
+    fn failed_amend_returns_to_resting_state() {
+        let manager = OrderManager::new();
+        let client_id = placed(&manager, "2");
+        manager.apply_trade(&trade("t1", "1", "1"));
+        let amend = crate::api::spot::amend_order("1", "BTC_USDT");
+        let (request, amended) = manager.begin_amend(amend).unwrap();
+        assert_eq!(request.order_id, "1");
+        assert_eq!(state(&manager, &client_id).0, OrderState::AmendPending);
+
+        // Fills during the amendment keep it pending
+        manager.apply_trade(&trade("t2", "1", "0.5"));
+        assert_eq!(state(&manager, &client_id).0, OrderState::AmendPending);
+
+        assert!(
+            manager
+                .finish_update(&amended, Err(GateError::Timeout))
+                .is_err()
+        );
+        assert_eq!(
+            state(&manager, &client_id),
+            (OrderState::PartiallyFilled, 1.5)
+        );
+    }
+
+    #[test]
+    fn unknown_order_cannot_be_amended() {
+        let manager = OrderManager::new();
+        let amend = crate::api::spot::amend_order("404", "BTC_USDT");
+        assert!(matches!(
+            manager.begin_amend(amend),
+            Err(OrderManagerError::UnknownOrder(_))
+        ));
+    }
+
+    #[test]
+    fn prune_drops_terminal_orders() {
+        let manager = OrderManager::new();
+        let closed = placed(&manager, "1");
+        manager.apply_order(&details("1", "", "closed", "1", "1"));
+        manager.prune();
+        assert!(manager.get(closed.as_str()).is_none());
+        assert!(!manager.lock().fills.contains_key(&closed));
+    }
+
+    #[cfg(feature = "enable-ureq")]
+    #[test]
+    fn paper_client_backs_the_manager() {
+        use crate::paper::{Fees, PaperClient, RecordedMarket};
+
+        let market = RecordedMarket::new().fixture(
+            "/api/v4/spot/order_book",
+            &[("currency_pair", "BTC_USDT")],
+            r#"{"asks": [["30000", "1"]], "bids": [["29990", "1"]]}"#,
+        );
+        let paper = PaperClient::with_source(market)
+            .fees(Fees::new(0.001, 0.002))
+            .balance("USDT", 50_000.0);
+        let manager = OrderManager::new();
+
+        let order = manager
+            .create(&paper, create_order("BTC_USDT", "buy", "1").price("29000"))
+            .unwrap();
+        assert_eq!(order.state, OrderState::Open);
+
+        let order = manager.cancel(&paper, order.client_id.as_str()).unwrap();
+        assert_eq!(order.state, OrderState::Cancelled);
+    }
+}
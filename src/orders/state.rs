@@ -0,0 +1,113 @@
+use crate::api::spot::client_order_id::ClientOrderId;
+use crate::api::spot::model::{OrderDetails, UserTrade};
+use crate::utils::number;
+
+/// Local lifecycle state of a managed order
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum OrderState {
+    /// Sent to Gate, not yet acknowledged
+    PendingNew,
+    /// Resting in the order book, nothing filled
+    Open,
+    /// Resting in the order book, partly filled
+    PartiallyFilled,
+    /// An amendment was sent and is not yet acknowledged
+    AmendPending,
+    /// Finished, fully filled or closed by the matching engine
+    Closed,
+    /// Cancelled, possibly after partial fills
+    Cancelled,
+    /// Refused by Gate when it was created
+    Rejected,
+}
+
+impl OrderState {
+    /// Whether no further transition can happen
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Closed | OrderState::Cancelled | OrderState::Rejected
+        )
+    }
+
+    /// State reported by the exchange for `details`
+    pub(crate) fn from_details(details: &OrderDetails) -> Self {
+        match details.status.as_str() {
+            "closed" => OrderState::Closed,
+            "cancelled" => OrderState::Cancelled,
+            _ if number(&details.filled_amount) > 0.0 => OrderState::PartiallyFilled,
+            _ => OrderState::Open,
+        }
+    }
+}
+
+/// Order tracked by an [`OrderManager`](crate::orders::OrderManager)
+#[derive(PartialEq, Clone, Debug)]
+pub struct ManagedOrder {
+    /// Client order id (`text`)
+    pub client_id: ClientOrderId,
+    /// Exchange order id, once acknowledged
+    pub order_id: Option<String>,
+    /// Currency pair
+    pub currency_pair: String,
+    /// Order side
+    pub side: String,
+    /// Requested amount
+    pub amount: String,
+    /// Limit price
+    pub price: Option<String>,
+    /// Current state
+    pub state: OrderState,
+    /// Amount filled so far
+    pub filled_amount: f64,
+    /// How the order finished, as reported by Gate
+    pub finish_as: Option<String>,
+    /// Local creation time (UNIX milliseconds)
+    pub created_at: i64,
+    /// Last local update (UNIX milliseconds)
+    pub updated_at: i64,
+}
+
+impl ManagedOrder {
+    /// Identifier accepted by the order endpoints: the order id, or the client id before acknowledgement
+    pub fn order_ref(&self) -> &str {
+        self.order_id.as_deref().unwrap_or(self.client_id.as_str())
+    }
+
+    /// State to return to when an amendment fails
+    pub(crate) fn resting_state(&self) -> OrderState {
+        if self.filled_amount > 0.0 {
+            OrderState::PartiallyFilled
+        } else {
+            OrderState::Open
+        }
+    }
+}
+
+/// Change published to [`OrderManager`](crate::orders::OrderManager) subscribers
+#[derive(PartialEq, Clone, Debug)]
+pub enum OrderEvent {
+    /// A new order was registered and is being sent
+    Submitted(ManagedOrder),
+    /// The state of an order changed
+    StateChanged {
+        /// State before the change
+        previous: OrderState,
+        /// Order after the change
+        order: ManagedOrder,
+    },
+    /// A trade of an order was seen for the first time
+    Fill {
+        /// The trade
+        trade: Box<UserTrade>,
+        /// Order after the fill
+        order: ManagedOrder,
+    },
+    /// Gate refused the order
+    Rejected {
+        /// Error returned by Gate
+        reason: String,
+        /// The rejected order
+        order: ManagedOrder,
+    },
+}
@@ -0,0 +1,275 @@
+//! Automatic pagination for paged endpoints.
+//!
+//! Requests implementing [`Paginated`] can be walked page by page with
+//! `paginate()` on either client: the ureq client returns an [`Iterator`],
+//! the hyper client an async `Stream`. Both yield individual records and stop
+//! after the first page shorter than the page size.
+//!
+//! Two strategies are supported:
+//!
+//! - `paginate(request)`: walk pages 1, 2, 3… of a single query
+//! - `paginate_windows(request, from, to, window)`: for [`TimeWindowed`]
+//!   endpoints, split a `from`/`to` range into windows (for endpoints that cap
+//!   the queried time span) and walk the pages of each window in turn
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::{api::spot::get_my_trades, http::Credentials, ureq::GateHttpClient};
+//! use serde_json::Value;
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//!
+//! for trade in client.paginate::<Value, _>(get_my_trades().currency_pair("BTC_USDT")) {
+//!     println!("{}", trade?);
+//! }
+//!
+//! // One month of history, queried one day at a time
+//! let trades = client
+//!     .paginate_windows::<Value, _>(get_my_trades(), 1_700_000_000, 1_702_592_000, 86_400)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::request::Request;
+
+/// Request builder of an endpoint returning a JSON array in pages
+pub trait Paginated: Clone + Into<Request> {
+    /// Page size Gate applies when no `limit` is sent
+    const DEFAULT_LIMIT: u32;
+
+    /// Returns the configured page size
+    fn page_limit(&self) -> Option<u32>;
+
+    /// Sets the page size
+    fn set_page_limit(&mut self, limit: u32);
+
+    /// Returns the configured 1-based start page
+    fn start_page(&self) -> u32 {
+        1
+    }
+
+    /// Positions the request on the 1-based `page`
+    fn set_page(&mut self, page: u32);
+}
+
+/// Paged endpoint that can also be restricted to a time range
+pub trait TimeWindowed: Paginated {
+    /// Restricts the request to the inclusive range `from..=to` (UNIX seconds)
+    fn set_window(&mut self, from: i64, to: i64);
+}
+
+/// Remaining time windows: current start, overall end, window length
+type Windows<R> = (i64, i64, i64, fn(&mut R, i64, i64));
+
+/// Backend independent pagination state machine
+pub(crate) struct Pager<R> {
+    request: R,
+    limit: u32,
+    page: u32,
+    windows: Option<Windows<R>>,
+    done: bool,
+}
+
+impl<R: Paginated> Pager<R> {
+    /// Walks the pages of `request`
+    pub(crate) fn new(request: R) -> Self {
+        let limit = request.page_limit().unwrap_or(R::DEFAULT_LIMIT).max(1);
+        let page = request.start_page();
+        Self {
+            request,
+            limit,
+            page,
+            windows: None,
+            done: false,
+        }
+    }
+
+    /// Walks the pages of every `window` seconds long slice of `from..=to`
+    pub(crate) fn windows(request: R, from: i64, to: i64, window: i64) -> Self
+    where
+        R: TimeWindowed,
+    {
+        let mut pager = Self::new(request);
+        pager.windows = Some((from, to, window.max(1), R::set_window));
+        pager.done = to < from;
+        pager
+    }
+
+    /// Next request to send, `None` once every page was fetched
+    pub(crate) fn next_request(&self) -> Option<R> {
+        if self.done {
+            return None;
+        }
+        let mut request = self.request.clone();
+        request.set_page_limit(self.limit);
+        request.set_page(self.page);
+        if let Some((from, to, window, set_window)) = self.windows {
+            set_window(&mut request, from, to.min(from.saturating_add(window - 1)));
+        }
+        Some(request)
+    }
+
+    /// Records the number of records returned by the last request
+    pub(crate) fn record(&mut self, count: usize) {
+        if count >= self.limit as usize {
+            self.page += 1;
+            return;
+        }
+        match self.windows {
+            Some((from, to, window, set_window)) if from.saturating_add(window) <= to => {
+                self.windows = Some((from + window, to, window, set_window));
+                self.page = self.request.start_page();
+            }
+            _ => self.done = true,
+        }
+    }
+
+    /// Stops the pagination, e.g. after an error
+    pub(crate) fn finish(&mut self) {
+        self.done = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::spot::{get_my_trades, get_my_trades::GetMyTrades, get_price_orders};
+
+    fn params<R: Paginated>(request: R) -> Vec<(String, String)> {
+        let request: Request = request.into();
+        request.params
+    }
+
+    fn param<R: Paginated>(request: R, key: &str) -> Option<String> {
+        params(request)
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[test]
+    fn walks_pages_until_short_page() {
+        let mut pager = Pager::new(get_my_trades().limit(2));
+        assert_eq!(
+            param(pager.next_request().unwrap(), "page").as_deref(),
+            Some("1")
+        );
+        pager.record(2);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "page").as_deref(),
+            Some("2")
+        );
+        pager.record(1);
+        assert!(pager.next_request().is_none());
+    }
+
+    #[test]
+    fn starts_on_configured_page() {
+        let mut pager = Pager::new(get_my_trades().page(3).limit(10));
+        assert_eq!(
+            param(pager.next_request().unwrap(), "page").as_deref(),
+            Some("3")
+        );
+        pager.record(10);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "page").as_deref(),
+            Some("4")
+        );
+    }
+
+    #[test]
+    fn empty_page_finishes() {
+        let mut pager = Pager::new(get_my_trades());
+        pager.record(0);
+        assert!(pager.next_request().is_none());
+    }
+
+    #[test]
+    fn offset_advances_from_raw_offset() {
+        let mut pager = Pager::new(get_price_orders().offset(150).limit(100));
+        assert_eq!(
+            param(pager.next_request().unwrap(), "offset").as_deref(),
+            Some("150")
+        );
+        pager.record(100);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "offset").as_deref(),
+            Some("250")
+        );
+        pager.record(100);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "offset").as_deref(),
+            Some("350")
+        );
+    }
+
+    #[test]
+    fn offset_defaults_to_zero() {
+        let mut pager = Pager::new(get_price_orders());
+        assert_eq!(
+            param(pager.next_request().unwrap(), "offset").as_deref(),
+            Some("0")
+        );
+        pager.record(100);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "offset").as_deref(),
+            Some("100")
+        );
+    }
+
+    #[test]
+    fn windows_restart_pages_and_cover_range() {
+        let mut pager = Pager::windows(get_my_trades().limit(1), 0, 25, 10);
+        let window = |request: GetMyTrades| {
+            (
+                param(request.clone(), "from").unwrap(),
+                param(request.clone(), "to").unwrap(),
+                param(request, "page").unwrap(),
+            )
+        };
+        assert_eq!(
+            window(pager.next_request().unwrap()),
+            ("0".into(), "9".into(), "1".into())
+        );
+        pager.record(1);
+        assert_eq!(
+            window(pager.next_request().unwrap()),
+            ("0".into(), "9".into(), "2".into())
+        );
+        pager.record(0);
+        assert_eq!(
+            window(pager.next_request().unwrap()),
+            ("10".into(), "19".into(), "1".into())
+        );
+        pager.record(0);
+        assert_eq!(
+            window(pager.next_request().unwrap()),
+            ("20".into(), "25".into(), "1".into())
+        );
+        pager.record(0);
+        assert!(pager.next_request().is_none());
+    }
+
+    #[test]
+    fn inverted_window_range_is_empty() {
+        let pager = Pager::windows(get_my_trades(), 10, 0, 5);
+        assert!(pager.next_request().is_none());
+    }
+
+    #[test]
+    fn zero_window_is_clamped() {
+        let mut pager = Pager::windows(get_my_trades(), 0, 1, 0);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "to").as_deref(),
+            Some("0")
+        );
+        pager.record(0);
+        assert_eq!(
+            param(pager.next_request().unwrap(), "from").as_deref(),
+            Some("1")
+        );
+        pager.record(0);
+        assert!(pager.next_request().is_none());
+    }
+}
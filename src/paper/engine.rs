@@ -0,0 +1,850 @@
+use crate::api::spot::{OrderDetails, UserTrade};
+use crate::http::{GateError, GateErrorLabel};
+use crate::utils::{format_number, number};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Amounts below this are treated as zero
+const EPSILON: f64 = 1e-12;
+
+/// Trading fee rates, as fractions
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Fees {
+    /// Rate paid by orders resting on the book
+    pub maker: f64,
+    /// Rate paid by orders taking liquidity
+    pub taker: f64,
+}
+
+impl Fees {
+    /// Creates fee rates, e.g. `Fees::new(0.002, 0.002)` for 0.2%
+    pub fn new(maker: f64, taker: f64) -> Self {
+        Self { maker, taker }
+    }
+
+    /// Parses a `get_fee` response
+    pub(crate) fn parse(body: &str) -> Result<Self, GateError> {
+        #[derive(Deserialize)]
+        struct Fee {
+            maker_fee: String,
+            taker_fee: String,
+        }
+        let fee: Fee = crate::utils::parse(body)?;
+        Ok(Self::new(number(&fee.maker_fee), number(&fee.taker_fee)))
+    }
+}
+
+/// Order book snapshot, best levels first
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Book {
+    asks: Vec<(f64, f64)>,
+    bids: Vec<(f64, f64)>,
+}
+
+impl Book {
+    /// Parses a `get_orderbook` response
+    pub(crate) fn parse(body: &str) -> Result<Self, GateError> {
+        #[derive(Deserialize)]
+        struct Snapshot {
+            asks: Vec<(String, String)>,
+            bids: Vec<(String, String)>,
+        }
+        let snapshot: Snapshot = crate::utils::parse(body)?;
+        let levels = |levels: Vec<(String, String)>| {
+            levels
+                .into_iter()
+                .map(|(price, amount)| (number(&price), number(&amount)))
+                .collect()
+        };
+        Ok(Self {
+            asks: levels(snapshot.asks),
+            bids: levels(snapshot.bids),
+        })
+    }
+
+    /// Levels an order on `side` trades against
+    fn opposite(&mut self, side: Side) -> &mut Vec<(f64, f64)> {
+        match side {
+            Side::Buy => &mut self.asks,
+            Side::Sell => &mut self.bids,
+        }
+    }
+}
+
+/// Request refused the way Gate would refuse it
+#[derive(Debug)]
+pub(crate) struct Rejection {
+    pub(crate) status: u16,
+    pub(crate) label: GateErrorLabel,
+    pub(crate) message: String,
+}
+
+impl Rejection {
+    fn new(label: GateErrorLabel, message: impl Into<String>) -> Self {
+        let status = match label {
+            GateErrorLabel::OrderNotFound => 404,
+            _ => 400,
+        };
+        Self {
+            status,
+            label,
+            message: message.into(),
+        }
+    }
+}
+
+/// Order fields read from a `create_order` or `create_batch_orders` payload
+#[derive(Deserialize)]
+pub(crate) struct NewOrder {
+    currency_pair: String,
+    side: String,
+    amount: String,
+    #[serde(default)]
+    price: Option<String>,
+    #[serde(rename = "type", default)]
+    order_type: Option<String>,
+    #[serde(default)]
+    time_in_force: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    account: Option<String>,
+}
+
+impl NewOrder {
+    pub(crate) fn currency_pair(&self) -> &str {
+        &self.currency_pair
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+/// Paper balance of a currency
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct Balance {
+    /// Amount free to trade
+    pub available: f64,
+    /// Amount held by open orders
+    pub locked: f64,
+}
+
+#[derive(Debug)]
+struct PaperOrder {
+    details: OrderDetails,
+    side: Side,
+    base: String,
+    quote: String,
+    /// Base amount, or quote amount for market buys
+    amount: f64,
+    /// Limit price, `None` for market orders
+    price: Option<f64>,
+    filled: f64,
+    filled_total: f64,
+    fee: f64,
+    /// Funds still locked by the order
+    locked: f64,
+}
+
+impl PaperOrder {
+    fn lock_currency(&self) -> &str {
+        match self.side {
+            Side::Buy => &self.quote,
+            Side::Sell => &self.base,
+        }
+    }
+
+    /// Amount still to fill, in the unit of `amount`
+    fn left(&self) -> f64 {
+        let done = match (self.side, self.price) {
+            (Side::Buy, None) => self.filled_total,
+            _ => self.filled,
+        };
+        (self.amount - done).max(0.0)
+    }
+
+    fn is_open(&self) -> bool {
+        self.details.status == "open"
+    }
+
+    fn sync_details(&mut self, now_ms: i64) {
+        let left = self.left();
+        let details = &mut self.details;
+        details.left = format_number(left);
+        details.filled_amount = format_number(self.filled);
+        details.filled_total = format_number(self.filled_total);
+        details.avg_deal_price = if self.filled > EPSILON {
+            format_number(self.filled_total / self.filled)
+        } else {
+            String::new()
+        };
+        details.fee = format_number(self.fee);
+        details.update_time_ms = now_ms;
+    }
+}
+
+/// In-memory exchange account: balances, orders and trades
+#[derive(Debug, Default)]
+pub(crate) struct Engine {
+    balances: BTreeMap<String, Balance>,
+    orders: Vec<PaperOrder>,
+    trades: Vec<UserTrade>,
+    next_id: u64,
+}
+
+impl Engine {
+    pub(crate) fn deposit(&mut self, currency: &str, amount: f64) {
+        self.balances
+            .entry(currency.to_owned())
+            .or_default()
+            .available += amount;
+    }
+
+    pub(crate) fn balances(&self) -> &BTreeMap<String, Balance> {
+        &self.balances
+    }
+
+    fn balance(&mut self, currency: &str) -> &mut Balance {
+        self.balances.entry(currency.to_owned()).or_default()
+    }
+
+    fn next_id(&mut self) -> String {
+        self.next_id += 1;
+        self.next_id.to_string()
+    }
+
+    /// Places an order, taking liquidity from `book` at `fees.taker`
+    pub(crate) fn create(
+        &mut self,
+        new: NewOrder,
+        mut book: Book,
+        fees: Fees,
+        now_ms: i64,
+    ) -> Result<OrderDetails, Rejection> {
+        let (base, quote) = new
+            .currency_pair
+            .split_once('_')
+            .map(|(base, quote)| (base.to_owned(), quote.to_owned()))
+            .ok_or_else(|| {
+                Rejection::new(
+                    GateErrorLabel::InvalidCurrencyPair,
+                    format!("Invalid currency pair {}", new.currency_pair),
+                )
+            })?;
+        let side = match new.side.as_str() {
+            "buy" => Side::Buy,
+            "sell" => Side::Sell,
+            other => {
+                return Err(Rejection::new(
+                    GateErrorLabel::InvalidParamValue,
+                    format!("Invalid side {}", other),
+                ));
+            }
+        };
+        let amount = positive("amount", &new.amount)?;
+        let market = new.order_type.as_deref() == Some("market");
+        let price = match (&new.price, market) {
+            (_, true) => None,
+            (Some(price), false) => Some(positive("price", price)?),
+            (None, false) => {
+                return Err(Rejection::new(
+                    GateErrorLabel::MissingRequiredParam,
+                    "Limit orders need a price",
+                ));
+            }
+        };
+        let time_in_force = new
+            .time_in_force
+            .clone()
+            .unwrap_or_else(|| if market { "ioc" } else { "gtc" }.to_owned());
+        if let Some(text) = &new.text
+            && self
+                .orders
+                .iter()
+                .any(|o| o.is_open() && &o.details.text == text)
+        {
+            return Err(Rejection::new(
+                GateErrorLabel::RepeatedCreation,
+                format!("Order {} already exists", text),
+            ));
+        }
+
+        let levels = book.opposite(side);
+        let crosses = |level: f64| match (side, price) {
+            (_, None) => true,
+            (Side::Buy, Some(price)) => level <= price + EPSILON,
+            (Side::Sell, Some(price)) => level >= price - EPSILON,
+        };
+        if time_in_force == "poc" && levels.first().is_some_and(|(p, _)| crosses(*p)) {
+            return Err(Rejection::new(
+                GateErrorLabel::PocFillImmediately,
+                "Order would be filled immediately",
+            ));
+        }
+        if time_in_force == "fok" {
+            let fillable: f64 = levels
+                .iter()
+                .take_while(|(p, _)| crosses(*p))
+                .map(|(p, q)| {
+                    if side == Side::Buy && market {
+                        p * q
+                    } else {
+                        *q
+                    }
+                })
+                .sum();
+            if fillable + EPSILON < amount {
+                return Err(Rejection::new(
+                    GateErrorLabel::FokNotFill,
+                    "Order cannot be filled completely",
+                ));
+            }
+        }
+
+        let (lock_currency, lock) = match (side, price) {
+            (Side::Buy, Some(price)) => (&quote, amount * price),
+            _ => (if side == Side::Buy { &quote } else { &base }, amount),
+        };
+        let available = self
+            .balances
+            .get(lock_currency)
+            .map_or(0.0, |b| b.available);
+        if available + EPSILON < lock {
+            return Err(Rejection::new(
+                GateErrorLabel::BalanceNotEnough,
+                format!(
+                    "Not enough {}: {} needed, {} available",
+                    lock_currency, lock, available
+                ),
+            ));
+        }
+        let balance = self.balance(lock_currency);
+        balance.available -= lock;
+        balance.locked += lock;
+
+        let id = self.next_id();
+        let mut order = PaperOrder {
+            details: OrderDetails {
+                id,
+                text: new.text.unwrap_or_default(),
+                amend_text: String::new(),
+                create_time_ms: now_ms,
+                update_time_ms: now_ms,
+                status: "open".into(),
+                currency_pair: new.currency_pair,
+                order_type: if market { "market" } else { "limit" }.into(),
+                account: new.account.unwrap_or_else(|| "spot".into()),
+                side: new.side,
+                amount: new.amount,
+                price: new.price.filter(|_| !market).unwrap_or_default(),
+                time_in_force: time_in_force.clone(),
+                left: String::new(),
+                filled_amount: String::new(),
+                filled_total: String::new(),
+                avg_deal_price: String::new(),
+                fee: String::new(),
+                fee_currency: match side {
+                    Side::Buy => base.clone(),
+                    Side::Sell => quote.clone(),
+                },
+                finish_as: "open".into(),
+            },
+            side,
+            base,
+            quote,
+            amount,
+            price,
+            filled: 0.0,
+            filled_total: 0.0,
+            fee: 0.0,
+            locked: lock,
+        };
+
+        for &(level_price, level_amount) in levels.iter() {
+            if !crosses(level_price) || order.left() <= EPSILON {
+                break;
+            }
+            let quantity = match (side, price) {
+                (Side::Buy, None) => level_amount.min(order.left() / level_price),
+                _ => level_amount.min(order.left()),
+            };
+            if quantity > EPSILON {
+                self.fill(
+                    &mut order,
+                    quantity,
+                    level_price,
+                    fees.taker,
+                    "taker",
+                    now_ms,
+                );
+            }
+        }
+
+        if order.left() <= EPSILON {
+            self.finish(&mut order, "closed", "filled");
+        } else if market || time_in_force == "ioc" || time_in_force == "fok" {
+            self.finish(&mut order, "cancelled", "ioc");
+        }
+        order.sync_details(now_ms);
+        let details = order.details.clone();
+        self.orders.push(order);
+        Ok(details)
+    }
+
+    /// Fills resting orders of `currency_pair` crossed by `book`, at their own price
+    pub(crate) fn match_resting(
+        &mut self,
+        currency_pair: &str,
+        mut book: Book,
+        fees: Fees,
+        now_ms: i64,
+    ) {
+        let mut orders = std::mem::take(&mut self.orders);
+        for order in orders
+            .iter_mut()
+            .filter(|o| o.is_open() && o.details.currency_pair == currency_pair)
+        {
+            let Some(price) = order.price else { continue };
+            for level in book.opposite(order.side).iter_mut() {
+                let crosses = match order.side {
+                    Side::Buy => level.0 <= price + EPSILON,
+                    Side::Sell => level.0 >= price - EPSILON,
+                };
+                if !crosses || order.left() <= EPSILON {
+                    break;
+                }
+                let quantity = level.1.min(order.left());
+                if quantity > EPSILON {
+                    // Liquidity taken by one resting order is gone for the next
+                    level.1 -= quantity;
+                    self.fill(order, quantity, price, fees.maker, "maker", now_ms);
+                }
+            }
+            if order.left() <= EPSILON {
+                self.finish(order, "closed", "filled");
+            }
+            order.sync_details(now_ms);
+        }
+        self.orders = orders;
+    }
+
+    /// Changes the price or amount of an open order
+    pub(crate) fn amend(
+        &mut self,
+        order_id: &str,
+        amount: Option<&str>,
+        price: Option<&str>,
+        now_ms: i64,
+    ) -> Result<OrderDetails, Rejection> {
+        let index = self.open_index(order_id)?;
+        let order = &self.orders[index];
+        let Some(old_price) = order.price else {
+            return Err(Rejection::new(
+                GateErrorLabel::InvalidParamValue,
+                "Market orders cannot be amended",
+            ));
+        };
+        let new_price = price.map(|p| positive("price", p)).transpose()?;
+        let new_amount = amount.map(|a| positive("amount", a)).transpose()?;
+        if new_amount.is_some_and(|a| a <= order.filled + EPSILON) {
+            return Err(Rejection::new(
+                GateErrorLabel::InvalidParamValue,
+                "Amount must stay above the filled amount",
+            ));
+        }
+        let price = new_price.unwrap_or(old_price);
+        let amount = new_amount.unwrap_or(order.amount);
+        if price == old_price && amount == order.amount {
+            return Err(Rejection::new(GateErrorLabel::NoChange, "Nothing to amend"));
+        }
+
+        let left = amount - order.filled;
+        let lock = match order.side {
+            Side::Buy => left * price,
+            Side::Sell => left,
+        };
+        let extra = lock - order.locked;
+        let currency = order.lock_currency().to_owned();
+        let available = self.balances.get(&currency).map_or(0.0, |b| b.available);
+        if extra > available + EPSILON {
+            return Err(Rejection::new(
+                GateErrorLabel::BalanceNotEnough,
+                format!("Not enough {}: {} more needed", currency, extra),
+            ));
+        }
+        let balance = self.balance(&currency);
+        balance.available -= extra;
+        balance.locked += extra;
+
+        let order = &mut self.orders[index];
+        order.locked = lock;
+        order.price = Some(price);
+        order.amount = amount;
+        order.details.price = format_number(price);
+        order.details.amount = format_number(amount);
+        order.sync_details(now_ms);
+        Ok(order.details.clone())
+    }
+
+    /// Cancels an open order, releasing its funds
+    pub(crate) fn cancel(
+        &mut self,
+        order_id: &str,
+        now_ms: i64,
+    ) -> Result<OrderDetails, Rejection> {
+        let index = self.open_index(order_id)?;
+        let mut order = self.orders.remove(index);
+        self.finish(&mut order, "cancelled", "cancelled");
+        order.sync_details(now_ms);
+        let details = order.details.clone();
+        self.orders.insert(index, order);
+        Ok(details)
+    }
+
+    pub(crate) fn order(&self, order_id: &str) -> Result<OrderDetails, Rejection> {
+        self.orders
+            .iter()
+            .find(|o| o.details.id == order_id || o.details.text == order_id)
+            .map(|o| o.details.clone())
+            .ok_or_else(|| Rejection::new(GateErrorLabel::OrderNotFound, "Order not found"))
+    }
+
+    /// Orders, newest first
+    pub(crate) fn orders(&self) -> impl Iterator<Item = &OrderDetails> {
+        self.orders.iter().rev().map(|o| &o.details)
+    }
+
+    /// Trades, newest first
+    pub(crate) fn trades(&self) -> impl Iterator<Item = &UserTrade> {
+        self.trades.iter().rev()
+    }
+
+    /// Currency pairs with open orders
+    pub(crate) fn open_pairs(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self
+            .orders
+            .iter()
+            .filter(|o| o.is_open())
+            .map(|o| o.details.currency_pair.clone())
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    fn open_index(&self, order_id: &str) -> Result<usize, Rejection> {
+        let index = self
+            .orders
+            .iter()
+            .position(|o| o.details.id == order_id || o.details.text == order_id)
+            .ok_or_else(|| Rejection::new(GateErrorLabel::OrderNotFound, "Order not found"))?;
+        match self.orders[index].details.status.as_str() {
+            "open" => Ok(index),
+            "cancelled" => Err(Rejection::new(
+                GateErrorLabel::OrderCancelled,
+                "Order cancelled",
+            )),
+            _ => Err(Rejection::new(GateErrorLabel::OrderClosed, "Order closed")),
+        }
+    }
+
+    fn fill(
+        &mut self,
+        order: &mut PaperOrder,
+        quantity: f64,
+        price: f64,
+        fee_rate: f64,
+        role: &str,
+        now_ms: i64,
+    ) {
+        let cost = quantity * price;
+        let unlocked = match (order.side, order.price) {
+            (Side::Buy, Some(limit)) => quantity * limit,
+            (Side::Buy, None) => cost,
+            (Side::Sell, _) => quantity,
+        };
+        let lock_currency = order.lock_currency().to_owned();
+        let balance = self.balance(&lock_currency);
+        balance.locked -= unlocked;
+        // A buy filled below its limit gets the difference back
+        if order.side == Side::Buy {
+            balance.available += unlocked - cost;
+        }
+        order.locked -= unlocked;
+
+        let (currency, received) = match order.side {
+            Side::Buy => (order.base.clone(), quantity),
+            Side::Sell => (order.quote.clone(), cost),
+        };
+        let fee = received * fee_rate;
+        self.balance(&currency).available += received - fee;
+        order.filled += quantity;
+        order.filled_total += cost;
+        order.fee += fee;
+
+        self.next_id += 1;
+        self.trades.push(UserTrade {
+            id: self.next_id.to_string(),
+            create_time_ms: now_ms as f64,
+            currency_pair: order.details.currency_pair.clone(),
+            side: order.details.side.clone(),
+            role: role.into(),
+            amount: format_number(quantity),
+            price: format_number(price),
+            order_id: order.details.id.clone(),
+            fee: format_number(fee),
+            fee_currency: currency,
+            point_fee: "0".into(),
+            gt_fee: "0".into(),
+            text: order.details.text.clone(),
+        });
+    }
+
+    /// Ends an order, releasing what it still locks
+    fn finish(&mut self, order: &mut PaperOrder, status: &str, finish_as: &str) {
+        let lock_currency = order.lock_currency().to_owned();
+        let locked = order.locked;
+        let balance = self.balance(&lock_currency);
+        balance.locked -= locked;
+        balance.available += locked;
+        order.locked = 0.0;
+        order.details.status = status.into();
+        order.details.finish_as = finish_as.into();
+    }
+}
+
+fn positive(name: &str, value: &str) -> Result<f64, Rejection> {
+    match value.parse::<f64>() {
+        Ok(number) if number > 0.0 && number.is_finite() => Ok(number),
+        _ => Err(Rejection::new(
+            GateErrorLabel::InvalidParamValue,
+            format!("Invalid {} {}", name, value),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NO_FEES: Fees = Fees {
+        maker: 0.0,
+        taker: 0.0,
+    };
+
+    fn book(asks: &[(f64, f64)], bids: &[(f64, f64)]) -> Book {
+        Book {
+            asks: asks.to_vec(),
+            bids: bids.to_vec(),
+        }
+    }
+
+    fn order(fields: serde_json::Value) -> NewOrder {
+        let mut order = json!({"currency_pair": "BTC_USDT"});
+        order
+            .as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(order).unwrap()
+    }
+
+    fn balance(engine: &Engine, currency: &str) -> Balance {
+        engine.balances().get(currency).copied().unwrap_or_default()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    fn label(result: Result<OrderDetails, Rejection>) -> GateErrorLabel {
+        result.unwrap_err().label
+    }
+
+    #[test]
+    fn parses_book_and_fees() {
+        let book = Book::parse(r#"{"asks":[["101","2"]],"bids":[["99","1.5"]]}"#).unwrap();
+        assert_eq!(book.asks, vec![(101.0, 2.0)]);
+        assert_eq!(book.bids, vec![(99.0, 1.5)]);
+        let fees = Fees::parse(r#"{"maker_fee":"0.001","taker_fee":"0.002"}"#).unwrap();
+        assert_eq!(fees, Fees::new(0.001, 0.002));
+        assert!(Book::parse("{}").is_err());
+    }
+
+    #[test]
+    fn limit_buy_takes_then_rests() {
+        let mut engine = Engine::default();
+        engine.deposit("USDT", 200.0);
+        let new = order(json!({"side": "buy", "amount": "1", "price": "100.5"}));
+        let details = engine
+            .create(
+                new,
+                book(&[(100.0, 0.5), (101.0, 1.0)], &[]),
+                Fees::new(0.001, 0.002),
+                1,
+            )
+            .unwrap();
+        assert_eq!(details.status, "open");
+        assert_eq!(details.filled_amount, "0.5");
+        assert_eq!(details.left, "0.5");
+        assert_eq!(details.fee, "0.001");
+        assert_eq!(details.fee_currency, "BTC");
+        assert_close(balance(&engine, "BTC").available, 0.499);
+        // The fill below the limit gives the difference back
+        assert_close(balance(&engine, "USDT").locked, 50.25);
+        assert_close(balance(&engine, "USDT").available, 99.75);
+        assert_eq!(engine.open_pairs(), vec!["BTC_USDT".to_owned()]);
+
+        engine.match_resting(
+            "BTC_USDT",
+            book(&[(100.2, 1.0)], &[]),
+            Fees::new(0.001, 0.002),
+            2,
+        );
+        let details = engine.order(&details.id).unwrap();
+        assert_eq!(details.status, "closed");
+        assert_eq!(details.finish_as, "filled");
+        assert_close(balance(&engine, "BTC").available, 0.9985);
+        assert_close(balance(&engine, "USDT").locked, 0.0);
+        assert_close(balance(&engine, "USDT").available, 99.75);
+        let roles: Vec<&str> = engine.trades().map(|t| t.role.as_str()).collect();
+        assert_eq!(roles, vec!["maker", "taker"]);
+        assert!(engine.open_pairs().is_empty());
+    }
+
+    #[test]
+    fn market_buy_spends_quote_amount() {
+        let mut engine = Engine::default();
+        engine.deposit("USDT", 100.0);
+        let new = order(json!({"side": "buy", "amount": "100", "type": "market"}));
+        let details = engine
+            .create(new, book(&[(100.0, 0.5), (200.0, 1.0)], &[]), NO_FEES, 1)
+            .unwrap();
+        assert_eq!(details.status, "closed");
+        assert_eq!(details.filled_total, "100");
+        assert_eq!(details.filled_amount, "0.75");
+        assert_eq!(details.price, "");
+        assert_close(balance(&engine, "BTC").available, 0.75);
+        assert_close(balance(&engine, "USDT").available, 0.0);
+        assert_close(balance(&engine, "USDT").locked, 0.0);
+    }
+
+    #[test]
+    fn ioc_remainder_is_released() {
+        let mut engine = Engine::default();
+        engine.deposit("BTC", 2.0);
+        let new = order(json!({
+            "side": "sell", "amount": "2", "price": "100", "time_in_force": "ioc"
+        }));
+        let details = engine
+            .create(new, book(&[], &[(101.0, 1.0), (99.0, 5.0)]), NO_FEES, 1)
+            .unwrap();
+        assert_eq!(details.status, "cancelled");
+        assert_eq!(details.finish_as, "ioc");
+        assert_eq!(details.filled_amount, "1");
+        assert_close(balance(&engine, "BTC").available, 1.0);
+        assert_close(balance(&engine, "BTC").locked, 0.0);
+        assert_close(balance(&engine, "USDT").available, 101.0);
+    }
+
+    #[test]
+    fn rejects_like_gate() {
+        let mut engine = Engine::default();
+        engine.deposit("USDT", 100.0);
+        let asks = book(&[(100.0, 0.5)], &[]);
+        let poc =
+            order(json!({"side": "buy", "amount": "0.1", "price": "100", "time_in_force": "poc"}));
+        assert_eq!(
+            label(engine.create(poc, asks.clone(), NO_FEES, 1)),
+            GateErrorLabel::PocFillImmediately
+        );
+        let fok =
+            order(json!({"side": "buy", "amount": "0.8", "price": "100", "time_in_force": "fok"}));
+        assert_eq!(
+            label(engine.create(fok, asks.clone(), NO_FEES, 1)),
+            GateErrorLabel::FokNotFill
+        );
+        let large = order(json!({"side": "buy", "amount": "2", "price": "99"}));
+        assert_eq!(
+            label(engine.create(large, asks.clone(), NO_FEES, 1)),
+            GateErrorLabel::BalanceNotEnough
+        );
+        let no_price = order(json!({"side": "buy", "amount": "0.1"}));
+        assert_eq!(
+            label(engine.create(no_price, asks.clone(), NO_FEES, 1)),
+            GateErrorLabel::MissingRequiredParam
+        );
+        let bad_amount = order(json!({"side": "buy", "amount": "-1", "price": "99"}));
+        assert_eq!(
+            label(engine.create(bad_amount, asks.clone(), NO_FEES, 1)),
+            GateErrorLabel::InvalidParamValue
+        );
+        let bad_pair =
+            order(json!({"currency_pair": "BTCUSDT", "side": "buy", "amount": "1", "price": "99"}));
+        assert_eq!(
+            label(engine.create(bad_pair, asks.clone(), NO_FEES, 1)),
+            GateErrorLabel::InvalidCurrencyPair
+        );
+
+        let first = order(json!({"side": "buy", "amount": "0.1", "price": "90", "text": "t-1"}));
+        engine.create(first, asks.clone(), NO_FEES, 1).unwrap();
+        let repeated = order(json!({"side": "buy", "amount": "0.1", "price": "90", "text": "t-1"}));
+        assert_eq!(
+            label(engine.create(repeated, asks, NO_FEES, 1)),
+            GateErrorLabel::RepeatedCreation
+        );
+        // Refused orders lock nothing
+        assert_close(balance(&engine, "USDT").locked, 9.0);
+    }
+
+    #[test]
+    fn amend_relocks_funds() {
+        let mut engine = Engine::default();
+        engine.deposit("USDT", 100.0);
+        let new = order(json!({"side": "buy", "amount": "0.5", "price": "90"}));
+        let details = engine.create(new, Book::default(), NO_FEES, 1).unwrap();
+        assert_close(balance(&engine, "USDT").locked, 45.0);
+
+        let amended = engine.amend(&details.id, Some("1"), Some("80"), 2).unwrap();
+        assert_eq!(amended.amount, "1");
+        assert_eq!(amended.price, "80");
+        assert_eq!(amended.left, "1");
+        assert_close(balance(&engine, "USDT").locked, 80.0);
+        assert_close(balance(&engine, "USDT").available, 20.0);
+
+        assert_eq!(
+            label(engine.amend(&details.id, None, Some("80"), 3)),
+            GateErrorLabel::NoChange
+        );
+        assert_eq!(
+            label(engine.amend(&details.id, Some("2"), None, 3)),
+            GateErrorLabel::BalanceNotEnough
+        );
+    }
+
+    #[test]
+    fn cancel_releases_funds() {
+        let mut engine = Engine::default();
+        engine.deposit("BTC", 1.0);
+        let new = order(json!({"side": "sell", "amount": "1", "price": "110", "text": "t-sell"}));
+        engine.create(new, Book::default(), NO_FEES, 1).unwrap();
+        assert_close(balance(&engine, "BTC").locked, 1.0);
+
+        let cancelled = engine.cancel("t-sell", 2).unwrap();
+        assert_eq!(cancelled.status, "cancelled");
+        assert_eq!(cancelled.update_time_ms, 2);
+        assert_close(balance(&engine, "BTC").available, 1.0);
+        assert_close(balance(&engine, "BTC").locked, 0.0);
+
+        assert_eq!(
+            label(engine.cancel(&cancelled.id, 3)),
+            GateErrorLabel::OrderCancelled
+        );
+        let missing = engine.cancel("42", 3).unwrap_err();
+        assert_eq!(missing.label, GateErrorLabel::OrderNotFound);
+        assert_eq!(missing.status, 404);
+    }
+}
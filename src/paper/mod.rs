@@ -0,0 +1,552 @@
+//! Paper trading client.
+//!
+//! [`PaperClient`] has the same `send` as [`GateHttpClient`] but never
+//! touches real funds. Public endpoints are answered by a market source (a
+//! real [`GateHttpClient`] or [`RecordedMarket`] fixtures); trading endpoints
+//! are simulated against an in-memory account:
+//!
+//! - `create_order`, `amend_order`, `cancel_order`, `cancel_all_open_orders`
+//!   and their batch variants
+//! - `get_order`, `get_orders`, `get_open_orders`, `get_my_trades`,
+//!   `get_account` and `get_fee`
+//!
+//! New orders cross against the latest `get_orderbook` snapshot and pay the
+//! taker rate; resting limit orders fill at their own price, paying the maker
+//! rate, once a later snapshot crosses them. Snapshots are fetched whenever
+//! an order of the pair is placed, amended or queried, or on
+//! [`PaperClient::match_orders`]. Fee rates come from `get_fee` on the source
+//! unless set with [`PaperClient::fees`]. Other signed endpoints are refused.
+//!
+//! Simulated errors are real Gate error responses, e.g. `BALANCE_NOT_ENOUGH`
+//! or `ORDER_NOT_FOUND`, so error handling can be exercised too.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::api::spot::{OrderDetails, create_order};
+//! use gateio_rs::paper::{Fees, PaperClient, RecordedMarket};
+//!
+//! let market = RecordedMarket::new().fixture(
+//!     "/api/v4/spot/order_book",
+//!     &[("currency_pair", "BTC_USDT")],
+//!     r#"{"asks": [["30000", "0.5"], ["30010", "1"]], "bids": [["29990", "1"]]}"#,
+//! );
+//! let client = PaperClient::with_source(market)
+//!     .fees(Fees::new(0.001, 0.002))
+//!     .balance("USDT", 50_000.0);
+//!
+//! let body = client
+//!     .send(create_order("BTC_USDT", "buy", "1").price("30010"))?
+//!     .into_body_str()?;
+//! let order: OrderDetails = serde_json::from_str(&body)?;
+//! assert_eq!(order.status, "closed");
+//! assert_eq!(order.filled_total, "30005");
+//! assert_eq!(order.fee, "0.002");
+//!
+//! let balances = client.balances();
+//! assert!((balances["BTC"].available - 0.998).abs() < 1e-9);
+//! assert!((balances["USDT"].available - 19_995.0).abs() < 1e-9);
+//!
+//! let refused = client.send(create_order("BTC_USDT", "buy", "10").price("30010"))?;
+//! assert!(refused.into_body_str().is_err());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod engine;
+
+pub use engine::{Balance, Fees};
+
+use crate::api::spot::{OpenOrders, get_fee, get_orderbook};
+use crate::http::{Method, request::Request};
+use crate::ureq::{Error, GateHttpClient, Response, SendRequest};
+use crate::utils::{format_number, now_ms};
+use engine::{Book, Engine, NewOrder, Rejection};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use ureq::Body;
+
+/// Depth of the snapshots orders are matched against
+const BOOK_DEPTH: i64 = 100;
+
+/// Simulated account state
+#[derive(Debug, Default)]
+struct State {
+    engine: Engine,
+    fees: HashMap<String, Fees>,
+}
+
+/// Client simulating trading endpoints against an in-memory account.
+///
+/// Clones share the same account.
+#[derive(Clone)]
+pub struct PaperClient<S = GateHttpClient> {
+    source: S,
+    fees: Option<Fees>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for PaperClient {
+    /// Creates a paper client on the Gate.io production market data
+    fn default() -> Self {
+        Self::with_source(GateHttpClient::default())
+    }
+}
+
+impl<S: SendRequest> PaperClient<S> {
+    /// Creates a paper client answering public endpoints from `source`
+    pub fn with_source(source: S) -> Self {
+        Self {
+            source,
+            fees: None,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Uses fixed fee rates instead of querying `get_fee` on the source
+    pub fn fees(mut self, fees: Fees) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
+    /// Credits `amount` of `currency` to the account
+    pub fn balance(self, currency: &str, amount: f64) -> Self {
+        self.deposit(currency, amount);
+        self
+    }
+
+    /// Credits `amount` of `currency` to the account
+    pub fn deposit(&self, currency: &str, amount: f64) {
+        self.state().engine.deposit(currency, amount);
+    }
+
+    /// Balances of the account
+    pub fn balances(&self) -> BTreeMap<String, Balance> {
+        self.state().engine.balances().clone()
+    }
+
+    /// The market data source
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Fills the resting orders crossed by the latest order book snapshots
+    pub fn match_orders(&self) -> Result<(), Box<Error>> {
+        let pairs = self.state().engine.open_pairs();
+        for pair in pairs {
+            self.match_pair(&pair)?;
+        }
+        Ok(())
+    }
+
+    /// Sends a request, simulating it when it touches the account
+    pub fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Box<Error>> {
+        let request = request.into();
+        let path = request.path().trim_start_matches("/api/v4");
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (request.method(), segments.as_slice()) {
+            (Method::Post, ["spot", "orders"]) => {
+                let order: NewOrder = match parse_payload(&request) {
+                    Ok(order) => order,
+                    Err(rejection) => return reject(rejection),
+                };
+                let (book, fees) = self.market(order.currency_pair())?;
+                let result = self.state().engine.create(order, book, fees, now_ms());
+                reply(result.map(|details| json!(details)))
+            }
+            (Method::Post, ["spot", "batch_orders"]) => {
+                let orders: Vec<NewOrder> = match parse_payload(&request) {
+                    Ok(orders) => orders,
+                    Err(rejection) => return reject(rejection),
+                };
+                let mut entries = Vec::new();
+                for order in orders {
+                    let (book, fees) = self.market(order.currency_pair())?;
+                    let result = self.state().engine.create(order, book, fees, now_ms());
+                    entries.push(batch_entry(result.map(|details| json!(details))));
+                }
+                respond(200, Value::Array(entries))
+            }
+            (Method::Patch, ["spot", "orders", order_id]) => {
+                let amendment: Amendment = match parse_payload(&request) {
+                    Ok(amendment) => amendment,
+                    Err(rejection) => return reject(rejection),
+                };
+                reply(self.amend(order_id, &amendment)?)
+            }
+            (Method::Post, ["spot", "amend_batch_orders"]) => {
+                let amendments: Vec<Amendment> = match parse_payload(&request) {
+                    Ok(amendments) => amendments,
+                    Err(rejection) => return reject(rejection),
+                };
+                let mut entries = Vec::new();
+                for amendment in amendments {
+                    let order_id = amendment.order_id.clone().unwrap_or_default();
+                    entries.push(batch_entry(self.amend(&order_id, &amendment)?));
+                }
+                respond(200, Value::Array(entries))
+            }
+            (Method::Delete, ["spot", "orders", order_id]) => {
+                let result = self.state().engine.cancel(order_id, now_ms());
+                reply(result.map(|details| json!(details)))
+            }
+            (Method::Delete, ["spot", "orders"]) => {
+                let pair = param(&request, "currency_pair");
+                let mut state = self.state();
+                let ids: Vec<String> = state
+                    .engine
+                    .orders()
+                    .filter(|o| o.status == "open" && pair.is_none_or(|p| o.currency_pair == p))
+                    .map(|o| o.id.clone())
+                    .collect();
+                let cancelled: Vec<Value> = ids
+                    .iter()
+                    .filter_map(|id| state.engine.cancel(id, now_ms()).ok())
+                    .map(|details| json!(details))
+                    .collect();
+                respond(200, Value::Array(cancelled))
+            }
+            (Method::Post, ["spot", "cancel_batch_orders"]) => {
+                let cancels: Vec<Cancel> = match parse_payload(&request) {
+                    Ok(cancels) => cancels,
+                    Err(rejection) => return reject(rejection),
+                };
+                let mut state = self.state();
+                let entries = cancels
+                    .into_iter()
+                    .map(|cancel| {
+                        let result = state.engine.cancel(&cancel.id, now_ms()).map(|details| {
+                            json!({
+                                "currency_pair": details.currency_pair,
+                                "id": details.id,
+                                "text": details.text,
+                                "account": details.account,
+                            })
+                        });
+                        let mut entry = batch_entry(result);
+                        entry["id"] = json!(cancel.id);
+                        entry["currency_pair"] = json!(cancel.currency_pair);
+                        entry
+                    })
+                    .collect();
+                respond(200, Value::Array(entries))
+            }
+            (Method::Get, ["spot", "orders", order_id]) => {
+                if let Some(pair) = param(&request, "currency_pair") {
+                    self.match_pair(pair)?;
+                }
+                reply(self.state().engine.order(order_id).map(|d| json!(d)))
+            }
+            (Method::Get, ["spot", "orders"]) => {
+                let pair = param(&request, "currency_pair");
+                if let Some(pair) = pair {
+                    self.match_pair(pair)?;
+                }
+                let open = param(&request, "status") != Some("finished");
+                let side = param(&request, "side");
+                let orders: Vec<Value> = self
+                    .state()
+                    .engine
+                    .orders()
+                    .filter(|o| (o.status == "open") == open)
+                    .filter(|o| pair.is_none_or(|p| o.currency_pair == p))
+                    .filter(|o| side.is_none_or(|s| o.side == s))
+                    .map(|o| json!(o))
+                    .collect();
+                respond(200, json!(page(orders, &request)))
+            }
+            (Method::Get, ["spot", "open_orders"]) => {
+                self.match_orders()?;
+                let state = self.state();
+                let groups: Vec<OpenOrders> = state
+                    .engine
+                    .open_pairs()
+                    .into_iter()
+                    .map(|pair| {
+                        let orders: Vec<_> = state
+                            .engine
+                            .orders()
+                            .filter(|o| o.status == "open" && o.currency_pair == pair)
+                            .cloned()
+                            .collect();
+                        OpenOrders {
+                            currency_pair: pair,
+                            total: orders.len() as i64,
+                            orders,
+                        }
+                    })
+                    .collect();
+                respond(200, json!(groups))
+            }
+            (Method::Get, ["spot", "my_trades"]) => {
+                let pair = param(&request, "currency_pair");
+                let order_id = param(&request, "order_id");
+                let trades: Vec<Value> = self
+                    .state()
+                    .engine
+                    .trades()
+                    .filter(|t| pair.is_none_or(|p| t.currency_pair == p))
+                    .filter(|t| order_id.is_none_or(|id| t.order_id == id))
+                    .map(|t| json!(t))
+                    .collect();
+                respond(200, json!(page(trades, &request)))
+            }
+            (Method::Get, ["spot", "accounts"]) => {
+                let currency = param(&request, "currency");
+                let accounts: Vec<Value> = self
+                    .balances()
+                    .into_iter()
+                    .filter(|(c, _)| currency.is_none_or(|currency| c == currency))
+                    .map(|(currency, balance)| {
+                        json!({
+                            "currency": currency,
+                            "available": format_number(balance.available),
+                            "locked": format_number(balance.locked),
+                        })
+                    })
+                    .collect();
+                respond(200, Value::Array(accounts))
+            }
+            (Method::Get, ["spot", "fee"]) => {
+                let pair = param(&request, "currency_pair").unwrap_or("BTC_USDT");
+                let fees = self.fees_for(pair)?;
+                respond(
+                    200,
+                    json!({
+                        "user_id": 0,
+                        "currency_pair": pair,
+                        "maker_fee": format_number(fees.maker),
+                        "taker_fee": format_number(fees.taker),
+                    }),
+                )
+            }
+            _ if !request.sign() => self.source.send(request),
+            _ => reject(Rejection {
+                status: 404,
+                label: crate::http::GateErrorLabel::NotFound,
+                message: format!(
+                    "{} {} is not simulated by the paper client",
+                    request.method().as_ref(),
+                    request.path()
+                ),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Order book snapshot and fee rates of `currency_pair`
+    fn market(&self, currency_pair: &str) -> Result<(Book, Fees), Box<Error>> {
+        let fees = self.fees_for(currency_pair)?;
+        let body = self
+            .source
+            .send(get_orderbook(currency_pair).limit(BOOK_DEPTH))?
+            .into_body_str()?;
+        Ok((Book::parse(&body)?, fees))
+    }
+
+    fn fees_for(&self, currency_pair: &str) -> Result<Fees, Box<Error>> {
+        if let Some(fees) = self.fees {
+            return Ok(fees);
+        }
+        if let Some(fees) = self.state().fees.get(currency_pair) {
+            return Ok(*fees);
+        }
+        let body = self
+            .source
+            .send(get_fee().currency_pair(currency_pair))?
+            .into_body_str()?;
+        let fees = Fees::parse(&body)?;
+        self.state().fees.insert(currency_pair.to_owned(), fees);
+        Ok(fees)
+    }
+
+    fn match_pair(&self, currency_pair: &str) -> Result<(), Box<Error>> {
+        let (book, fees) = self.market(currency_pair)?;
+        self.state()
+            .engine
+            .match_resting(currency_pair, book, fees, now_ms());
+        Ok(())
+    }
+
+    fn amend(
+        &self,
+        order_id: &str,
+        amendment: &Amendment,
+    ) -> Result<Result<Value, Rejection>, Box<Error>> {
+        let result = self.state().engine.amend(
+            order_id,
+            amendment.amount.as_deref(),
+            amendment.price.as_deref(),
+            now_ms(),
+        );
+        if result.is_ok() {
+            self.match_pair(&amendment.currency_pair)?;
+        }
+        // Answer with the order as it stands after matching
+        Ok(result.and_then(|_| self.state().engine.order(order_id).map(|d| json!(d))))
+    }
+}
+
+impl<S: SendRequest> SendRequest for PaperClient<S> {
+    fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Box<Error>> {
+        PaperClient::send(self, request)
+    }
+}
+
+/// Market data served from fixtures, keyed by path and query parameters.
+///
+/// A request gets the fixture of its path whose parameters it all carries,
+/// preferring the most specific one; other requests get a 404. Fixtures can
+/// be replaced while in use to move the market.
+#[derive(Clone, Default)]
+pub struct RecordedMarket {
+    fixtures: Arc<Mutex<Vec<Fixture>>>,
+}
+
+struct Fixture {
+    path: String,
+    params: Vec<(String, String)>,
+    body: String,
+}
+
+impl RecordedMarket {
+    /// Creates a source without fixtures
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a fixture answering `path` requests carrying `params`
+    pub fn fixture(self, path: &str, params: &[(&str, &str)], body: &str) -> Self {
+        self.set_fixture(path, params, body);
+        self
+    }
+
+    /// Adds or replaces the fixture answering `path` requests carrying `params`
+    pub fn set_fixture(&self, path: &str, params: &[(&str, &str)], body: &str) {
+        let params: Vec<(String, String)> = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut fixtures = self.fixtures.lock().unwrap_or_else(|e| e.into_inner());
+        fixtures.retain(|f| !(f.path == path && f.params == params));
+        fixtures.push(Fixture {
+            path: path.to_owned(),
+            params,
+            body: body.to_owned(),
+        });
+    }
+}
+
+impl SendRequest for RecordedMarket {
+    fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Box<Error>> {
+        let request = request.into();
+        let fixtures = self.fixtures.lock().unwrap_or_else(|e| e.into_inner());
+        let fixture = fixtures
+            .iter()
+            .filter(|f| f.path == request.path())
+            .filter(|f| f.params.iter().all(|p| request.params().contains(p)))
+            .max_by_key(|f| f.params.len());
+        match fixture {
+            Some(fixture) => respond_raw(200, fixture.body.clone()),
+            None => reject(Rejection {
+                status: 404,
+                label: crate::http::GateErrorLabel::NotFound,
+                message: format!("No fixture for {}", request.path()),
+            }),
+        }
+    }
+}
+
+/// Order fields read from an `amend_order` or `amend_batch_orders` payload
+#[derive(Deserialize)]
+struct Amendment {
+    #[serde(default)]
+    order_id: Option<String>,
+    currency_pair: String,
+    #[serde(default)]
+    amount: Option<String>,
+    #[serde(default)]
+    price: Option<String>,
+}
+
+/// Entry of a `cancel_batch_orders` payload
+#[derive(Deserialize)]
+struct Cancel {
+    id: String,
+    currency_pair: String,
+}
+
+fn parse_payload<T: serde::de::DeserializeOwned>(request: &Request) -> Result<T, Rejection> {
+    serde_json::from_str(request.payload()).map_err(|err| Rejection {
+        status: 400,
+        label: crate::http::GateErrorLabel::InvalidRequestBody,
+        message: err.to_string(),
+    })
+}
+
+fn param<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .params()
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Applies the `page` and `limit` parameters of a list request
+fn page(items: Vec<Value>, request: &Request) -> Vec<Value> {
+    let limit = param(request, "limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(100);
+    let page: usize = param(request, "page")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(1);
+    items
+        .into_iter()
+        .skip(page.saturating_sub(1) * limit)
+        .take(limit)
+        .collect()
+}
+
+fn batch_entry(result: Result<Value, Rejection>) -> Value {
+    match result {
+        Ok(mut entry) => {
+            entry["succeeded"] = json!(true);
+            entry
+        }
+        Err(rejection) => json!({
+            "succeeded": false,
+            "label": rejection.label.as_str(),
+            "message": rejection.message,
+        }),
+    }
+}
+
+fn reply(result: Result<Value, Rejection>) -> Result<Response, Box<Error>> {
+    match result {
+        Ok(body) => respond(200, body),
+        Err(rejection) => reject(rejection),
+    }
+}
+
+fn reject(rejection: Rejection) -> Result<Response, Box<Error>> {
+    respond(
+        rejection.status,
+        json!({"label": rejection.label.as_str(), "message": rejection.message}),
+    )
+}
+
+fn respond(status: u16, body: Value) -> Result<Response, Box<Error>> {
+    respond_raw(status, body.to_string())
+}
+
+fn respond_raw(status: u16, body: String) -> Result<Response, Box<Error>> {
+    let response = http::Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::builder().mime_type("application/json").data(body))?;
+    Ok(Response::from(response))
+}
@@ -0,0 +1,658 @@
+//! Balances of every Gate wallet valued in one quote currency.
+//!
+//! [`Portfolio::snapshot`] reads the selected accounts, prices each asset
+//! with the spot tickers and returns a [`Snapshot`] broken down per account
+//! and per asset:
+//!
+//! | Account | Endpoint | Amount |
+//! |---|---|---|
+//! | spot | `/spot/accounts` | available + locked |
+//! | margin | `/margin/accounts` | available + locked - borrowed - interest |
+//! | unified | `/unified/accounts` | equity |
+//! | futures | `/futures/{settle}/accounts` | total + unrealised PnL |
+//! | earn | `/earn/uni/lends` | lent amount |
+//!
+//! Assets are priced from the last price of `<ASSET>_<QUOTE>`, of the
+//! inverse pair or through USDT; those without any are listed in
+//! [`Snapshot::unpriced`]. `/wallet/total_balance` is read alongside as
+//! Gate's own estimate of the total, to check the valuation against.
+//!
+//! An account that cannot be read or parsed is recorded in
+//! [`Snapshot::errors`] rather than failing the snapshot. Accounts in unified
+//! mode hold their spot and margin funds in the unified account, so it is
+//! left out by default: opt in with
+//! `.accounts(&[Account::Unified, Account::Futures, Account::Earn])` instead
+//! of the spot and margin ones to avoid counting them twice.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::paper::RecordedMarket;
+//! use gateio_rs::portfolio::{Account, Portfolio};
+//!
+//! let gate = RecordedMarket::new()
+//!     .fixture("/api/v4/spot/accounts", &[], r#"[{"currency":"BTC","available":"0.5","locked":"0.1"}]"#)
+//!     .fixture("/api/v4/futures/usdt/accounts", &[], r#"{"currency":"USDT","total":"1000","unrealised_pnl":"-50"}"#)
+//!     .fixture("/api/v4/spot/tickers", &[], r#"[{"currency_pair":"BTC_USDT","last":"30000"}]"#)
+//!     .fixture("/api/v4/wallet/total_balance", &[], r#"{"total":{"amount":"18950","currency":"USDT"}}"#);
+//!
+//! let snapshot = Portfolio::new("USDT")
+//!     .accounts(&[Account::Spot, Account::Futures])
+//!     .snapshot(&gate)?;
+//!
+//! assert_eq!(snapshot.total_value, 18_950.0);
+//! assert_eq!(snapshot.account(Account::Futures).map(|a| a.value), Some(950.0));
+//! assert_eq!(snapshot.asset("BTC").and_then(|h| h.value), Some(18_000.0));
+//! assert_eq!(snapshot.reported_total, Some(18_950.0));
+//! assert!(snapshot.is_complete());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::{GateError, Method};
+use crate::utils::{now_ms, number, parse_value};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Currency bridging assets without a pair against the quote currency
+const BRIDGE: &str = "USDT";
+
+/// Gate wallet holding funds
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Account {
+    /// Spot account
+    Spot,
+    /// Isolated margin accounts
+    Margin,
+    /// Unified account
+    Unified,
+    /// Perpetual futures accounts, one per settle currency
+    Futures,
+    /// Simple earn (uni lending)
+    Earn,
+}
+
+impl Account {
+    /// Every account
+    pub const ALL: [Account; 5] = [
+        Account::Spot,
+        Account::Margin,
+        Account::Unified,
+        Account::Futures,
+        Account::Earn,
+    ];
+
+    /// Lowercase name of the account
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Account::Spot => "spot",
+            Account::Margin => "margin",
+            Account::Unified => "unified",
+            Account::Futures => "futures",
+            Account::Earn => "earn",
+        }
+    }
+}
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Amount of one asset and its value in the quote currency
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Holding {
+    /// Currency
+    pub currency: String,
+    /// Net amount held, negative when more is owed than held
+    pub amount: f64,
+    /// Price in the quote currency, `None` when no ticker prices it
+    pub price: Option<f64>,
+    /// Value in the quote currency, `None` when unpriced
+    pub value: Option<f64>,
+}
+
+/// Holdings of one account
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct AccountValue {
+    /// Account
+    pub account: Account,
+    /// Holdings by currency
+    pub holdings: Vec<Holding>,
+    /// Value of the priced holdings
+    pub value: f64,
+}
+
+/// Account that could not be read
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct AccountError {
+    /// Account
+    pub account: Account,
+    /// Error reported by the client
+    pub error: String,
+}
+
+/// Valuation of the portfolio at one point in time
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Currency values are expressed in
+    pub quote: String,
+    /// Time the snapshot was taken (UNIX milliseconds)
+    pub timestamp: i64,
+    /// Breakdown per account
+    pub accounts: Vec<AccountValue>,
+    /// Holdings summed across accounts, by currency
+    pub assets: Vec<Holding>,
+    /// Value of every priced holding
+    pub total_value: f64,
+    /// Total estimated by Gate with `/wallet/total_balance`
+    pub reported_total: Option<f64>,
+    /// Currencies held without a price
+    pub unpriced: Vec<String>,
+    /// Accounts that could not be read
+    pub errors: Vec<AccountError>,
+}
+
+impl Snapshot {
+    /// Breakdown of `account`, `None` when not selected or not read
+    pub fn account(&self, account: Account) -> Option<&AccountValue> {
+        self.accounts.iter().find(|a| a.account == account)
+    }
+
+    /// Holding of `currency` summed across accounts
+    pub fn asset(&self, currency: &str) -> Option<&Holding> {
+        self.assets
+            .iter()
+            .find(|h| h.currency.eq_ignore_ascii_case(currency))
+    }
+
+    /// Whether every account was read and every holding priced
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty() && self.unpriced.is_empty()
+    }
+}
+
+/// Portfolio valuation across wallets, see the [module documentation](self)
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Portfolio {
+    /// Currency values are expressed in
+    pub quote: String,
+    /// Accounts read, all but [`Account::Unified`] by default
+    pub accounts: Vec<Account>,
+    /// Settle currencies of the futures accounts, `usdt` by default
+    pub futures_settle: Vec<String>,
+    /// Whether to read Gate's estimate with `/wallet/total_balance`
+    pub total_balance: bool,
+}
+
+impl Portfolio {
+    /// Creates a valuation in `quote` of the spot, margin, futures and earn accounts
+    pub fn new(quote: &str) -> Self {
+        Self {
+            quote: quote.to_uppercase(),
+            accounts: vec![
+                Account::Spot,
+                Account::Margin,
+                Account::Futures,
+                Account::Earn,
+            ],
+            futures_settle: vec!["usdt".to_owned()],
+            total_balance: true,
+        }
+    }
+
+    /// Selects the accounts to read
+    pub fn accounts(mut self, accounts: &[Account]) -> Self {
+        self.accounts = accounts.to_vec();
+        self
+    }
+
+    /// Sets the settle currencies of the futures accounts (`usdt`, `btc`)
+    pub fn futures_settle(mut self, settle: &[&str]) -> Self {
+        self.futures_settle = settle.iter().map(|s| s.to_lowercase()).collect();
+        self
+    }
+
+    /// Sets whether to read Gate's estimate with `/wallet/total_balance`
+    pub fn total_balance(mut self, total_balance: bool) -> Self {
+        self.total_balance = total_balance;
+        self
+    }
+
+    /// Endpoints to read, in order
+    fn sources(&self) -> Vec<Source> {
+        let mut sources = Vec::new();
+        for &account in &self.accounts {
+            match account {
+                Account::Futures => {
+                    for settle in &self.futures_settle {
+                        let path = format!("/api/v4/futures/{}/accounts", settle);
+                        sources.push(Source::signed(Fetch::Account(account), path));
+                    }
+                }
+                _ => {
+                    let path = match account {
+                        Account::Spot => "/api/v4/spot/accounts",
+                        Account::Margin => "/api/v4/margin/accounts",
+                        Account::Unified => "/api/v4/unified/accounts",
+                        _ => "/api/v4/earn/uni/lends",
+                    };
+                    sources.push(Source::signed(Fetch::Account(account), path.to_owned()));
+                }
+            }
+        }
+        if self.total_balance {
+            let mut source = Source::signed(
+                Fetch::TotalBalance,
+                "/api/v4/wallet/total_balance".to_owned(),
+            );
+            source
+                .params
+                .push(("currency".to_owned(), self.quote.clone()));
+            sources.push(source);
+        }
+        sources.push(Source {
+            fetch: Fetch::Tickers,
+            path: "/api/v4/spot/tickers".to_owned(),
+            params: Vec::new(),
+            sign: false,
+        });
+        sources
+    }
+
+    /// Builds the snapshot from the responses of [`Portfolio::sources`]
+    fn assemble(
+        &self,
+        timestamp: i64,
+        fetched: Vec<(Fetch, Result<Value, GateError>)>,
+    ) -> Result<Snapshot, GateError> {
+        let mut balances: BTreeMap<Account, BTreeMap<String, f64>> = BTreeMap::new();
+        let mut errors = Vec::new();
+        let mut reported_total = None;
+        let mut prices = None;
+
+        for (fetch, result) in fetched {
+            match (fetch, result) {
+                (Fetch::Tickers, result) => {
+                    prices = Some(Prices::new(parse_value(result?)?, &self.quote))
+                }
+                (Fetch::TotalBalance, Ok(value)) => {
+                    let total: TotalBalance = parse_value(value)?;
+                    reported_total = Some(number(&total.total.amount));
+                }
+                // Gate's estimate is informative only
+                (Fetch::TotalBalance, Err(_)) => {}
+                (Fetch::Account(account), result) => {
+                    match result.and_then(|value| account_balances(account, value)) {
+                        Ok(parsed) => {
+                            let amounts = balances.entry(account).or_default();
+                            for (currency, amount) in parsed {
+                                *amounts.entry(currency.to_uppercase()).or_default() += amount;
+                            }
+                        }
+                        // Failures of one futures settle currency are reported once per account
+                        Err(err) if !errors.iter().any(|e: &AccountError| e.account == account) => {
+                            errors.push(AccountError {
+                                account,
+                                error: err.to_string(),
+                            });
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+        let prices = prices.unwrap_or_default();
+
+        let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+        let mut accounts = Vec::new();
+        for (account, amounts) in balances {
+            let holdings: Vec<Holding> = amounts
+                .into_iter()
+                .filter(|(_, amount)| *amount != 0.0)
+                .map(|(currency, amount)| {
+                    *totals.entry(currency.clone()).or_default() += amount;
+                    prices.holding(currency, amount)
+                })
+                .collect();
+            accounts.push(AccountValue {
+                account,
+                value: holdings.iter().filter_map(|h| h.value).sum(),
+                holdings,
+            });
+        }
+        let assets: Vec<Holding> = totals
+            .into_iter()
+            .map(|(currency, amount)| prices.holding(currency, amount))
+            .collect();
+
+        Ok(Snapshot {
+            quote: self.quote.clone(),
+            timestamp,
+            total_value: assets.iter().filter_map(|h| h.value).sum(),
+            unpriced: assets
+                .iter()
+                .filter(|h| h.price.is_none())
+                .map(|h| h.currency.clone())
+                .collect(),
+            accounts,
+            assets,
+            reported_total,
+            errors,
+        })
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl Portfolio {
+    /// Reads the accounts and values them
+    pub fn snapshot<S: crate::ureq::SendRequest>(&self, client: &S) -> Result<Snapshot, GateError> {
+        let timestamp = now_ms();
+        let fetched = self
+            .sources()
+            .into_iter()
+            .map(|source| {
+                let result = source.call(client).send().map_err(|err| *err);
+                (source.fetch, result)
+            })
+            .collect();
+        self.assemble(timestamp, fetched)
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl Portfolio {
+    /// Reads the accounts and values them
+    pub async fn snapshot_async(
+        &self,
+        client: &crate::hyper::GateHttpClient,
+    ) -> Result<Snapshot, GateError> {
+        let timestamp = now_ms();
+        let mut fetched = Vec::new();
+        for source in self.sources() {
+            let result = source.call(client).send().await;
+            fetched.push((source.fetch, result));
+        }
+        self.assemble(timestamp, fetched)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Fetch {
+    Account(Account),
+    TotalBalance,
+    Tickers,
+}
+
+/// Endpoint read for a snapshot
+struct Source {
+    fetch: Fetch,
+    path: String,
+    params: Vec<(String, String)>,
+    sign: bool,
+}
+
+impl Source {
+    fn signed(fetch: Fetch, path: String) -> Self {
+        Self {
+            fetch,
+            path,
+            params: Vec::new(),
+            sign: true,
+        }
+    }
+
+    fn call<'a, C>(&self, client: &'a C) -> crate::http::Call<'a, C, Value> {
+        let mut call = crate::http::Call::new(client, Method::Get, &self.path);
+        for (key, value) in &self.params {
+            call = call.param(key, value);
+        }
+        if self.sign { call.sign() } else { call }
+    }
+}
+
+/// Last prices, by currency pair
+#[derive(Default)]
+struct Prices {
+    quote: String,
+    last: HashMap<String, f64>,
+}
+
+impl Prices {
+    fn new(tickers: Vec<Ticker>, quote: &str) -> Self {
+        let last = tickers
+            .into_iter()
+            .map(|t| (t.currency_pair.to_uppercase(), number(&t.last)))
+            .filter(|(_, last)| *last > 0.0)
+            .collect();
+        Self {
+            quote: quote.to_owned(),
+            last,
+        }
+    }
+
+    /// Price of `base` in `quote`, from the pair, its inverse or none
+    fn pair(&self, base: &str, quote: &str) -> Option<f64> {
+        if base == quote {
+            return Some(1.0);
+        }
+        self.last
+            .get(&format!("{}_{}", base, quote))
+            .copied()
+            .or_else(|| {
+                self.last
+                    .get(&format!("{}_{}", quote, base))
+                    .map(|last| 1.0 / last)
+            })
+    }
+
+    fn price(&self, currency: &str) -> Option<f64> {
+        self.pair(currency, &self.quote)
+            .or_else(|| Some(self.pair(currency, BRIDGE)? * self.pair(BRIDGE, &self.quote)?))
+    }
+
+    fn holding(&self, currency: String, amount: f64) -> Holding {
+        let price = self.price(&currency);
+        Holding {
+            value: price.map(|price| price * amount),
+            currency,
+            amount,
+            price,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Ticker {
+    currency_pair: String,
+    #[serde(default)]
+    last: String,
+}
+
+#[derive(Deserialize)]
+struct TotalBalance {
+    total: TotalAmount,
+}
+
+#[derive(Deserialize)]
+struct TotalAmount {
+    amount: String,
+}
+
+#[derive(Deserialize)]
+struct SpotBalance {
+    currency: String,
+    #[serde(default)]
+    available: String,
+    #[serde(default)]
+    locked: String,
+}
+
+#[derive(Deserialize)]
+struct MarginAccount {
+    base: MarginBalance,
+    quote: MarginBalance,
+}
+
+#[derive(Deserialize)]
+struct MarginBalance {
+    currency: String,
+    #[serde(default)]
+    available: String,
+    #[serde(default)]
+    locked: String,
+    #[serde(default)]
+    borrowed: String,
+    #[serde(default)]
+    interest: String,
+}
+
+#[derive(Deserialize)]
+struct UnifiedAccount {
+    #[serde(default)]
+    balances: HashMap<String, UnifiedBalance>,
+}
+
+#[derive(Deserialize)]
+struct UnifiedBalance {
+    #[serde(default)]
+    equity: Option<String>,
+    #[serde(default)]
+    available: String,
+    #[serde(default)]
+    freeze: String,
+    #[serde(default)]
+    borrowed: String,
+}
+
+#[derive(Deserialize)]
+struct FuturesAccount {
+    currency: String,
+    #[serde(default)]
+    total: String,
+    #[serde(default)]
+    unrealised_pnl: String,
+}
+
+#[derive(Deserialize)]
+struct EarnLend {
+    currency: String,
+    #[serde(default)]
+    amount: String,
+}
+
+/// Net amounts of an account response, by currency
+fn account_balances(account: Account, value: Value) -> Result<Vec<(String, f64)>, GateError> {
+    Ok(match account {
+        Account::Spot => parse_value::<Vec<SpotBalance>>(value)?
+            .into_iter()
+            .map(|b| (b.currency, number(&b.available) + number(&b.locked)))
+            .collect(),
+        Account::Margin => parse_value::<Vec<MarginAccount>>(value)?
+            .into_iter()
+            .flat_map(|a| [a.base, a.quote])
+            .map(|b| {
+                let net = number(&b.available) + number(&b.locked)
+                    - number(&b.borrowed)
+                    - number(&b.interest);
+                (b.currency, net)
+            })
+            .collect(),
+        Account::Unified => parse_value::<UnifiedAccount>(value)?
+            .balances
+            .into_iter()
+            .map(|(currency, b)| {
+                let net = match b.equity.as_deref().filter(|e| !e.is_empty()) {
+                    Some(equity) => number(equity),
+                    None => number(&b.available) + number(&b.freeze) - number(&b.borrowed),
+                };
+                (currency, net)
+            })
+            .collect(),
+        Account::Futures => {
+            let account: FuturesAccount = parse_value(value)?;
+            vec![(
+                account.currency,
+                number(&account.total) + number(&account.unrealised_pnl),
+            )]
+        }
+        Account::Earn => parse_value::<Vec<EarnLend>>(value)?
+            .into_iter()
+            .map(|l| (l.currency, number(&l.amount)))
+            .collect(),
+    })
+}
+
+#[cfg(all(test, feature = "enable-ureq"))]
+mod tests {
+    use super::*;
+    use crate::paper::RecordedMarket;
+
+    fn market() -> RecordedMarket {
+        RecordedMarket::new()
+            .fixture(
+                "/api/v4/spot/accounts",
+                &[],
+                r#"[{"currency":"BTC","available":"0.5","locked":"0.1"}]"#,
+            )
+            .fixture(
+                "/api/v4/spot/tickers",
+                &[],
+                r#"[{"currency_pair":"BTC_USDT","last":"30000"}]"#,
+            )
+            .fixture("/api/v4/earn/uni/lends", &[], "[]")
+            .fixture(
+                "/api/v4/futures/usdt/accounts",
+                &[],
+                r#"{"currency":"USDT","total":"1000","unrealised_pnl":"-50"}"#,
+            )
+    }
+
+    #[test]
+    fn unified_is_opt_in() {
+        let portfolio = Portfolio::new("usdt");
+        assert_eq!(portfolio.quote, "USDT");
+        assert!(!portfolio.accounts.contains(&Account::Unified));
+        assert_eq!(portfolio.accounts.len(), Account::ALL.len() - 1);
+    }
+
+    #[test]
+    fn malformed_account_is_reported() {
+        let gate = market().fixture("/api/v4/margin/accounts", &[], r#"{"unexpected":true}"#);
+        let snapshot = Portfolio::new("USDT")
+            .total_balance(false)
+            .snapshot(&gate)
+            .unwrap();
+        assert_eq!(snapshot.errors.len(), 1);
+        assert_eq!(snapshot.errors[0].account, Account::Margin);
+        assert!(snapshot.account(Account::Margin).is_none());
+        assert_eq!(
+            snapshot.account(Account::Spot).map(|a| a.value),
+            Some(18_000.0)
+        );
+        assert_eq!(snapshot.total_value, 18_950.0);
+        assert!(!snapshot.is_complete());
+    }
+
+    #[test]
+    fn settle_failures_are_reported_once() {
+        let gate = market()
+            .fixture("/api/v4/margin/accounts", &[], "[]")
+            .fixture("/api/v4/futures/btc/accounts", &[], "not json");
+        let snapshot = Portfolio::new("USDT")
+            .futures_settle(&["usdt", "btc", "eth"])
+            .total_balance(false)
+            .snapshot(&gate)
+            .unwrap();
+        let failed: Vec<Account> = snapshot.errors.iter().map(|e| e.account).collect();
+        assert_eq!(failed, vec![Account::Futures]);
+        // The settle currency that could be read is still valued
+        assert_eq!(
+            snapshot.account(Account::Futures).map(|a| a.value),
+            Some(950.0)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use super::{Ledger, PnlReport};
+use crate::utils::format_number;
+use std::io::{self, BufWriter, Write};
+
+/// Writes the ledger entries with their category
+pub(super) fn write_entries<W: Write>(ledger: &Ledger, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(
+        writer,
+        "id,time_ms,currency,change,balance,type,code,category,text"
+    )?;
+    for entry in &ledger.entries {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            field(&entry.id),
+            entry.time,
+            field(&entry.currency),
+            field(&entry.change),
+            field(&entry.balance),
+            field(&entry.entry_type),
+            field(&entry.code),
+            ledger.category(entry),
+            field(&entry.text)
+        )?;
+    }
+    writer.flush()
+}
+
+/// Writes the disposals of a PnL report
+pub(super) fn write_disposals<W: Write>(report: &PnlReport, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(
+        writer,
+        "trade_id,time_ms,currency_pair,method,amount,proceeds,cost,pnl,uncovered"
+    )?;
+    for disposal in &report.disposals {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            field(&disposal.trade_id),
+            disposal.time_ms,
+            field(&disposal.currency_pair),
+            report.method,
+            format_number(disposal.amount),
+            format_number(disposal.proceeds),
+            format_number(disposal.cost),
+            format_number(disposal.pnl),
+            format_number(disposal.uncovered)
+        )?;
+    }
+    writer.flush()
+}
+
+/// Quotes a CSV field when it holds a separator, a quote or a line break
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporting::CostMethod;
+    use crate::reporting::tests::entry;
+
+    #[test]
+    fn quotes_fields_that_need_it() {
+        assert_eq!(field("order_fill"), "order_fill");
+        assert_eq!(field(""), "");
+        assert_eq!(field("a,b"), "\"a,b\"");
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(field("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn writes_entries() {
+        let mut fill = entry("1", "order_fill", "-10.5", "89.5");
+        fill.text = "order 1, \"t-a\"".into();
+        let ledger = Ledger::new(
+            vec![fill, entry("2", "deposit", "100", "189.5")],
+            Vec::new(),
+        );
+        let mut out = Vec::new();
+        ledger.write_csv(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,time_ms,currency,change,balance,type,code,category,text\n\
+             1,1,USDT,-10.5,89.5,order_fill,,trade,\"order 1, \"\"t-a\"\"\"\n\
+             2,2,USDT,100,189.5,deposit,,deposit,\n"
+        );
+    }
+
+    #[test]
+    fn writes_disposals() {
+        let report = PnlReport {
+            method: CostMethod::AverageCost,
+            disposals: vec![crate::reporting::Disposal {
+                trade_id: "7,8".into(),
+                time_ms: 1_700_000_000_000.0,
+                currency_pair: "BTC_USDT".into(),
+                amount: 0.1,
+                proceeds: 4_000.0,
+                cost: 3_500.5,
+                pnl: 499.5,
+                uncovered: 0.0,
+            }],
+            positions: Vec::new(),
+            other_fees: Default::default(),
+        };
+        let mut out = Vec::new();
+        report.write_csv(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "trade_id,time_ms,currency_pair,method,amount,proceeds,cost,pnl,uncovered\n\
+             \"7,8\",1700000000000,BTC_USDT,average_cost,0.1,4000,3500.5,499.5,0\n"
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Account book reconciliation and realized PnL reports.
+//!
+//! A [`Ledger`] combines the spot account book (`get_account_book`) with the
+//! personal trades (`get_my_trades`) of the same period:
+//!
+//! - every ledger entry gets a [`Category`] from its `type`, overridable with
+//!   [`Ledger::rule`]
+//! - [`Ledger::reconcile`] checks that the trading entries of each currency
+//!   add up to what the trades imply, and that balances follow from one entry
+//!   to the next, exposing missing or unexpected entries
+//! - [`Ledger::pnl`] computes the realized PnL of the trades with the
+//!   [`CostMethod`] of choice: FIFO, LIFO or average cost
+//!
+//! Categorized entries and PnL disposals export to CSV for accounting and tax
+//! tooling.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use gateio_rs::api::spot::{AccountBookEntry, UserTrade, get_account_book, get_my_trades};
+//! use gateio_rs::reporting::{CostMethod, Ledger};
+//! use gateio_rs::{http::Credentials, ureq::GateHttpClient};
+//! use std::fs::File;
+//!
+//! let client = GateHttpClient::default().credentials(Credentials::new("key", "secret"));
+//! let (from, to) = (1_704_067_200, 1_735_689_599);
+//!
+//! let entries = client
+//!     .paginate_windows::<AccountBookEntry, _>(get_account_book(), from, to, 30 * 86_400)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! let trades = client
+//!     .paginate_windows::<UserTrade, _>(get_my_trades(), from, to, 30 * 86_400)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//!
+//! let ledger = Ledger::new(entries, trades);
+//! let reconciliation = ledger.reconcile(1e-8);
+//! for difference in &reconciliation.differences {
+//!     eprintln!("{} off by {}", difference.currency, difference.difference());
+//! }
+//!
+//! let report = ledger.pnl(CostMethod::Fifo);
+//! report.write_csv(File::create("disposals_2024.csv")?)?;
+//! ledger.write_csv(File::create("ledger_2024.csv")?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod csv;
+mod pnl;
+
+pub use pnl::{CostMethod, Disposal, OpenPosition, PnlReport};
+
+use crate::api::spot::{AccountBookEntry, UserTrade};
+use crate::utils::{number, split_pair};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+
+/// Currency debited by point fee deductions
+pub const POINT_CURRENCY: &str = "POINT";
+
+/// Currency debited by GT fee deductions
+pub const GT_CURRENCY: &str = "GT";
+
+/// Kind of a ledger entry
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Order fill or swap
+    Trade,
+    /// Trading fee
+    Fee,
+    /// Trading fee paid with points
+    PointFee,
+    /// Deposit
+    Deposit,
+    /// Withdrawal
+    Withdrawal,
+    /// Transfer between accounts
+    Transfer,
+    /// Interest paid or received
+    Interest,
+    /// Anything else (rebates, airdrops, ...)
+    Other,
+}
+
+impl Category {
+    /// Category of an account book entry type
+    pub fn of(entry_type: &str) -> Self {
+        let entry_type = entry_type.to_ascii_lowercase();
+        let kind = entry_type.as_str();
+        if kind.contains("point") {
+            Category::PointFee
+        } else if kind.contains("interest") {
+            Category::Interest
+        } else if kind.contains("deposit") {
+            Category::Deposit
+        } else if kind.contains("withdraw") {
+            Category::Withdrawal
+        } else if matches!(kind, "fee" | "order_fee" | "trade_fee" | "gt_fee") {
+            Category::Fee
+        } else if kind.contains("transfer") || kind.ends_with("_in") || kind.ends_with("_out") {
+            Category::Transfer
+        } else if kind.contains("fill") || kind.contains("trade") || kind.contains("swap") {
+            Category::Trade
+        } else {
+            Category::Other
+        }
+    }
+
+    /// Snake case name of the category
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Trade => "trade",
+            Category::Fee => "fee",
+            Category::PointFee => "point_fee",
+            Category::Deposit => "deposit",
+            Category::Withdrawal => "withdrawal",
+            Category::Transfer => "transfer",
+            Category::Interest => "interest",
+            Category::Other => "other",
+        }
+    }
+
+    /// Whether entries of the category come from trading
+    pub fn is_trading(&self) -> bool {
+        matches!(self, Category::Trade | Category::Fee | Category::PointFee)
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Trading entries of a currency disagreeing with the trades
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Difference {
+    /// Currency
+    pub currency: String,
+    /// Sum of the trade and fee entries of the ledger
+    pub ledger: f64,
+    /// Change implied by the trades and their fees
+    pub trades: f64,
+}
+
+impl Difference {
+    /// Ledger change minus trade change
+    pub fn difference(&self) -> f64 {
+        self.ledger - self.trades
+    }
+}
+
+/// Ledger entry whose balance does not follow from the previous one
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct BalanceBreak {
+    /// Currency
+    pub currency: String,
+    /// ID of the entry
+    pub entry_id: String,
+    /// Previous balance plus the change of the entry
+    pub expected: f64,
+    /// Balance reported by the entry
+    pub actual: f64,
+}
+
+/// Outcome of [`Ledger::reconcile`]
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Reconciliation {
+    /// Currencies whose trading entries disagree with the trades
+    pub differences: Vec<Difference>,
+    /// Entries breaking the running balance, usually after a missing entry
+    pub balance_breaks: Vec<BalanceBreak>,
+}
+
+impl Reconciliation {
+    /// Whether the ledger and the trades agree
+    pub fn is_clean(&self) -> bool {
+        self.differences.is_empty() && self.balance_breaks.is_empty()
+    }
+}
+
+/// Account book entries and trades of one period, see the [module documentation](self)
+#[derive(Clone, Debug, Default)]
+pub struct Ledger {
+    /// Account book entries, oldest first
+    pub entries: Vec<AccountBookEntry>,
+    /// Trades, oldest first
+    pub trades: Vec<UserTrade>,
+    /// Categories overriding [`Category::of`], by entry type or code
+    pub rules: Vec<(String, Category)>,
+}
+
+impl Ledger {
+    /// Creates a ledger, ordering entries and trades and dropping duplicates
+    pub fn new(mut entries: Vec<AccountBookEntry>, mut trades: Vec<UserTrade>) -> Self {
+        entries.sort_by(|a, b| (a.time, id_order(&a.id)).cmp(&(b.time, id_order(&b.id))));
+        entries.dedup_by(|a, b| a.id == b.id);
+        trades.sort_by(|a, b| {
+            a.create_time_ms
+                .total_cmp(&b.create_time_ms)
+                .then_with(|| id_order(&a.id).cmp(&id_order(&b.id)))
+        });
+        trades.dedup_by(|a, b| a.id == b.id);
+        Self {
+            entries,
+            trades,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Files entries of `entry_type` (or with that code) under `category`
+    pub fn rule(mut self, entry_type: &str, category: Category) -> Self {
+        self.rules.push((entry_type.to_owned(), category));
+        self
+    }
+
+    /// Category of `entry`
+    pub fn category(&self, entry: &AccountBookEntry) -> Category {
+        self.rules
+            .iter()
+            .find(|(kind, _)| {
+                kind == &entry.entry_type || (!entry.code.is_empty() && kind == &entry.code)
+            })
+            .map_or_else(
+                || Category::of(&entry.entry_type),
+                |(_, category)| *category,
+            )
+    }
+
+    /// Sum of the changes by currency and category
+    pub fn totals(&self) -> BTreeMap<(String, Category), f64> {
+        let mut totals = BTreeMap::new();
+        for entry in &self.entries {
+            *totals
+                .entry((entry.currency.to_uppercase(), self.category(entry)))
+                .or_default() += number(&entry.change);
+        }
+        totals
+    }
+
+    /// Checks the ledger against the trades, ignoring gaps up to `tolerance`
+    pub fn reconcile(&self, tolerance: f64) -> Reconciliation {
+        let mut ledger: BTreeMap<String, f64> = BTreeMap::new();
+        for ((currency, category), change) in self.totals() {
+            if category.is_trading() {
+                *ledger.entry(currency).or_default() += change;
+            }
+        }
+        let mut expected: BTreeMap<String, f64> = BTreeMap::new();
+        for trade in &self.trades {
+            for (currency, change) in trade_changes(trade) {
+                *expected.entry(currency).or_default() += change;
+            }
+        }
+
+        let mut currencies: Vec<&String> = ledger.keys().chain(expected.keys()).collect();
+        currencies.sort();
+        currencies.dedup();
+        let differences = currencies
+            .into_iter()
+            .map(|currency| Difference {
+                currency: currency.clone(),
+                ledger: ledger.get(currency).copied().unwrap_or_default(),
+                trades: expected.get(currency).copied().unwrap_or_default(),
+            })
+            .filter(|d| d.difference().abs() > tolerance)
+            .collect();
+
+        let mut balances: BTreeMap<String, f64> = BTreeMap::new();
+        let mut balance_breaks = Vec::new();
+        for entry in &self.entries {
+            let currency = entry.currency.to_uppercase();
+            let actual = number(&entry.balance);
+            if let Some(previous) = balances.insert(currency.clone(), actual) {
+                let expected = previous + number(&entry.change);
+                if (expected - actual).abs() > tolerance {
+                    balance_breaks.push(BalanceBreak {
+                        currency,
+                        entry_id: entry.id.clone(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Reconciliation {
+            differences,
+            balance_breaks,
+        }
+    }
+
+    /// Realized PnL of the trades
+    pub fn pnl(&self, method: CostMethod) -> PnlReport {
+        PnlReport::from_trades(&self.trades, method)
+    }
+
+    /// Writes the entries with their category as CSV
+    pub fn write_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        csv::write_entries(self, writer)
+    }
+}
+
+/// Balance changes of a trade and its fees, by currency.
+///
+/// The fee is charged in `fee_currency`; points and GT used to deduct it
+/// are charged on top when the fee is not already in that currency.
+fn trade_changes(trade: &UserTrade) -> Vec<(String, f64)> {
+    let (base, quote) = split_pair(&trade.currency_pair);
+    let amount = number(&trade.amount);
+    let total = amount * number(&trade.price);
+    let sign = if trade.side == "sell" { -1.0 } else { 1.0 };
+    let fee_currency = trade.fee_currency.to_uppercase();
+    let mut changes = vec![
+        (base, sign * amount),
+        (quote, -sign * total),
+        (fee_currency.clone(), -number(&trade.fee)),
+    ];
+    for (currency, deducted) in [
+        (POINT_CURRENCY, &trade.point_fee),
+        (GT_CURRENCY, &trade.gt_fee),
+    ] {
+        if fee_currency != currency && number(deducted) != 0.0 {
+            changes.push((currency.to_owned(), -number(deducted)));
+        }
+    }
+    changes
+}
+
+/// Numeric IDs sort by value, others after them by text
+fn id_order(id: &str) -> (u64, &str) {
+    (id.parse().unwrap_or(u64::MAX), id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn entry(
+        id: &str,
+        entry_type: &str,
+        change: &str,
+        balance: &str,
+    ) -> AccountBookEntry {
+        AccountBookEntry {
+            id: id.into(),
+            time: id.parse().unwrap_or_default(),
+            currency: "USDT".into(),
+            change: change.into(),
+            balance: balance.into(),
+            entry_type: entry_type.into(),
+            code: String::new(),
+            text: String::new(),
+        }
+    }
+
+    #[test]
+    fn categorizes_entry_types() {
+        for (entry_type, category) in [
+            ("order_fill", Category::Trade),
+            ("Swap", Category::Trade),
+            ("order_fee", Category::Fee),
+            ("gt_fee", Category::Fee),
+            ("point_fee", Category::PointFee),
+            ("deposit", Category::Deposit),
+            ("withdraw", Category::Withdrawal),
+            ("margin_in", Category::Transfer),
+            ("transfer", Category::Transfer),
+            ("margin_interest", Category::Interest),
+            ("rebate", Category::Other),
+        ] {
+            assert_eq!(Category::of(entry_type), category, "{}", entry_type);
+        }
+        assert!(Category::PointFee.is_trading());
+        assert!(!Category::Deposit.is_trading());
+    }
+
+    #[test]
+    fn rules_override_types_and_codes() {
+        let mut coded = entry("2", "other", "1", "2");
+        coded.code = "105".into();
+        let ledger = Ledger::new(
+            vec![
+                entry("1", "rebate", "1", "1"),
+                coded,
+                entry("3", "rebate", "1", "3"),
+            ],
+            Vec::new(),
+        )
+        .rule("105", Category::Fee)
+        .rule("rebate", Category::Trade);
+
+        let categories: Vec<Category> = ledger.entries.iter().map(|e| ledger.category(e)).collect();
+        assert_eq!(
+            categories,
+            [Category::Trade, Category::Fee, Category::Trade]
+        );
+        assert_eq!(
+            ledger.totals(),
+            BTreeMap::from([
+                (("USDT".to_owned(), Category::Trade), 2.0),
+                (("USDT".to_owned(), Category::Fee), 1.0),
+            ])
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use crate::api::spot::UserTrade;
+use crate::utils::{number, split_pair};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::{self, Write};
+
+/// Order in which bought lots are matched against sales
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostMethod {
+    /// First in, first out
+    Fifo,
+    /// Last in, first out
+    Lifo,
+    /// Every lot at the running average cost
+    AverageCost,
+}
+
+impl CostMethod {
+    /// Snake case name of the method
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostMethod::Fifo => "fifo",
+            CostMethod::Lifo => "lifo",
+            CostMethod::AverageCost => "average_cost",
+        }
+    }
+}
+
+impl fmt::Display for CostMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Sale matched against bought lots
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Disposal {
+    /// ID of the sell trade
+    pub trade_id: String,
+    /// Time of the trade (UNIX milliseconds)
+    pub time_ms: f64,
+    /// Currency pair
+    pub currency_pair: String,
+    /// Amount of base currency disposed of
+    pub amount: f64,
+    /// Proceeds in quote currency, net of quote fees
+    pub proceeds: f64,
+    /// Cost basis of the lots matched
+    pub cost: f64,
+    /// Realized PnL in quote currency
+    pub pnl: f64,
+    /// Amount sold without a matching lot, given a zero cost basis
+    pub uncovered: f64,
+}
+
+/// Lots still held at the end of the trades
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct OpenPosition {
+    /// Currency pair
+    pub currency_pair: String,
+    /// Amount of base currency held
+    pub amount: f64,
+    /// Cost basis in quote currency
+    pub cost: f64,
+}
+
+/// Realized PnL of a series of trades.
+///
+/// Positions are tracked per currency pair in its quote currency. Fees in
+/// the quote currency add to the cost of buys and reduce the proceeds of
+/// sales, fees in the base currency reduce the amount bought or add to the
+/// amount sold; fees in other currencies (GT, points) are summed in
+/// [`PnlReport::other_fees`] without entering the PnL.
+///
+/// ```rust
+/// use gateio_rs::api::spot::UserTrade;
+/// use gateio_rs::reporting::{CostMethod, PnlReport};
+///
+/// let trade = |id: &str, side: &str, amount: &str, price: &str| UserTrade {
+///     id: id.into(),
+///     create_time_ms: id.parse().unwrap(),
+///     currency_pair: "BTC_USDT".into(),
+///     side: side.into(),
+///     role: "taker".into(),
+///     amount: amount.into(),
+///     price: price.into(),
+///     order_id: id.into(),
+///     fee: "0".into(),
+///     fee_currency: "USDT".into(),
+///     point_fee: "0".into(),
+///     gt_fee: "0".into(),
+///     text: String::new(),
+/// };
+/// let trades = [
+///     trade("1", "buy", "1", "100"),
+///     trade("2", "buy", "1", "200"),
+///     trade("3", "sell", "1", "250"),
+/// ];
+///
+/// assert_eq!(PnlReport::from_trades(&trades, CostMethod::Fifo).realized("BTC_USDT"), 150.0);
+/// assert_eq!(PnlReport::from_trades(&trades, CostMethod::Lifo).realized("BTC_USDT"), 50.0);
+/// let average = PnlReport::from_trades(&trades, CostMethod::AverageCost);
+/// assert_eq!(average.realized("BTC_USDT"), 100.0);
+/// assert_eq!(average.positions[0].cost, 150.0);
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct PnlReport {
+    /// Method lots were matched with
+    pub method: CostMethod,
+    /// Sales, in trade order
+    pub disposals: Vec<Disposal>,
+    /// Lots still held
+    pub positions: Vec<OpenPosition>,
+    /// Fees paid in neither currency of the pair, by currency
+    pub other_fees: BTreeMap<String, f64>,
+}
+
+impl PnlReport {
+    /// Matches the sales of `trades` against their buys.
+    ///
+    /// Trades are replayed oldest first, by time then ID, whatever their order
+    /// in `trades`: `get_my_trades` pages can be passed newest first as
+    /// returned. Trades whose side is neither `buy` nor `sell` are skipped.
+    pub fn from_trades(trades: &[UserTrade], method: CostMethod) -> Self {
+        let mut books: BTreeMap<String, Lots> = BTreeMap::new();
+        let mut disposals = Vec::new();
+        let mut other_fees: BTreeMap<String, f64> = BTreeMap::new();
+
+        let mut trades: Vec<&UserTrade> = trades.iter().collect();
+        trades.sort_by(|a, b| {
+            a.create_time_ms
+                .total_cmp(&b.create_time_ms)
+                .then_with(|| super::id_order(&a.id).cmp(&super::id_order(&b.id)))
+        });
+        for trade in trades {
+            let sale = match trade.side.as_str() {
+                "buy" => false,
+                "sell" => true,
+                _ => continue,
+            };
+            let pair = trade.currency_pair.to_uppercase();
+            let (base, quote) = split_pair(&pair);
+            let amount = number(&trade.amount);
+            let total = amount * number(&trade.price);
+            let fee = number(&trade.fee);
+            let fee_currency = trade.fee_currency.to_uppercase();
+            let (base_fee, quote_fee) = match fee_currency.as_str() {
+                currency if currency == base => (fee, 0.0),
+                currency if currency == quote => (0.0, fee),
+                _ => {
+                    if fee != 0.0 {
+                        *other_fees.entry(fee_currency.clone()).or_default() += fee;
+                    }
+                    (0.0, 0.0)
+                }
+            };
+            for (currency, deducted) in [
+                (super::POINT_CURRENCY, &trade.point_fee),
+                (super::GT_CURRENCY, &trade.gt_fee),
+            ] {
+                let deducted = number(deducted);
+                if fee_currency != currency && deducted != 0.0 {
+                    *other_fees.entry(currency.to_owned()).or_default() += deducted;
+                }
+            }
+
+            let lots = books.entry(pair.clone()).or_default();
+            if sale {
+                let sold = amount + base_fee;
+                let (cost, uncovered) = lots.take(sold, method);
+                let proceeds = total - quote_fee;
+                disposals.push(Disposal {
+                    trade_id: trade.id.clone(),
+                    time_ms: trade.create_time_ms,
+                    currency_pair: pair,
+                    amount: sold,
+                    proceeds,
+                    cost,
+                    pnl: proceeds - cost,
+                    uncovered,
+                });
+            } else {
+                lots.add(amount - base_fee, total + quote_fee, method);
+            }
+        }
+
+        let positions = books
+            .into_iter()
+            .map(|(currency_pair, lots)| OpenPosition {
+                currency_pair,
+                amount: lots.lots.iter().map(|lot| lot.0).sum(),
+                cost: lots.lots.iter().map(|lot| lot.1).sum(),
+            })
+            .filter(|position| position.amount > 0.0)
+            .collect();
+
+        Self {
+            method,
+            disposals,
+            positions,
+            other_fees,
+        }
+    }
+
+    /// Realized PnL of `currency_pair`
+    pub fn realized(&self, currency_pair: &str) -> f64 {
+        self.disposals
+            .iter()
+            .filter(|d| d.currency_pair.eq_ignore_ascii_case(currency_pair))
+            .map(|d| d.pnl)
+            .sum()
+    }
+
+    /// Realized PnL by currency pair
+    pub fn realized_by_pair(&self) -> BTreeMap<String, f64> {
+        let mut realized = BTreeMap::new();
+        for disposal in &self.disposals {
+            *realized.entry(disposal.currency_pair.clone()).or_default() += disposal.pnl;
+        }
+        realized
+    }
+
+    /// Writes the disposals as CSV
+    pub fn write_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        super::csv::write_disposals(self, writer)
+    }
+}
+
+/// Held lots of one pair: amount and cost basis
+#[derive(Default)]
+struct Lots {
+    lots: VecDeque<(f64, f64)>,
+}
+
+impl Lots {
+    fn add(&mut self, amount: f64, cost: f64, method: CostMethod) {
+        if amount <= 0.0 {
+            return;
+        }
+        match (method, self.lots.front_mut()) {
+            (CostMethod::AverageCost, Some(lot)) => {
+                lot.0 += amount;
+                lot.1 += cost;
+            }
+            _ => self.lots.push_back((amount, cost)),
+        }
+    }
+
+    /// Removes `amount` from the lots, returning its cost and the amount not held
+    fn take(&mut self, sold: f64, method: CostMethod) -> (f64, f64) {
+        let mut amount = sold;
+        let mut cost = 0.0;
+        while amount > 0.0 {
+            let lot = match method {
+                CostMethod::Lifo => self.lots.back_mut(),
+                CostMethod::Fifo | CostMethod::AverageCost => self.lots.front_mut(),
+            };
+            let Some(lot) = lot else {
+                break;
+            };
+            let taken = amount.min(lot.0);
+            let taken_cost = lot.1 * taken / lot.0;
+            cost += taken_cost;
+            amount -= taken;
+            lot.0 -= taken;
+            lot.1 -= taken_cost;
+            // Drop exhausted lots, rounding dust included
+            if lot.0 <= taken * 1e-12 {
+                match method {
+                    CostMethod::Lifo => self.lots.pop_back(),
+                    CostMethod::Fifo | CostMethod::AverageCost => self.lots.pop_front(),
+                };
+            }
+        }
+        let uncovered = if amount > sold * 1e-12 { amount } else { 0.0 };
+        (cost, uncovered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: &str, side: &str, amount: &str, price: &str) -> UserTrade {
+        UserTrade {
+            id: id.into(),
+            create_time_ms: id.parse::<f64>().unwrap() * 1000.0,
+            currency_pair: "BTC_USDT".into(),
+            side: side.into(),
+            role: "taker".into(),
+            amount: amount.into(),
+            price: price.into(),
+            order_id: id.into(),
+            fee: "0".into(),
+            fee_currency: "USDT".into(),
+            point_fee: "0".into(),
+            gt_fee: "0".into(),
+            text: String::new(),
+        }
+    }
+
+    fn with_fee(mut trade: UserTrade, fee: &str, currency: &str) -> UserTrade {
+        trade.fee = fee.into();
+        trade.fee_currency = currency.into();
+        trade
+    }
+
+    fn buys_then_sale() -> Vec<UserTrade> {
+        vec![
+            trade("1", "buy", "1", "100"),
+            trade("2", "buy", "1", "200"),
+            trade("3", "sell", "1.5", "300"),
+        ]
+    }
+
+    #[test]
+    fn matches_lots_by_method() {
+        for (method, cost, held_cost) in [
+            (CostMethod::Fifo, 200.0, 100.0),
+            (CostMethod::Lifo, 250.0, 50.0),
+            (CostMethod::AverageCost, 225.0, 75.0),
+        ] {
+            let report = PnlReport::from_trades(&buys_then_sale(), method);
+            let disposal = &report.disposals[0];
+            assert_eq!(disposal.amount, 1.5, "{}", method);
+            assert_eq!(disposal.proceeds, 450.0, "{}", method);
+            assert_eq!(disposal.cost, cost, "{}", method);
+            assert_eq!(disposal.pnl, 450.0 - cost, "{}", method);
+            assert_eq!(disposal.uncovered, 0.0, "{}", method);
+            assert_eq!(
+                report.positions,
+                vec![OpenPosition {
+                    currency_pair: "BTC_USDT".into(),
+                    amount: 0.5,
+                    cost: held_cost,
+                }],
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn replays_trades_oldest_first() {
+        let mut newest_first = buys_then_sale();
+        newest_first.reverse();
+        assert_eq!(
+            PnlReport::from_trades(&newest_first, CostMethod::Fifo),
+            PnlReport::from_trades(&buys_then_sale(), CostMethod::Fifo)
+        );
+
+        // Same millisecond: numeric IDs order the trades
+        let mut sale = trade("10", "sell", "1", "150");
+        sale.create_time_ms = 9_000.0;
+        let report =
+            PnlReport::from_trades(&[sale, trade("9", "buy", "1", "100")], CostMethod::Fifo);
+        assert_eq!(report.disposals[0].uncovered, 0.0);
+        assert_eq!(report.realized("btc_usdt"), 50.0);
+    }
+
+    #[test]
+    fn nets_fees_in_the_pair_currencies() {
+        let trades = [
+            // 0.5 BTC of fee leaves 2 BTC costing 250
+            with_fee(trade("1", "buy", "2.5", "100"), "0.5", "BTC"),
+            // 1 BTC costing 131
+            with_fee(trade("2", "buy", "1", "130"), "1", "USDT"),
+            // Proceeds of 198 for 125 of cost
+            with_fee(trade("3", "sell", "1", "200"), "2", "USDT"),
+            // 1.5 BTC leave the account: 125 + 65.5 of cost
+            with_fee(trade("4", "sell", "1", "200"), "0.5", "btc"),
+        ];
+        let report = PnlReport::from_trades(&trades, CostMethod::Fifo);
+
+        let sales: Vec<(f64, f64, f64, f64)> = report
+            .disposals
+            .iter()
+            .map(|d| (d.amount, d.proceeds, d.cost, d.pnl))
+            .collect();
+        assert_eq!(
+            sales,
+            vec![(1.0, 198.0, 125.0, 73.0), (1.5, 200.0, 190.5, 9.5)]
+        );
+        assert_eq!(report.positions[0].amount, 0.5);
+        assert_eq!(report.positions[0].cost, 65.5);
+        assert!(report.other_fees.is_empty());
+    }
+
+    #[test]
+    fn sums_fees_in_other_currencies() {
+        let mut points = trade("2", "sell", "1", "100");
+        points.point_fee = "3".into();
+        let trades = [with_fee(trade("1", "buy", "1", "100"), "0.1", "GT"), points];
+        let report = PnlReport::from_trades(&trades, CostMethod::Fifo);
+
+        assert_eq!(report.realized("BTC_USDT"), 0.0);
+        assert_eq!(
+            report.other_fees,
+            BTreeMap::from([
+                ("GT".to_owned(), 0.1),
+                (crate::reporting::POINT_CURRENCY.to_owned(), 3.0)
+            ])
+        );
+    }
+
+    #[test]
+    fn sales_beyond_the_lots_are_uncovered() {
+        let mut eth = trade("3", "sell", "1", "10");
+        eth.currency_pair = "ETH_USDT".into();
+        let trades = [
+            trade("1", "buy", "0.5", "80"),
+            trade("2", "sell", "2", "100"),
+            eth,
+        ];
+        let report = PnlReport::from_trades(&trades, CostMethod::Lifo);
+
+        assert_eq!(report.disposals[0].cost, 40.0);
+        assert_eq!(report.disposals[0].uncovered, 1.5);
+        assert_eq!(report.disposals[0].pnl, 160.0);
+        assert_eq!(report.disposals[1].uncovered, 1.0);
+        assert!(report.positions.is_empty());
+        assert_eq!(
+            report.realized_by_pair(),
+            BTreeMap::from([
+                ("BTC_USDT".to_owned(), 160.0),
+                ("ETH_USDT".to_owned(), 10.0)
+            ])
+        );
+    }
+
+    #[test]
+    fn skips_unknown_sides() {
+        let trades = [
+            trade("1", "buy", "1", "100"),
+            trade("2", "", "5", "1"),
+            trade("3", "BUY", "5", "1"),
+        ];
+        let report = PnlReport::from_trades(&trades, CostMethod::Fifo);
+        assert!(report.disposals.is_empty());
+        assert_eq!(report.positions[0].amount, 1.0);
+        assert_eq!(report.positions[0].cost, 100.0);
+    }
+}
@@ -0,0 +1,1235 @@
+//! Pre-trade risk checks.
+//!
+//! [`RiskGate`] wraps a client and checks every `create_order`,
+//! `create_batch_orders`, `amend_order` and `amend_batch_orders` request
+//! against the configured [`Limits`] before sending it:
+//!
+//! - a kill switch, [`RiskGate::kill`], refusing every new order or amendment
+//! - the notional of each order, per pair
+//! - the number of open orders, per pair and account-wide
+//! - a price band around the last ticker price
+//! - the notional traded per pair and UTC day
+//!
+//! A request breaking a limit fails with a [`RiskError`] and is not sent; a
+//! batch with one offending order is refused whole. Cancellations and every
+//! other request pass through unchecked.
+//!
+//! The gate keeps its state from the responses passing through it: orders
+//! are counted as open from the create, amend, cancel and query responses,
+//! and a `get_open_orders` response replaces them all. Last prices come from
+//! ticker responses, or from `get_ticker` when a price band needs one that is
+//! missing or older than [`RiskGate::ticker_max_age`]. The kill switch and
+//! the limits that do not depend on the last price are checked first, so a
+//! refused request never costs a ticker request. Clones share the state and
+//! the kill switch.
+//!
+//! New orders passing the checks reserve their notional and an open order
+//! slot in the same lock, so that concurrent requests cannot exceed a limit
+//! together. The reservation becomes the tracked order once the response is
+//! read, and is given back when sending fails or Gate refuses the order.
+//! Amendments raising the notional of an order reserve the increase against
+//! the daily volume the same way.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::api::spot::create_order;
+//! use gateio_rs::paper::{Fees, PaperClient, RecordedMarket};
+//! use gateio_rs::risk::{Limits, RiskError, RiskGate};
+//!
+//! let market = RecordedMarket::new()
+//!     .fixture(
+//!         "/api/v4/spot/tickers",
+//!         &[("currency_pair", "BTC_USDT")],
+//!         r#"[{"currency_pair": "BTC_USDT", "last": "30000"}]"#,
+//!     )
+//!     .fixture(
+//!         "/api/v4/spot/order_book",
+//!         &[("currency_pair", "BTC_USDT")],
+//!         r#"{"asks": [["30000", "1"]], "bids": [["29990", "1"]]}"#,
+//!     );
+//! let paper = PaperClient::with_source(market)
+//!     .fees(Fees::new(0.001, 0.002))
+//!     .balance("USDT", 50_000.0);
+//! let gate = RiskGate::new(paper)
+//!     .limits(Limits::new().max_notional(10_000.0).price_band(0.05))
+//!     .max_open_orders(1);
+//!
+//! // 15,000 USDT is over the notional limit
+//! let refused = gate.send(create_order("BTC_USDT", "buy", "0.5").price("30000"));
+//! assert!(matches!(refused, Err(RiskError::MaxNotional { .. })));
+//!
+//! // 10% under the last price is outside the band
+//! let refused = gate.send(create_order("BTC_USDT", "buy", "0.1").price("27000"));
+//! assert!(matches!(refused, Err(RiskError::PriceBand { .. })));
+//!
+//! // A resting bid fills the only open order slot
+//! gate.send(create_order("BTC_USDT", "buy", "0.1").price("29500"))?
+//!     .into_body_str()?;
+//! assert_eq!(gate.open_orders(), 1);
+//! let refused = gate.send(create_order("BTC_USDT", "buy", "0.1").price("29600"));
+//! assert!(matches!(refused, Err(RiskError::MaxOpenOrders { .. })));
+//!
+//! gate.kill();
+//! let refused = gate.send(create_order("BTC_USDT", "sell", "0.01").price("30000"));
+//! assert!(matches!(refused, Err(RiskError::KillSwitch)));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::request::Request;
+use crate::http::{GateError, Method};
+use crate::utils::{format_number, number};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default age after which a last price is fetched again
+const DEFAULT_TICKER_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Limits applied to the orders of a currency pair.
+///
+/// Notionals and volumes are in the quote currency of the pair.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct Limits {
+    /// Largest notional of one order
+    pub max_notional: Option<f64>,
+    /// Most open orders on the pair
+    pub max_open_orders: Option<usize>,
+    /// Largest relative distance of a limit price to the last price, e.g. `0.05`
+    pub price_band: Option<f64>,
+    /// Largest notional of the orders placed on the pair in a UTC day,
+    /// amendment increases included
+    pub max_daily_volume: Option<f64>,
+}
+
+impl Limits {
+    /// Creates limits allowing everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest notional of one order
+    pub fn max_notional(mut self, max_notional: f64) -> Self {
+        self.max_notional = Some(max_notional);
+        self
+    }
+
+    /// Sets the most open orders on the pair
+    pub fn max_open_orders(mut self, max_open_orders: usize) -> Self {
+        self.max_open_orders = Some(max_open_orders);
+        self
+    }
+
+    /// Sets the largest relative distance of a limit price to the last price
+    pub fn price_band(mut self, price_band: f64) -> Self {
+        self.price_band = Some(price_band);
+        self
+    }
+
+    /// Sets the largest notional placed on the pair in a UTC day
+    pub fn max_daily_volume(mut self, max_daily_volume: f64) -> Self {
+        self.max_daily_volume = Some(max_daily_volume);
+        self
+    }
+
+    /// These limits, completed with `defaults` where unset
+    fn or(self, defaults: Limits) -> Limits {
+        Limits {
+            max_notional: self.max_notional.or(defaults.max_notional),
+            max_open_orders: self.max_open_orders.or(defaults.max_open_orders),
+            price_band: self.price_band.or(defaults.price_band),
+            max_daily_volume: self.max_daily_volume.or(defaults.max_daily_volume),
+        }
+    }
+}
+
+/// Request refused by a [`RiskGate`], or failure of the wrapped client
+#[derive(Debug)]
+pub enum RiskError {
+    /// The kill switch is engaged
+    KillSwitch,
+    /// The order notional is over the limit of the pair
+    MaxNotional {
+        /// Currency pair
+        currency_pair: String,
+        /// Notional of the order
+        notional: f64,
+        /// Largest notional allowed
+        limit: f64,
+    },
+    /// Another open order would exceed the limit, account-wide without a pair
+    MaxOpenOrders {
+        /// Currency pair, `None` for the account-wide limit
+        currency_pair: Option<String>,
+        /// Most open orders allowed
+        limit: usize,
+    },
+    /// The limit price is too far from the last price
+    PriceBand {
+        /// Currency pair
+        currency_pair: String,
+        /// Limit price of the order
+        price: f64,
+        /// Last price of the pair
+        last: f64,
+        /// Largest relative distance allowed
+        band: f64,
+    },
+    /// The order would take the volume of the day over the limit
+    DailyVolume {
+        /// Currency pair
+        currency_pair: String,
+        /// Volume of the day including the order
+        volume: f64,
+        /// Largest volume allowed
+        limit: f64,
+    },
+    /// A limit needs the last price of the pair and none is known
+    NoReferencePrice(String),
+    /// An amendment leaves the amount or price of an order the gate does not track
+    UnknownOrder(String),
+    /// The request payload is not a valid order
+    InvalidOrder(String),
+    /// Request to the Gate API failed
+    Gate(Box<GateError>),
+}
+
+impl RiskError {
+    /// Whether the gate refused the request, rather than the client failing
+    pub fn is_rejection(&self) -> bool {
+        !matches!(self, RiskError::Gate(_))
+    }
+}
+
+impl fmt::Display for RiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskError::KillSwitch => write!(f, "Kill switch engaged"),
+            RiskError::MaxNotional {
+                currency_pair,
+                notional,
+                limit,
+            } => write!(
+                f,
+                "Order notional {} on {} exceeds the limit of {}",
+                format_number(*notional),
+                currency_pair,
+                format_number(*limit)
+            ),
+            RiskError::MaxOpenOrders {
+                currency_pair: Some(pair),
+                limit,
+            } => write!(f, "Open order limit of {} reached on {}", limit, pair),
+            RiskError::MaxOpenOrders {
+                currency_pair: None,
+                limit,
+            } => write!(f, "Open order limit of {} reached", limit),
+            RiskError::PriceBand {
+                currency_pair,
+                price,
+                last,
+                band,
+            } => write!(
+                f,
+                "Price {} on {} is more than {}% away from the last price {}",
+                format_number(*price),
+                currency_pair,
+                format_number(band * 100.0),
+                format_number(*last)
+            ),
+            RiskError::DailyVolume {
+                currency_pair,
+                volume,
+                limit,
+            } => write!(
+                f,
+                "Daily volume {} on {} exceeds the limit of {}",
+                format_number(*volume),
+                currency_pair,
+                format_number(*limit)
+            ),
+            RiskError::NoReferencePrice(pair) => write!(f, "No last price for {}", pair),
+            RiskError::UnknownOrder(id) => write!(f, "Order {} is not tracked", id),
+            RiskError::InvalidOrder(reason) => write!(f, "Invalid order: {}", reason),
+            RiskError::Gate(e) => write!(f, "Gate API error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RiskError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RiskError::Gate(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<GateError> for RiskError {
+    fn from(err: GateError) -> Self {
+        RiskError::Gate(Box::new(err))
+    }
+}
+
+impl From<Box<GateError>> for RiskError {
+    fn from(err: Box<GateError>) -> Self {
+        RiskError::Gate(err)
+    }
+}
+
+/// Open order as last seen by the gate
+#[derive(Clone, Debug)]
+struct TrackedOrder {
+    currency_pair: String,
+    text: String,
+    amount: f64,
+    price: f64,
+}
+
+/// State shared by the clones of a gate
+#[derive(Debug, Default)]
+struct State {
+    open: HashMap<String, TrackedOrder>,
+    /// UNIX day the volumes are counted for
+    day: u64,
+    volume: HashMap<String, f64>,
+    last: HashMap<String, (f64, Instant)>,
+    /// Open order slots reserved by orders awaiting their response, by pair
+    pending: HashMap<String, usize>,
+}
+
+impl State {
+    fn roll(&mut self) {
+        let today = today();
+        if self.day != today {
+            self.day = today;
+            self.volume.clear();
+        }
+    }
+
+    fn volume(&mut self, currency_pair: &str) -> f64 {
+        self.roll();
+        self.volume.get(currency_pair).copied().unwrap_or_default()
+    }
+
+    fn find(&self, order_ref: &str) -> Option<&TrackedOrder> {
+        self.open
+            .get(order_ref)
+            .or_else(|| self.open.values().find(|order| order.text == order_ref))
+    }
+
+    /// Open orders on `currency_pair`, reserved slots included
+    fn open_on(&self, currency_pair: &str) -> usize {
+        let pending = self.pending.get(currency_pair).copied().unwrap_or_default();
+        self.open
+            .values()
+            .filter(|order| order.currency_pair == currency_pair)
+            .count()
+            + pending
+    }
+
+    /// Open orders across pairs, reserved slots included
+    fn open_total(&self) -> usize {
+        self.open.len() + self.pending.values().sum::<usize>()
+    }
+
+    /// Counts the notional and an open order slot for each of `orders`, and
+    /// the notional of each of `increases`
+    fn reserve(&mut self, orders: Notionals, increases: Notionals) -> Reservation {
+        self.roll();
+        for (pair, notional) in orders.iter().chain(&increases) {
+            *self.volume.entry(pair.clone()).or_default() += notional;
+        }
+        for (pair, _) in &orders {
+            *self.pending.entry(pair.clone()).or_default() += 1;
+        }
+        Reservation {
+            day: self.day,
+            orders,
+            increases,
+        }
+    }
+
+    /// Frees the slot of a reserved order, and its notional when it was not placed
+    fn release(&mut self, day: u64, order: &(String, f64), placed: bool) {
+        if let Some(pending) = self.pending.get_mut(&order.0) {
+            *pending = pending.saturating_sub(1);
+            if *pending == 0 {
+                self.pending.remove(&order.0);
+            }
+        }
+        if !placed {
+            self.refund(day, order);
+        }
+    }
+
+    /// Takes a reserved notional back from the volume of `day`
+    fn refund(&mut self, day: u64, (pair, notional): &(String, f64)) {
+        self.roll();
+        if self.day == day
+            && let Some(volume) = self.volume.get_mut(pair)
+        {
+            *volume = (*volume - notional).max(0.0);
+        }
+    }
+
+    /// Tracks or forgets the order described by `value`
+    fn update(&mut self, value: &Value) {
+        let Some(id) = text(value, "id") else {
+            return;
+        };
+        if text(value, "status").as_deref() != Some("open") {
+            self.open.remove(&id);
+            return;
+        }
+        let price = text(value, "price").map(|p| number(&p)).unwrap_or_default();
+        let amount = text(value, "left")
+            .or_else(|| text(value, "amount"))
+            .map(|a| number(&a))
+            .unwrap_or_default();
+        self.open.insert(
+            id,
+            TrackedOrder {
+                currency_pair: text(value, "currency_pair")
+                    .unwrap_or_default()
+                    .to_uppercase(),
+                text: text(value, "text").unwrap_or_default(),
+                amount,
+                price,
+            },
+        );
+    }
+}
+
+/// Order fields read from a create or amend payload
+#[derive(Deserialize)]
+struct OrderFields {
+    #[serde(default)]
+    order_id: Option<String>,
+    #[serde(default)]
+    currency_pair: Option<String>,
+    #[serde(default)]
+    side: String,
+    #[serde(default)]
+    amount: Option<String>,
+    #[serde(default)]
+    price: Option<String>,
+    #[serde(rename = "type", default)]
+    order_type: Option<String>,
+}
+
+/// Order a request would create or amend
+#[derive(Debug)]
+struct Proposal {
+    currency_pair: String,
+    buy: bool,
+    market: bool,
+    amount: Option<f64>,
+    price: Option<f64>,
+    /// Order amended, `None` for new orders
+    amends: Option<String>,
+}
+
+impl Proposal {
+    fn new(fields: OrderFields, amends: Option<String>) -> Result<Self, RiskError> {
+        let currency_pair = fields
+            .currency_pair
+            .filter(|pair| !pair.is_empty())
+            .ok_or_else(|| RiskError::InvalidOrder("missing currency_pair".to_owned()))?
+            .to_uppercase();
+        let positive = |field: &str, value: Option<String>| match value {
+            Some(value) if number(&value) > 0.0 => Ok(Some(number(&value))),
+            Some(value) => Err(RiskError::InvalidOrder(format!(
+                "{} {:?} is not a positive number",
+                field, value
+            ))),
+            None => Ok(None),
+        };
+        let amount = positive("amount", fields.amount)?;
+        let market = fields.order_type.as_deref() == Some("market");
+        let price = if market {
+            None
+        } else {
+            positive("price", fields.price)?
+        };
+        if amends.is_none() && amount.is_none() {
+            return Err(RiskError::InvalidOrder("missing amount".to_owned()));
+        }
+        Ok(Self {
+            currency_pair,
+            buy: fields.side == "buy",
+            market,
+            amount,
+            price,
+            amends,
+        })
+    }
+}
+
+/// Pair and notional of orders
+type Notionals = Vec<(String, f64)>;
+
+/// Notional and open order slots held by new orders and amendments until
+/// their response is read
+#[derive(Debug, Default)]
+struct Reservation {
+    /// UNIX day the notional was counted for
+    day: u64,
+    /// Pair and notional of each new order
+    orders: Notionals,
+    /// Pair and notional increase of each amendment, in request order
+    increases: Notionals,
+}
+
+/// What a response tells about the state
+#[derive(Debug)]
+enum Observe {
+    Nothing,
+    /// New orders, reserved by the checks
+    Created(Reservation),
+    /// Amended orders, their notional increases reserved by the checks
+    Amended(Reservation),
+    /// Orders as they stand
+    Orders,
+    /// Entries of `cancel_batch_orders`
+    CancelledBatch,
+    /// Open orders, replacing the tracked ones when the whole set was read
+    OpenOrders(bool),
+    Tickers,
+}
+
+/// Client wrapper checking orders against risk limits, see the [module documentation](self)
+#[derive(Clone)]
+pub struct RiskGate<C> {
+    client: C,
+    limits: Limits,
+    pair_limits: HashMap<String, Limits>,
+    max_open_orders: Option<usize>,
+    ticker_max_age: Duration,
+    killed: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl<C> RiskGate<C> {
+    /// Wraps `client` without any limit
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            limits: Limits::default(),
+            pair_limits: HashMap::new(),
+            max_open_orders: None,
+            ticker_max_age: DEFAULT_TICKER_MAX_AGE,
+            killed: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Sets the limits of every pair
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the limits of `currency_pair`, completed with the ones of every pair
+    pub fn pair_limits(mut self, currency_pair: &str, limits: Limits) -> Self {
+        self.pair_limits
+            .insert(currency_pair.to_uppercase(), limits);
+        self
+    }
+
+    /// Sets the most open orders across pairs
+    pub fn max_open_orders(mut self, max_open_orders: usize) -> Self {
+        self.max_open_orders = Some(max_open_orders);
+        self
+    }
+
+    /// Sets the age after which a last price is fetched again (10 seconds by default)
+    pub fn ticker_max_age(mut self, ticker_max_age: Duration) -> Self {
+        self.ticker_max_age = ticker_max_age;
+        self
+    }
+
+    /// Wrapped client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Refuses every new order and amendment until [`RiskGate::resume`]
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::SeqCst);
+    }
+
+    /// Releases the kill switch
+    pub fn resume(&self) {
+        self.killed.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether the kill switch is engaged
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    /// Number of orders tracked as open
+    pub fn open_orders(&self) -> usize {
+        self.state().open.len()
+    }
+
+    /// Notional placed on `currency_pair` today (UTC), amendment increases included
+    pub fn daily_volume(&self, currency_pair: &str) -> f64 {
+        self.state().volume(&currency_pair.to_uppercase())
+    }
+
+    /// Sets the last price of `currency_pair`
+    pub fn set_last_price(&self, currency_pair: &str, last: f64) {
+        self.state()
+            .last
+            .insert(currency_pair.to_uppercase(), (last, Instant::now()));
+    }
+
+    /// Limits applying to `currency_pair`
+    fn limits_for(&self, currency_pair: &str) -> Limits {
+        self.pair_limits
+            .get(currency_pair)
+            .map_or(self.limits, |limits| limits.or(self.limits))
+    }
+
+    /// Pairs whose last price is needed and missing or stale
+    fn stale_tickers(&self, proposals: &[Proposal]) -> Vec<String> {
+        let state = self.state();
+        let mut pairs: Vec<String> = proposals
+            .iter()
+            .filter(|proposal| {
+                let limits = self.limits_for(&proposal.currency_pair);
+                let band = limits.price_band.is_some() && proposal.price.is_some();
+                // Market sells are sized in base currency, valued at the last price
+                let valued = proposal.market
+                    && !proposal.buy
+                    && (limits.max_notional.is_some() || limits.max_daily_volume.is_some());
+                band || valued
+            })
+            .map(|proposal| proposal.currency_pair.clone())
+            .filter(|pair| {
+                state
+                    .last
+                    .get(pair)
+                    .is_none_or(|(_, at)| at.elapsed() > self.ticker_max_age)
+            })
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Checks `proposals` against the limits, reserving what they take
+    fn check(&self, proposals: &[Proposal]) -> Result<Reservation, RiskError> {
+        let mut state = self.state();
+        let (created, increases) = self.evaluate(&mut state, proposals, true)?;
+        Ok(state.reserve(created, increases))
+    }
+
+    /// Checks `proposals` against the kill switch and the limits not needing
+    /// a last price, before any is fetched
+    fn precheck(&self, proposals: &[Proposal]) -> Result<(), RiskError> {
+        self.evaluate(&mut self.state(), proposals, false)
+            .map(|_| ())
+    }
+
+    /// Checks `proposals` against the limits, returning the pair and notional
+    /// of the new orders and the notional increase of the amendments.
+    ///
+    /// Unless `priced`, the checks needing the last price are skipped and
+    /// market sells count for no notional.
+    fn evaluate(
+        &self,
+        state: &mut State,
+        proposals: &[Proposal],
+        priced: bool,
+    ) -> Result<(Notionals, Notionals), RiskError> {
+        if proposals.is_empty() {
+            return Ok(Default::default());
+        }
+        if self.is_killed() {
+            return Err(RiskError::KillSwitch);
+        }
+        let mut created = Vec::new();
+        let mut increases = Vec::new();
+        let mut added_open: HashMap<&str, usize> = HashMap::new();
+        let mut added_volume: HashMap<&str, f64> = HashMap::new();
+
+        for proposal in proposals {
+            let pair = proposal.currency_pair.as_str();
+            let limits = self.limits_for(pair);
+            let last = state
+                .last
+                .get(pair)
+                .map(|(last, _)| *last)
+                .filter(|_| priced);
+            let tracked = proposal.amends.as_deref().and_then(|id| state.find(id));
+
+            let amount = proposal.amount.or(tracked.map(|order| order.amount));
+            let price = proposal.price.or(tracked.map(|order| order.price));
+            let notional = match (proposal.market, proposal.buy) {
+                (true, true) => amount,
+                (true, false) => amount.zip(last).map(|(amount, last)| amount * last),
+                (false, _) => amount.zip(price).map(|(amount, price)| amount * price),
+            };
+            let needs_notional = limits.max_notional.is_some() || limits.max_daily_volume.is_some();
+            let notional = match (notional, &proposal.amends) {
+                (Some(notional), _) => notional,
+                (None, _) if !needs_notional => 0.0,
+                (None, Some(id)) => return Err(RiskError::UnknownOrder(id.clone())),
+                // Market sells are valued once the last price is known
+                (None, None) if !priced => 0.0,
+                (None, None) => return Err(RiskError::NoReferencePrice(pair.to_owned())),
+            };
+            // Orders the gate does not track count in full
+            let increase =
+                (notional - tracked.map_or(0.0, |order| order.amount * order.price)).max(0.0);
+
+            if let Some(limit) = limits.max_notional
+                && notional > limit
+            {
+                return Err(RiskError::MaxNotional {
+                    currency_pair: pair.to_owned(),
+                    notional,
+                    limit,
+                });
+            }
+
+            if let (true, Some(band), Some(price)) = (priced, limits.price_band, proposal.price) {
+                let last = last.ok_or_else(|| RiskError::NoReferencePrice(pair.to_owned()))?;
+                if (price - last).abs() > band * last {
+                    return Err(RiskError::PriceBand {
+                        currency_pair: pair.to_owned(),
+                        price,
+                        last,
+                        band,
+                    });
+                }
+            }
+
+            if proposal.amends.is_none() {
+                let open = added_open.entry(pair).or_default();
+                *open += 1;
+                if let Some(limit) = limits.max_open_orders
+                    && state.open_on(pair) + *open > limit
+                {
+                    return Err(RiskError::MaxOpenOrders {
+                        currency_pair: Some(pair.to_owned()),
+                        limit,
+                    });
+                }
+                let total: usize = added_open.values().sum();
+                if let Some(limit) = self.max_open_orders
+                    && state.open_total() + total > limit
+                {
+                    return Err(RiskError::MaxOpenOrders {
+                        currency_pair: None,
+                        limit,
+                    });
+                }
+            }
+
+            let added = added_volume.entry(pair).or_default();
+            *added += if proposal.amends.is_some() {
+                increase
+            } else {
+                notional
+            };
+            if let Some(limit) = limits.max_daily_volume {
+                let volume = state.volume(pair) + *added;
+                if volume > limit {
+                    return Err(RiskError::DailyVolume {
+                        currency_pair: pair.to_owned(),
+                        volume,
+                        limit,
+                    });
+                }
+            }
+            if proposal.amends.is_some() {
+                increases.push((pair.to_owned(), increase));
+            } else {
+                created.push((pair.to_owned(), notional));
+            }
+        }
+        Ok((created, increases))
+    }
+
+    /// Gives back what `observe` reserved for a request that was not answered
+    fn cancel(&self, observe: &Observe) {
+        if let Observe::Created(reservation) | Observe::Amended(reservation) = observe {
+            let mut state = self.state();
+            for order in &reservation.orders {
+                state.release(reservation.day, order, false);
+            }
+            for increase in &reservation.increases {
+                state.refund(reservation.day, increase);
+            }
+        }
+    }
+
+    /// Records what the response to a request tells about the state
+    fn observe(&self, observe: Observe, status: u16, body: &str) {
+        let value = serde_json::from_str::<Value>(body)
+            .ok()
+            .filter(|_| status < 400);
+        let mut state = self.state();
+        if let Observe::Created(reservation) | Observe::Amended(reservation) = &observe {
+            let entries = value.as_ref().map(entries).unwrap_or_default();
+            // An unreadable answer keeps the notional of an order that may have been placed
+            let refused = |index: usize| {
+                status >= 400
+                    || entries
+                        .get(index)
+                        .is_some_and(|e| e.get("succeeded") == Some(&Value::Bool(false)))
+            };
+            for (index, order) in reservation.orders.iter().enumerate() {
+                state.release(reservation.day, order, !refused(index));
+                if let Some(entry) = entries.get(index)
+                    && !refused(index)
+                {
+                    state.update(entry);
+                }
+            }
+            for (index, increase) in reservation.increases.iter().enumerate() {
+                if refused(index) {
+                    state.refund(reservation.day, increase);
+                }
+            }
+        }
+        let Some(value) = value else {
+            return;
+        };
+        match observe {
+            Observe::Nothing | Observe::Created(_) => {}
+            Observe::Amended(_) | Observe::Orders => {
+                for entry in entries(&value) {
+                    if entry.get("succeeded") != Some(&Value::Bool(false)) {
+                        state.update(entry);
+                    }
+                }
+            }
+            Observe::CancelledBatch => {
+                for entry in entries(&value) {
+                    if entry.get("succeeded") != Some(&Value::Bool(false))
+                        && let Some(id) = text(entry, "id")
+                    {
+                        state.open.remove(&id);
+                    }
+                }
+            }
+            Observe::OpenOrders(complete) => {
+                let groups = entries(&value);
+                // Paged groups only add what they list
+                let complete = complete
+                    && groups.iter().all(|group| {
+                        let listed = group["orders"].as_array().map_or(0, Vec::len);
+                        group["total"]
+                            .as_u64()
+                            .is_none_or(|total| listed as u64 >= total)
+                    });
+                if complete {
+                    state.open.clear();
+                }
+                for group in groups {
+                    for order in group["orders"].as_array().into_iter().flatten() {
+                        state.update(order);
+                    }
+                }
+            }
+            Observe::Tickers => {
+                for ticker in entries(&value) {
+                    if let (Some(pair), Some(last)) =
+                        (text(ticker, "currency_pair"), text(ticker, "last"))
+                        && number(&last) > 0.0
+                    {
+                        state
+                            .last
+                            .insert(pair.to_uppercase(), (number(&last), Instant::now()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "enable-ureq")]
+impl<S: crate::ureq::SendRequest> RiskGate<S> {
+    /// Sends a request with the wrapped client once it passes the checks
+    pub fn send<R: Into<Request>>(&self, request: R) -> Result<crate::ureq::Response, RiskError> {
+        let request = request.into();
+        let proposals = proposals(&request)?;
+        self.precheck(&proposals)?;
+        for pair in self.stale_tickers(&proposals) {
+            let ticker = crate::api::spot::get_ticker().currency_pair(&pair);
+            let body = self.client.send(ticker)?.into_body_str()?;
+            self.observe(Observe::Tickers, 200, &body);
+        }
+        let observe = observe(&request, self.check(&proposals)?);
+
+        let response = match self.client.send(request) {
+            Ok(response) => response,
+            Err(err) => {
+                self.cancel(&observe);
+                return Err(err.into());
+            }
+        };
+        if let Observe::Nothing = observe {
+            return Ok(response);
+        }
+        let status = response.status();
+        match response.into_raw() {
+            Ok((parts, body)) => {
+                self.observe(observe, status, &body);
+                Ok(crate::cassette::rebuild_ureq(parts, body))
+            }
+            Err(err) => {
+                self.observe(observe, status, "");
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Refusals surface as [`GateError::Risk`] wrapping the [`RiskError`], which
+/// is not retryable
+#[cfg(feature = "enable-ureq")]
+impl<S: crate::ureq::SendRequest> crate::ureq::SendRequest for RiskGate<S> {
+    fn send<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::ureq::Response, Box<crate::ureq::Error>> {
+        RiskGate::send(self, request).map_err(|err| match err {
+            RiskError::Gate(err) => err,
+            refused => Box::new(GateError::Risk(Box::new(refused))),
+        })
+    }
+}
+
+#[cfg(feature = "enable-hyper")]
+impl RiskGate<crate::hyper::GateHttpClient> {
+    /// Sends a request with the wrapped async client once it passes the checks
+    pub async fn send_async<R: Into<Request>>(
+        &self,
+        request: R,
+    ) -> Result<crate::hyper::Response, RiskError> {
+        let request = request.into();
+        let proposals = proposals(&request)?;
+        self.precheck(&proposals)?;
+        for pair in self.stale_tickers(&proposals) {
+            let ticker = crate::api::spot::get_ticker().currency_pair(&pair);
+            let body = self.client.send(ticker).await?.into_body_str().await?;
+            self.observe(Observe::Tickers, 200, &body);
+        }
+        let observe = observe(&request, self.check(&proposals)?);
+
+        let response = match self.client.send(request).await {
+            Ok(response) => response,
+            Err(err) => {
+                self.cancel(&observe);
+                return Err(err.into());
+            }
+        };
+        if let Observe::Nothing = observe {
+            return Ok(response);
+        }
+        let status = response.status();
+        match response.into_raw().await {
+            Ok((parts, body)) => {
+                self.observe(observe, status, &body);
+                Ok(crate::cassette::rebuild_hyper(parts, body))
+            }
+            Err(err) => {
+                self.observe(observe, status, "");
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Orders `request` would create or amend
+fn proposals(request: &Request) -> Result<Vec<Proposal>, RiskError> {
+    let parse = |payload: &str| -> Result<Value, RiskError> {
+        serde_json::from_str(payload).map_err(|err| RiskError::InvalidOrder(err.to_string()))
+    };
+    let fields = |value: Value| -> Result<OrderFields, RiskError> {
+        serde_json::from_value(value).map_err(|err| RiskError::InvalidOrder(err.to_string()))
+    };
+    let path = request.path().trim_start_matches("/api/v4");
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (request.method(), segments.as_slice()) {
+        (Method::Post, ["spot", "orders"]) => Ok(vec![Proposal::new(
+            fields(parse(request.payload())?)?,
+            None,
+        )?]),
+        (Method::Post, ["spot", "batch_orders"]) => entries(&parse(request.payload())?)
+            .into_iter()
+            .map(|entry| Proposal::new(fields(entry.clone())?, None))
+            .collect(),
+        (Method::Patch, ["spot", "orders", order_id]) => {
+            let mut fields = fields(parse(request.payload())?)?;
+            if fields.currency_pair.is_none() {
+                fields.currency_pair = param(request, "currency_pair");
+            }
+            Ok(vec![Proposal::new(fields, Some((*order_id).to_owned()))?])
+        }
+        (Method::Post, ["spot", "amend_batch_orders"]) => entries(&parse(request.payload())?)
+            .into_iter()
+            .map(|entry| {
+                let fields = fields(entry.clone())?;
+                let order_id = fields.order_id.clone().unwrap_or_default();
+                Proposal::new(fields, Some(order_id))
+            })
+            .collect(),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// What to read from the response to `request`
+fn observe(request: &Request, reservation: Reservation) -> Observe {
+    let path = request.path().trim_start_matches("/api/v4");
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (request.method(), segments.as_slice()) {
+        (Method::Post, ["spot", "orders" | "batch_orders"]) => Observe::Created(reservation),
+        (Method::Patch, ["spot", "orders", _]) | (Method::Post, ["spot", "amend_batch_orders"]) => {
+            Observe::Amended(reservation)
+        }
+        (Method::Get | Method::Delete, ["spot", "orders", ..]) => Observe::Orders,
+        (Method::Post, ["spot", "cancel_batch_orders"]) => Observe::CancelledBatch,
+        (Method::Get, ["spot", "open_orders"]) => Observe::OpenOrders(
+            param(request, "page").is_none_or(|page| page == "1")
+                && param(request, "account").is_none(),
+        ),
+        (Method::Get, ["spot", "tickers"]) => Observe::Tickers,
+        _ => Observe::Nothing,
+    }
+}
+
+/// Elements of an array, or the value itself
+fn entries(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(values) => values.iter().collect(),
+        value => vec![value],
+    }
+}
+
+/// String or number field of a JSON object
+fn text(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn param(request: &Request, name: &str) -> Option<String> {
+    request
+        .params()
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.clone())
+}
+
+/// Current UNIX day (UTC)
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / 86_400
+}
+
+#[cfg(all(test, feature = "enable-ureq"))]
+mod tests {
+    use super::*;
+    use crate::api::spot::{Order, amend_order, create_batch_orders, create_order};
+    use crate::paper::RecordedMarket;
+
+    /// Client failing every request before it reaches Gate
+    struct Unreachable;
+
+    impl crate::ureq::SendRequest for Unreachable {
+        fn send<R: Into<Request>>(
+            &self,
+            _request: R,
+        ) -> Result<crate::ureq::Response, Box<crate::ureq::Error>> {
+            Err(Box::new(GateError::Timeout))
+        }
+    }
+
+    fn order(price: &str) -> Request {
+        create_order("BTC_USDT", "buy", "0.1").price(price).into()
+    }
+
+    fn open_order(id: &str) -> String {
+        format!(
+            r#"{{"id":"{id}","status":"open","currency_pair":"BTC_USDT","amount":"0.1","price":"30000"}}"#
+        )
+    }
+
+    #[test]
+    fn checks_reserve_limits() {
+        let gate = RiskGate::new(RecordedMarket::new())
+            .limits(Limits::new().max_open_orders(1).max_daily_volume(5_000.0));
+        let first = gate.check(&proposals(&order("30000")).unwrap()).unwrap();
+        assert_eq!(first.orders, vec![("BTC_USDT".to_owned(), 3_000.0)]);
+        assert_eq!(gate.daily_volume("BTC_USDT"), 3_000.0);
+        // A second order checked before the first is answered sees its slot taken
+        assert!(matches!(
+            gate.check(&proposals(&order("30000")).unwrap()),
+            Err(RiskError::MaxOpenOrders { .. })
+        ));
+
+        gate.cancel(&Observe::Created(first));
+        assert_eq!(gate.daily_volume("BTC_USDT"), 0.0);
+        assert!(gate.check(&proposals(&order("30000")).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn failed_send_releases_reservation() {
+        let gate = RiskGate::new(Unreachable).limits(Limits::new().max_open_orders(1));
+        assert!(matches!(gate.send(order("30000")), Err(RiskError::Gate(_))));
+        assert!(matches!(gate.send(order("30000")), Err(RiskError::Gate(_))));
+        assert_eq!(gate.daily_volume("BTC_USDT"), 0.0);
+        assert!(gate.state().pending.is_empty());
+    }
+
+    #[test]
+    fn refused_order_releases_reservation() {
+        // No fixture: the recorded market answers 404
+        let gate = RiskGate::new(RecordedMarket::new()).limits(Limits::new().max_open_orders(1));
+        let response = gate.send(order("30000")).unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(gate.daily_volume("BTC_USDT"), 0.0);
+        assert!(gate.check(&proposals(&order("30000")).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn placed_order_replaces_reservation() {
+        let market = RecordedMarket::new().fixture("/api/v4/spot/orders", &[], &open_order("1"));
+        let gate = RiskGate::new(market).limits(Limits::new().max_open_orders(2));
+        gate.send(order("30000")).unwrap();
+        assert_eq!(gate.open_orders(), 1);
+        assert!(gate.state().pending.is_empty());
+        assert_eq!(gate.daily_volume("BTC_USDT"), 3_000.0);
+    }
+
+    #[test]
+    fn unsucceeded_batch_entries_are_released() {
+        let body = format!(
+            r#"[{{"succeeded":false,"label":"BALANCE_NOT_ENOUGH"}},{}]"#,
+            open_order("2").replace("{", r#"{"succeeded":true,"#)
+        );
+        let market = RecordedMarket::new().fixture("/api/v4/spot/batch_orders", &[], &body);
+        let gate = RiskGate::new(market).limits(Limits::new().max_open_orders(2));
+        gate.send(create_batch_orders(vec![
+            Order::new("BTC_USDT", "buy", "0.2").price("30000"),
+            Order::new("BTC_USDT", "buy", "0.1").price("30000"),
+        ]))
+        .unwrap();
+        assert_eq!(gate.open_orders(), 1);
+        assert!(gate.state().pending.is_empty());
+        assert_eq!(gate.daily_volume("BTC_USDT"), 3_000.0);
+    }
+
+    #[test]
+    fn refuses_without_fetching_tickers() {
+        // Any ticker request would fail with a timeout
+        let gate = RiskGate::new(Unreachable).limits(Limits::new().price_band(0.05));
+        gate.kill();
+        assert!(matches!(
+            gate.send(order("30000")),
+            Err(RiskError::KillSwitch)
+        ));
+
+        let gate =
+            RiskGate::new(Unreachable).limits(Limits::new().price_band(0.05).max_open_orders(0));
+        assert!(matches!(
+            gate.send(order("30000")),
+            Err(RiskError::MaxOpenOrders { .. })
+        ));
+        let gate =
+            RiskGate::new(Unreachable).limits(Limits::new().price_band(0.05).max_notional(1_000.0));
+        assert!(matches!(
+            gate.send(order("30000")),
+            Err(RiskError::MaxNotional { .. })
+        ));
+        let gate = RiskGate::new(Unreachable)
+            .limits(Limits::new().price_band(0.05).max_daily_volume(1_000.0));
+        assert!(matches!(
+            gate.send(create_order("BTC_USDT", "buy", "3000").order_type("market")),
+            Err(RiskError::DailyVolume { .. })
+        ));
+
+        // Passing the other limits, the price band needs the ticker
+        let gate = RiskGate::new(Unreachable).limits(Limits::new().price_band(0.05));
+        assert!(matches!(gate.send(order("30000")), Err(RiskError::Gate(_))));
+    }
+
+    #[test]
+    fn refusals_are_not_retryable() {
+        let gate = RiskGate::new(RecordedMarket::new());
+        gate.kill();
+        let err = crate::ureq::SendRequest::send(&gate, order("30000"))
+            .err()
+            .unwrap();
+        assert!(matches!(*err, GateError::Risk(_)));
+        assert!(!err.is_retryable());
+        assert_eq!(
+            err.to_string(),
+            "Refused by risk checks: Kill switch engaged"
+        );
+        let source = std::error::Error::source(err.as_ref()).unwrap();
+        assert!(matches!(
+            source.downcast_ref::<RiskError>(),
+            Some(RiskError::KillSwitch)
+        ));
+
+        // Client failures pass through as they are
+        let err = crate::ureq::SendRequest::send(&RiskGate::new(Unreachable), order("30000"))
+            .err()
+            .unwrap();
+        assert!(matches!(*err, GateError::Timeout));
+    }
+
+    #[test]
+    fn amendment_increases_count_against_daily_volume() {
+        let market = RecordedMarket::new().fixture("/api/v4/spot/orders", &[], &open_order("1"));
+        let gate = RiskGate::new(market.clone()).limits(Limits::new().max_daily_volume(5_000.0));
+        gate.send(order("30000")).unwrap();
+        assert_eq!(gate.daily_volume("BTC_USDT"), 3_000.0);
+
+        // Doubling the order adds 3,000
+        assert!(matches!(
+            gate.send(amend_order("1", "BTC_USDT").amount("0.2")),
+            Err(RiskError::DailyVolume { volume, .. }) if volume == 6_000.0
+        ));
+
+        // Lowering the price adds nothing
+        gate.send(amend_order("1", "BTC_USDT").price("29000"))
+            .unwrap();
+        assert_eq!(gate.daily_volume("BTC_USDT"), 3_000.0);
+
+        // Without a fixture Gate answers 404: the increase is given back
+        let response = gate
+            .send(amend_order("1", "BTC_USDT").amount("0.15"))
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(gate.daily_volume("BTC_USDT"), 3_000.0);
+
+        let amended = open_order("1").replace(r#""amount":"0.1""#, r#""amount":"0.15""#);
+        market.set_fixture("/api/v4/spot/orders/1", &[], &amended);
+        gate.send(amend_order("1", "BTC_USDT").amount("0.15"))
+            .unwrap();
+        assert_eq!(gate.daily_volume("BTC_USDT"), 4_500.0);
+        assert_eq!(gate.state().find("1").unwrap().amount, 0.15);
+    }
+}
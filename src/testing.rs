@@ -0,0 +1,701 @@
+//! Local mock Gate server for integration tests.
+//!
+//! [`MockGate`] binds to a free port on localhost and answers HTTP/1.1
+//! requests on a background thread, so both the ureq and the hyper clients can
+//! be pointed at it with `with_url(&mock.url())`:
+//!
+//! - fixtures are configured per method and `api::spot` path, path segments
+//!   written `{name}` or `*` matching anything (`/api/v4/spot/orders/{id}`)
+//! - once [`MockGate::credentials`] are set, signed requests are checked the
+//!   way Gate does: `KEY`, `Timestamp` and `SIGN` are verified with the same
+//!   HMAC SHA-512 algorithm as the clients and rejected with Gate's labels
+//! - [`MockResponse::error`] and [`MockResponse::too_many_requests`] simulate
+//!   error responses, [`MockGate::rate_limit`] a request quota with Gate's
+//!   `X-Gate-RateLimit-*` headers and 429s once it is used up
+//! - every request received is recorded for assertions
+//!
+//! # Examples
+//!
+//! ```rust
+//! use gateio_rs::api::spot::{get_account, get_ticker};
+//! use gateio_rs::http::{Credentials, Method};
+//! use gateio_rs::testing::{MockGate, MockResponse};
+//! use gateio_rs::ureq::GateHttpClient;
+//!
+//! let mock = MockGate::start()?;
+//! mock.credentials(Credentials::new("key", "secret"))
+//!     .respond(
+//!         Method::Get,
+//!         "/api/v4/spot/accounts",
+//!         MockResponse::json(r#"[{"currency":"USDT","available":"100","locked":"0"}]"#),
+//!     )
+//!     .respond_once(Method::Get, "/api/v4/spot/tickers", MockResponse::too_many_requests());
+//!
+//! let client = GateHttpClient::with_url(&mock.url()).credentials(Credentials::new("key", "secret"));
+//! let accounts = client.send(get_account())?.into_body_str()?;
+//! assert!(accounts.contains("USDT"));
+//!
+//! let ticker = client.send(get_ticker().currency_pair("BTC_USDT"))?;
+//! let err = ticker.into_body_str().unwrap_err();
+//! assert_eq!(err.status_code(), Some(429));
+//!
+//! let requests = mock.requests();
+//! assert_eq!(requests.len(), 2);
+//! assert!(requests[0].signed);
+//! assert_eq!(requests[1].param("currency_pair"), Some("BTC_USDT"));
+//!
+//! // A client with the wrong secret is turned away
+//! let intruder = GateHttpClient::with_url(&mock.url()).credentials(Credentials::new("key", "guess"));
+//! let err = intruder.send(get_account())?.into_body_str().unwrap_err();
+//! assert_eq!(err.label().map(|l| l.as_str()), Some("INVALID_SIGNATURE"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::http::{Credentials, GateErrorLabel, Method};
+use percent_encoding::percent_decode_str;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Spot paths served without authentication
+const PUBLIC_PATHS: &[&str] = &[
+    "/api/v4/spot/currencies",
+    "/api/v4/spot/currency_pairs",
+    "/api/v4/spot/tickers",
+    "/api/v4/spot/order_book",
+    "/api/v4/spot/trades",
+    "/api/v4/spot/candlesticks",
+    "/api/v4/spot/time",
+];
+
+/// Response served by [`MockGate`]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MockResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Extra response headers
+    pub headers: Vec<(String, String)>,
+    /// Response body
+    pub body: String,
+    /// Time to wait before answering
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    /// Creates a response with the given status and JSON body
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Creates a `200 OK` response with a JSON body
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new(200, body)
+    }
+
+    /// Creates a Gate error response with `label` and `message`
+    pub fn error(status: u16, label: GateErrorLabel, message: &str) -> Self {
+        Self::new(status, error_body(label.as_str(), message))
+    }
+
+    /// Creates the `429` answered by Gate when the rate limit is exceeded
+    pub fn too_many_requests() -> Self {
+        Self::error(
+            429,
+            GateErrorLabel::TooManyRequests,
+            "Request Rate Limit Exceeded",
+        )
+        .header("X-Gate-RateLimit-Requests-Remain", "0")
+    }
+
+    /// Adds a response header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Waits `delay` before answering, e.g. to trigger client timeouts
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Request received by [`MockGate`]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RecordedRequest {
+    /// HTTP method, e.g. `GET`
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Raw query string, as signed by the client
+    pub query: String,
+    /// Decoded query parameters, in order
+    pub params: Vec<(String, String)>,
+    /// Request headers with lowercase names
+    pub headers: Vec<(String, String)>,
+    /// Request body
+    pub body: String,
+    /// Whether the request carried a valid signature
+    pub signed: bool,
+    /// Status code answered
+    pub status: u16,
+}
+
+impl RecordedRequest {
+    /// Value of a header, looked up case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Value of a query parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Body parsed as JSON, `None` when empty or invalid
+    pub fn json(&self) -> Option<serde_json::Value> {
+        serde_json::from_str(&self.body).ok()
+    }
+}
+
+/// Local mock of the Gate REST API, see the [module documentation](self)
+///
+/// The server stops when the mock is dropped.
+pub struct MockGate {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    acceptor: Option<JoinHandle<()>>,
+}
+
+struct Route {
+    method: Method,
+    path: String,
+    params: Vec<(String, String)>,
+    response: MockResponse,
+    once: bool,
+}
+
+struct RateLimit {
+    limit: u32,
+    window: Duration,
+    started: Instant,
+    used: u32,
+}
+
+struct State {
+    credentials: Option<Credentials>,
+    max_clock_skew: Duration,
+    routes: Vec<Route>,
+    rate_limit: Option<RateLimit>,
+    requests: Vec<RecordedRequest>,
+    connections: Vec<TcpStream>,
+}
+
+impl MockGate {
+    /// Starts a mock server on a free port of `127.0.0.1`
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            credentials: None,
+            max_clock_skew: Duration::from_secs(60),
+            routes: Vec::new(),
+            rate_limit: None,
+            requests: Vec::new(),
+            connections: Vec::new(),
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let acceptor = {
+            let state = state.clone();
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("gateio-mock".into())
+                .spawn(move || accept(listener, state, stop))?
+        };
+        Ok(Self {
+            addr,
+            state,
+            stop,
+            acceptor: Some(acceptor),
+        })
+    }
+
+    /// Base URL to give to `GateHttpClient::with_url`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Verifies the signature of private requests against `credentials`
+    pub fn credentials(&self, credentials: Credentials) -> &Self {
+        self.lock().credentials = Some(credentials);
+        self
+    }
+
+    /// Sets how far `Timestamp` may be from the local clock, 60 seconds by default
+    pub fn max_clock_skew(&self, skew: Duration) -> &Self {
+        self.lock().max_clock_skew = skew;
+        self
+    }
+
+    /// Answers every `method` request on `path` with `response`
+    ///
+    /// The last fixture registered for a path wins.
+    pub fn respond(&self, method: Method, path: &str, response: MockResponse) -> &Self {
+        self.route(method, path, &[], response, false)
+    }
+
+    /// Answers the next `method` request on `path` with `response`
+    ///
+    /// One-off responses are served in the order registered, before the
+    /// fixtures set with [`MockGate::respond`].
+    pub fn respond_once(&self, method: Method, path: &str, response: MockResponse) -> &Self {
+        self.route(method, path, &[], response, true)
+    }
+
+    /// Answers `method` requests on `path` carrying all `params` with `response`
+    ///
+    /// Parameters are matched against the query string, or the top-level
+    /// fields of a JSON body. Fixtures with more parameters take precedence.
+    pub fn respond_with_params(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, &str)],
+        response: MockResponse,
+    ) -> &Self {
+        self.route(method, path, params, response, false)
+    }
+
+    /// Allows `limit` requests per `window`, answering 429 beyond it
+    ///
+    /// Every response then carries the `X-Gate-RateLimit-Requests-Remain`,
+    /// `X-Gate-RateLimit-Limit` and `X-Gate-RateLimit-Reset-Timestamp` headers.
+    pub fn rate_limit(&self, limit: u32, window: Duration) -> &Self {
+        self.lock().rate_limit = Some(RateLimit {
+            limit,
+            window,
+            started: Instant::now(),
+            used: 0,
+        });
+        self
+    }
+
+    /// Removes every fixture and the rate limit
+    pub fn reset(&self) -> &Self {
+        let mut state = self.lock();
+        state.routes.clear();
+        state.rate_limit = None;
+        self
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Requests received so far on `path`, oldest first
+    pub fn requests_to(&self, path: &str) -> Vec<RecordedRequest> {
+        self.lock()
+            .requests
+            .iter()
+            .filter(|r| path_matches(path, &r.path))
+            .cloned()
+            .collect()
+    }
+
+    /// Forgets the requests received so far
+    pub fn clear_requests(&self) {
+        self.lock().requests.clear();
+    }
+
+    fn route(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, &str)],
+        response: MockResponse,
+        once: bool,
+    ) -> &Self {
+        self.lock().routes.push(Route {
+            method,
+            path: path.to_owned(),
+            params: params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            response,
+            once,
+        });
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockGate {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for connection in self.lock().connections.drain(..) {
+            let _ = connection.shutdown(Shutdown::Both);
+        }
+        // Wake the acceptor up so that it sees the stop flag
+        let _ = TcpStream::connect(self.addr);
+        if let Some(acceptor) = self.acceptor.take() {
+            let _ = acceptor.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for MockGate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockGate")
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+fn accept(listener: TcpListener, state: Arc<Mutex<State>>, stop: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else { continue };
+        if let Ok(clone) = stream.try_clone() {
+            lock(&state).connections.push(clone);
+        }
+        let state = state.clone();
+        let _ = thread::Builder::new()
+            .name("gateio-mock-conn".into())
+            .spawn(move || serve(stream, state));
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Serves the requests of one keep-alive connection
+fn serve(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    while let Ok(Some((request, close))) = read_request(&mut reader) {
+        let response = handle(&state, request);
+        if !response.delay.is_zero() {
+            thread::sleep(response.delay);
+        }
+        if write_response(&mut writer, &response, close).is_err() || close {
+            break;
+        }
+    }
+    let peer = writer.peer_addr().ok();
+    lock(&state)
+        .connections
+        .retain(|c| c.peer_addr().ok() != peer);
+}
+
+/// Reads a request, `None` once the client closed the connection
+fn read_request(reader: &mut BufReader<TcpStream>) -> io::Result<Option<(RecordedRequest, bool)>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "bad request line",
+        ));
+    };
+    let (method, target) = (method.to_owned(), target.to_owned());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let length = header("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let close = header("connection").is_some_and(|v| v.eq_ignore_ascii_case("close"));
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let request = RecordedRequest {
+        method,
+        path: path.to_owned(),
+        query: query.to_owned(),
+        params: decode_query(query),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+        signed: false,
+        status: 0,
+    };
+    Ok(Some((request, close)))
+}
+
+fn write_response(writer: &mut TcpStream, response: &MockResponse, close: bool) -> io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if close {
+        head.push_str("connection: close\r\n");
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(response.body.as_bytes())?;
+    writer.flush()
+}
+
+/// Authenticates, rate limits and routes a request, then records it
+fn handle(state: &Mutex<State>, mut request: RecordedRequest) -> MockResponse {
+    let mut state = lock(state);
+    let mut rate_headers = Vec::new();
+    let response = match check_rate_limit(&mut state, &mut rate_headers) {
+        Some(throttled) => throttled,
+        None => match authenticate(&state, &request) {
+            Err(rejection) => rejection,
+            Ok(signed) => {
+                request.signed = signed;
+                find_route(&mut state, &request).unwrap_or_else(|| {
+                    MockResponse::error(
+                        404,
+                        GateErrorLabel::NotFound,
+                        &format!("no fixture for {} {}", request.method, request.path),
+                    )
+                })
+            }
+        },
+    };
+    let mut response = response;
+    for (name, value) in rate_headers {
+        if !response
+            .headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case(&name))
+        {
+            response.headers.push((name, value));
+        }
+    }
+    request.status = response.status;
+    state.requests.push(request);
+    response
+}
+
+/// Verifies `KEY`, `Timestamp` and `SIGN`, telling whether the request was signed
+fn authenticate(state: &State, request: &RecordedRequest) -> Result<bool, MockResponse> {
+    let Some(credentials) = &state.credentials else {
+        return Ok(false);
+    };
+    let public = PUBLIC_PATHS
+        .iter()
+        .any(|p| request.path == *p || request.path.starts_with(&format!("{}/", p)));
+    let (key, timestamp, sign) = match (
+        request.header("KEY"),
+        request.header("Timestamp"),
+        request.header("SIGN"),
+    ) {
+        (Some(key), Some(timestamp), Some(sign)) => (key, timestamp, sign),
+        (None, None, None) if public => return Ok(false),
+        _ => {
+            return Err(MockResponse::error(
+                401,
+                GateErrorLabel::MissingRequiredHeader,
+                "Missing required header: KEY, Timestamp or SIGN",
+            ));
+        }
+    };
+    if key != credentials.api_key {
+        return Err(MockResponse::error(
+            401,
+            GateErrorLabel::InvalidKey,
+            "Invalid key provided",
+        ));
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    let fresh = timestamp
+        .parse::<f64>()
+        .is_ok_and(|ts| (now - ts).abs() <= state.max_clock_skew.as_secs_f64());
+    if !fresh {
+        return Err(MockResponse::error(
+            401,
+            GateErrorLabel::RequestExpired,
+            "Request timestamp expired",
+        ));
+    }
+    let expected = crate::utils::sign_hmac(
+        &request.method,
+        &request.path,
+        &request.query,
+        &request.body,
+        timestamp,
+        &credentials.api_secret,
+    )
+    .unwrap_or_default();
+    if expected.is_empty() || !expected.eq_ignore_ascii_case(sign) {
+        return Err(MockResponse::error(
+            401,
+            GateErrorLabel::InvalidSignature,
+            "Signature mismatch",
+        ));
+    }
+    Ok(true)
+}
+
+/// Counts the request against the rate limit, returning the 429 when exceeded
+fn check_rate_limit(
+    state: &mut State,
+    headers: &mut Vec<(String, String)>,
+) -> Option<MockResponse> {
+    let limit = state.rate_limit.as_mut()?;
+    if limit.started.elapsed() >= limit.window {
+        limit.started = Instant::now();
+        limit.used = 0;
+    }
+    let reset = SystemTime::now() + limit.window.saturating_sub(limit.started.elapsed());
+    let reset_ms = reset
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let allowed = limit.used < limit.limit;
+    if allowed {
+        limit.used += 1;
+    }
+    headers.push((
+        "X-Gate-RateLimit-Requests-Remain".into(),
+        (limit.limit - limit.used).to_string(),
+    ));
+    headers.push(("X-Gate-RateLimit-Limit".into(), limit.limit.to_string()));
+    headers.push((
+        "X-Gate-RateLimit-Reset-Timestamp".into(),
+        reset_ms.to_string(),
+    ));
+    (!allowed).then(MockResponse::too_many_requests)
+}
+
+/// Picks the fixture for a request, consuming it when served once
+fn find_route(state: &mut State, request: &RecordedRequest) -> Option<MockResponse> {
+    let body = request.json();
+    let matches = |route: &Route| {
+        route.method.as_ref() == request.method
+            && path_matches(&route.path, &request.path)
+            && route.params.iter().all(|(k, v)| {
+                request.param(k) == Some(v)
+                    || body.as_ref().and_then(|b| b.get(k)).is_some_and(|field| {
+                        field.as_str() == Some(v)
+                            || serde_json::from_str::<serde_json::Value>(v)
+                                .is_ok_and(|v| v == *field)
+                    })
+            })
+    };
+    if let Some(index) = state.routes.iter().position(|r| r.once && matches(r)) {
+        return Some(state.routes.remove(index).response);
+    }
+    state
+        .routes
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| !r.once && matches(r))
+        .max_by_key(|(index, r)| (r.params.len(), *index))
+        .map(|(_, r)| r.response.clone())
+}
+
+/// Matches a path against a pattern where `{name}` and `*` segments match anything
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('/');
+    let mut path = path.split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s))
+                if p == s || p == "*" || (p.starts_with('{') && p.ends_with('}')) => {}
+            _ => return false,
+        }
+    }
+}
+
+fn decode_query(query: &str) -> Vec<(String, String)> {
+    let decode = |s: &str| {
+        percent_decode_str(&s.replace('+', " "))
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(k), decode(v))
+        })
+        .collect()
+}
+
+fn error_body(label: &str, message: &str) -> String {
+    serde_json::json!({ "label": label, "message": message }).to_string()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,29 @@
+use crate::batch::{Batch, BatchLimits, BatchResult, BatchResults, split};
+use crate::ureq::GateHttpClient;
+
+impl GateHttpClient {
+    /// Sends a batch of any size, split to fit the endpoint's limits.
+    ///
+    /// Returns one result per item, in the order of the items in `batch`.
+    pub fn send_batch<B: Batch>(&self, batch: B) -> Vec<BatchResult<B::Output>> {
+        self.send_batch_with(batch, B::limits())
+    }
+
+    /// Sends a batch of any size, split to fit `limits`
+    pub fn send_batch_with<B: Batch>(
+        &self,
+        batch: B,
+        limits: BatchLimits,
+    ) -> Vec<BatchResult<B::Output>> {
+        let chunks = split(batch, &limits);
+        let mut results = BatchResults::new(chunks.iter().map(|(indices, _)| indices.len()).sum());
+        for (indices, chunk) in chunks {
+            let response = self
+                .send(chunk)
+                .and_then(|response| response.into_body_str())
+                .map_err(|err| *err);
+            results.record(&indices, response);
+        }
+        results.finish()
+    }
+}
@@ -1,9 +1,15 @@
-use crate::http::{Credentials, request::Request};
+use crate::http::middleware::{self, CannedResponse, Middleware, Next, Outgoing, Received};
+use crate::http::trace::Trace;
+use crate::http::{
+    Call, ClientConfig, Credentials, DEFAULT_MAX_BODY_SIZE, Method, request::Request,
+};
 use crate::ureq::{Error, Response};
 use crate::version::VERSION;
 use http::Uri;
-use std::time::{SystemTime, UNIX_EPOCH};
-use ureq::{Agent, Error as UreqError};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use ureq::{Agent, AsSendBody, Body, Error as UreqError, Proxy};
 
 /// Synchronous HTTP client for Gate.io API using ureq.
 ///
@@ -28,7 +34,7 @@ use ureq::{Agent, Error as UreqError};
 /// let client = GateHttpClient::default();
 /// let request = get_ticker().currency_pair("BTC_USDT");
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// ## Authenticated Usage
@@ -44,7 +50,7 @@ use ureq::{Agent, Error as UreqError};
 /// let client = GateHttpClient::default().credentials(credentials);
 /// let request = get_account();
 /// let response = client.send(request)?;
-/// # Ok::<(), Box<dyn std::error::Error>>(()).expect("");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
 /// ## Custom Configuration
@@ -62,31 +68,78 @@ pub struct GateHttpClient {
     base_url: String,
     timestamp_delta: u64,
     credentials: Option<Credentials>,
+    max_body_size: usize,
+    middleware: Vec<Arc<dyn Middleware>>,
+    trace_fields: Vec<String>,
 }
 
-impl GateHttpClient {
+impl Default for GateHttpClient {
     /// Creates a new client with default settings and Gate.io production URL
-    pub fn default() -> Self {
+    fn default() -> Self {
         Self::with_url("https://api.gateio.ws")
     }
+}
 
+impl GateHttpClient {
     /// Creates a new client with a custom base URL
     pub fn with_url(url: &str) -> Self {
         Self {
-            client: Agent::config_builder().build().into(),
+            client: Agent::config_builder()
+                .http_status_as_error(false)
+                .build()
+                .into(),
             base_url: url.to_owned(),
             timestamp_delta: 0,
             credentials: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            middleware: Vec::new(),
+            trace_fields: Vec::new(),
         }
     }
 
-    /// Creates a new client with a custom ureq Agent and base URL
+    /// Creates a new client with a custom base URL and transport configuration.
+    ///
+    /// `tcp_keepalive` is not supported by ureq and is ignored.
+    pub fn with_config(url: &str, config: &ClientConfig) -> Result<Self, Box<Error>> {
+        let mut builder = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_connect(config.connect_timeout)
+            .timeout_global(config.timeout);
+
+        if let Some(read_timeout) = config.read_timeout {
+            builder = builder
+                .timeout_recv_response(Some(read_timeout))
+                .timeout_recv_body(Some(read_timeout));
+        }
+        if let Some(proxy) = &config.proxy {
+            let proxy = Proxy::new(proxy).map_err(|_| Error::InvalidProxy(proxy.clone()))?;
+            builder = builder.proxy(Some(proxy));
+        }
+        if let Some(pool_idle_timeout) = config.pool_idle_timeout {
+            builder = builder.max_idle_age(pool_idle_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = config.pool_max_idle_per_host {
+            builder = builder.max_idle_connections_per_host(pool_max_idle_per_host);
+        }
+
+        let mut client = Self::with_custom_agent(builder.build().into(), url);
+        client.max_body_size = config.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE);
+        Ok(client)
+    }
+
+    /// Creates a new client with a custom ureq Agent and base URL.
+    ///
+    /// Build the agent with `http_status_as_error(false)` so that error
+    /// response bodies reach [`Response::into_body_str`].
     pub fn with_custom_agent(agent: Agent, url: &str) -> Self {
         Self {
             client: agent,
             base_url: url.to_owned(),
             timestamp_delta: 0,
             credentials: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            middleware: Vec::new(),
+            trace_fields: Vec::new(),
         }
     }
 
@@ -102,8 +155,110 @@ impl GateHttpClient {
         self
     }
 
+    /// Registers a middleware, run after those registered before it
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Reveals payload fields in the `tracing` spans of requests
+    #[cfg(feature = "enable-tracing")]
+    pub fn trace_fields(mut self, fields: &[&str]) -> Self {
+        self.trace_fields = fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    /// Starts a typed call to any endpoint, see [`Call`]
+    pub fn call<T>(&self, method: Method, path: &str) -> Call<'_, Self, T> {
+        Call::new(self, method, path)
+    }
+
     /// Sends an HTTP request to the Gate.io API
     pub fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Box<Error>> {
+        let request = request.into();
+        if self.middleware.is_empty() && !Trace::ENABLED {
+            return self.execute(request, &[]);
+        }
+        let trace = Trace::start(&request, &self.trace_fields);
+        trace.in_scope(|| self.dispatch(request, &trace))
+    }
+
+    /// Runs the attempts of a request through the middleware
+    fn dispatch(&self, request: Request, trace: &Trace) -> Result<Response, Box<Error>> {
+        let mut attempt = 0;
+        loop {
+            let mut outgoing = Outgoing::new(request.clone(), attempt);
+            let mut canned = None;
+            let mut ran = 0;
+            for middleware in &self.middleware {
+                ran += 1;
+                canned = middleware.before_send(&mut outgoing);
+                if canned.is_some() {
+                    break;
+                }
+            }
+
+            let started = Instant::now();
+            let result = match &canned {
+                Some(canned) => canned_response(canned),
+                None => {
+                    let (request, headers) = outgoing.clone().into_parts();
+                    self.execute(request, &headers)
+                }
+            };
+            let latency = started.elapsed();
+
+            // Error bodies are buffered so that middleware sees the Gate error
+            let (result, status, headers, error) = match result {
+                Ok(response) if 400 <= response.status() => {
+                    let status = response.status();
+                    let (parts, body) = response.into_raw()?;
+                    let headers = crate::utils::header_map(&parts.headers);
+                    let error = middleware::response_error(status, body.clone(), headers.clone());
+                    let response = http::Response::from_parts(parts, Body::builder().data(body));
+                    let response = Response::from(response).max_body_size(self.max_body_size);
+                    (Ok(response), Some(status), headers, Some(error))
+                }
+                Ok(response) => {
+                    let headers = crate::utils::header_map(response.headers());
+                    let status = response.status();
+                    (Ok(response), Some(status), headers, None)
+                }
+                Err(err) => (Err(err), None, HashMap::new(), None),
+            };
+            let received = Received {
+                status,
+                headers: &headers,
+                error: error.as_ref().or(result.as_ref().err().map(|err| &**err)),
+                latency,
+                canned: canned.is_some(),
+            };
+            let next = middleware::combine(
+                self.middleware[..ran]
+                    .iter()
+                    .rev()
+                    .map(|m| m.after_receive(&outgoing, &received)),
+            );
+
+            match next {
+                Next::Retry(delay) => {
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                Next::Continue => {
+                    trace.finish(&outgoing, &received);
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Signs and sends a request with extra headers
+    fn execute(
+        &self,
+        request: Request,
+        extra_headers: &[(String, String)],
+    ) -> Result<Response, Box<Error>> {
         let Request {
             method,
             path,
@@ -112,19 +267,20 @@ impl GateHttpClient {
             x_gate_exp_time,
             credentials,
             sign,
-        } = request.into();
+            timeout,
+        } = request;
 
-        // Map query parameters (no-ureq)
-        let query_string = params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect::<Vec<String>>()
-            .join("&");
+        // Percent-encode query parameters; the same string is signed
+        let query_string = crate::utils::encode_query(&params);
 
         // Build URL
-        let full_url: Uri = format!("{}{}?{}", self.base_url, path, query_string).parse()?;
+        let full_url = if query_string.is_empty() {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}{}?{}", self.base_url, path, query_string)
+        };
+        let full_url: Uri = full_url.parse()?;
 
-        // Handle different HTTP methods and their respective RequestBuilder types
         let url_string = full_url.to_string();
         let user_agent = &format!("gateio-rs/{}", VERSION);
 
@@ -144,117 +300,102 @@ impl GateHttpClient {
             api_key,
             api_secret,
         }) = request_credentials.or(client_credentials)
+            && sign
         {
-            if sign {
-                // Use system clock, panic if system clock is behind `std::time::UNIX_EPOCH`
-                let mut timestamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Clock may have gone backwards")
-                    .as_secs();
-
-                // Append timestamp delta to sync up with server time.
-                timestamp -= self.timestamp_delta;
-
-                // Set API-Key and Timestamp in header
-                auth_headers.push(("KEY", api_key.clone()));
-                auth_headers.push(("Timestamp", timestamp.to_string()));
-
-                // Set x-gate-exptime header
-                if let Some(exp_time_ms) = x_gate_exp_time {
-                    auth_headers.push(("x-gate-exptime", exp_time_ms.to_string()));
-                }
+            // Use system clock, fail if system clock is behind `std::time::UNIX_EPOCH`
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| Error::Clock)?
+                .as_secs();
 
-                // Stringify available query parameters and append back to query parameters
-                let signature = crate::utils::sign_hmac(
-                    method.as_ref(),
-                    &path.to_string(),
-                    &query_string,
-                    &payload,
-                    &timestamp.to_string(),
-                    api_secret,
-                )
-                .map_err(|_| Error::InvalidApiSecret)?;
-
-                auth_headers.push(("SIGN", signature));
+            // Append timestamp delta to sync up with server time.
+            let timestamp = timestamp.saturating_sub(self.timestamp_delta);
+
+            // Set API-Key and Timestamp in header
+            auth_headers.push(("KEY", api_key.clone()));
+            auth_headers.push(("Timestamp", timestamp.to_string()));
+
+            // Set x-gate-exptime header
+            if let Some(exp_time_ms) = x_gate_exp_time {
+                auth_headers.push(("x-gate-exptime", exp_time_ms.to_string()));
             }
+
+            // Stringify available query parameters and append back to query parameters
+            let signature = crate::utils::sign_hmac(
+                method.as_ref(),
+                &path.to_string(),
+                &query_string,
+                &payload,
+                &timestamp.to_string(),
+                api_secret,
+            )
+            .map_err(|_| Error::InvalidApiSecret)?;
+
+            auth_headers.push(("SIGN", signature));
         }
 
-        // Make the request based on method type
-        let raw_response = match method {
-            crate::http::Method::Get => {
-                let mut req = self.client.get(&url_string);
-                for (key, value) in &headers {
-                    req = req.header(*key, *value);
-                }
-                for (key, value) in &auth_headers {
-                    req = req.header(*key, value.as_str());
-                }
-                req.call()
-            }
-            crate::http::Method::Post => {
-                let mut req = self.client.post(&url_string);
-                for (key, value) in &headers {
-                    req = req.header(*key, *value);
-                }
-                for (key, value) in &auth_headers {
-                    req = req.header(*key, value.as_str());
-                }
-                if payload.is_empty() {
-                    req.send_empty()
-                } else {
-                    req.send(payload.as_bytes())
-                }
-            }
-            crate::http::Method::Put => {
-                let mut req = self.client.put(&url_string);
-                for (key, value) in &headers {
-                    req = req.header(*key, *value);
-                }
-                for (key, value) in &auth_headers {
-                    req = req.header(*key, value.as_str());
-                }
-                if payload.is_empty() {
-                    req.send_empty()
-                } else {
-                    req.send(payload.as_bytes())
-                }
-            }
-            crate::http::Method::Delete => {
-                let mut req = self.client.delete(&url_string);
-                for (key, value) in &headers {
-                    req = req.header(*key, *value);
-                }
-                for (key, value) in &auth_headers {
-                    req = req.header(*key, value.as_str());
-                }
-                req.call()
-            }
-            crate::http::Method::Patch => {
-                let mut req = self.client.patch(&url_string);
-                for (key, value) in &headers {
-                    req = req.header(*key, *value);
-                }
-                for (key, value) in &auth_headers {
-                    req = req.header(*key, value.as_str());
-                }
-                if payload.is_empty() {
-                    req.send_empty()
-                } else {
-                    req.send(payload.as_bytes())
-                }
-            }
+        // Build the request and attach headers
+        let mut builder = http::Request::builder()
+            .method(method.as_ref())
+            .uri(&url_string);
+        for (key, value) in &headers {
+            builder = builder.header(*key, *value);
+        }
+        for (key, value) in extra_headers {
+            builder = builder.header(key, value);
+        }
+        for (key, value) in &auth_headers {
+            builder = builder.header(*key, value.as_str());
+        }
+
+        // Only send a body when there is a payload
+        let raw_response = if payload.is_empty() {
+            self.run(builder.body(())?, timeout)
+        } else {
+            self.run(builder.body(payload.into_bytes())?, timeout)
         };
 
-        let response = match raw_response {
-            Ok(response) => Ok(response),
-            Err(UreqError::StatusCode(status)) => {
-                // In ureq 3.x, StatusCode errors need to be handled differently
-                // We need to get the response from the error
-                return Err(Box::new(Error::Send(UreqError::StatusCode(status))));
+        let response = raw_response.map_err(|err| Box::new(Error::from(err)))?;
+
+        Ok(Response::from(response).max_body_size(self.max_body_size))
+    }
+
+    fn run<S: AsSendBody>(
+        &self,
+        request: http::Request<S>,
+        timeout: Option<Duration>,
+    ) -> Result<http::Response<Body>, UreqError> {
+        match timeout {
+            Some(timeout) => {
+                let request = self
+                    .client
+                    .configure_request(request)
+                    .timeout_global(Some(timeout))
+                    .build();
+                self.client.run(request)
             }
-            Err(err) => Err(Error::Send(err)),
-        }?;
+            None => self.client.run(request),
+        }
+    }
+}
 
-        Ok(Response::from(response))
+/// Blocking client sending Gate requests.
+///
+/// Implemented by [`GateHttpClient`] and by stand-ins such as
+/// [`PaperClient`](crate::paper::PaperClient), so code written against it
+/// runs unchanged on either.
+pub trait SendRequest {
+    /// Sends an HTTP request to the Gate.io API
+    fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Box<Error>>;
+}
+
+impl SendRequest for GateHttpClient {
+    fn send<R: Into<Request>>(&self, request: R) -> Result<Response, Box<Error>> {
+        GateHttpClient::send(self, request)
     }
 }
+
+fn canned_response(canned: &CannedResponse) -> Result<Response, Box<Error>> {
+    let body = Body::builder().data(canned.body.clone());
+    Ok(Response::from(canned.to_http(body)?))
+}
@@ -1,41 +1,28 @@
-use crate::http::error::{ClientError, HttpError as GateHttpError};
-use http::{Error as HttpError, uri::InvalidUri};
+use crate::http::error::{ClientError, GateError};
+use std::collections::HashMap;
 use ureq::Error as UreqError;
 
 /// Communication error with the server.
-#[derive(Debug)]
-pub enum Error {
-    /// 4XX error from the server.
-    Client(ClientError),
-    /// 5XX error from the server.
-    Server(GateHttpError<String>),
-    /// The format of the API secret is invalid.
-    InvalidApiSecret,
-    /// Error serializing request payload to JSON
-    PayloadSerializationError,
-    /// Error parsing HTTP request or response
-    Parse(HttpError),
-    /// Error sending HTTP request
-    Send(UreqError),
-}
+///
+/// Alias of the backend independent [`GateError`].
+pub type Error = GateError;
 
-impl From<InvalidUri> for Box<Error> {
-    fn from(err: InvalidUri) -> Box<Error> {
-        Box::new(Error::Parse(err.into()))
-    }
-}
-
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::Client(e) => write!(f, "Client error: {:?}", e),
-            Error::Server(e) => write!(f, "Server error: {:?}", e),
-            Error::InvalidApiSecret => write!(f, "Invalid API secret"),
-            Error::PayloadSerializationError => write!(f, "Payload serialization error"),
-            Error::Parse(e) => write!(f, "Parse error: {}", e),
-            Error::Send(e) => write!(f, "Send error: {}", e),
+impl From<UreqError> for GateError {
+    fn from(err: UreqError) -> GateError {
+        match err {
+            UreqError::Timeout(_) => GateError::Timeout,
+            UreqError::BodyExceedsLimit(limit) => GateError::BodyTooLarge(limit as usize),
+            // Only raised by custom agents with `http_status_as_error` enabled,
+            // in which case ureq has already discarded the body.
+            UreqError::StatusCode(status) if 500 <= status => GateError::Server(
+                ClientError::from_body(status, String::new(), HashMap::new()),
+            ),
+            UreqError::StatusCode(status) => GateError::Client(ClientError::from_body(
+                status,
+                String::new(),
+                HashMap::new(),
+            )),
+            err => GateError::Send(Box::new(err)),
         }
     }
 }
-
-impl std::error::Error for Error {}
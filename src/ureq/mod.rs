@@ -45,10 +45,55 @@
 //! # Error Handling
 //!
 //! All errors emitted by the client implement the standard [`Error`] trait.
+//! Both backends share [`GateError`](crate::http::GateError), which keeps the
+//! response body and exposes the documented Gate error label:
+//!
+//! ```no_run
+//! use gateio_rs::{api::spot::create_order, http::GateErrorLabel, ureq::GateHttpClient};
+//!
+//! let client = GateHttpClient::default();
+//! let result = client
+//!     .send(create_order("BTC_USDT", "buy", "0.001").price("50000"))
+//!     .and_then(|response| response.into_body_str());
+//!
+//! if let Err(err) = result {
+//!     match err.label() {
+//!         Some(GateErrorLabel::BalanceNotEnough) => println!("Top up first"),
+//!         _ if err.is_retryable() => println!("Try again later"),
+//!         _ => println!("Failed: {}", err),
+//!     }
+//! }
+//! ```
 //!
 //! # Custom Timeout
 //!
-//! Configure custom timeouts using ureq's config builder:
+//! Configure timeouts, proxies and connection pooling with [`ClientConfig`](crate::http::ClientConfig):
+//!
+//! ```
+//! use gateio_rs::{http::ClientConfig, ureq::GateHttpClient};
+//! use std::time::Duration;
+//!
+//! let config = ClientConfig::new()
+//!     .connect_timeout(Duration::from_secs(5))
+//!     .timeout(Duration::from_secs(10))
+//!     .proxy("http://127.0.0.1:8080");
+//!
+//! let client = GateHttpClient::with_config("https://api.gateio.ws", &config)
+//!     .expect("Invalid client configuration");
+//! ```
+//!
+//! Latency-critical calls can override the client timeout per request:
+//!
+//! ```no_run
+//! use gateio_rs::{api::spot::cancel_order, ureq::GateHttpClient};
+//! use std::time::Duration;
+//!
+//! let client = GateHttpClient::default();
+//! let request = cancel_order("12345", "BTC_USDT").timeout(Duration::from_millis(500));
+//! let response = client.send(request);
+//! ```
+//!
+//! A fully custom ureq [`Agent`](ureq::Agent) can still be supplied:
 //!
 //! ```
 //! use ureq::Agent;
@@ -56,18 +101,21 @@
 //! use std::time::Duration;
 //!
 //! let agent: Agent = Agent::config_builder()
-//!     .timeout_request(Duration::from_secs(10))
-//!     .timeout_connect(Duration::from_secs(5))
+//!     .timeout_global(Some(Duration::from_secs(10)))
+//!     .timeout_connect(Some(Duration::from_secs(5)))
 //!     .build()
 //!     .into();
 //!
 //! let client = GateHttpClient::with_custom_agent(agent, "https://api.gateio.ws");
 //! ```
 
+mod batch;
 mod client;
 mod error;
+mod pagination;
 mod response;
 
 pub use client::*;
 pub use error::*;
+pub use pagination::*;
 pub use response::*;
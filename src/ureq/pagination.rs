@@ -0,0 +1,86 @@
+use crate::pagination::{Pager, Paginated, TimeWindowed};
+use crate::ureq::{Error, GateHttpClient, SendRequest};
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+/// Blocking iterator over the records of a paged endpoint.
+///
+/// Created by [`GateHttpClient::paginate`] and
+/// [`GateHttpClient::paginate_windows`]. Pages are fetched lazily; iteration
+/// ends after the first error.
+pub struct Pages<'a, R, T, C = GateHttpClient> {
+    client: &'a C,
+    pager: Pager<R>,
+    buffer: VecDeque<T>,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<'a, R: Paginated, T: DeserializeOwned, C: SendRequest> Pages<'a, R, T, C> {
+    /// Walks the pages of `request` with any blocking client
+    pub(crate) fn new(client: &'a C, request: R) -> Self {
+        Self::with_pager(client, Pager::new(request))
+    }
+
+    fn with_pager(client: &'a C, pager: Pager<R>) -> Self {
+        Self {
+            client,
+            pager,
+            buffer: VecDeque::new(),
+            _record: PhantomData,
+        }
+    }
+
+    fn fetch(&mut self, request: R) -> Result<(), Box<Error>> {
+        let body = self.client.send(request)?.into_body_str()?;
+        let records: Vec<T> =
+            serde_json::from_str(&body).map_err(|err| Error::InvalidBody(Box::new(err)))?;
+        self.pager.record(records.len());
+        self.buffer.extend(records);
+        Ok(())
+    }
+}
+
+impl<R: Paginated, T: DeserializeOwned, C: SendRequest> Iterator for Pages<'_, R, T, C> {
+    type Item = Result<T, Box<Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.buffer.pop_front() {
+                return Some(Ok(record));
+            }
+            let request = self.pager.next_request()?;
+            if let Err(err) = self.fetch(request) {
+                self.pager.finish();
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+impl GateHttpClient {
+    /// Iterates over every record of a paged endpoint, fetching pages on demand
+    pub fn paginate<T, R>(&self, request: R) -> Pages<'_, R, T>
+    where
+        T: DeserializeOwned,
+        R: Paginated,
+    {
+        Pages::new(self, request)
+    }
+
+    /// Iterates over every record between `from` and `to` (inclusive, UNIX
+    /// seconds), querying at most `window` seconds at a time
+    pub fn paginate_windows<T, R>(
+        &self,
+        request: R,
+        from: i64,
+        to: i64,
+        window: i64,
+    ) -> Pages<'_, R, T>
+    where
+        T: DeserializeOwned,
+        R: TimeWindowed,
+    {
+        Pages::with_pager(self, Pager::windows(request, from, to, window))
+    }
+}
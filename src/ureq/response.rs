@@ -1,64 +1,97 @@
-use crate::http::error::{ClientError, GateApiError, HttpError};
+use crate::http::DEFAULT_MAX_BODY_SIZE;
+use crate::http::error::ClientError;
 use crate::ureq::Error;
 use std::collections::HashMap;
-use ureq::Body;
+use ureq::{Body, BodyReader};
 
 /// HTTP response wrapper for Gate.io API responses
 pub struct Response {
     inner_response: http::Response<Body>,
+    max_body_size: usize,
 }
 
 impl Response {
     /// Creates a new Response from an HTTP response
     pub fn new(inner_response: http::Response<Body>) -> Self {
-        Self { inner_response }
+        Self {
+            inner_response,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Sets the maximum body size read by [`Response::into_body_str`]
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Returns the HTTP status code
+    pub fn status(&self) -> u16 {
+        self.inner_response.status().as_u16()
+    }
+
+    /// Returns the response headers
+    pub(crate) fn headers(&self) -> &http::HeaderMap {
+        self.inner_response.headers()
+    }
+
+    /// Reads the head and body, leaving error statuses to the caller
+    pub(crate) fn into_raw(self) -> Result<(http::response::Parts, String), Box<Error>> {
+        let (parts, body) = self.inner_response.into_parts();
+        Ok((parts, read_limited(body, self.max_body_size)?))
     }
 
     /// Fetch the data received from the API.
     pub fn into_body_str(self) -> Result<String, Box<Error>> {
-        let status = self.inner_response.status().as_u16();
-        if 400 <= status {
-            let headers: HashMap<String, String> =
-                self.inner_response
-                    .headers()
-                    .iter()
-                    .fold(HashMap::new(), |mut headers, (k, v)| {
-                        headers
-                            .entry(k.as_str().to_owned())
-                            .or_insert_with(|| v.to_str().unwrap_or("").to_owned());
-                        headers
-                    });
-
-            let (_, mut body) = self.inner_response.into_parts();
-            let content = body
-                .read_to_string()
-                .expect("Response failed UTF-8 encoding.");
-            if 500 <= status {
-                Err(Box::new(Error::Server(HttpError::new(
-                    status, content, headers,
-                ))))
-            } else {
-                let client_error = match serde_json::from_str::<GateApiError>(&content) {
-                    Ok(err) => ClientError::Structured(HttpError::new(status, err, headers)),
-                    Err(_) => ClientError::Raw(HttpError::new(status, content, headers)),
-                };
+        let status = self.status();
+        let headers = crate::utils::header_map(self.inner_response.headers());
+        let content = read_limited(self.inner_response.into_body(), self.max_body_size)?;
 
-                Err(Box::new(Error::Client(client_error)))
-            }
+        if 400 <= status {
+            Err(error_response(status, content, headers))
         } else {
-            let (_, mut body) = self.inner_response.into_parts();
-            Ok(body
-                .read_to_string()
-                .expect("Response failed UTF-8 encoding."))
+            Ok(content)
+        }
+    }
+
+    /// Streams the body of a successful response without a size limit.
+    ///
+    /// Error responses are read (within the size limit) and returned as errors.
+    pub fn into_reader(self) -> Result<BodyReader<'static>, Box<Error>> {
+        let status = self.status();
+        if 400 <= status {
+            let headers = crate::utils::header_map(self.inner_response.headers());
+            let content = read_limited(self.inner_response.into_body(), self.max_body_size)?;
+            return Err(error_response(status, content, headers));
         }
+        Ok(self.inner_response.into_body().into_reader())
     }
 }
 
+fn error_response(status: u16, content: String, headers: HashMap<String, String>) -> Box<Error> {
+    if 500 <= status {
+        Box::new(Error::Server(ClientError::from_body(
+            status, content, headers,
+        )))
+    } else {
+        Box::new(Error::Client(ClientError::from_body(
+            status, content, headers,
+        )))
+    }
+}
+
+fn read_limited(mut body: Body, limit: usize) -> Result<String, Box<Error>> {
+    let bytes = body
+        .with_config()
+        .limit(limit as u64)
+        .read_to_vec()
+        .map_err(|err| Box::new(Error::from(err)))?;
+    String::from_utf8(bytes).map_err(|err| Box::new(Error::InvalidBody(Box::new(err))))
+}
+
 impl From<http::Response<Body>> for Response {
     fn from(response: http::Response<Body>) -> Response {
-        Response {
-            inner_response: response,
-        }
+        Response::new(response)
     }
 }
 
@@ -1,7 +1,107 @@
 use hmac::digest::Digest;
 use hmac::{Hmac, Mac};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::de::DeserializeOwned;
 use sha2::Sha512;
 use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::http::GateError;
+
+/// Everything except RFC 3986 unreserved characters is percent-encoded
+const QUERY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Builds the query string sent on the wire and used for signing
+pub fn encode_query(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(k, v)| {
+            format!(
+                "{}={}",
+                utf8_percent_encode(k, QUERY_ENCODE_SET),
+                utf8_percent_encode(v, QUERY_ENCODE_SET)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Parses a decimal string sent by Gate, `0` when empty or invalid
+pub(crate) fn number(value: &str) -> f64 {
+    value.parse().unwrap_or(0.0)
+}
+
+/// Formats like Gate: plain decimal notation without trailing zeros
+pub(crate) fn format_number(value: f64) -> String {
+    let text = format!("{:.12}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".into()
+    } else {
+        text.into()
+    }
+}
+
+/// Base and quote currencies of a pair, upper case
+pub(crate) fn split_pair(currency_pair: &str) -> (String, String) {
+    let pair = currency_pair.to_uppercase();
+    match pair.split_once('_') {
+        Some((base, quote)) => (base.to_owned(), quote.to_owned()),
+        None => (pair, String::new()),
+    }
+}
+
+/// Milliseconds since the UNIX epoch, `0` when the clock is behind it
+pub(crate) fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Deserializes a response body; an empty body is read as JSON `null`
+pub(crate) fn parse<T: DeserializeOwned>(body: &str) -> Result<T, GateError> {
+    let body = if body.trim().is_empty() { "null" } else { body };
+    serde_json::from_str(body).map_err(|err| GateError::InvalidBody(Box::new(err)))
+}
+
+/// Deserializes an already decoded JSON value
+pub(crate) fn parse_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, GateError> {
+    serde_json::from_value(value).map_err(|err| GateError::InvalidBody(Box::new(err)))
+}
+
+/// Sends `request` and deserializes the body of a successful response
+#[cfg(feature = "enable-ureq")]
+pub(crate) fn fetch<T, S, R>(client: &S, request: R) -> Result<T, GateError>
+where
+    T: DeserializeOwned,
+    S: crate::ureq::SendRequest,
+    R: Into<crate::http::request::Request>,
+{
+    let body = client
+        .send(request)
+        .and_then(|response| response.into_body_str())
+        .map_err(|err| *err)?;
+    parse(&body)
+}
+
+/// Sends `request` and deserializes the body of a successful response
+#[cfg(feature = "enable-hyper")]
+pub(crate) async fn fetch_async<T, R>(
+    client: &crate::hyper::GateHttpClient,
+    request: R,
+) -> Result<T, GateError>
+where
+    T: DeserializeOwned,
+    R: Into<crate::http::request::Request>,
+{
+    let body = client.send(request).await?.into_body_str().await?;
+    parse(&body)
+}
 
 pub fn sign_hmac(
     method: &str,
@@ -26,3 +126,70 @@ pub fn sign_hmac(
     let signature_bytes = mac.finalize().into_bytes();
     Ok(hex::encode(signature_bytes))
 }
+
+#[cfg(any(feature = "enable-tracing", feature = "enable-metrics"))]
+/// Endpoint of a path, ids of single resources replaced by `{id}`
+pub fn endpoint(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    // "", "api", "v4", "spot", resource, id...
+    if segments.len() > 5 {
+        format!("{}/{{id}}", segments[..5].join("/"))
+    } else {
+        path.to_owned()
+    }
+}
+
+#[cfg(any(feature = "enable-tracing", feature = "enable-metrics"))]
+/// Coarse class of an endpoint: `market`, `account` or `trading`
+pub fn endpoint_class(path: &str) -> &'static str {
+    match path.split('/').nth(4).unwrap_or_default() {
+        "currencies" | "currency_pairs" | "tickers" | "order_book" | "trades" | "candlesticks"
+        | "time" | "insurance_history" => "market",
+        "accounts" | "account_book" | "fee" | "batch_fee" => "account",
+        _ => "trading",
+    }
+}
+
+/// Collects response headers, keeping the first value of repeated keys
+pub fn header_map(headers: &http::HeaderMap) -> std::collections::HashMap<String, String> {
+    headers
+        .iter()
+        .fold(std::collections::HashMap::new(), |mut map, (k, v)| {
+            map.entry(k.as_str().to_owned())
+                .or_insert_with(|| v.to_str().unwrap_or("").to_owned());
+            map
+        })
+}
+
+/// Serde adapter for numbers sent as JSON strings; plain numbers are accepted as well
+pub mod number_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw<T> {
+        Number(T),
+        String(String),
+    }
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match Raw::<T>::deserialize(deserializer)? {
+            Raw::Number(value) => Ok(value),
+            Raw::String(value) => value.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
@@ -0,0 +1,562 @@
+//! Rust sources emitted for a [`Spec`]: one module per API group with a
+//! request builder per operation, in the style of `api::spot`, and the
+//! response models shared by every group.
+
+use crate::spec::{Body, Field, Model, Operation, Param, Spec, Type};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const HEADER: &str =
+    "Generated from Gate's OpenAPI specification by `cargo xtask codegen`, do not edit.";
+
+/// Coverage note of the root module: only what the spec describes is emitted
+const SCOPE: &str = "Only the operations of the spec are covered; the pinned\n\
+    //! `xtask/spec/openapi.yaml` is an excerpt with the public spot market endpoints.";
+
+/// Generated files, by path relative to the output directory
+pub type Files = BTreeMap<String, String>;
+
+/// Generates the sources of `spec`, `module` being the Rust path of the
+/// output directory (`crate::api::generated`)
+pub fn generate(spec: &Spec, module: &str) -> Files {
+    let mut files = Files::new();
+    let mut groups: BTreeMap<String, Vec<&Operation>> = BTreeMap::new();
+    for operation in &spec.operations {
+        groups
+            .entry(snake(&operation.group))
+            .or_default()
+            .push(operation);
+    }
+
+    let mut root = format!(
+        "//! Endpoints and models of the Gate v4 API.\n//!\n//! {}\n//!\n//! {}\n\n",
+        HEADER, SCOPE
+    );
+    root.push_str("pub mod models;\n");
+    for group in groups.keys() {
+        let _ = writeln!(root, "pub mod {};", group);
+    }
+    files.insert("mod.rs".to_owned(), root);
+    files.insert("models.rs".to_owned(), models(&spec.models));
+
+    for (group, operations) in &groups {
+        let mut index = format!(
+            "//! `{}` endpoints of the Gate v4 API.\n//!\n//! {}\n\n",
+            operations[0].group, HEADER
+        );
+        for operation in operations {
+            let name = snake(&operation.id);
+            let _ = writeln!(index, "mod {};", name);
+            let _ = writeln!(index, "pub use {}::*;", name);
+            files.insert(
+                format!("{}/{}.rs", group, name),
+                endpoint(operation, module),
+            );
+        }
+        files.insert(format!("{}/mod.rs", group), index);
+    }
+    files
+}
+
+fn models(models: &[Model]) -> String {
+    let mut out = format!("//! Models of the Gate v4 API.\n//!\n//! {}\n\n", HEADER);
+    out.push_str("use serde::{Deserialize, Serialize};\n");
+    for model in models {
+        let name = pascal(&model.name);
+        out.push('\n');
+        doc(&mut out, &model.doc, &format!("`{}` model", model.name));
+        out.push_str("#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]\n");
+        let _ = writeln!(out, "pub struct {} {{", name);
+        for field in &model.fields {
+            let ident = ident(&field.name);
+            let mut ty = rust_type(&field.ty, "");
+            if field.ty == Type::Model(model.name.clone()) {
+                ty = format!("Box<{}>", ty);
+            }
+            let mut serde = Vec::new();
+            if ident.trim_start_matches("r#") != field.name {
+                serde.push(format!("rename = {:?}", field.name));
+            }
+            if !field.required {
+                serde.push("default".to_owned());
+                serde.push("skip_serializing_if = \"Option::is_none\"".to_owned());
+                ty = format!("Option<{}>", ty);
+            }
+            doc(&mut out, &field.doc, &format!("`{}` field", field.name));
+            if !serde.is_empty() {
+                let _ = writeln!(out, "#[serde({})]", serde.join(", "));
+            }
+            let _ = writeln!(out, "pub {}: {},", ident, ty);
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+fn endpoint(operation: &Operation, module: &str) -> String {
+    let name = pascal(&operation.id);
+    let models = format!("{}::models::", module);
+
+    let mut fields: Vec<(&Field, Option<&Param>)> = operation
+        .params
+        .iter()
+        .map(|param| (&param.field, Some(param)))
+        .collect();
+    let whole = match &operation.body {
+        Some(Body::Fields(body)) => {
+            for field in body {
+                if !fields
+                    .iter()
+                    .any(|(f, _)| ident(&f.name) == ident(&field.name))
+                {
+                    fields.push((field, None));
+                }
+            }
+            None
+        }
+        Some(Body::Whole(ty)) => Some(ty),
+        None => None,
+    };
+    let body_field = whole.map(|ty| Field {
+        name: "body".to_owned(),
+        doc: "Request body".to_owned(),
+        required: true,
+        read_only: false,
+        ty: ty.clone(),
+    });
+    if let Some(field) = &body_field {
+        fields.push((field, None));
+    }
+    let required: Vec<&Field> = fields
+        .iter()
+        .map(|(f, _)| *f)
+        .filter(|f| f.required)
+        .collect();
+    let optional: Vec<&Field> = fields
+        .iter()
+        .map(|(f, _)| *f)
+        .filter(|f| !f.required)
+        .collect();
+
+    let mut out = format!("//! {}\n\n", HEADER);
+    out.push_str("use crate::http::{Credentials, Method, request::Request};\n");
+    match &operation.body {
+        Some(Body::Fields(_)) => out.push_str("use serde_json::{Map, Value, json};\n"),
+        Some(Body::Whole(_)) => out.push_str("use serde_json::json;\n"),
+        None => {}
+    }
+    out.push('\n');
+
+    let summary = [&operation.summary, &operation.doc]
+        .into_iter()
+        .find_map(|text| text.lines().next().filter(|line| !line.trim().is_empty()))
+        .unwrap_or(&operation.id);
+    doc(&mut out, summary, "");
+    out.push_str("///\n");
+    if !operation.summary.is_empty()
+        && !operation.doc.is_empty()
+        && operation.doc != operation.summary
+    {
+        doc(&mut out, &operation.doc, "");
+        out.push_str("///\n");
+    }
+    let _ = writeln!(
+        out,
+        "/// # API Endpoint\n/// `{} {}`",
+        operation.method, operation.path
+    );
+    if operation.response.is_some() {
+        let _ = writeln!(out, "///\n/// Responds with [`{}Response`].", name);
+    }
+    out.push_str("#[derive(Clone, Debug)]\n");
+    let _ = writeln!(out, "pub struct {} {{", name);
+    for (field, _) in &fields {
+        doc(&mut out, &field.doc, &format!("`{}` parameter", field.name));
+        let ty = rust_type(&field.ty, &models);
+        let ty = if field.required {
+            ty
+        } else {
+            format!("Option<{}>", ty)
+        };
+        let _ = writeln!(out, "pub {}: {},", ident(&field.name), ty);
+    }
+    out.push_str(
+        "/// API credentials for authentication\npub credentials: Option<Credentials>,\n}\n\n",
+    );
+
+    if let Some(response) = &operation.response {
+        let _ = writeln!(out, "/// Response of [`{}`]", name);
+        let _ = writeln!(
+            out,
+            "pub type {}Response = {};\n",
+            name,
+            rust_type(response, &models)
+        );
+    }
+
+    let args: Vec<String> = required
+        .iter()
+        .map(|f| format!("{}: {}", ident(&f.name), arg_type(&f.ty, &models)))
+        .collect();
+    let arg_names: Vec<String> = required.iter().map(|f| ident(&f.name)).collect();
+
+    let _ = writeln!(out, "impl {} {{", name);
+    let _ = writeln!(out, "/// Creates a new {} request", name);
+    let _ = writeln!(out, "pub fn new({}) -> Self {{\nSelf {{", args.join(", "));
+    for (field, _) in &fields {
+        let ident = ident(&field.name);
+        if !field.required {
+            let _ = writeln!(out, "{}: None,", ident);
+        } else if field.ty == Type::String {
+            let _ = writeln!(out, "{}: {}.into(),", ident, ident);
+        } else {
+            let _ = writeln!(out, "{},", ident);
+        }
+    }
+    out.push_str("credentials: None,\n}\n}\n");
+    for field in &optional {
+        let ident = ident(&field.name);
+        let value = if field.ty == Type::String {
+            format!("{}.into()", ident)
+        } else {
+            ident.clone()
+        };
+        out.push('\n');
+        doc(&mut out, &field.doc, &format!("Sets `{}`", field.name));
+        let _ = writeln!(
+            out,
+            "pub fn {}(mut self, {}: {}) -> Self {{\nself.{} = Some({});\nself\n}}",
+            ident,
+            ident,
+            arg_type(&field.ty, &models),
+            ident,
+            value
+        );
+    }
+    out.push_str(
+        "\n/// Sets the API credentials for authentication\n\
+         pub fn credentials(mut self, creds: Credentials) -> Self {\n\
+         self.credentials = Some(creds);\nself\n}\n}\n\n",
+    );
+
+    if required.is_empty() {
+        let _ = writeln!(
+            out,
+            "impl Default for {} {{\nfn default() -> Self {{\nSelf::new()\n}}\n}}\n",
+            name
+        );
+    }
+
+    doc(&mut out, summary, "");
+    let _ = writeln!(
+        out,
+        "pub fn {}({}) -> {} {{\n{}::new({})\n}}\n",
+        snake(&operation.id),
+        args.join(", "),
+        name,
+        name,
+        arg_names.join(", ")
+    );
+
+    let _ = writeln!(out, "impl From<{}> for Request {{", name);
+    let _ = writeln!(out, "fn from(request: {}) -> Request {{", name);
+    let queried: Vec<&Field> = operation
+        .params
+        .iter()
+        .filter(|p| !p.in_path)
+        .map(|p| &p.field)
+        .collect();
+    if queried.is_empty() {
+        out.push_str("let params = Vec::new();\n");
+    } else {
+        out.push_str("let mut params = Vec::new();\n");
+    }
+    for field in &queried {
+        let ident = ident(&field.name);
+        let value = query_value(&field.ty, "value");
+        if field.required {
+            let _ = writeln!(
+                out,
+                "let value = request.{};\nparams.push((\"{}\".into(), {}));",
+                ident, field.name, value
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                "\nif let Some(value) = request.{} {{\nparams.push((\"{}\".into(), {}));\n}}",
+                ident, field.name, value
+            );
+        }
+    }
+
+    let payload = match &operation.body {
+        Some(Body::Fields(_)) => {
+            out.push_str("\nlet mut payload = Map::new();\n");
+            for (field, param) in &fields {
+                if param.is_some() || body_field.as_ref().is_some_and(|b| std::ptr::eq(*field, b)) {
+                    continue;
+                }
+                let ident = ident(&field.name);
+                if field.required {
+                    let _ = writeln!(
+                        out,
+                        "payload.insert({:?}.to_string(), json!(request.{}));",
+                        field.name, ident
+                    );
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "if let Some(value) = request.{} {{\npayload.insert({:?}.to_string(), json!(value));\n}}",
+                        ident, field.name
+                    );
+                }
+            }
+            "Value::Object(payload).to_string()".to_owned()
+        }
+        Some(Body::Whole(_)) => "json!(request.body).to_string()".to_owned(),
+        None => "\"\".to_string()".to_owned(),
+    };
+
+    let path_params: Vec<&Param> = operation.params.iter().filter(|p| p.in_path).collect();
+    let path = if path_params.is_empty() {
+        format!("{:?}.into()", operation.path)
+    } else {
+        let mut template = operation.path.clone();
+        let mut values = Vec::new();
+        for param in &path_params {
+            template = template.replace(&format!("{{{}}}", param.field.name), "{}");
+            values.push(format!("request.{}", ident(&param.field.name)));
+        }
+        format!("format!({:?}, {})", template, values.join(", "))
+    };
+    let method = pascal(&operation.method.to_lowercase());
+    let _ = writeln!(
+        out,
+        "\nRequest {{\nmethod: Method::{},\npath: {},\nparams,\npayload: {},\nx_gate_exp_time: None,\ncredentials: request.credentials,\nsign: {},\ntimeout: None,\n}}\n}}\n}}",
+        method, path, payload, operation.signed
+    );
+    out
+}
+
+/// Rust type of a field, models prefixed with `models`
+fn rust_type(ty: &Type, models: &str) -> String {
+    match ty {
+        Type::String => "String".to_owned(),
+        Type::I32 => "i32".to_owned(),
+        Type::I64 => "i64".to_owned(),
+        Type::F64 => "f64".to_owned(),
+        Type::Bool => "bool".to_owned(),
+        Type::Array(item) => format!("Vec<{}>", rust_type(item, models)),
+        Type::Model(name) => format!("{}{}", models, pascal(name)),
+        Type::Any => "serde_json::Value".to_owned(),
+    }
+}
+
+/// Type of a builder argument: strings are borrowed
+fn arg_type(ty: &Type, models: &str) -> String {
+    match ty {
+        Type::String => "&str".to_owned(),
+        ty => rust_type(ty, models),
+    }
+}
+
+/// Query string form of `value`: arrays comma separated, objects as JSON
+fn query_value(ty: &Type, value: &str) -> String {
+    match ty {
+        Type::String => value.to_owned(),
+        Type::I32 | Type::I64 | Type::F64 | Type::Bool => format!("{}.to_string()", value),
+        Type::Array(item) if matches!(**item, Type::String) => format!("{}.join(\",\")", value),
+        Type::Array(item) if !matches!(**item, Type::Model(_) | Type::Any | Type::Array(_)) => {
+            format!(
+                "{}.iter().map(ToString::to_string).collect::<Vec<_>>().join(\",\")",
+                value
+            )
+        }
+        _ => format!("serde_json::to_string(&{}).unwrap_or_default()", value),
+    }
+}
+
+/// Appends `text` as a doc comment, `fallback` when empty
+fn doc(out: &mut String, text: &str, fallback: &str) {
+    let text = if text.trim().is_empty() {
+        fallback
+    } else {
+        text
+    };
+    let mut fenced = false;
+    for line in text.lines() {
+        // Indented lines and untagged fences would become doctests
+        let line = line.trim();
+        let line = if line.starts_with("```") {
+            fenced = !fenced;
+            if fenced && line == "```" {
+                "```text"
+            } else {
+                line
+            }
+        } else {
+            line
+        };
+        if line.is_empty() {
+            out.push_str("///\n");
+        } else {
+            let _ = writeln!(out, "/// {}", line);
+        }
+    }
+}
+
+/// `snake_case` form of a camelCase or PascalCase name
+pub fn snake(name: &str) -> String {
+    let mut out = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let after_lower =
+                i > 0 && (chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit());
+            let before_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            let acronym_end = i > 0 && chars[i - 1].is_ascii_uppercase() && before_lower;
+            if (after_lower || acronym_end) && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let out = out.trim_end_matches('_').to_owned();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", out)
+    } else {
+        out
+    }
+}
+
+/// `PascalCase` form of a name
+pub fn pascal(name: &str) -> String {
+    snake(name)
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |c| {
+                c.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+/// Field or function identifier, keywords escaped
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe",
+        "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+        "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    let ident = snake(name);
+    match ident.as_str() {
+        "" => "value".to_owned(),
+        "self" | "super" | "crate" | "credentials" => format!("{}_", ident),
+        ident if KEYWORDS.contains(&ident) => format!("r#{}", ident),
+        _ => ident,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture() -> Files {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/minimal.yaml");
+        let spec = Spec::load(&path).unwrap();
+        generate(&spec, "crate::api::generated")
+    }
+
+    #[test]
+    fn snake_case() {
+        assert_eq!(snake("listCurrencyPairs"), "list_currency_pairs");
+        assert_eq!(snake("GetHTTPStatus"), "get_http_status");
+        assert_eq!(snake("high_24h"), "high_24h");
+        assert_eq!(snake("Margin Uni"), "margin_uni");
+        assert_eq!(snake("sub-account"), "sub_account");
+        assert_eq!(snake("v2Keys"), "v2_keys");
+        assert_eq!(snake("24hChange"), "_24h_change");
+    }
+
+    #[test]
+    fn pascal_case() {
+        assert_eq!(pascal("listCurrencyPairs"), "ListCurrencyPairs");
+        assert_eq!(pascal("currency_pair"), "CurrencyPair");
+        assert_eq!(pascal("Margin Uni"), "MarginUni");
+        assert_eq!(pascal("GET"), "Get");
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(ident("type"), "r#type");
+        assert_eq!(ident("currencyPair"), "currency_pair");
+        assert_eq!(ident("self"), "self_");
+        assert_eq!(ident("credentials"), "credentials_");
+        assert_eq!(ident("--"), "value");
+    }
+
+    #[test]
+    fn fixture_files() {
+        let files = fixture();
+        let paths: Vec<&str> = files.keys().map(String::as_str).collect();
+        assert_eq!(
+            paths,
+            [
+                "mod.rs",
+                "models.rs",
+                "things/amend_thing.rs",
+                "things/create_things.rs",
+                "things/get_thing.rs",
+                "things/mod.rs",
+            ]
+        );
+        assert!(files["mod.rs"].contains("pub mod things;"));
+        assert!(files["things/mod.rs"].contains("mod get_thing;\npub use get_thing::*;"));
+    }
+
+    #[test]
+    fn fixture_models() {
+        let models = &fixture()["models.rs"];
+        // Fields of `allOf` parts come first, in document order
+        let name = models.find("pub name: String,").unwrap();
+        let id = models.find("pub id: Option<String>,").unwrap();
+        assert!(name < id);
+        // Serde strips the raw prefix, no rename needed
+        assert!(models.contains("pub r#type: Option<String>,"));
+        assert!(!models.contains("rename"));
+        assert!(models.contains("pub amount: Option<f64>,"));
+        assert!(models.contains("pub parent: Option<Box<Thing>>,"));
+    }
+
+    #[test]
+    fn fixture_endpoints() {
+        let files = fixture();
+        let get = &files["things/get_thing.rs"];
+        assert!(get.contains("pub fn get_thing(thing_id: &str) -> GetThing"));
+        assert!(get.contains("pub ids: Option<Vec<i32>>,"));
+        assert!(get.contains("format!(\"/api/v4/things/{}\", request.thing_id)"));
+        assert!(get.contains("params.push((\"type\".into(), value));"));
+        assert!(get.contains("sign: false,"));
+        assert!(get.contains("pub type GetThingResponse = crate::api::generated::models::Thing;"));
+
+        // Read-only fields are left out of request bodies
+        let amend = &files["things/amend_thing.rs"];
+        assert!(amend.contains("sign: true,"));
+        assert!(amend.contains("payload.insert(\"name\".to_string(), json!(request.name));"));
+        assert!(!amend.contains("request.id"));
+
+        let create = &files["things/create_things.rs"];
+        assert!(create.contains("pub body: Vec<crate::api::generated::models::Thing>,"));
+        assert!(create.contains("payload: json!(request.body).to_string(),"));
+        assert!(!create.contains("Response"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Development tasks of gateio-rs, run with `cargo xtask <task>`.
+//!
+//! `codegen` turns a Gate OpenAPI document into request builders,
+//! `From<..> for Request` impls and response models for each API group it
+//! describes:
+//!
+//! ```text
+//! cargo xtask codegen [--spec xtask/spec/openapi.yaml] [--out src/api/generated] [--check]
+//! ```
+//!
+//! The spec defaults to the copy pinned in `xtask/spec`, so that the output
+//! only changes when that file does. That copy is a hand-trimmed excerpt of
+//! the published document with the public spot market endpoints only; the
+//! other groups are not generated until the full document is pinned. With `--check` nothing is written: the
+//! task fails when the committed sources differ from what the spec generates,
+//! listing the stale files.
+
+mod codegen;
+mod spec;
+
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::{env, fs};
+
+const USAGE: &str =
+    "usage: cargo xtask codegen [--spec <openapi.yaml|openapi.json>] [--out <dir>] [--check]";
+
+/// Pinned specification, relative to the workspace root
+const DEFAULT_SPEC: &str = "xtask/spec/openapi.yaml";
+
+/// Default output directory, relative to the workspace root
+const DEFAULT_OUT: &str = "src/api/generated";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("codegen") => Codegen::parse(&args[1..]).and_then(|task| task.run()),
+        _ => Err(USAGE.to_owned()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct Codegen {
+    spec: PathBuf,
+    out: String,
+    check: bool,
+}
+
+impl Codegen {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut spec = None;
+        let mut out = DEFAULT_OUT.to_owned();
+        let mut check = false;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--spec" => spec = Some(args.next().ok_or(USAGE)?.into()),
+                "--out" => out = args.next().ok_or(USAGE)?.trim_end_matches('/').to_owned(),
+                "--check" => check = true,
+                _ => return Err(format!("unknown argument {:?}\n{}", arg, USAGE)),
+            }
+        }
+        Ok(Self {
+            spec: spec.unwrap_or_else(|| workspace_root().join(DEFAULT_SPEC)),
+            out,
+            check,
+        })
+    }
+
+    fn run(&self) -> Result<(), String> {
+        let module = self
+            .out
+            .strip_prefix("src/")
+            .map(|path| format!("crate::{}", path.replace('/', "::")))
+            .ok_or_else(|| format!("the output directory must be under src/, not {}", self.out))?;
+        let spec = spec::Spec::load(&self.spec)?;
+        let mut files = codegen::generate(&spec, &module);
+        for source in files.values_mut() {
+            *source = rustfmt(source)?;
+        }
+
+        let out = workspace_root().join(&self.out);
+        let existing = existing_files(&out);
+        let stale: Vec<&String> = files
+            .iter()
+            .filter(|(path, source)| {
+                fs::read_to_string(out.join(path)).ok().as_ref() != Some(*source)
+            })
+            .map(|(path, _)| path)
+            .collect();
+        let removed: Vec<&String> = existing
+            .iter()
+            .filter(|path| !files.contains_key(*path))
+            .collect();
+
+        if self.check {
+            if stale.is_empty() && removed.is_empty() {
+                println!("{} is up to date with {}", self.out, self.spec.display());
+                return Ok(());
+            }
+            let mut report = format!("{} is out of date with {}:", self.out, self.spec.display());
+            for path in &stale {
+                report.push_str(&format!("\n  changed  {}", path));
+            }
+            for path in &removed {
+                report.push_str(&format!("\n  extra    {}", path));
+            }
+            report.push_str("\nrun `cargo xtask codegen` without --check to regenerate");
+            return Err(report);
+        }
+
+        for path in &stale {
+            let target = out.join(path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|err| format!("{}: {}", parent.display(), err))?;
+            }
+            fs::write(&target, &files[*path])
+                .map_err(|err| format!("{}: {}", target.display(), err))?;
+        }
+        for path in &removed {
+            let target = out.join(path);
+            fs::remove_file(&target).map_err(|err| format!("{}: {}", target.display(), err))?;
+        }
+        println!(
+            "{}: {} operations, {} models, {} files written, {} removed",
+            self.out,
+            spec.operations.len(),
+            spec.models.len(),
+            stale.len(),
+            removed.len()
+        );
+        Ok(())
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// `.rs` files under `dir`, relative to it
+fn existing_files(dir: &Path) -> BTreeSet<String> {
+    fn walk(dir: &Path, prefix: &str, files: &mut BTreeSet<String>) {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, &format!("{}{}/", prefix, name), files);
+            } else if name.ends_with(".rs") {
+                files.insert(format!("{}{}", prefix, name));
+            }
+        }
+    }
+    let mut files = BTreeSet::new();
+    walk(dir, "", &mut files);
+    files
+}
+
+/// Formats `source` with the rustfmt of the toolchain
+fn rustfmt(source: &str) -> Result<String, String> {
+    let mut child = Command::new(env::var("RUSTFMT").unwrap_or_else(|_| "rustfmt".to_owned()))
+        .args(["--edition", "2024", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("cannot run rustfmt: {}", err))?;
+    child
+        .stdin
+        .take()
+        .ok_or("rustfmt stdin unavailable")?
+        .write_all(source.as_bytes())
+        .map_err(|err| format!("cannot run rustfmt: {}", err))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("cannot run rustfmt: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "rustfmt rejected generated code:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|err| err.to_string())
+}
@@ -0,0 +1,320 @@
+//! Reading Gate's OpenAPI 3 document into operations and models.
+
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Type of a parameter, body or model field
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    String,
+    I32,
+    I64,
+    F64,
+    Bool,
+    Array(Box<Type>),
+    /// Object schema of `components/schemas`
+    Model(String),
+    /// Inline objects and anything without a usable schema
+    Any,
+}
+
+/// Field of a request body or a model
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name: String,
+    pub doc: String,
+    pub required: bool,
+    /// Set by the server only, left out of request bodies
+    pub read_only: bool,
+    pub ty: Type,
+}
+
+/// Parameter of an operation, in the path or the query string
+#[derive(Clone, Debug)]
+pub struct Param {
+    pub field: Field,
+    pub in_path: bool,
+}
+
+/// JSON body of an operation
+#[derive(Clone, Debug)]
+pub enum Body {
+    /// Object body, its fields set one by one
+    Fields(Vec<Field>),
+    /// Any other body (arrays of orders...), set as a whole
+    Whole(Type),
+}
+
+/// Endpoint of the specification
+#[derive(Clone, Debug)]
+pub struct Operation {
+    pub group: String,
+    pub id: String,
+    pub method: String,
+    pub path: String,
+    pub summary: String,
+    pub doc: String,
+    pub params: Vec<Param>,
+    pub body: Option<Body>,
+    pub signed: bool,
+    pub response: Option<Type>,
+}
+
+/// Object schema of `components/schemas`
+#[derive(Clone, Debug)]
+pub struct Model {
+    pub name: String,
+    pub doc: String,
+    pub fields: Vec<Field>,
+}
+
+/// Operations and models of a specification
+#[derive(Debug)]
+pub struct Spec {
+    pub operations: Vec<Operation>,
+    pub models: Vec<Model>,
+}
+
+impl Spec {
+    /// Reads a JSON or YAML OpenAPI 3 document
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        let document: Value = if text.trim_start().starts_with('{') {
+            serde_json::from_str(&text).map_err(|err| format!("invalid JSON spec: {}", err))?
+        } else {
+            serde_yaml::from_str(&text).map_err(|err| format!("invalid YAML spec: {}", err))?
+        };
+        Self::parse(&document)
+    }
+
+    /// Extracts the operations and the models of an OpenAPI 3 document
+    pub fn parse(document: &Value) -> Result<Self, String> {
+        let version = document["openapi"].as_str().unwrap_or_default();
+        if !version.starts_with('3') {
+            return Err(format!(
+                "expected an OpenAPI 3 document, found {:?}",
+                version
+            ));
+        }
+        let resolver = Resolver { document };
+        let base_path = document["servers"][0]["url"]
+            .as_str()
+            .and_then(|url| url.find("/api/").map(|at| url[at..].to_owned()))
+            .unwrap_or_else(|| "/api/v4".to_owned());
+        let default_signed = document["security"]
+            .as_array()
+            .is_some_and(|security| !security.is_empty());
+
+        let mut operations = Vec::new();
+        for (path, item) in object(&document["paths"]) {
+            let item = resolver.resolve(item);
+            let shared = item["parameters"].as_array().cloned().unwrap_or_default();
+            for method in ["get", "post", "put", "patch", "delete"] {
+                let Some(operation) = item.get(method) else {
+                    continue;
+                };
+                let path = format!("{}{}", base_path.trim_end_matches('/'), path);
+                operations.push(resolver.operation(
+                    method,
+                    &path,
+                    operation,
+                    &shared,
+                    default_signed,
+                )?);
+            }
+        }
+        operations.sort_by(|a, b| (&a.group, &a.id).cmp(&(&b.group, &b.id)));
+
+        let mut models = Vec::new();
+        for (name, schema) in object(&document["components"]["schemas"]) {
+            let schema = resolver.resolve(schema);
+            if is_object(schema) {
+                models.push(Model {
+                    name: name.clone(),
+                    doc: description(schema),
+                    fields: resolver.fields(schema),
+                });
+            }
+        }
+        Ok(Self { operations, models })
+    }
+}
+
+/// Follows `$ref`s within the document
+struct Resolver<'a> {
+    document: &'a Value,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&self, value: &'a Value) -> &'a Value {
+        let mut value = value;
+        for _ in 0..16 {
+            let Some(reference) = value["$ref"].as_str() else {
+                break;
+            };
+            let Some(pointer) = reference.strip_prefix('#') else {
+                break;
+            };
+            match self.document.pointer(pointer) {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn operation(
+        &self,
+        method: &str,
+        path: &str,
+        operation: &Value,
+        shared: &[Value],
+        default_signed: bool,
+    ) -> Result<Operation, String> {
+        let id = operation["operationId"]
+            .as_str()
+            .ok_or_else(|| format!("{} {} has no operationId", method.to_uppercase(), path))?;
+        let group = operation["tags"][0].as_str().unwrap_or("misc").to_owned();
+
+        let mut params: Vec<Param> = Vec::new();
+        let declared = operation["parameters"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        for param in shared.iter().chain(&declared) {
+            let param = self.resolve(param);
+            let location = param["in"].as_str().unwrap_or_default();
+            if location != "query" && location != "path" {
+                continue;
+            }
+            let name = param["name"].as_str().unwrap_or_default().to_owned();
+            params.retain(|p| p.field.name != name);
+            params.push(Param {
+                field: Field {
+                    name,
+                    doc: description(param),
+                    required: location == "path" || param["required"].as_bool().unwrap_or(false),
+                    read_only: false,
+                    ty: self.ty(&param["schema"]),
+                },
+                in_path: location == "path",
+            });
+        }
+
+        let body = self
+            .resolve(&operation["requestBody"])
+            .get("content")
+            .and_then(|content| content.get("application/json"))
+            .map(|content| {
+                let schema = self.resolve(&content["schema"]);
+                if is_object(schema) {
+                    let mut fields = self.fields(schema);
+                    fields.retain(|field| !field.read_only);
+                    Body::Fields(fields)
+                } else {
+                    Body::Whole(self.ty(&content["schema"]))
+                }
+            });
+
+        let signed = match operation["security"].as_array() {
+            Some(security) => !security.is_empty(),
+            None => default_signed,
+        };
+
+        let responses = object(&operation["responses"]);
+        let response = responses
+            .iter()
+            .find(|(status, _)| status.starts_with('2'))
+            .and_then(|(_, response)| {
+                self.resolve(response)["content"]["application/json"].get("schema")
+            })
+            .map(|schema| self.ty(schema));
+
+        Ok(Operation {
+            group,
+            id: id.to_owned(),
+            method: method.to_uppercase(),
+            path: path.to_owned(),
+            summary: operation["summary"]
+                .as_str()
+                .unwrap_or_default()
+                .trim()
+                .to_owned(),
+            doc: description(operation),
+            params,
+            body,
+            signed,
+            response,
+        })
+    }
+
+    fn fields(&self, schema: &Value) -> Vec<Field> {
+        let mut fields = Vec::new();
+        for part in schema["allOf"].as_array().into_iter().flatten() {
+            fields.extend(self.fields(self.resolve(part)));
+        }
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        for (name, property) in object(&schema["properties"]) {
+            fields.retain(|field: &Field| &field.name != name);
+            fields.push(Field {
+                name: name.clone(),
+                doc: description(self.resolve(property)),
+                required: required.contains(&name.as_str()),
+                read_only: self.resolve(property)["readOnly"] == true,
+                ty: self.ty(property),
+            });
+        }
+        fields
+    }
+
+    fn ty(&self, schema: &Value) -> Type {
+        if let Some(name) = schema["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+            && is_object(self.resolve(schema))
+        {
+            return Type::Model(name.to_owned());
+        }
+        let schema = self.resolve(schema);
+        match schema["type"].as_str() {
+            Some("string") => Type::String,
+            Some("integer") if schema["format"] == "int32" => Type::I32,
+            Some("integer") => Type::I64,
+            Some("number") => Type::F64,
+            Some("boolean") => Type::Bool,
+            Some("array") => Type::Array(Box::new(self.ty(&schema["items"]))),
+            _ => Type::Any,
+        }
+    }
+}
+
+/// Entries of a JSON object, in document order
+fn object(value: &Value) -> Vec<(&String, &Value)> {
+    value
+        .as_object()
+        .map(Map::iter)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn is_object(schema: &Value) -> bool {
+    schema["type"] == "object" && schema.get("properties").is_some()
+        || schema.get("allOf").is_some()
+}
+
+fn description(value: &Value) -> String {
+    value["description"]
+        .as_str()
+        .or_else(|| value["title"].as_str())
+        .unwrap_or_default()
+        .trim()
+        .replace('\r', "")
+}